@@ -41,5 +41,46 @@ fn benchmark(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, benchmark);
+/// long exact segments and filenames, which the `simd` feature
+/// compares and scans eight bytes at a time
+fn assets(c: &mut Criterion) {
+    let mut router = Router::new();
+    router
+        .add("/static/js/vendor/react-dom.production.min.js", 1)
+        .unwrap();
+    router
+        .add("/static/js/vendor/react.production.min.js", 2)
+        .unwrap();
+    router.add("/static/css/:name.:ext", 3).unwrap();
+    router.add("/static/js/:name.:ext", 4).unwrap();
+    router.add("/static/*", 5).unwrap();
+
+    c.bench_function("asset exact", |b| {
+        b.iter(|| router.best_match(black_box("/static/js/vendor/react-dom.production.min.js")))
+    });
+
+    c.bench_function("asset name and extension", |b| {
+        b.iter(|| router.best_match(black_box("/static/js/application-5f3a9c2b7e.js")))
+    });
+
+    c.bench_function("asset fallthrough", |b| {
+        b.iter(|| router.best_match(black_box("/static/js/vendor/react-dom.development.js")))
+    });
+}
+
+/// naming the captures of a match, which borrows each name from the
+/// route. tests/allocations.rs checks the allocations
+fn captures(c: &mut Criterion) {
+    let mut router = Router::new();
+    router.add("/orgs/:org/repos/:repo/issues/:id", 1).unwrap();
+    let m = router
+        .best_match("/orgs/jbr/repos/routefinder/issues/1")
+        .unwrap();
+
+    c.bench_function("three named captures", |b| {
+        b.iter(|| black_box(&m).captures())
+    });
+}
+
+criterion_group!(benches, benchmark, assets, captures);
 criterion_main!(benches);
@@ -0,0 +1,36 @@
+//! best_match latency with and without per-route hit counts, on the
+//! same routes as the main benchmark. Counting is a relaxed atomic
+//! increment, so the two should be indistinguishable.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use routefinder::Router;
+
+fn router(hit_counts: bool) -> Router<usize> {
+    let mut router = Router::new();
+    router.add("/posts/:post_id/comments/:id", 1).unwrap();
+    router.add("/posts/:post_id/comments", 2).unwrap();
+    router.add("/posts/:post_id", 3).unwrap();
+    router.add("/posts", 4).unwrap();
+    router.add("/comments", 5).unwrap();
+    router.add("/comments/:id", 6).unwrap();
+    router.add("/*", 7).unwrap();
+    router.set_hit_counts(hit_counts);
+    router
+}
+
+fn hit_counts(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hit counts");
+    for enabled in [false, true] {
+        let router = router(enabled);
+        let label = if enabled { "enabled" } else { "disabled" };
+        for path in ["/posts/100/comments/200", "/comments", "/a/b/c/d/e/f"] {
+            group.bench_with_input(BenchmarkId::new(label, path), path, |b, path| {
+                b.iter(|| router.best_match(black_box(path)).is_some())
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, hit_counts);
+criterion_main!(benches);
@@ -0,0 +1,181 @@
+//! best_match latency against generated route tables of 100, 1k, and
+//! 10k routes, to show how matching scales with the size of the table.
+//! The heap size of each table is printed before its benchmarks run.
+//!
+//! The skewed tables put most routes under a few mount points, as
+//! large apps tend to, with a handful of routes elsewhere. The sorted
+//! tables are the same as the first, stored in a sorted vec, and the
+//! static tables are the same again, converted to a `StaticRouter`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use routefinder::{Router, Storage};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// counts live heap bytes so that the size of each table can be reported
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const SIZES: [usize; 3] = [100, 1_000, 10_000];
+const ROUTES_PER_RESOURCE: usize = 10;
+
+/// a rails-like table of rest resources with nested ids, along with
+/// static assets and api routes, of about `size` routes
+fn table(size: usize) -> Router<usize> {
+    let mut router = Router::new();
+    for resource in 0..size / ROUTES_PER_RESOURCE {
+        let name = format!("resource{}", resource);
+        for route in [
+            format!("/{}", name),
+            format!("/{}/new", name),
+            format!("/{}/:id", name),
+            format!("/{}/:id/edit", name),
+            format!("/{}/:{}_id/items", name, name),
+            format!("/{}/:{}_id/items/:id", name, name),
+            format!("/{}/:{}_id/items/:id/edit", name, name),
+            format!("/api/v1/{}/:id.json", name),
+            format!("/assets/{}/app.js", name),
+            format!("/assets/{}/*", name),
+        ] {
+            router.add(&*route, resource).unwrap();
+        }
+    }
+    router
+}
+
+/// paths that exercise the first and last resources in the table, plus
+/// a path that no route matches
+fn paths(size: usize) -> Vec<(&'static str, String)> {
+    let last = size / ROUTES_PER_RESOURCE - 1;
+    vec![
+        ("static first", String::from("/resource0")),
+        ("static last", format!("/resource{}/new", last)),
+        ("param last", format!("/resource{}/123", last)),
+        ("nested last", format!("/resource{}/1/items/2/edit", last)),
+        ("dotted last", format!("/api/v1/resource{}/5.json", last)),
+        (
+            "wildcard last",
+            format!("/assets/resource{}/img/logo.png", last),
+        ),
+        ("miss", String::from("/nothing/matches/this/path")),
+    ]
+}
+
+/// a table of about `size` routes where nearly every route is under
+/// `/api` or `/admin`, along with a few top level pages and a fallback
+fn skewed_table(size: usize) -> Router<usize> {
+    let mut router = Router::new();
+    for route in ["/", "/about", "/login", "/assets/*", "/:page"] {
+        router.add(route, 0).unwrap();
+    }
+    for resource in 0..size / ROUTES_PER_RESOURCE {
+        for mount in ["api", "admin"] {
+            for route in [
+                format!("/{}/resource{}", mount, resource),
+                format!("/{}/resource{}/:id", mount, resource),
+                format!("/{}/resource{}/:id/edit", mount, resource),
+                format!("/{}/resource{}/:id/items/:item", mount, resource),
+                format!("/{}/resource{}/search/*", mount, resource),
+            ] {
+                router.add(&*route, resource).unwrap();
+            }
+        }
+    }
+    router
+}
+
+/// paths under each mount point, at the top level, and unmatched
+fn skewed_paths(size: usize) -> Vec<(&'static str, String)> {
+    let last = size / ROUTES_PER_RESOURCE - 1;
+    vec![
+        ("top level", String::from("/about")),
+        ("top level param", String::from("/pricing")),
+        ("api last", format!("/api/resource{}/1/items/2", last)),
+        ("admin last", format!("/admin/resource{}/1/edit", last)),
+        ("asset", String::from("/assets/app.js")),
+        ("miss", String::from("/api/nothing/matches/this/path")),
+    ]
+}
+
+fn benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("large tables");
+    for size in SIZES {
+        let before = ALLOCATED.load(Ordering::Relaxed);
+        let router = table(size);
+        let bytes = ALLOCATED.load(Ordering::Relaxed).saturating_sub(before);
+        eprintln!(
+            "{} routes: {} bytes ({} bytes per route)",
+            router.len(),
+            bytes,
+            bytes / router.len()
+        );
+
+        for (name, path) in paths(size) {
+            group.bench_with_input(BenchmarkId::new(name, size), &path, |b, path| {
+                b.iter(|| router.best_match(black_box(path)))
+            });
+        }
+    }
+    group.finish();
+}
+
+fn skewed(c: &mut Criterion) {
+    let mut group = c.benchmark_group("skewed tables");
+    for size in SIZES {
+        let router = skewed_table(size);
+        for (name, path) in skewed_paths(size) {
+            group.bench_with_input(BenchmarkId::new(name, size), &path, |b, path| {
+                b.iter(|| router.best_match(black_box(path)))
+            });
+        }
+    }
+    group.finish();
+}
+
+fn sorted(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sorted tables");
+    for size in SIZES {
+        let mut router = table(size);
+        router.set_storage(Storage::Sorted);
+        for (name, path) in paths(size) {
+            group.bench_with_input(BenchmarkId::new(name, size), &path, |b, path| {
+                b.iter(|| router.best_match(black_box(path)))
+            });
+        }
+    }
+    group.finish();
+}
+
+fn static_tables(c: &mut Criterion) {
+    let mut group = c.benchmark_group("static tables");
+    for size in SIZES {
+        let router = table(size).into_static();
+        for (name, path) in paths(size) {
+            group.bench_with_input(BenchmarkId::new(name, size), &path, |b, path| {
+                b.iter(|| router.best_match(black_box(path)))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, benchmark, skewed, sorted, static_tables);
+criterion_main!(benches);
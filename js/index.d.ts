@@ -0,0 +1,18 @@
+export interface Match<Handler> {
+  handler: Handler;
+  route: string;
+  params: Record<string, string>;
+  wildcard?: string;
+}
+
+export function load(source?: RequestInfo | URL | BufferSource): Promise<unknown>;
+
+export class Router<Handler = unknown> {
+  constructor();
+  readonly size: number;
+  /** returns the numeric id of the added route */
+  add(route: string, handler: Handler): number;
+  bestMatch(path: string): Match<Handler> | null;
+  urlFor(route: string, params: Record<string, string>): string | undefined;
+  free(): void;
+}
@@ -0,0 +1,15 @@
+import init, { Router } from "./pkg/routefinder.js";
+
+let ready;
+
+/**
+ * loads the webassembly module. this must be awaited once before
+ * constructing a Router. `source` is passed to wasm-bindgen's init,
+ * and defaults to the bundled .wasm file.
+ */
+export function load(source) {
+  ready ??= init(source);
+  return ready;
+}
+
+export { Router };
@@ -0,0 +1,5 @@
+//! The routefinder library is only an rlib, so that crates that depend
+//! on it do not also build a cdylib. This crate links its `wasm`
+//! bindings into the cdylib that wasm-pack packages for npm.
+
+pub use routefinder::wasm::*;
@@ -0,0 +1,99 @@
+use crate::normalize::NormalizedPath;
+use std::borrow::Cow;
+
+/// A path-like input to a [`Router`][crate::Router], so that
+/// frameworks can match their own request types without first copying
+/// the path into a string. Matching methods such as
+/// [`Router::best_match`][crate::Router::best_match] accept any
+/// `AsPath`.
+///
+/// This is implemented for `str`, `String`, `Box<str>`, `Cow<str>`,
+/// [`NormalizedPath`], and, with the `http` feature, `http::Uri`.
+///
+/// ```rust
+/// use routefinder::{AsPath, Router};
+///
+/// struct Request {
+///     target: String,
+/// }
+///
+/// impl AsPath for Request {
+///     fn raw(&self) -> &str {
+///         &self.target
+///     }
+///
+///     fn trimmed(&self) -> &str {
+///         self.target.split('?').next().unwrap_or_default()
+///     }
+/// }
+///
+/// let mut router = Router::new();
+/// router.add("/users/:id", ()).unwrap();
+/// let request = Request { target: "/users/1?page=2".into() };
+/// let m = router.best_match(&request).unwrap();
+/// assert_eq!(m.captures().get("id"), Some("1"));
+/// ```
+pub trait AsPath {
+    /// Returns the input as it was received, which may include more
+    /// than the path, such as a query
+    fn raw(&self) -> &str;
+
+    /// Returns the path to match, without anything that follows it,
+    /// such as a query or fragment. Defaults to [`AsPath::raw`].
+    fn trimmed(&self) -> &str {
+        self.raw()
+    }
+}
+
+impl AsPath for str {
+    fn raw(&self) -> &str {
+        self
+    }
+}
+
+impl AsPath for String {
+    fn raw(&self) -> &str {
+        self
+    }
+}
+
+impl AsPath for Box<str> {
+    fn raw(&self) -> &str {
+        self
+    }
+}
+
+impl AsPath for Cow<'_, str> {
+    fn raw(&self) -> &str {
+        self
+    }
+}
+
+impl AsPath for NormalizedPath<'_> {
+    fn raw(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<P: AsPath + ?Sized> AsPath for &P {
+    fn raw(&self) -> &str {
+        P::raw(self)
+    }
+
+    fn trimmed(&self) -> &str {
+        P::trimmed(self)
+    }
+}
+
+#[cfg(feature = "http")]
+impl AsPath for http::Uri {
+    /// the path and query, or the path if there is no query
+    fn raw(&self) -> &str {
+        self.path_and_query()
+            .map_or_else(|| self.path(), |path_and_query| path_and_query.as_str())
+    }
+
+    fn trimmed(&self) -> &str {
+        self.path()
+    }
+}
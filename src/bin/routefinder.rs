@@ -0,0 +1,360 @@
+//! Inspects a route table from the command line. Run
+//! `routefinder help` for usage.
+
+use routefinder::{Captures, RouteSpec, Router, Segment};
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    hint::black_box,
+    process::exit,
+    ptr,
+    time::{Duration, Instant},
+};
+
+const USAGE: &str = "\
+usage: routefinder <routes-file> <command> [args]
+
+commands:
+  match <path>                   show the best match for a path and its captures
+  explain <path>                 show how every route applies to a path
+  lint                           report duplicate and shadowed routes
+  table                          list routes in order of precedence
+  reverse <name> [key=value...]  build a path for a named route. use * for the wildcard
+  replay <log-file>              match every request path in a log, reporting hits per
+                                 route, unmatched paths, and throughput
+
+the routes file has one route per line, optionally followed by a name:
+
+  /users/:id    users.show
+  /assets/*     assets
+  # comments and blank lines are ignored
+
+the log file for replay may be a plain list of paths or an access log
+in common or combined log format. query strings are ignored.
+";
+
+#[derive(Debug, Clone)]
+struct Entry {
+    name: Option<String>,
+    line: usize,
+}
+
+impl Entry {
+    fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or("-")
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Err(error) = run(&args) {
+        eprintln!("{}", error);
+        exit(1);
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let (file, command, args) = match args {
+        [first, ..] if first == "help" || first == "--help" || first == "-h" => {
+            print!("{}", USAGE);
+            return Ok(());
+        }
+        [file, command, args @ ..] => (file, command.as_str(), args),
+        _ => return Err(String::from(USAGE)),
+    };
+
+    let source = fs::read_to_string(file).map_err(|e| format!("could not read {}: {}", file, e))?;
+    let entries = parse(&source)?;
+    let mut router = Router::new();
+    for (spec, entry) in &entries {
+        router
+            .add(spec.clone(), entry.clone())
+            .unwrap_or_else(|never| match never {});
+    }
+
+    match (command, args) {
+        ("match", [path]) => match_path(&router, path),
+        ("explain", [path]) => explain(&router, path),
+        ("lint", []) => lint(&router, &entries),
+        ("table", []) => table(&router),
+        ("reverse", [name, pairs @ ..]) => reverse(&router, name, pairs),
+        ("replay", [log]) => replay(&router, log),
+        _ => Err(String::from(USAGE)),
+    }
+}
+
+fn parse(source: &str) -> Result<Vec<(RouteSpec, Entry)>, String> {
+    let mut entries = vec![];
+    for (index, line) in source.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.split('#').next().unwrap_or_default();
+        let mut words = line.split_whitespace();
+        let route = match words.next() {
+            Some(route) => route,
+            None => continue,
+        };
+
+        let spec = route
+            .parse::<RouteSpec>()
+            .map_err(|e| format!("line {}: {}", line_number, e))?;
+        let name = words.next().map(String::from);
+        if let Some(extra) = words.next() {
+            return Err(format!("line {}: unexpected `{}`", line_number, extra));
+        }
+
+        entries.push((
+            spec,
+            Entry {
+                name,
+                line: line_number,
+            },
+        ));
+    }
+    Ok(entries)
+}
+
+fn match_path(router: &Router<Entry>, path: &str) -> Result<(), String> {
+    let m = router
+        .best_match(path)
+        .ok_or_else(|| format!("no route matches {}", path))?;
+
+    println!("route: {} (line {})", m.route(), m.line);
+    println!("name: {}", m.name());
+    let captures = m.captures();
+    for (key, value) in captures.iter() {
+        println!("{} = {}", key, value);
+    }
+    if let Some(wildcard) = captures.wildcard() {
+        println!("* = {}", wildcard);
+    }
+    Ok(())
+}
+
+fn explain(router: &Router<Entry>, path: &str) -> Result<(), String> {
+    let matches = router.matches(path);
+    let width = route_width(router);
+    for (route, entry) in router.iter() {
+        let position = matches.iter().position(|m| ptr::eq(m.route(), route));
+        let (marker, status) = match position {
+            Some(0) => (">", "selected"),
+            Some(_) => (" ", "matches, but a route above takes precedence"),
+            None => (" ", "-"),
+        };
+        println!(
+            "{} {:<width$}  {:<12}  {}",
+            marker,
+            route.to_string(),
+            entry.name(),
+            status,
+            width = width
+        );
+    }
+
+    if matches.is_empty() {
+        Err(format!("no route matches {}", path))
+    } else {
+        Ok(())
+    }
+}
+
+fn table(router: &Router<Entry>) -> Result<(), String> {
+    let width = route_width(router);
+    for (route, entry) in router.iter() {
+        println!(
+            "{:<width$}  {:<12}  line {}",
+            route.to_string(),
+            entry.name(),
+            entry.line,
+            width = width
+        );
+    }
+    Ok(())
+}
+
+fn lint(router: &Router<Entry>, entries: &[(RouteSpec, Entry)]) -> Result<(), String> {
+    let mut problems = vec![];
+
+    for (index, (spec, entry)) in entries.iter().enumerate() {
+        let duplicate = entries[..index]
+            .iter()
+            .find(|(other, _)| other.to_string() == spec.to_string());
+        if let Some((_, original)) = duplicate {
+            problems.push(format!(
+                "line {}: {} duplicates line {}",
+                entry.line, spec, original.line
+            ));
+        }
+    }
+
+    for (route, entry) in router.iter() {
+        let samples = sample_paths(route);
+        if samples.is_empty() {
+            continue;
+        }
+
+        let claimed: Vec<_> = samples
+            .iter()
+            .filter_map(|sample| {
+                router
+                    .best_match(sample)
+                    .filter(|m| !ptr::eq(m.route(), route))
+                    .map(|m| (sample, m))
+            })
+            .collect();
+
+        // duplicates have already been reported
+        match claimed.first() {
+            Some((sample, m))
+                if claimed.len() == samples.len() && m.route().to_string() != route.to_string() =>
+            {
+                problems.push(format!(
+                    "line {}: {} is shadowed by {} (line {}), as for {}",
+                    entry.line,
+                    route,
+                    m.route(),
+                    m.line,
+                    sample
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    if problems.is_empty() {
+        println!("no problems found in {} routes", router.len());
+        Ok(())
+    } else {
+        for problem in &problems {
+            println!("{}", problem);
+        }
+        Err(format!("{} problems found", problems.len()))
+    }
+}
+
+/// example paths for a route, using each param's name as its value
+fn sample_paths(route: &RouteSpec) -> Vec<String> {
+    let mut values = vec![(String::from("*"), vec![String::from("wildcard")])];
+    for segment in route.segments() {
+        if let Segment::Param(name)
+        | Segment::OptionalParam(name)
+        | Segment::OneOrMore(name)
+        | Segment::ZeroOrMore(name) = segment
+        {
+            values.push((name.to_string(), vec![name.to_string()]));
+        }
+    }
+
+    let mut single = Router::new();
+    single.add(route.clone(), ()).ok();
+    single.expand_with(values)
+}
+
+fn reverse(router: &Router<Entry>, name: &str, pairs: &[String]) -> Result<(), String> {
+    let route = router
+        .iter()
+        .find(|(_, entry)| entry.name.as_deref() == Some(name))
+        .map(|(route, _)| route)
+        .ok_or_else(|| format!("no route is named {}", name))?;
+
+    let mut captures = Captures::new();
+    for pair in pairs {
+        let (key, value) = pair
+            .split_once('=')
+            .ok_or_else(|| format!("expected key=value, found `{}`", pair))?;
+        if key == "*" {
+            captures.set_wildcard(value);
+        } else {
+            captures.push((key, value));
+        }
+    }
+
+    let reverse_match = route
+        .template(&captures)
+        .ok_or_else(|| format!("{} cannot be built from {}", route, pairs.join(" ")))?;
+    println!("{}", reverse_match);
+    Ok(())
+}
+
+fn replay(router: &Router<Entry>, log: &str) -> Result<(), String> {
+    let log = fs::read_to_string(log).map_err(|e| format!("could not read {}: {}", log, e))?;
+    let paths: Vec<&str> = log.lines().filter_map(request_path).collect();
+
+    // matching is timed on its own so that counting does not skew
+    // the throughput
+    let start = Instant::now();
+    for path in &paths {
+        black_box(router.best_match(path));
+    }
+    let elapsed = start.elapsed();
+
+    let mut hits = BTreeMap::new();
+    let mut unmatched: BTreeMap<&str, usize> = BTreeMap::new();
+    for path in &paths {
+        match router.lookup(path) {
+            Some(index) => *hits.entry(index.id()).or_insert(0) += 1,
+            None => *unmatched.entry(path).or_default() += 1,
+        }
+    }
+
+    println!(
+        "replayed {} paths in {:?} ({} paths/s)",
+        paths.len(),
+        elapsed,
+        throughput(paths.len(), elapsed)
+    );
+
+    println!("\nhits:");
+    let width = route_width(router);
+    for (route, entry) in router.iter() {
+        let hits = route.id().and_then(|id| hits.get(&id)).unwrap_or(&0);
+        println!(
+            "{:>8}  {:<width$}  {}",
+            hits,
+            route.to_string(),
+            entry.name(),
+            width = width
+        );
+    }
+
+    let mut unmatched: Vec<_> = unmatched.into_iter().collect();
+    unmatched.sort_by(|(_, a), (_, b)| b.cmp(a));
+    println!(
+        "\nunmatched: {}",
+        unmatched.iter().map(|(_, count)| count).sum::<usize>()
+    );
+    for (path, count) in unmatched {
+        println!("{:>8}  {}", count, path);
+    }
+
+    Ok(())
+}
+
+/// the path of a request from a line of an access log, or the line
+/// itself if it is just a path
+fn request_path(line: &str) -> Option<&str> {
+    let line = line.trim();
+    let path = if line.starts_with('/') {
+        line.split_whitespace().next()?
+    } else {
+        // common log format: host ident user [time] "GET /path HTTP/1.1" ...
+        let request = line.split('"').nth(1)?;
+        request.split_whitespace().nth(1)?
+    };
+
+    path.split('?').next().filter(|path| path.starts_with('/'))
+}
+
+fn throughput(count: usize, elapsed: Duration) -> u128 {
+    match elapsed.as_nanos() {
+        0 => 0,
+        nanos => count as u128 * 1_000_000_000 / nanos,
+    }
+}
+
+fn route_width(router: &Router<Entry>) -> usize {
+    router
+        .iter()
+        .map(|(route, _)| route.to_string().len())
+        .max()
+        .unwrap_or_default()
+}
@@ -1,12 +1,14 @@
+use crate::Collision;
 use smartcow::SmartCow;
 use std::{
     borrow::Cow,
+    fmt::{self, Debug, Formatter},
     iter::FromIterator,
     ops::{Deref, DerefMut},
 };
 
 /// An individual key-value pair
-#[derive(Debug, Default)]
+#[derive(Default)]
 pub struct Capture<'key, 'value> {
     key: SmartCow<'key>,
     value: SmartCow<'value>,
@@ -45,18 +47,75 @@ impl<'key, 'value> Capture<'key, 'value> {
 }
 
 /// Captured params and a wildcard
-#[derive(Debug, Default)]
+#[derive(Default)]
 pub struct Captures<'keys, 'values> {
     pub(crate) params: Vec<Capture<'keys, 'values>>,
     pub(crate) wildcard: Option<SmartCow<'values>>,
 }
 
+impl Debug for Capture<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Capture")
+            .field(&self.name())
+            .field(&self.value())
+            .finish()
+    }
+}
+
+/// Captures are displayed as a map from each name to its value, with
+/// the wildcard, if any, under `"*"`.
+///
+/// ```rust
+/// let mut captures = routefinder::Captures::new();
+/// captures.push(routefinder::Capture::new("id", "1"));
+/// captures.set_wildcard("a/b");
+/// assert_eq!(format!("{:?}", captures), r#"{"id": "1", "*": "a/b"}"#);
+/// ```
+impl Debug for Captures<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.iter())
+            .entries(self.wildcard().map(|wildcard| ("*", wildcard)))
+            .finish()
+    }
+}
+
 impl<'keys, 'values> Captures<'keys, 'values> {
     /// Builds a new empty Captures
     pub fn new() -> Self {
         Self::default()
     }
 
+    /// Builds a new empty Captures with room for this many params
+    /// without reallocating. [`Match::captures`][crate::Match::captures]
+    /// sizes its Captures for the params of the route.
+    ///
+    /// ```rust
+    /// use routefinder::{Capture, Captures};
+    /// let mut captures = Captures::with_capacity(2);
+    /// assert!(captures.capacity() >= 2);
+    /// captures.push(Capture::new("org", "jbr"));
+    /// captures.reserve(3);
+    /// assert!(captures.capacity() >= 4);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            params: Vec::with_capacity(capacity),
+            wildcard: None,
+        }
+    }
+
+    /// Reserves room for at least this many more params
+    pub fn reserve(&mut self, additional: usize) {
+        self.params.reserve(additional);
+    }
+
+    /// Returns the number of params this Captures can hold without
+    /// reallocating
+    pub fn capacity(&self) -> usize {
+        self.params.capacity()
+    }
+
     /// Transforms this Captures into a 'static Captures which can
     /// outlive the source data. This allocates new strings if needed,
     /// and should be avoided unless necessary for a particular
@@ -68,8 +127,24 @@ impl<'keys, 'values> Captures<'keys, 'values> {
         }
     }
 
+    /// owns the keys, but not the values, of this Captures, so that it
+    /// can outlive the route that it came from
+    pub(crate) fn into_owned_keys(self) -> Captures<'static, 'values> {
+        Captures {
+            params: self
+                .params
+                .into_iter()
+                .map(|c| Capture {
+                    key: c.key.into_owned(),
+                    value: c.value,
+                })
+                .collect(),
+            wildcard: self.wildcard,
+        }
+    }
+
     /// returns a slice of captures
-    pub fn params(&self) -> &[Capture] {
+    pub fn params(&self) -> &[Capture<'_, '_>] {
         &self.params[..]
     }
 
@@ -106,6 +181,36 @@ impl<'keys, 'values> Captures<'keys, 'values> {
         self.wildcard = captures.wildcard;
     }
 
+    /// Merges the params of another Captures into these, resolving
+    /// params with the same name as the [`Collision`] specifies. The
+    /// wildcard of the other Captures replaces this one's, even if it
+    /// has none, as the other captures are expected to come from
+    /// routing what this one's wildcard matched.
+    ///
+    /// ```rust
+    /// use routefinder::{Captures, Collision};
+    /// let mut outer: Captures = [("tenant", "acme"), ("id", "1")].into();
+    /// outer.set_wildcard("users/2");
+    /// let inner: Captures = [("id", "2")].into();
+    ///
+    /// outer.merge(inner, Collision::KeepLast);
+    /// assert_eq!(outer.get("tenant"), Some("acme"));
+    /// assert_eq!(outer.get("id"), Some("2"));
+    /// assert_eq!(outer.wildcard(), None);
+    /// ```
+    pub fn merge(&mut self, captures: Captures<'keys, 'values>, collision: Collision) {
+        self.reserve(captures.params.len());
+        for capture in captures.params {
+            let existing = self.params.iter_mut().find(|c| c.key == capture.key);
+            match (existing, collision) {
+                (Some(_), Collision::KeepFirst) => {}
+                (Some(existing), Collision::KeepLast) => *existing = capture,
+                _ => self.params.push(capture),
+            }
+        }
+        self.wildcard = captures.wildcard;
+    }
+
     /// Iterate over params as str pairs
     pub fn iter(&self) -> Iter<'_, '_, '_> {
         self.into()
@@ -0,0 +1,91 @@
+use crate::{RouteId, RouteSpec};
+use std::fmt::{self, Display, Formatter};
+
+/// A change to the routes of a [`Router`][crate::Router], passed to
+/// the observers added with
+/// [`Router::on_change`][crate::Router::on_change] as the change is
+/// made.
+///
+/// ```rust
+/// use routefinder::{ChangeEvent, Router};
+/// use std::sync::{Arc, Mutex};
+///
+/// let log = Arc::new(Mutex::new(vec![]));
+/// let logged = log.clone();
+/// let mut router = Router::new();
+/// router.on_change(move |event: &ChangeEvent<'_>| {
+///     logged.lock().unwrap().push(event.to_string());
+/// });
+///
+/// let id = router.add("/users/:id", "v1").unwrap();
+/// router.replace(id, "v2");
+/// router.remove(id);
+/// assert_eq!(
+///     *log.lock().unwrap(),
+///     ["added /users/:id", "replaced /users/:id", "removed /users/:id"]
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeEvent<'router> {
+    /// A route, or a deny route from [`Router::deny`][crate::Router::deny],
+    /// was added
+    Added {
+        /// the id assigned to the route
+        id: RouteId,
+        /// the route
+        route: &'router RouteSpec,
+    },
+
+    /// The handler of a route was replaced with
+    /// [`Router::replace`][crate::Router::replace]
+    Replaced {
+        /// the id of the route
+        id: RouteId,
+        /// the route
+        route: &'router RouteSpec,
+    },
+
+    /// A route was removed, either with
+    /// [`Router::remove`][crate::Router::remove] or when mapping the
+    /// router's handlers
+    Removed {
+        /// the id that the route had
+        id: RouteId,
+        /// the route
+        route: &'router RouteSpec,
+    },
+}
+
+impl<'router> ChangeEvent<'router> {
+    /// Returns the id of the route that changed
+    pub fn id(&self) -> RouteId {
+        match self {
+            ChangeEvent::Added { id, .. }
+            | ChangeEvent::Replaced { id, .. }
+            | ChangeEvent::Removed { id, .. } => *id,
+        }
+    }
+
+    /// Returns the route that changed
+    pub fn route(&self) -> &'router RouteSpec {
+        match self {
+            ChangeEvent::Added { route, .. }
+            | ChangeEvent::Replaced { route, .. }
+            | ChangeEvent::Removed { route, .. } => route,
+        }
+    }
+}
+
+impl Display for ChangeEvent<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let change = match self {
+            ChangeEvent::Added { .. } => "added",
+            ChangeEvent::Replaced { .. } => "replaced",
+            ChangeEvent::Removed { .. } => "removed",
+        };
+        write!(f, "{} {}", change, self.route())
+    }
+}
+
+/// an observer added with [`Router::on_change`][crate::Router::on_change]
+pub(crate) type Observer = dyn Fn(&ChangeEvent<'_>) + Send + Sync;
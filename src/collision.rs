@@ -0,0 +1,19 @@
+/// Which value is kept when two [`Captures`][crate::Captures] being
+/// merged both have a param with the same name. See
+/// [`Captures::merge`][crate::Captures::merge] and
+/// [`Match::chain_with`][crate::Match::chain_with].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Collision {
+    /// The value that is already present is kept, and the other is
+    /// discarded. This is the default.
+    #[default]
+    KeepFirst,
+
+    /// The value that is merged in replaces the value that is already
+    /// present, in the same position.
+    KeepLast,
+
+    /// Both values are kept, in order, so
+    /// [`Captures::get`][crate::Captures::get] returns the first.
+    KeepBoth,
+}
@@ -0,0 +1,295 @@
+use crate::{Capture, Captures, RouteId, RouteSpec, Router};
+use std::{
+    fmt::{self, Debug, Formatter},
+    ops::Deref,
+};
+
+/// A router for command-line arguments, for tools that dispatch
+/// subcommands with routefinder's captures and precedence.
+///
+/// A command is a whitespace-separated list of words in routefinder's
+/// syntax, such as `remote add :name :url`, where each word matches one
+/// argument. As in a path, `:name?` is an optional argument, `:name+`
+/// and `:name*` capture one or more or zero or more arguments, and a
+/// trailing `*` captures the rest as the wildcard. Arguments are
+/// matched whole, so they may contain slashes and spaces. A capture of
+/// several arguments joins them with single spaces.
+///
+/// ```rust
+/// let mut router = routefinder::CommandRouter::new();
+/// router.add("remote add :name :url", "add").unwrap();
+/// router.add("remote remove :name", "remove").unwrap();
+/// router.add("remote :name", "show").unwrap();
+/// router.add("commit :files*", "commit").unwrap();
+///
+/// let m = router.best_match(&["remote", "add", "origin", "https://example.com/repo.git"]).unwrap();
+/// assert_eq!(*m, "add");
+/// assert_eq!(m.captures().get("url"), Some("https://example.com/repo.git"));
+/// assert_eq!(*router.best_match(&["remote", "origin"]).unwrap(), "show");
+///
+/// let m = router.best_match(&["commit", "a.rs", "b.rs"]).unwrap();
+/// assert_eq!(m.captures().get("files"), Some("a.rs b.rs"));
+///
+/// assert_eq!(router.suggest(&["remote", "rmove", "origin"]), ["remote remove origin"]);
+/// ```
+pub struct CommandRouter<Handler> {
+    router: Router<Handler>,
+}
+
+impl<Handler> Debug for CommandRouter<Handler> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.commands()).finish()
+    }
+}
+
+impl<Handler> Default for CommandRouter<Handler> {
+    fn default() -> Self {
+        Self {
+            router: Router::new(),
+        }
+    }
+}
+
+impl<Handler> CommandRouter<Handler> {
+    /// Builds a new command router
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a command to the router. Returns an error if a word
+    /// contains a slash or the command does not parse as a route.
+    pub fn add(&mut self, command: &str, handler: Handler) -> Result<RouteId, String> {
+        let words: Vec<&str> = command.split_whitespace().collect();
+        if let Some(word) = words.iter().find(|word| word.contains('/')) {
+            return Err(format!(
+                "command words cannot contain `/`, found `{}`",
+                word
+            ));
+        }
+
+        let spec: RouteSpec = format!("/{}", words.join("/")).parse()?;
+        self.router
+            .add(spec, handler)
+            .map_err(|never| match never {})
+    }
+
+    /// Returns the best match for these arguments, not including the
+    /// name of the program
+    pub fn best_match<'a, A>(&'a self, args: &[A]) -> Option<CommandMatch<'a, Handler>>
+    where
+        A: AsRef<str>,
+    {
+        let path = encode(args);
+        let m = self.router.best_match(&path)?;
+        let route = m.route();
+        let captures = m.captures();
+
+        let mut decoded = Captures::with_capacity(captures.len());
+        for capture in captures.params() {
+            let name = route
+                .segments()
+                .iter()
+                .find_map(|segment| segment.name().filter(|name| *name == capture.name()))
+                .unwrap_or_default();
+            decoded.push(Capture::new(name, decode_list(capture.value())));
+        }
+        if let Some(wildcard) = captures.wildcard() {
+            decoded.set_wildcard(decode_list(wildcard));
+        }
+
+        Some(CommandMatch {
+            route,
+            handler: m.handler(),
+            captures: decoded,
+        })
+    }
+
+    /// Suggests corrections for arguments that no command matches: for
+    /// the first argument that is not a command word at its position
+    /// and is within a small edit distance of some, each of those
+    /// words, closest first, with the other arguments unchanged.
+    /// Returns nothing if a command matches the arguments.
+    pub fn suggest<A: AsRef<str>>(&self, args: &[A]) -> Vec<String> {
+        if self.best_match(args).is_some() {
+            return vec![];
+        }
+
+        for (index, arg) in args.iter().enumerate() {
+            let arg = arg.as_ref();
+            let mut prefix = encode(&args[..index]);
+            if !prefix.ends_with('/') {
+                prefix.push('/');
+            }
+
+            let completion = self.router.complete(&prefix);
+            if completion.literals().any(|literal| literal == arg) {
+                continue;
+            }
+
+            let limit = (arg.chars().count() / 3).max(1);
+            let mut candidates: Vec<(usize, &str)> = completion
+                .literals()
+                .map(|literal| (distance(arg, literal), literal))
+                .filter(|(distance, _)| *distance <= limit)
+                .collect();
+            candidates.sort_by_key(|(distance, _)| *distance);
+            if candidates.is_empty() {
+                if completion.param() || completion.wildcard() {
+                    continue;
+                }
+                break;
+            }
+
+            return candidates
+                .into_iter()
+                .map(|(_, literal)| {
+                    let words = args.iter().map(AsRef::as_ref);
+                    let before = words.clone().take(index);
+                    let after = words.skip(index + 1);
+                    before
+                        .chain(Some(literal))
+                        .chain(after)
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .collect();
+        }
+
+        vec![]
+    }
+
+    /// Returns each command, in order of precedence
+    pub fn commands(&self) -> impl Iterator<Item = String> + '_ {
+        self.router.iter().map(|(route, _)| command(route))
+    }
+
+    /// Returns the underlying [`Router`], whose routes are the
+    /// commands with their words separated by slashes
+    pub fn router(&self) -> &Router<Handler> {
+        &self.router
+    }
+
+    /// returns the number of commands that have been added
+    pub fn len(&self) -> usize {
+        self.router.len()
+    }
+
+    /// returns true if no commands have been added
+    pub fn is_empty(&self) -> bool {
+        self.router.is_empty()
+    }
+}
+
+/// The output of a successful application of a [`CommandRouter`] to a
+/// list of arguments. It dereferences to the contained Handler type.
+#[derive(Debug)]
+pub struct CommandMatch<'router, Handler> {
+    route: &'router RouteSpec,
+    handler: &'router Handler,
+    captures: Captures<'router, 'static>,
+}
+
+impl<'router, Handler> CommandMatch<'router, Handler> {
+    /// Returns a reference to the handler associated with this command
+    pub fn handler(&self) -> &'router Handler {
+        self.handler
+    }
+
+    /// Returns the command that matched, as it was added
+    pub fn command(&self) -> String {
+        command(self.route)
+    }
+
+    /// Returns the [`Captures`] of the arguments
+    pub fn captures(&self) -> &Captures<'router, 'static> {
+        &self.captures
+    }
+
+    /// Takes ownership of the [`Captures`] of the arguments
+    pub fn into_captures(self) -> Captures<'router, 'static> {
+        self.captures
+    }
+}
+
+impl<'router, Handler> Deref for CommandMatch<'router, Handler> {
+    type Target = Handler;
+
+    fn deref(&self) -> &Self::Target {
+        self.handler
+    }
+}
+
+/// displays a route as a command
+fn command(route: &RouteSpec) -> String {
+    route.to_string().trim_start_matches('/').replace('/', " ")
+}
+
+/// joins arguments as the segments of a path. Slashes and percent
+/// signs are percent-encoded, as are arguments that would be dot
+/// segments, and an empty argument is a lone percent sign.
+fn encode<A: AsRef<str>>(args: &[A]) -> String {
+    let mut path = String::from("/");
+    for (index, arg) in args.iter().enumerate() {
+        if index > 0 {
+            path.push('/');
+        }
+        match arg.as_ref() {
+            "" => path.push('%'),
+            "." => path.push_str("%2E"),
+            ".." => path.push_str("%2E%2E"),
+            arg => {
+                for c in arg.chars() {
+                    match c {
+                        '%' => path.push_str("%25"),
+                        '/' => path.push_str("%2F"),
+                        c => path.push(c),
+                    }
+                }
+            }
+        }
+    }
+    path
+}
+
+/// decodes each argument of a capture that may span several, joining
+/// them with spaces
+fn decode_list(value: &str) -> String {
+    value.split('/').map(decode).collect::<Vec<_>>().join(" ")
+}
+
+/// reverses [`encode`] for a single argument
+fn decode(segment: &str) -> String {
+    if segment == "%" {
+        return String::new();
+    }
+
+    let mut decoded = String::with_capacity(segment.len());
+    let mut rest = segment;
+    while let Some(index) = rest.find('%') {
+        decoded.push_str(&rest[..index]);
+        let escape = rest.get(index..index + 3).unwrap_or("%");
+        decoded.push(match escape {
+            "%2F" => '/',
+            "%2E" => '.',
+            _ => '%',
+        });
+        rest = &rest[index + escape.len()..];
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// the levenshtein distance between two words, in characters
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != *b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
@@ -0,0 +1,137 @@
+use crate::{RouteSpec, Segment};
+use std::collections::BTreeSet;
+
+/// What can follow a partial path in the routes of a
+/// [`Router`][crate::Router], for tab-completion and route discovery.
+/// Returned by [`Router::complete`][crate::Router::complete].
+///
+/// The partial path is split into the segments before its last slash,
+/// which must each be matched by a route, and the text after it, which
+/// the next segment is completed from.
+///
+/// ```rust
+/// let mut router = routefinder::Router::new();
+/// router.add("/users/:id", ()).unwrap();
+/// router.add("/users/new", ()).unwrap();
+/// router.add("/uploads/*", ()).unwrap();
+///
+/// let completion = router.complete("/u");
+/// assert_eq!(completion.literals().collect::<Vec<_>>(), ["uploads", "users"]);
+/// assert!(!completion.param());
+///
+/// let completion = router.complete("/users/");
+/// assert_eq!(completion.literals().collect::<Vec<_>>(), ["new"]);
+/// assert!(completion.param());
+/// assert!(router.complete("/uploads/").wildcard());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Completion {
+    literals: BTreeSet<String>,
+    param: bool,
+    wildcard: bool,
+}
+
+impl Completion {
+    pub(crate) fn new<'a>(routes: impl Iterator<Item = &'a RouteSpec>, prefix: &str) -> Self {
+        let prefix = prefix.trim_start_matches('/');
+        let (complete, partial) = prefix.rsplit_once('/').unwrap_or(("", prefix));
+        let segments: Vec<&str> = complete.split('/').filter(|s| !s.is_empty()).collect();
+
+        let mut completion = Self::default();
+        for route in routes {
+            let groups: Vec<&[Segment]> = route
+                .segments()
+                .split(|segment| *segment == Segment::Slash)
+                .filter(|group| !group.is_empty())
+                .collect();
+            completion.walk(route, &groups, &segments, partial);
+        }
+        completion
+    }
+
+    /// follows the slash-separated groups of a route through the
+    /// complete segments of the path, and records what the route
+    /// expects after them
+    fn walk(&mut self, route: &RouteSpec, groups: &[&[Segment]], segments: &[&str], partial: &str) {
+        let (group, rest) = match groups.split_first() {
+            Some(split) => split,
+            None => {
+                // a prefix route matches anything after its end
+                self.wildcard |= route.mode().is_prefix();
+                return;
+            }
+        };
+
+        if group.iter().any(|segment| {
+            matches!(
+                segment,
+                Segment::Wildcard | Segment::OneOrMore(_) | Segment::ZeroOrMore(_)
+            )
+        }) {
+            self.wildcard = true;
+            return;
+        }
+
+        if let [Segment::OptionalParam(_)] = group {
+            self.walk(route, rest, segments, partial);
+        }
+
+        match segments.split_first() {
+            Some((segment, remaining)) => {
+                if group_matches(route, group, segment) {
+                    self.walk(route, rest, remaining, partial);
+                }
+            }
+
+            None if group
+                .iter()
+                .all(|segment| matches!(segment, Segment::Exact(_) | Segment::Dot)) =>
+            {
+                let literal: String = group.iter().map(ToString::to_string).collect();
+                if literal.starts_with(partial) {
+                    self.literals.insert(literal);
+                }
+            }
+
+            None => self.param = true,
+        }
+    }
+
+    /// Returns the literal text of each next path segment, in sorted
+    /// order, that begins with the text after the last slash
+    pub fn literals(&self) -> impl Iterator<Item = &str> {
+        self.literals.iter().map(String::as_str)
+    }
+
+    /// Returns true if a route has a param, or other text that is not
+    /// literal, as the next path segment
+    pub fn param(&self) -> bool {
+        self.param
+    }
+
+    /// Returns true if a route has a wildcard or multi-segment param,
+    /// or is a prefix route, that could match what follows
+    pub fn wildcard(&self) -> bool {
+        self.wildcard
+    }
+
+    /// Returns true if nothing in the routes can follow the partial
+    /// path
+    pub fn is_empty(&self) -> bool {
+        self.literals.is_empty() && !self.param && !self.wildcard
+    }
+}
+
+/// whether one slash-separated group of a route matches a path segment
+fn group_matches(route: &RouteSpec, group: &[Segment], segment: &str) -> bool {
+    if RouteSpec::from(group.to_vec()).matches(segment).is_none() {
+        return false;
+    }
+
+    group.iter().all(|part| match part {
+        Segment::Param(name) | Segment::OptionalParam(name) if group.len() == 1 => route
+            .constraint(name)
+            .is_none_or(|constraint| constraint.is_match(segment)),
+        _ => true,
+    })
+}
@@ -0,0 +1,148 @@
+use std::{
+    any::Any,
+    fmt::{self, Debug, Formatter},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// A check that is evaluated each time a route added with
+/// [`Router::add_conditional`][crate::Router::add_conditional] is
+/// considered. When it returns false, the route is skipped and lower
+/// precedence routes may match instead.
+///
+/// This is implemented for any `Fn() -> bool` and for [`Flag`].
+pub trait Condition: Send + Sync {
+    /// whether the route is currently enabled
+    fn is_enabled(&self) -> bool;
+}
+
+impl<F> Condition for F
+where
+    F: Fn() -> bool + Send + Sync,
+{
+    fn is_enabled(&self) -> bool {
+        self()
+    }
+}
+
+/// A shared on/off switch for conditional routes. Clones of a flag
+/// share the same state, so a flag can be handed to the router and
+/// toggled elsewhere without rebuilding the router.
+///
+/// ```rust
+/// use routefinder::{Flag, Router};
+/// let new_checkout = Flag::new(false);
+/// let mut router = Router::new();
+/// router.add("/checkout", "old").unwrap();
+/// router.add_conditional("/checkout", "new", new_checkout.clone()).unwrap();
+///
+/// assert_eq!(*router.best_match("/checkout").unwrap(), "old");
+/// new_checkout.enable();
+/// assert_eq!(*router.best_match("/checkout").unwrap(), "new");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Flag(Arc<AtomicBool>);
+
+impl Flag {
+    /// Builds a new flag in the provided state
+    pub fn new(enabled: bool) -> Self {
+        Self(Arc::new(AtomicBool::new(enabled)))
+    }
+
+    /// Sets the state of this flag and all of its clones
+    pub fn set(&self, enabled: bool) {
+        self.0.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Turns this flag on
+    pub fn enable(&self) {
+        self.set(true);
+    }
+
+    /// Turns this flag off
+    pub fn disable(&self) {
+        self.set(false);
+    }
+
+    /// whether this flag is on
+    pub fn is_enabled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl Condition for Flag {
+    fn is_enabled(&self) -> bool {
+        Flag::is_enabled(self)
+    }
+}
+
+/// the condition attached to a [`RouteSpec`][crate::RouteSpec]:
+/// either a [`Condition`], or a guard on the context of a match from
+/// [`Router::add_guarded`][crate::Router::add_guarded]
+#[derive(Clone)]
+pub(crate) enum RouteCondition {
+    Condition(Arc<dyn Condition>),
+    Guard(Arc<Guard>),
+}
+
+/// a guard on a context of any type
+type Guard = dyn Fn(&dyn Any) -> bool + Send + Sync;
+
+impl RouteCondition {
+    pub(crate) fn new(condition: impl Condition + 'static) -> Self {
+        Self::Condition(Arc::new(condition))
+    }
+
+    /// a guard that is met for a context of type `C` for which it
+    /// returns true
+    pub(crate) fn guard<C: 'static>(guard: impl Fn(&C) -> bool + Send + Sync + 'static) -> Self {
+        Self::Guard(Arc::new(move |context: &dyn Any| {
+            context.downcast_ref::<C>().is_some_and(&guard)
+        }))
+    }
+
+    /// whether the route is enabled for this context, if any. guards
+    /// are never met without a context
+    pub(crate) fn is_enabled(&self, context: Option<&dyn Any>) -> bool {
+        match (self, context) {
+            (Self::Condition(condition), _) => condition.is_enabled(),
+            (Self::Guard(guard), Some(context)) => guard(context),
+            (Self::Guard(_), None) => false,
+        }
+    }
+
+    pub(crate) fn is_guard(&self) -> bool {
+        matches!(self, Self::Guard(_))
+    }
+
+    /// an arbitrary order that is only equal for the same condition
+    pub(crate) fn cmp_identity(&self, other: &Self) -> std::cmp::Ordering {
+        fn key(condition: &RouteCondition) -> (bool, usize) {
+            match condition {
+                RouteCondition::Condition(c) => (false, Arc::as_ptr(c).cast::<()>() as usize),
+                RouteCondition::Guard(g) => (true, Arc::as_ptr(g).cast::<()>() as usize),
+            }
+        }
+        key(self).cmp(&key(other))
+    }
+}
+
+impl PartialEq for RouteCondition {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Condition(a), Self::Condition(b)) => Arc::ptr_eq(a, b),
+            (Self::Guard(a), Self::Guard(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for RouteCondition {}
+
+impl Debug for RouteCondition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("RouteCondition")
+    }
+}
@@ -0,0 +1,115 @@
+use smartstring::alias::String as SmartString;
+use std::{
+    fmt::{self, Debug, Formatter},
+    hash::{Hash, Hasher},
+    str::Split,
+};
+
+/// a pattern that the value of a named capture must satisfy, such as
+/// the `(\d+)` in the express-style route `/users/:id(\d+)`
+#[derive(Clone)]
+pub(crate) struct Constraint {
+    name: SmartString,
+    source: SmartString,
+    kind: Kind,
+}
+
+#[derive(Clone)]
+enum Kind {
+    #[cfg(feature = "regex")]
+    Regex(regex::Regex),
+
+    /// a sequence of path segments, where `*` matches any one segment
+    /// and `**` matches any number of segments, as in the bindings of
+    /// [`Syntax::HttpRule`][crate::Syntax::HttpRule] templates
+    Segments,
+}
+
+impl Constraint {
+    #[cfg(feature = "regex")]
+    pub(crate) fn new(name: &str, source: &str) -> Result<Self, String> {
+        let regex = regex::Regex::new(&format!("^(?:{})$", source))
+            .map_err(|e| format!("invalid pattern for `{}`: {}", name, e))?;
+
+        Ok(Self {
+            name: SmartString::from(name),
+            source: SmartString::from(source),
+            kind: Kind::Regex(regex),
+        })
+    }
+
+    #[cfg(not(feature = "regex"))]
+    pub(crate) fn new(name: &str, _source: &str) -> Result<Self, String> {
+        Err(format!(
+            "the pattern for `{}` requires the `regex` feature of routefinder",
+            name
+        ))
+    }
+
+    /// a constraint that matches whole path segments, such as
+    /// `projects/*/locations/*`
+    pub(crate) fn segments(name: &str, source: &str) -> Self {
+        Self {
+            name: SmartString::from(name),
+            source: SmartString::from(source),
+            kind: Kind::Segments,
+        }
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub(crate) fn is_match(&self, value: &str) -> bool {
+        match &self.kind {
+            #[cfg(feature = "regex")]
+            Kind::Regex(regex) => regex.is_match(value),
+            Kind::Segments => segments_match(self.source.split('/'), value.split('/')),
+        }
+    }
+}
+
+fn segments_match(mut patterns: Split<'_, char>, mut values: Split<'_, char>) -> bool {
+    match patterns.next() {
+        None => values.next().is_none(),
+        Some("**") => loop {
+            if segments_match(patterns.clone(), values.clone()) {
+                return true;
+            }
+            if values.next().is_none() {
+                return false;
+            }
+        },
+        Some(pattern) => match values.next() {
+            Some(value) if !value.is_empty() && (pattern == "*" || pattern == value) => {
+                segments_match(patterns, values)
+            }
+            _ => false,
+        },
+    }
+}
+
+impl PartialEq for Constraint {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.source == other.source
+    }
+}
+
+impl Eq for Constraint {}
+
+impl Hash for Constraint {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.source.hash(state);
+    }
+}
+
+impl Debug for Constraint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, ":{}({})", self.name, self.source)
+    }
+}
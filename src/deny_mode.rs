@@ -0,0 +1,16 @@
+/// How the routes added with [`Router::deny`][crate::Router::deny]
+/// block the other routes of a router. See
+/// [`Router::set_deny_mode`][crate::Router::set_deny_mode].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DenyMode {
+    /// A deny route that matches a path blocks the routes that it
+    /// takes precedence over, so more specific routes still match. A
+    /// deny route takes precedence over a route with the same spec.
+    /// This is the default.
+    #[default]
+    Precedence,
+
+    /// A deny route that matches a path blocks every route for that
+    /// path, regardless of precedence.
+    Always,
+}
@@ -0,0 +1,79 @@
+//! Helpers for invoking async handlers stored in a [`Router`]
+//!
+//! These are available with the `dispatch` feature. Each helper finds
+//! the best match for a path and calls its handler with the
+//! [`Captures`] and a caller-provided context, returning the future
+//! that the handler produces without awaiting it.
+//!
+//! ```rust
+//! use routefinder::{Captures, Router};
+//! use std::future::{ready, Ready};
+//!
+//! type Handler = fn(Captures<'static, 'static>, u32) -> Ready<String>;
+//!
+//! let mut router: Router<Handler> = Router::new();
+//! router
+//!     .add("/users/:id", |captures, request_id| {
+//!         ready(format!("user {} for request {}", captures.get("id").unwrap(), request_id))
+//!     })
+//!     .unwrap();
+//!
+//! // the returned future owns its captures, so it can be spawned
+//! // or otherwise outlive the path
+//! let future = router.dispatch_owned(&String::from("/users/1"), 7).ok().unwrap();
+//!
+//! // on a miss, the context is returned so that the caller can use it
+//! assert_eq!(router.dispatch_owned("/nope", 7).err(), Some(7));
+//! ```
+
+use crate::{Captures, Router};
+use std::future::Future;
+
+impl<Handler> Router<Handler> {
+    /// Calls the handler for the best match for this path with its
+    /// captures and the provided context, returning the handler's
+    /// future. The captures borrow from the router and the path, so
+    /// the future may only hold them as long as both are alive. If no
+    /// route matches, the context is returned as the error.
+    pub fn dispatch<'a, 'b, Ctx, Fut>(&'a self, path: &'b str, ctx: Ctx) -> Result<Fut, Ctx>
+    where
+        Handler: Fn(Captures<'a, 'b>, Ctx) -> Fut,
+        Fut: Future,
+    {
+        match self.best_match(path) {
+            Some(m) => Ok((m.handler())(m.captures(), ctx)),
+            None => Err(ctx),
+        }
+    }
+
+    /// Like [`Router::dispatch`], but the captures are converted with
+    /// [`Captures::into_owned`] so that the handler's future can
+    /// outlive the router and the path, as is needed for a future that
+    /// is spawned onto an executor.
+    pub fn dispatch_owned<Ctx, Fut>(&self, path: &str, ctx: Ctx) -> Result<Fut, Ctx>
+    where
+        Handler: Fn(Captures<'static, 'static>, Ctx) -> Fut,
+        Fut: Future,
+    {
+        match self.best_match(path) {
+            Some(m) => Ok((m.handler())(m.captures().into_owned(), ctx)),
+            None => Err(ctx),
+        }
+    }
+
+    /// Like [`Router::dispatch`], but calls the provided fallback
+    /// handler with empty captures if no route matches
+    pub fn dispatch_or<'a, 'b, Ctx, Fut>(
+        &'a self,
+        path: &'b str,
+        ctx: Ctx,
+        fallback: &Handler,
+    ) -> Fut
+    where
+        Handler: Fn(Captures<'a, 'b>, Ctx) -> Fut,
+        Fut: Future,
+    {
+        self.dispatch(path, ctx)
+            .unwrap_or_else(|ctx| fallback(Captures::new(), ctx))
+    }
+}
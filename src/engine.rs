@@ -0,0 +1,36 @@
+/// How a [`Router`][crate::Router] finds the routes to try for a path.
+/// See [`Router::engine`][crate::Router::engine].
+///
+/// Every engine tries routes in the same order of precedence and finds
+/// the same matches; they differ only in how many routes are skipped
+/// without being applied to the path. Unless an engine is pinned with
+/// [`Router::pin_engine`][crate::Router::pin_engine], the router
+/// chooses one from the shape of its routes the first time it matches
+/// a path after they change: `Hashed` when every route can be indexed
+/// by its path, `Linear` for other small tables, `Trie` for large
+/// tables, and `Partitioned` otherwise. Routers that match case
+/// insensitively always try every route.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Engine {
+    /// Every route is tried in order. This has no index to build, and
+    /// suits small tables.
+    Linear,
+
+    /// Routes are indexed by the literal text of their first path
+    /// segment, such as `api` in `/api/users/:id`, so only the routes
+    /// for the first segment of a path and the routes without a
+    /// literal first segment are tried.
+    Partitioned,
+
+    /// Routes are indexed by the literal text of up to four leading
+    /// path segments, such as `api/users` in `/api/users/:id`, so only
+    /// the routes for each of the leading segments of a path and the
+    /// routes without a literal first segment are tried. This suits
+    /// large tables, including those that share a first segment.
+    Trie,
+
+    /// Routes without params, wildcards, or globs, other than prefix
+    /// routes, are indexed by their whole path, so only the routes for
+    /// a path and the routes that are not indexed are tried.
+    Hashed,
+}
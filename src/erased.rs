@@ -0,0 +1,156 @@
+use crate::{AsPath, Captures, FrozenRouter, Match, RouteSpec, Router};
+use std::{
+    any::{Any, TypeId},
+    borrow::Cow,
+    fmt::{self, Debug, Formatter},
+};
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// A [`Router`] with its handler type erased, so that routers with
+/// different handler types can be kept together, such as in a
+/// registry of `Box<dyn ErasedRouter>`. Matches are returned as
+/// [`ErasedMatch`]es, whose handler can be downcast to its type.
+///
+/// This trait is sealed, and is implemented for [`Router`] and
+/// [`FrozenRouter`] with any `'static` handler type.
+///
+/// ```rust
+/// use routefinder::{ErasedRouter, Router};
+/// let mut pages = Router::new();
+/// pages.add("/about", "about page").unwrap();
+/// let mut api = Router::new();
+/// api.add("/api/users/:id", 404_u16).unwrap();
+///
+/// let registry: Vec<Box<dyn ErasedRouter>> = vec![Box::new(pages), Box::new(api)];
+/// let m = registry
+///     .iter()
+///     .find_map(|router| router.best_match_erased(&"/api/users/1"))
+///     .unwrap();
+/// assert_eq!(m.downcast_handler::<u16>(), Some(&404));
+/// assert_eq!(m.downcast_handler::<&str>(), None);
+/// assert_eq!(m.captures().get("id"), Some("1"));
+/// ```
+pub trait ErasedRouter: private::Sealed + Debug + Send + Sync {
+    /// Returns the best match for this path, with its handler erased.
+    /// The path is any [`AsPath`], passed as a trait object so that
+    /// this trait stays object safe, which means that a `&str` is
+    /// passed by reference, as in `&"/users/1"`. See
+    /// [`Router::best_match`].
+    fn best_match_erased<'a, 'b>(&'a self, path: &'b dyn AsPath) -> Option<ErasedMatch<'a, 'b>>;
+
+    /// Returns the [`TypeId`] of the handlers of this router
+    fn handler_type(&self) -> TypeId;
+
+    /// returns the number of routes in this router
+    fn len(&self) -> usize;
+
+    /// returns true if this router has no routes
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<Handler> private::Sealed for Router<Handler> {}
+
+impl<Handler: Any + Send + Sync> ErasedRouter for Router<Handler> {
+    fn best_match_erased<'a, 'b>(&'a self, path: &'b dyn AsPath) -> Option<ErasedMatch<'a, 'b>> {
+        self.best_match(path).map(ErasedMatch::from)
+    }
+
+    fn handler_type(&self) -> TypeId {
+        TypeId::of::<Handler>()
+    }
+
+    fn len(&self) -> usize {
+        Router::len(self)
+    }
+}
+
+impl<Handler> private::Sealed for FrozenRouter<Handler> {}
+
+impl<Handler: Any + Send + Sync> ErasedRouter for FrozenRouter<Handler> {
+    fn best_match_erased<'a, 'b>(&'a self, path: &'b dyn AsPath) -> Option<ErasedMatch<'a, 'b>> {
+        self.router().best_match_erased(path)
+    }
+
+    fn handler_type(&self) -> TypeId {
+        TypeId::of::<Handler>()
+    }
+
+    fn len(&self) -> usize {
+        self.router().len()
+    }
+}
+
+/// A [`Match`] with its handler erased, returned by
+/// [`ErasedRouter::best_match_erased`]
+pub struct ErasedMatch<'router, 'path> {
+    route: &'router RouteSpec,
+    handler: &'router dyn Any,
+    path: Cow<'path, str>,
+    captures: Captures<'router, 'path>,
+}
+
+impl Debug for ErasedMatch<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ErasedMatch")
+            .field("route", &format_args!("{}", self.route))
+            .field("path", &self.path())
+            .field("captures", &self.captures)
+            .finish()
+    }
+}
+
+impl<'router, 'path, Handler: Any> From<Match<'router, 'path, Handler>>
+    for ErasedMatch<'router, 'path>
+{
+    fn from(m: Match<'router, 'path, Handler>) -> Self {
+        Self {
+            route: m.route,
+            handler: m.handler,
+            captures: m.captures(),
+            path: m.path,
+        }
+    }
+}
+
+impl<'router, 'path> ErasedMatch<'router, 'path> {
+    /// Returns the handler of this match as [`Any`]
+    pub fn handler(&self) -> &'router dyn Any {
+        self.handler
+    }
+
+    /// Returns the handler of this match if it is a `T`
+    pub fn downcast_handler<T: Any>(&self) -> Option<&'router T> {
+        self.handler.downcast_ref()
+    }
+
+    /// Returns the routespec for this route
+    pub fn route(&self) -> &'router RouteSpec {
+        self.route
+    }
+
+    /// Returns the exact path that was matched. See [`Match::path`].
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the [`Captures`] for this match
+    pub fn captures(&self) -> &Captures<'router, 'path> {
+        &self.captures
+    }
+
+    /// Returns a match that owns its path and captures, so that it
+    /// can outlive the path
+    pub fn into_owned(self) -> ErasedMatch<'router, 'static> {
+        ErasedMatch {
+            route: self.route,
+            handler: self.handler,
+            path: Cow::Owned(self.path.into_owned()),
+            captures: self.captures.into_owned(),
+        }
+    }
+}
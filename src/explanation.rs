@@ -0,0 +1,347 @@
+use crate::{RouteId, RouteSpec, Router, Segment};
+use std::{
+    fmt::{self, Display, Formatter},
+    ptr,
+};
+
+/// How the routes of a [`Router`] apply to a path, as structured data.
+/// Returned by [`Router::explain`]. With the `serde` feature, this and
+/// the types it contains are serializable.
+///
+/// ```rust
+/// use routefinder::{Outcome, Rejection, Router};
+/// let mut router = Router::new();
+/// let user = router.add("/users/:id", ()).unwrap();
+/// router.add("/posts/:id", ()).unwrap();
+///
+/// let explanation = router.explain("/users/1/posts");
+/// let route = explanation.route(user).unwrap();
+/// assert_eq!(route.route(), "/users/:id");
+/// assert_eq!(
+///     route.outcome(),
+///     &Outcome::Rejected(Rejection::PathContinues { segment: 2, found: "posts".into() })
+/// );
+/// assert!(explanation.selected().is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Explanation {
+    path: String,
+    routes: Vec<RouteExplanation>,
+}
+
+/// How one route of a [`Router`] applies to a path. See
+/// [`Explanation`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RouteExplanation {
+    id: RouteId,
+    route: String,
+    skipped: bool,
+    outcome: Outcome,
+}
+
+/// Whether a route matches a path. See [`RouteExplanation::outcome`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Outcome {
+    /// The route is the best match for the path
+    Selected,
+
+    /// The route matches the path, but a route of higher precedence
+    /// is selected
+    Shadowed,
+
+    /// The route does not match the path
+    Rejected(Rejection),
+}
+
+/// Why a route does not match a path. Path segments are counted from
+/// zero, ignoring empty segments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Rejection {
+    /// The path segment at this index does not match the part of the
+    /// route expected there
+    Mismatch {
+        /// the index of the path segment
+        segment: usize,
+        /// the part of the route, as displayed
+        expected: String,
+        /// the path segment
+        found: String,
+    },
+
+    /// The part of the route from this path segment on, which can
+    /// match a variable number of segments or is constrained, does not
+    /// match the rest of the path
+    Rest {
+        /// the index of the path segment
+        segment: usize,
+        /// the part of the route, as displayed
+        expected: String,
+        /// the rest of the path
+        found: String,
+    },
+
+    /// The path ends at this segment, before the part of the route
+    /// expected there
+    PathEnded {
+        /// the index of the path segment
+        segment: usize,
+        /// the part of the route, as displayed
+        expected: String,
+    },
+
+    /// The path continues at this segment after the end of the route
+    PathContinues {
+        /// the index of the path segment
+        segment: usize,
+        /// the path segment
+        found: String,
+    },
+
+    /// Every segment of the route agrees with the path, but the route
+    /// is ruled out by a condition, a deny route, a reserved prefix, or
+    /// the match options
+    Excluded {
+        /// the number of path segments that agree with the route
+        segment: usize,
+    },
+}
+
+impl Explanation {
+    pub(crate) fn new<Handler>(router: &Router<Handler>, path: &str) -> Self {
+        let matches = router.matches(path);
+        let best = router.best_match(path);
+        let candidates: Vec<&RouteSpec> = router.candidates(path).collect();
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+        let routes = router
+            .iter()
+            .filter_map(|(route, _)| {
+                let outcome = if best
+                    .as_ref()
+                    .is_some_and(|best| ptr::eq(best.route(), route))
+                {
+                    Outcome::Selected
+                } else if matches.iter().any(|m| ptr::eq(m.route(), route)) {
+                    Outcome::Shadowed
+                } else {
+                    Outcome::Rejected(Rejection::new(route, &segments))
+                };
+
+                Some(RouteExplanation {
+                    id: route.id()?,
+                    route: route.to_string(),
+                    skipped: !candidates.iter().any(|c| ptr::eq(*c, route)),
+                    outcome,
+                })
+            })
+            .collect();
+
+        Self {
+            path: path.to_string(),
+            routes,
+        }
+    }
+
+    /// Returns the path that is explained
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns how each route applies to the path, in order of
+    /// precedence
+    pub fn routes(&self) -> &[RouteExplanation] {
+        &self.routes
+    }
+
+    /// Returns how the route with this id applies to the path
+    pub fn route(&self, id: RouteId) -> Option<&RouteExplanation> {
+        self.routes.iter().find(|route| route.id == id)
+    }
+
+    /// Returns the route that is selected for the path, if any
+    pub fn selected(&self) -> Option<&RouteExplanation> {
+        self.routes
+            .iter()
+            .find(|route| route.outcome == Outcome::Selected)
+    }
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let width = self
+            .routes
+            .iter()
+            .map(|route| route.route.len())
+            .max()
+            .unwrap_or_default();
+
+        write!(f, "routes for {}:", self.path)?;
+        for route in &self.routes {
+            let marker = if route.outcome == Outcome::Selected {
+                '>'
+            } else {
+                ' '
+            };
+            write!(
+                f,
+                "\n  {} {:<width$}  {}",
+                marker,
+                route.route,
+                route.outcome,
+                width = width
+            )?;
+        }
+
+        if self.routes.is_empty() {
+            f.write_str("\n    the router has no routes")?;
+        }
+        Ok(())
+    }
+}
+
+impl RouteExplanation {
+    /// Returns the id of the route
+    pub fn id(&self) -> RouteId {
+        self.id
+    }
+
+    /// Returns the route, as displayed by [`RouteSpec`]
+    pub fn route(&self) -> &str {
+        &self.route
+    }
+
+    /// Returns true if the router does not try this route for the
+    /// path, because the route cannot match a path that begins with
+    /// the path's first segment. See [`Engine`][crate::Engine].
+    pub fn skipped(&self) -> bool {
+        self.skipped
+    }
+
+    /// Returns whether the route matches the path
+    pub fn outcome(&self) -> &Outcome {
+        &self.outcome
+    }
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Selected => f.write_str("selected"),
+            Outcome::Shadowed => f.write_str("matches, but a route above takes precedence"),
+            Outcome::Rejected(rejection) => write!(f, "does not match: {}", rejection),
+        }
+    }
+}
+
+impl Rejection {
+    /// compares a route that does not match with the path's segments,
+    /// one slash-separated part of the route at a time
+    pub(crate) fn new(route: &RouteSpec, segments: &[&str]) -> Self {
+        let groups = route
+            .segments()
+            .split(|segment| *segment == Segment::Slash)
+            .filter(|group| !group.is_empty());
+
+        for (index, group) in groups.clone().enumerate() {
+            let expected: String = group.iter().map(ToString::to_string).collect();
+            let fixed = group.iter().all(|segment| match segment {
+                Segment::Exact(_) | Segment::Dot | Segment::Glob(_) => true,
+                Segment::Param(name) => route.constraint(name).is_none(),
+                _ => false,
+            });
+
+            if !fixed {
+                return Rejection::Rest {
+                    segment: index,
+                    expected,
+                    found: segments.get(index..).unwrap_or_default().join("/"),
+                };
+            }
+
+            match segments.get(index) {
+                None => {
+                    return Rejection::PathEnded {
+                        segment: index,
+                        expected,
+                    }
+                }
+                Some(found) if RouteSpec::from(group.to_vec()).matches(found).is_none() => {
+                    return Rejection::Mismatch {
+                        segment: index,
+                        expected,
+                        found: found.to_string(),
+                    }
+                }
+                Some(_) => {}
+            }
+        }
+
+        let segment = groups.count();
+        match segments.get(segment) {
+            Some(found) => Rejection::PathContinues {
+                segment,
+                found: found.to_string(),
+            },
+            None => Rejection::Excluded { segment },
+        }
+    }
+
+    /// Returns the index of the path segment at which the route stops
+    /// agreeing with the path
+    pub fn segment(&self) -> usize {
+        match self {
+            Rejection::Mismatch { segment, .. }
+            | Rejection::Rest { segment, .. }
+            | Rejection::PathEnded { segment, .. }
+            | Rejection::PathContinues { segment, .. }
+            | Rejection::Excluded { segment } => *segment,
+        }
+    }
+
+    /// Returns the part of the route that does not match, as
+    /// displayed, if any
+    pub fn expected(&self) -> Option<&str> {
+        match self {
+            Rejection::Mismatch { expected, .. }
+            | Rejection::Rest { expected, .. }
+            | Rejection::PathEnded { expected, .. } => Some(expected),
+            _ => None,
+        }
+    }
+
+    /// Returns the part of the path that does not match, if any
+    pub fn found(&self) -> Option<&str> {
+        match self {
+            Rejection::Mismatch { found, .. }
+            | Rejection::Rest { found, .. }
+            | Rejection::PathContinues { found, .. } => Some(found),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Rejection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Rejection::Mismatch {
+                expected, found, ..
+            } => write!(f, "`{}` does not match `{}`", found, expected),
+            Rejection::Rest { expected, .. } => {
+                write!(f, "`{}` does not match the rest of the path", expected)
+            }
+            Rejection::PathEnded { expected, .. } => {
+                write!(f, "the path ends before `{}`", expected)
+            }
+            Rejection::PathContinues { .. } => {
+                f.write_str("the path continues after the end of the route")
+            }
+            Rejection::Excluded { .. } => f.write_str(
+                "ruled out by a condition, a deny route, a reserved prefix, or the match options",
+            ),
+        }
+    }
+}
@@ -0,0 +1,344 @@
+//! Exporters that describe a [`Router`]'s routes to other languages
+//!
+//! These are available with the `export` feature, so that frontend
+//! clients can generate url helpers from the same route table that
+//! the backend matches against. Routefinder routes have no names of
+//! their own, so each exporter takes a function that names a route
+//! from its spec and handler. Routes it returns `None` for are left
+//! out of the typescript export, which needs a key for each route.
+//!
+//! ```rust
+//! let mut router = routefinder::Router::new();
+//! router.add("/", "home").unwrap();
+//! router.add("/users/:id", "user").unwrap();
+//!
+//! let name = |_: &routefinder::RouteSpec, handler: &&str| Some(handler.to_string());
+//!
+//! assert_eq!(
+//!     router.export_json(name),
+//!     concat!(
+//!         r#"[{"name":"home","template":"/","params":[],"optional":[],"wildcard":false},"#,
+//!         r#"{"name":"user","template":"/users/:id","params":["id"],"optional":[],"wildcard":false}]"#
+//!     )
+//! );
+//!
+//! let typescript = router.export_typescript(name);
+//! assert!(typescript.contains(
+//!     "url: (params: { id: string }) => `/users/${encodeURIComponent(params.id)}`,"
+//! ));
+//! ```
+
+use crate::{RouteSpec, Router, Segment};
+use std::{collections::BTreeMap, fmt::Write};
+
+impl<Handler> Router<Handler> {
+    /// Describes every route as a json array of objects, in
+    /// precedence order. Each object has the route's `name` (or
+    /// `null`), its `template` as displayed by [`RouteSpec`], the
+    /// names of its `params`, which of those are `optional`, and
+    /// whether it has a `wildcard`.
+    pub fn export_json<F>(&self, name: F) -> String
+    where
+        F: Fn(&RouteSpec, &Handler) -> Option<String>,
+    {
+        let mut json = String::from("[");
+        for (index, (route, handler)) in self.iter().enumerate() {
+            if index > 0 {
+                json.push(',');
+            }
+            let name = name(route, handler).map_or_else(|| String::from("null"), |n| quote(&n));
+            let (params, optional) = params(route);
+            write!(
+                json,
+                r#"{{"name":{},"template":{},"params":{},"optional":{},"wildcard":{}}}"#,
+                name,
+                quote(&route.to_string()),
+                array(&params),
+                array(&optional),
+                has_wildcard(route)
+            )
+            .unwrap();
+        }
+        json.push(']');
+        json
+    }
+
+    /// Describes every named route as a typescript module exporting
+    /// a `routes` object, keyed by name, with each route's `template`,
+    /// `params`, and a `url` function that builds a path from its
+    /// params, percent-encoding single-segment values. The wildcard
+    /// is passed as `"*"`. Glob routes have no `url` function. When
+    /// two routes share a name, the higher precedence route is used.
+    pub fn export_typescript<F>(&self, name: F) -> String
+    where
+        F: Fn(&RouteSpec, &Handler) -> Option<String>,
+    {
+        let mut names = vec![];
+        let mut typescript =
+            String::from("// generated by routefinder. do not edit.\n\nexport const routes = {\n");
+
+        for (route, handler) in self.iter() {
+            let name = match name(route, handler) {
+                Some(name) if !names.contains(&name) => name,
+                _ => continue,
+            };
+
+            let (params, _) = params(route);
+            writeln!(typescript, "  {}: {{", quote(&name)).unwrap();
+            writeln!(typescript, "    template: {},", quote(&route.to_string())).unwrap();
+            writeln!(typescript, "    params: {},", array(&params)).unwrap();
+            if let Some(url) = url_function(route) {
+                writeln!(typescript, "    url: {},", url).unwrap();
+            }
+            typescript.push_str("  },\n");
+            names.push(name);
+        }
+
+        typescript.push_str("} as const;\n");
+        typescript
+    }
+
+    /// Describes every route as a markdown reference, with a section
+    /// for each first path segment, in alphabetical order, and a table
+    /// of the routes in that section, in precedence order, with the
+    /// descriptions they were added with by [`Router::add_documented`].
+    ///
+    /// ```rust
+    /// let mut router = routefinder::Router::new();
+    /// router.add_documented("/users/:id", (), "Returns a user by id").unwrap();
+    /// router.add_documented("/users", (), "Lists users").unwrap();
+    /// router.add("/", ()).unwrap();
+    ///
+    /// assert_eq!(
+    ///     router.export_markdown(),
+    ///     concat!(
+    ///         "## /\n\n",
+    ///         "| Route | Description |\n",
+    ///         "| --- | --- |\n",
+    ///         "| `/` |  |\n",
+    ///         "\n",
+    ///         "## /users\n\n",
+    ///         "| Route | Description |\n",
+    ///         "| --- | --- |\n",
+    ///         "| `/users` | Lists users |\n",
+    ///         "| `/users/:id` | Returns a user by id |\n",
+    ///     )
+    /// );
+    /// ```
+    pub fn export_markdown(&self) -> String {
+        let mut markdown = String::new();
+        for (index, (prefix, routes)) in self.sections().into_iter().enumerate() {
+            if index > 0 {
+                markdown.push('\n');
+            }
+            write!(
+                markdown,
+                "## {}\n\n| Route | Description |\n| --- | --- |\n",
+                prefix
+            )
+            .unwrap();
+            for route in routes {
+                let description = route.description().unwrap_or_default();
+                writeln!(
+                    markdown,
+                    "| `{}` | {} |",
+                    route,
+                    description.replace('|', "\\|").replace('\n', " ")
+                )
+                .unwrap();
+            }
+        }
+        markdown
+    }
+
+    /// Describes every route as a plain text reference, grouped and
+    /// ordered as with [`Router::export_markdown`], with the routes of
+    /// each group indented and their descriptions aligned.
+    ///
+    /// ```rust
+    /// let mut router = routefinder::Router::new();
+    /// router.add_documented("/users/:id", (), "Returns a user by id").unwrap();
+    /// router.add_documented("/health", (), "Reports readiness").unwrap();
+    ///
+    /// assert_eq!(
+    ///     router.export_text(),
+    ///     concat!(
+    ///         "/health\n",
+    ///         "  /health      Reports readiness\n",
+    ///         "\n",
+    ///         "/users\n",
+    ///         "  /users/:id   Returns a user by id\n",
+    ///     )
+    /// );
+    /// ```
+    pub fn export_text(&self) -> String {
+        let sections = self.sections();
+        let width = sections
+            .values()
+            .flatten()
+            .map(|route| route.to_string().len())
+            .max()
+            .unwrap_or_default();
+
+        let mut text = String::new();
+        for (index, (prefix, routes)) in sections.into_iter().enumerate() {
+            if index > 0 {
+                text.push('\n');
+            }
+            writeln!(text, "{}", prefix).unwrap();
+            for route in routes {
+                let line = format!(
+                    "  {:<width$}   {}",
+                    route.to_string(),
+                    route.description().unwrap_or_default().replace('\n', " "),
+                    width = width
+                );
+                writeln!(text, "{}", line.trim_end()).unwrap();
+            }
+        }
+        text
+    }
+
+    /// the routes grouped by their first path segment, in precedence
+    /// order within each group
+    fn sections(&self) -> BTreeMap<String, Vec<&RouteSpec>> {
+        let mut sections: BTreeMap<String, Vec<&RouteSpec>> = BTreeMap::new();
+        for (route, _) in self.iter() {
+            let first: String = route
+                .segments()
+                .split(|segment| *segment == Segment::Slash)
+                .next()
+                .unwrap_or_default()
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            sections
+                .entry(format!("/{}", first))
+                .or_default()
+                .push(route);
+        }
+        sections
+    }
+}
+
+fn params(route: &RouteSpec) -> (Vec<&str>, Vec<&str>) {
+    let mut params = vec![];
+    let mut optional = vec![];
+    for segment in route.segments() {
+        if let Some(name) = segment.name() {
+            if !params.contains(&name) {
+                params.push(name);
+                if segment.may_be_empty() {
+                    optional.push(name);
+                }
+            }
+        }
+    }
+    (params, optional)
+}
+
+fn has_wildcard(route: &RouteSpec) -> bool {
+    route.segments().contains(&Segment::Wildcard)
+}
+
+fn url_function(route: &RouteSpec) -> Option<String> {
+    let segments = route.segments();
+    let mut types = vec![];
+    let mut body = String::from("`/");
+
+    for (index, segment) in segments.iter().enumerate() {
+        match segment {
+            Segment::Slash if segments.get(index + 1).is_some_and(Segment::may_be_empty) => {}
+            Segment::Slash => body.push('/'),
+            Segment::Dot => body.push('.'),
+            Segment::Exact(text) => body.push_str(&escape_template(text)),
+            Segment::Glob(_) => return None,
+            _ => {
+                let key = segment.name().unwrap_or("*");
+                let (property, accessor) = if is_identifier(key) {
+                    (String::from(key), format!("params.{}", key))
+                } else {
+                    (quote(key), format!("params[{}]", quote(key)))
+                };
+
+                let optional = segment.may_be_empty();
+                let property_type =
+                    format!("{}{}: string", property, if optional { "?" } else { "" });
+                if !types.contains(&property_type) {
+                    types.push(property_type);
+                }
+
+                // multi-segment values keep their slashes
+                let value = match segment {
+                    Segment::Param(_) | Segment::OptionalParam(_) => {
+                        format!("encodeURIComponent({})", accessor)
+                    }
+                    _ => accessor.clone(),
+                };
+
+                if optional {
+                    let slash = if index > 0 && segments[index - 1] == Segment::Slash {
+                        "/"
+                    } else {
+                        ""
+                    };
+                    write!(
+                        body,
+                        r#"${{{accessor} === undefined || {accessor} === "" ? "" : "{slash}" + {value}}}"#,
+                        accessor = accessor,
+                        slash = slash,
+                        value = value
+                    )
+                    .unwrap();
+                } else {
+                    write!(body, "${{{}}}", value).unwrap();
+                }
+            }
+        }
+    }
+    body.push('`');
+
+    if types.is_empty() {
+        Some(format!("() => {}", body))
+    } else {
+        Some(format!("(params: {{ {} }}) => {}", types.join("; "), body))
+    }
+}
+
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+fn array(items: &[&str]) -> String {
+    let quoted: Vec<String> = items.iter().map(|item| quote(item)).collect();
+    format!("[{}]", quoted.join(","))
+}
+
+/// a json string literal, which is also a valid typescript string
+fn quote(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if u32::from(c) < 0x20 => write!(quoted, "\\u{:04x}", u32::from(c)).unwrap(),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn escape_template(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('`', "\\`")
+        .replace("${", "\\${")
+}
@@ -0,0 +1,139 @@
+use crate::{constraint::Constraint, Segment};
+use smartstring::alias::String as SmartString;
+use std::{iter::Peekable, str::Chars};
+
+/// parses a route in the dialect of path-to-regexp, as used by
+/// express: `/users/:id(\d+)/:tab?`, `/files/:path*`, `/(.*)`.
+pub(crate) fn parse(source: &str) -> Result<(Vec<Segment>, Vec<Constraint>), String> {
+    let mut segments = vec![];
+    let mut constraints = vec![];
+    let mut exact = SmartString::new();
+    let mut unnamed = 0;
+    let mut chars = source
+        .trim_start_matches('/')
+        .trim_end_matches('/')
+        .chars()
+        .peekable();
+
+    while let Some(c) = chars.next() {
+        let (name, pattern) = match c {
+            '/' => {
+                flush(&mut segments, &mut exact);
+                if !matches!(segments.last(), None | Some(Segment::Slash)) {
+                    segments.push(Segment::Slash);
+                }
+                continue;
+            }
+
+            '\\' => {
+                exact.push(chars.next().ok_or("routes cannot end with a backslash")?);
+                continue;
+            }
+
+            '*' => {
+                flush(&mut segments, &mut exact);
+                push_capture(&mut segments, Segment::Wildcard)?;
+                continue;
+            }
+
+            ':' => {
+                let mut name = SmartString::new();
+                while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || *c == '_') {
+                    name.push(c);
+                }
+
+                if name.is_empty() {
+                    return Err(String::from("params must be named"));
+                }
+
+                let pattern = if chars.next_if_eq(&'(').is_some() {
+                    Some(group(&mut chars)?)
+                } else {
+                    None
+                };
+
+                (name, pattern)
+            }
+
+            '(' => {
+                let name = SmartString::from(unnamed.to_string());
+                unnamed += 1;
+                (name, Some(group(&mut chars)?))
+            }
+
+            '?' | '+' | ')' => {
+                return Err(format!("unexpected `{}` in `{}`", c, source));
+            }
+
+            c => {
+                exact.push(c);
+                continue;
+            }
+        };
+
+        flush(&mut segments, &mut exact);
+
+        let segment = match chars.next_if(|c| matches!(c, '?' | '*' | '+')) {
+            Some('?') => Segment::OptionalParam(name),
+            Some('*') => Segment::ZeroOrMore(name),
+            Some('+') => Segment::OneOrMore(name),
+            _ => Segment::Param(name),
+        };
+
+        if let Some(pattern) = pattern {
+            constraints.push(Constraint::new(segment.name().unwrap(), &pattern)?);
+        }
+
+        push_capture(&mut segments, segment)?;
+    }
+
+    flush(&mut segments, &mut exact);
+    Ok((segments, constraints))
+}
+
+fn flush(segments: &mut Vec<Segment>, exact: &mut SmartString) {
+    if !exact.is_empty() {
+        segments.push(Segment::Exact(std::mem::take(exact)));
+    }
+}
+
+fn push_capture(segments: &mut Vec<Segment>, segment: Segment) -> Result<(), String> {
+    if segments.last().is_some_and(Segment::is_capture) {
+        return Err(String::from(
+            "params must be separated by a slash or some exact text",
+        ));
+    }
+
+    segments.push(segment);
+    Ok(())
+}
+
+/// reads a parenthesized pattern, after the opening paren
+fn group(chars: &mut Peekable<Chars<'_>>) -> Result<String, String> {
+    let mut pattern = String::new();
+    let mut depth = 0;
+    loop {
+        match chars.next() {
+            Some(')') if depth == 0 => break,
+            Some('\\') => {
+                pattern.push('\\');
+                pattern.push(chars.next().ok_or("unterminated pattern")?);
+            }
+            Some(c) => {
+                match c {
+                    '(' => depth += 1,
+                    ')' => depth -= 1,
+                    _ => {}
+                }
+                pattern.push(c);
+            }
+            None => return Err(String::from("unterminated pattern")),
+        }
+    }
+
+    if pattern.is_empty() {
+        return Err(String::from("patterns cannot be empty"));
+    }
+
+    Ok(pattern)
+}
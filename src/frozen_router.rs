@@ -0,0 +1,142 @@
+use crate::Router;
+use std::{
+    fmt::{self, Debug, Formatter},
+    ops::Deref,
+    sync::Arc,
+};
+
+/// An immutable snapshot of a [`Router`] that is cheap to clone and
+/// share between threads
+///
+/// A `FrozenRouter` dereferences to its [`Router`] for matching.
+/// Changes are made with [`FrozenRouter::update`], which returns a new
+/// snapshot and leaves this one untouched, so a server can publish a
+/// new snapshot while requests still in flight finish with the old
+/// one. The new snapshot shares each [`RouteSpec`][crate::RouteSpec]
+/// with the old one, and only copies the table of routes and the
+/// handlers, so handlers that are expensive to clone are best wrapped
+/// in an [`Arc`].
+///
+/// ```rust
+/// use routefinder::Router;
+/// let mut router = Router::new();
+/// router.add("/users/:id", "user").unwrap();
+/// let snapshot = router.freeze();
+///
+/// let updated = snapshot.update(|router| {
+///     router.add("/users/new", "new user").unwrap();
+/// });
+///
+/// assert_eq!(*snapshot.best_match("/users/new").unwrap(), "user");
+/// assert_eq!(*updated.best_match("/users/new").unwrap(), "new user");
+/// assert_eq!(*updated.best_match("/users/1").unwrap(), "user");
+/// ```
+pub struct FrozenRouter<Handler> {
+    router: Arc<Router<Handler>>,
+}
+
+impl<Handler> Clone for FrozenRouter<Handler> {
+    fn clone(&self) -> Self {
+        Self {
+            router: Arc::clone(&self.router),
+        }
+    }
+}
+
+impl<Handler> Debug for FrozenRouter<Handler> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&*self.router, f)
+    }
+}
+
+impl<Handler> Default for FrozenRouter<Handler> {
+    fn default() -> Self {
+        Self::new(Router::default())
+    }
+}
+
+impl<Handler> From<Router<Handler>> for FrozenRouter<Handler> {
+    fn from(router: Router<Handler>) -> Self {
+        Self::new(router)
+    }
+}
+
+impl<Handler> Deref for FrozenRouter<Handler> {
+    type Target = Router<Handler>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.router
+    }
+}
+
+impl<Handler> Router<Handler> {
+    /// Builds a [`FrozenRouter`] from this router without copying it
+    pub fn freeze(self) -> FrozenRouter<Handler> {
+        FrozenRouter::new(self)
+    }
+
+    /// Builds a [`FrozenRouter`] from a copy of this router, which
+    /// shares each [`RouteSpec`][crate::RouteSpec] with this router
+    pub fn snapshot(&self) -> FrozenRouter<Handler>
+    where
+        Handler: Clone,
+    {
+        FrozenRouter::new(self.clone())
+    }
+}
+
+impl<Handler> FrozenRouter<Handler> {
+    /// Builds a new FrozenRouter from a router. See
+    /// [`Router::freeze`].
+    pub fn new(router: Router<Handler>) -> Self {
+        Self {
+            router: Arc::new(router),
+        }
+    }
+
+    /// Returns the router for this snapshot
+    pub fn router(&self) -> &Router<Handler> {
+        &self.router
+    }
+
+    /// Returns a new snapshot with the changes made by `f` to a copy
+    /// of this one's router. This snapshot is unchanged, and readers
+    /// of it are never blocked.
+    pub fn update(&self, f: impl FnOnce(&mut Router<Handler>)) -> Self
+    where
+        Handler: Clone,
+    {
+        let mut router = Router::clone(&self.router);
+        f(&mut router);
+        Self::new(router)
+    }
+
+    /// Like [`FrozenRouter::update`], but discards the changes if `f`
+    /// returns an error
+    pub fn try_update<E>(
+        &self,
+        f: impl FnOnce(&mut Router<Handler>) -> Result<(), E>,
+    ) -> Result<Self, E>
+    where
+        Handler: Clone,
+    {
+        let mut router = Router::clone(&self.router);
+        f(&mut router)?;
+        Ok(Self::new(router))
+    }
+
+    /// Returns true if both snapshots are the same, rather than
+    /// updates of each other
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.router, &other.router)
+    }
+
+    /// Returns the router for this snapshot, copying it if other
+    /// clones of this snapshot remain
+    pub fn into_router(self) -> Router<Handler>
+    where
+        Handler: Clone,
+    {
+        Arc::try_unwrap(self.router).unwrap_or_else(|router| Router::clone(&router))
+    }
+}
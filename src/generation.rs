@@ -0,0 +1,37 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// identifies the routes of one router at one point in time, so that a
+/// [`MatchIndex`][crate::MatchIndex] only resolves on the router that
+/// it was looked up from, until that router's routes change. Each
+/// generation is unique across every router, including clones, which
+/// get a generation of their own.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Generation(u64);
+
+impl Generation {
+    pub(crate) fn new() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// moves to a new generation, after the routes change
+    pub(crate) fn bump(&mut self) {
+        *self = Self::new();
+    }
+
+    pub(crate) fn get(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Default for Generation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clone for Generation {
+    fn clone(&self) -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,86 @@
+use crate::Segment;
+use smartstring::alias::String as SmartString;
+
+/// parses a glob pattern such as `assets/**/*.min.?s` into segments.
+///
+/// `**` must be an entire path segment and matches any number of
+/// segments, including none. `*` and `?` can appear anywhere within
+/// a segment. Dots and colons have no special meaning.
+pub(crate) fn parse(source: &str) -> Result<Vec<Segment>, String> {
+    let mut segments = vec![];
+    for section in source.split('/').filter(|section| !section.is_empty()) {
+        if !segments.is_empty() {
+            segments.push(Segment::Slash);
+        }
+
+        let segment = if section == "**" {
+            Segment::Wildcard
+        } else if section.contains("**") {
+            return Err(format!(
+                "`**` must be an entire path segment, found `{}`",
+                section
+            ));
+        } else if section.contains(['*', '?']) {
+            Segment::Glob(SmartString::from(section))
+        } else {
+            Segment::Exact(SmartString::from(section))
+        };
+
+        segments.push(segment);
+    }
+
+    Ok(segments)
+}
+
+/// shell-style matching of a single path segment, where `*` matches
+/// any run of characters and `?` matches exactly one character
+pub(crate) fn matches(pattern: &str, candidate: &str) -> bool {
+    star_matches(pattern, candidate, Some('?'))
+}
+
+/// like [`matches`], but `?` is an ordinary character, as in
+/// robots.txt patterns
+pub(crate) fn matches_stars(pattern: &str, candidate: &str) -> bool {
+    star_matches(pattern, candidate, None)
+}
+
+fn star_matches(pattern: &str, candidate: &str, any_char: Option<char>) -> bool {
+    let (mut pattern_rest, mut candidate_rest) = (pattern, candidate);
+    let mut last_star: Option<(&str, &str)> = None;
+
+    loop {
+        let mut pattern_chars = pattern_rest.chars();
+        let mut candidate_chars = candidate_rest.chars();
+        match (pattern_chars.next(), candidate_chars.next()) {
+            (Some('*'), _) => {
+                pattern_rest = pattern_chars.as_str();
+                last_star = Some((pattern_rest, candidate_rest));
+                continue;
+            }
+
+            (Some(expected), Some(actual)) if Some(expected) == any_char || expected == actual => {
+                pattern_rest = pattern_chars.as_str();
+                candidate_rest = candidate_chars.as_str();
+                continue;
+            }
+
+            (None, None) => return true,
+
+            _ => {}
+        }
+
+        // on a mismatch, let the most recent star absorb one more character
+        match last_star {
+            Some((star_pattern, star_candidate)) => {
+                let mut star_candidate_chars = star_candidate.chars();
+                if star_candidate_chars.next().is_none() {
+                    return false;
+                }
+                pattern_rest = star_pattern;
+                candidate_rest = star_candidate_chars.as_str();
+                last_star = Some((pattern_rest, candidate_rest));
+            }
+            None => return false,
+        }
+    }
+}
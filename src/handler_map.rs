@@ -0,0 +1,134 @@
+use crate::{Match, RouteId, RouteSpec, Router};
+use std::{
+    any::{Any, TypeId},
+    collections::BTreeMap,
+    convert::TryInto,
+    fmt::{self, Debug, Formatter},
+};
+
+/// Handlers of different types for the routes of a [`Router`], keyed
+/// by [`RouteId`]
+///
+/// A router holds one handler type, so routes that are served by
+/// different types, such as one controller type per resource, can
+/// keep their handlers in a `HandlerMap` instead of an enum of every
+/// type. Each handler is retrieved as the type it was inserted as.
+/// [`HandlerMap::add`] adds a route to a router and its handler to
+/// the map together.
+///
+/// ```rust
+/// use routefinder::{HandlerMap, Router};
+/// struct Users;
+/// struct Posts {
+///     per_page: usize,
+/// }
+///
+/// let mut router: Router<()> = Router::new();
+/// let mut handlers = HandlerMap::new();
+/// let users = handlers.add(&mut router, "/users/:id", Users).unwrap();
+/// handlers.add(&mut router, "/posts", Posts { per_page: 20 }).unwrap();
+///
+/// let m = router.best_match("/posts").unwrap();
+/// assert_eq!(handlers.for_match::<Posts, _>(&m).unwrap().per_page, 20);
+/// assert!(handlers.for_match::<Users, _>(&m).is_none());
+/// assert!(handlers.get::<Users>(users).is_some());
+/// ```
+#[derive(Default)]
+pub struct HandlerMap {
+    handlers: BTreeMap<RouteId, Box<dyn Any + Send + Sync>>,
+}
+
+impl Debug for HandlerMap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.handlers.keys()).finish()
+    }
+}
+
+impl HandlerMap {
+    /// Builds a new empty HandlerMap
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a route to the router with a default handler, such as
+    /// `()`, and this handler to the map under the route's id. If the
+    /// route does not parse, neither the router nor the map is
+    /// changed.
+    pub fn add<R, T, Handler>(
+        &mut self,
+        router: &mut Router<Handler>,
+        route: R,
+        handler: T,
+    ) -> Result<RouteId, <R as TryInto<RouteSpec>>::Error>
+    where
+        R: TryInto<RouteSpec>,
+        T: Any + Send + Sync,
+        Handler: Default,
+    {
+        let id = router.add(route, Handler::default())?;
+        self.insert(id, handler);
+        Ok(id)
+    }
+
+    /// Sets the handler for this route id, returning the previous
+    /// handler, if any
+    pub fn insert<T: Any + Send + Sync>(
+        &mut self,
+        id: RouteId,
+        handler: T,
+    ) -> Option<Box<dyn Any + Send + Sync>> {
+        self.handlers.insert(id, Box::new(handler))
+    }
+
+    /// Returns the handler for this route id, if it is a `T`
+    pub fn get<T: Any>(&self, id: RouteId) -> Option<&T> {
+        self.handlers.get(&id)?.downcast_ref()
+    }
+
+    /// Returns a mutable reference to the handler for this route id,
+    /// if it is a `T`
+    pub fn get_mut<T: Any>(&mut self, id: RouteId) -> Option<&mut T> {
+        self.handlers.get_mut(&id)?.downcast_mut()
+    }
+
+    /// Returns the handler for the route of this match, if it is a `T`
+    pub fn for_match<T: Any, Handler>(&self, m: &Match<'_, '_, Handler>) -> Option<&T> {
+        self.get(m.route().id()?)
+    }
+
+    /// Returns the [`TypeId`] of the handler for this route id, if any
+    pub fn type_id(&self, id: RouteId) -> Option<TypeId> {
+        self.handlers.get(&id).map(|handler| (**handler).type_id())
+    }
+
+    /// Removes the handler for this route id, returning it if it is a
+    /// `T`. A handler of another type is left in place.
+    pub fn remove<T: Any>(&mut self, id: RouteId) -> Option<T> {
+        if !self.handlers.get(&id)?.is::<T>() {
+            return None;
+        }
+
+        let handler = self.handlers.remove(&id)?;
+        handler.downcast().ok().map(|handler| *handler)
+    }
+
+    /// Returns true if there is a handler for this route id
+    pub fn contains(&self, id: RouteId) -> bool {
+        self.handlers.contains_key(&id)
+    }
+
+    /// Returns the ids of every route with a handler, in order
+    pub fn ids(&self) -> impl Iterator<Item = RouteId> + '_ {
+        self.handlers.keys().copied()
+    }
+
+    /// returns the number of handlers
+    pub fn len(&self) -> usize {
+        self.handlers.len()
+    }
+
+    /// returns true if there are no handlers
+    pub fn is_empty(&self) -> bool {
+        self.handlers.is_empty()
+    }
+}
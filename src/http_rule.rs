@@ -0,0 +1,157 @@
+use crate::{constraint::Constraint, Segment};
+use smartstring::alias::String as SmartString;
+
+/// parses a google api http rule path template, as used for grpc
+/// transcoding: `/v1/{name=projects/*/locations/*}/datasets:export`
+pub(crate) fn parse(source: &str) -> Result<(Vec<Segment>, Vec<Constraint>), String> {
+    let template = source
+        .strip_prefix('/')
+        .ok_or_else(|| format!("`{}` must start with a slash", source))?;
+    let (template, verb) = split_verb(template);
+
+    let mut segments = vec![];
+    let mut constraints: Vec<Constraint> = vec![];
+    for section in sections(template)? {
+        if !segments.is_empty() {
+            segments.push(Segment::Slash);
+        }
+
+        let segment = match section {
+            "" => return Err(format!("`{}` has an empty path segment", source)),
+            "*" => Segment::Glob(SmartString::from("*")),
+            "**" => Segment::Wildcard,
+            _ if section.starts_with('{') && section.ends_with('}') => {
+                let variable = &section[1..section.len() - 1];
+                let (field, pattern) = variable.split_once('=').unwrap_or((variable, "*"));
+                validate_field(field)?;
+                validate_pattern(pattern)?;
+
+                let already_bound = segments
+                    .iter()
+                    .any(|segment: &Segment| segment.name() == Some(field));
+                if already_bound {
+                    return Err(format!("`{}` binds `{}` more than once", source, field));
+                }
+
+                match pattern {
+                    "*" => Segment::Param(SmartString::from(field)),
+                    "**" => Segment::ZeroOrMore(SmartString::from(field)),
+                    _ => {
+                        constraints.push(Constraint::segments(field, pattern));
+                        Segment::Param(SmartString::from(field))
+                    }
+                }
+            }
+            _ if section.contains(['{', '}', '*']) => {
+                return Err(format!("unexpected `{}` in `{}`", section, source));
+            }
+            _ => Segment::Exact(SmartString::from(section)),
+        };
+
+        if segment == Segment::Wildcard && segments.contains(&Segment::Wildcard) {
+            return Err(format!("`{}` has more than one `**`", source));
+        }
+
+        segments.push(segment);
+    }
+
+    if let Some(verb) = verb {
+        if verb.is_empty() || verb.contains(['/', '{', '}', '*', ':']) {
+            return Err(format!("`{}` has an invalid verb `{}`", source, verb));
+        }
+
+        // a glob has to match its whole path segment, so the verb
+        // becomes part of the glob
+        match segments.last_mut() {
+            Some(Segment::Glob(pattern)) => {
+                pattern.push(':');
+                pattern.push_str(verb);
+            }
+            _ => {
+                let mut exact = SmartString::from(":");
+                exact.push_str(verb);
+                segments.push(Segment::Exact(exact));
+            }
+        }
+    }
+
+    Ok((segments, constraints))
+}
+
+/// separates the `:verb` at the end of a template, which is the first
+/// colon outside of a variable
+fn split_verb(template: &str) -> (&str, Option<&str>) {
+    let mut depth = 0;
+    for (index, c) in template.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ':' if depth == 0 => return (&template[..index], Some(&template[index + 1..])),
+            _ => {}
+        }
+    }
+    (template, None)
+}
+
+/// splits a template on the slashes that are not within a variable
+fn sections(template: &str) -> Result<Vec<&str>, String> {
+    if template.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut sections = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (index, c) in template.char_indices() {
+        match c {
+            '{' if depth == 0 => depth += 1,
+            '{' => return Err(String::from("variables cannot be nested")),
+            '}' if depth == 1 => depth -= 1,
+            '}' => return Err(format!("unexpected `}}` in `{}`", template)),
+            '/' if depth == 0 => {
+                sections.push(&template[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+
+    if depth != 0 {
+        return Err(format!("unclosed variable in `{}`", template));
+    }
+
+    sections.push(&template[start..]);
+    Ok(sections)
+}
+
+fn validate_field(field: &str) -> Result<(), String> {
+    let valid = field.split('.').all(|ident| {
+        let mut chars = ident.chars();
+        chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+    });
+
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("`{}` is not a valid field path", field))
+    }
+}
+
+fn validate_pattern(pattern: &str) -> Result<(), String> {
+    let sections: Vec<&str> = pattern.split('/').collect();
+    for (index, section) in sections.iter().enumerate() {
+        let valid = match *section {
+            "*" => true,
+            "**" => index == sections.len() - 1,
+            _ => !section.is_empty() && !section.contains(['*', '=']),
+        };
+
+        if !valid {
+            return Err(format!("`{}` is not a valid variable pattern", pattern));
+        }
+    }
+    Ok(())
+}
@@ -1,4 +1,5 @@
-#![forbid(unsafe_code)]
+#![cfg_attr(not(feature = "wasm"), forbid(unsafe_code))]
+#![cfg_attr(feature = "wasm", deny(unsafe_code))]
 #![deny(
     clippy::dbg_macro,
     missing_copy_implementations,
@@ -43,11 +44,26 @@
 mod captures;
 pub use captures::{Capture, Captures};
 
+mod collision;
+pub use collision::Collision;
+
+mod command_router;
+pub use command_router::{CommandMatch, CommandRouter};
+
+mod completion;
+pub use completion::Completion;
+
+mod explanation;
+pub use explanation::{Explanation, Outcome, Rejection, RouteExplanation};
+
+mod shadow_comparison;
+pub use shadow_comparison::ShadowComparison;
+
 mod r#match;
-pub use r#match::Match;
+pub use r#match::{Match, MatchIndex};
 
 mod router;
-pub use router::Router;
+pub use router::{MatchIter, Router};
 
 mod segment;
 pub use segment::Segment;
@@ -55,5 +71,156 @@ pub use segment::Segment;
 mod reverse_match;
 pub use reverse_match::ReverseMatch;
 
+mod separator_policy;
+pub use separator_policy::SeparatorPolicy;
+
 mod route_spec;
 pub use route_spec::RouteSpec;
+
+mod route;
+pub use route::Route;
+
+mod route_config;
+pub use route_config::RouteConfig;
+
+mod template_error;
+pub use template_error::TemplateError;
+
+mod transform;
+pub use transform::Transform;
+
+mod route_id;
+pub use route_id::RouteId;
+
+mod route_table;
+
+mod storage;
+pub use storage::Storage;
+
+mod deny_mode;
+pub use deny_mode::DenyMode;
+
+mod unrouted;
+pub use unrouted::Unrouted;
+
+mod engine;
+pub use engine::Engine;
+
+mod static_router;
+pub use static_router::StaticRouter;
+
+mod frozen_router;
+pub use frozen_router::FrozenRouter;
+
+mod shared;
+
+mod erased;
+pub use erased::{ErasedMatch, ErasedRouter};
+
+mod url_router;
+pub use url_router::{UrlMatch, UrlRouter, UrlSpec};
+
+mod match_mode;
+pub use match_mode::MatchMode;
+
+mod match_options;
+pub use match_options::MatchOptions;
+
+mod root_policy;
+pub use root_policy::RootPolicy;
+
+mod syntax;
+pub use syntax::Syntax;
+
+mod method_router;
+pub use method_router::{MethodMatch, MethodRouter};
+
+mod resource;
+pub use resource::Resource;
+
+mod router_map;
+pub use router_map::RouterMap;
+
+mod stack;
+pub use stack::Stack;
+
+mod route_decision;
+pub use route_decision::RouteDecision;
+
+mod router_view;
+pub use router_view::RouterView;
+
+mod handler_map;
+pub use handler_map::HandlerMap;
+
+mod route_set;
+pub use route_set::RouteSet;
+
+mod policy;
+pub use policy::{Effect, PolicyMode, PolicyRouter};
+
+mod matcher;
+pub use matcher::Matcher;
+
+#[cfg(feature = "dispatch")]
+pub mod dispatch;
+
+#[cfg(feature = "export")]
+pub mod export;
+
+#[cfg(feature = "openapi")]
+pub mod openapi;
+
+#[cfg(feature = "metrics")]
+pub mod metrics;
+
+#[cfg(feature = "simd")]
+mod simd;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+mod weighted;
+pub use weighted::{RandomWeights, WeightStrategy};
+
+mod condition;
+pub use condition::{Condition, Flag};
+
+mod change_event;
+pub use change_event::ChangeEvent;
+
+mod transaction;
+pub use transaction::Transaction;
+
+mod scope;
+pub use scope::{BoundScope, RouteScope, Scope};
+
+mod scope_params;
+pub use scope_params::{ScopeParams, TypedScope};
+
+mod sort_key;
+pub use sort_key::SortKey;
+
+mod limits;
+pub use limits::Limits;
+
+mod negotiator;
+pub use negotiator::Negotiator;
+
+mod as_path;
+pub use as_path::AsPath;
+
+pub mod normalize;
+
+pub mod rails;
+
+pub mod robots;
+
+pub mod testing;
+
+mod constraint;
+mod express;
+mod generation;
+mod glob;
+mod http_rule;
+mod split;
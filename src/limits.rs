@@ -0,0 +1,108 @@
+/// Bounds on the size and shape of paths that a [`Router`][crate::Router]
+/// will attempt to match. A path that exceeds any of these limits is
+/// rejected with a single cheap pass before any route is tried, which
+/// keeps the worst case cost of routing predictable for untrusted
+/// input. No limits are set by default.
+///
+/// ```rust
+/// use routefinder::{Limits, Router};
+/// let mut router = Router::new();
+/// router.add("/*", ()).unwrap();
+/// router.set_limits(
+///     Limits::new()
+///         .max_bytes(2048)
+///         .max_segments(32)
+///         .max_consecutive_slashes(2),
+/// );
+///
+/// assert!(router.best_match("/a/b/c").is_some());
+/// assert!(router.best_match(&"/a".repeat(33)).is_none());
+/// assert!(router.best_match("/a///b").is_none());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Limits {
+    max_bytes: Option<usize>,
+    max_segments: Option<usize>,
+    max_consecutive_slashes: Option<usize>,
+}
+
+impl Limits {
+    /// Builds a new Limits with nothing limited
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rejects paths that are longer than this many bytes
+    pub fn max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Rejects paths with more than this many segments, where both
+    /// slashes and dots separate segments
+    pub fn max_segments(mut self, max_segments: usize) -> Self {
+        self.max_segments = Some(max_segments);
+        self
+    }
+
+    /// Rejects paths that contain more than this many slashes in a row
+    pub fn max_consecutive_slashes(mut self, max_consecutive_slashes: usize) -> Self {
+        self.max_consecutive_slashes = Some(max_consecutive_slashes);
+        self
+    }
+
+    /// whether this path is within all of the limits
+    ///
+    /// ```rust
+    /// use routefinder::Limits;
+    /// let limits = Limits::new().max_segments(3);
+    /// assert!(limits.allows("/a/b.c"));
+    /// assert!(!limits.allows("/a/b.c.d"));
+    /// assert!(Limits::new().allows(&"/a".repeat(10_000)));
+    /// ```
+    pub fn allows(&self, path: &str) -> bool {
+        if self.max_bytes.is_some_and(|max| path.len() > max) {
+            return false;
+        }
+
+        if self.max_segments.is_none() && self.max_consecutive_slashes.is_none() {
+            return true;
+        }
+
+        let max_segments = self.max_segments.unwrap_or(usize::MAX);
+        let max_slashes = self.max_consecutive_slashes.unwrap_or(usize::MAX);
+        let mut segments = 0;
+        let mut slashes = 0;
+        let mut in_segment = false;
+
+        for byte in path.bytes() {
+            match byte {
+                b'/' => {
+                    slashes += 1;
+                    if slashes > max_slashes {
+                        return false;
+                    }
+                    in_segment = false;
+                }
+
+                b'.' => {
+                    slashes = 0;
+                    in_segment = false;
+                }
+
+                _ => {
+                    slashes = 0;
+                    if !in_segment {
+                        in_segment = true;
+                        segments += 1;
+                        if segments > max_segments {
+                            return false;
+                        }
+                    }
+                }
+            }
+        }
+
+        true
+    }
+}
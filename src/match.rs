@@ -1,20 +1,124 @@
-use crate::{Capture, Captures, RouteSpec, Segment};
-use std::{cmp::Ordering, ops::Deref};
+use crate::{
+    normalize, Captures, Collision, MatchOptions, RouteConfig, RouteId, RouteSpec, Segment,
+};
+use smartcow::SmartCow;
+use std::{
+    any::Any,
+    borrow::Cow,
+    cmp::Ordering,
+    fmt::{self, Debug, Formatter},
+    iter,
+    ops::Deref,
+};
 
 /// The output of a successful application of a [`RouteSpec`] to a str
 /// path, as well as references to any captures.
 ///
 /// It dereferences to the contained Handler type
-
-#[derive(Debug)]
 pub struct Match<'router, 'path, Handler> {
-    pub(crate) path: &'path str,
+    pub(crate) path: Cow<'path, str>,
+    pub(crate) raw: Cow<'path, str>,
+    pub(crate) options: MatchOptions,
     pub(crate) route: &'router RouteSpec,
-    pub(crate) captures: Vec<&'path str>,
+    pub(crate) captures: Vec<Cow<'path, str>>,
     pub(crate) handler: &'router Handler,
+    // the format chosen by Router::best_match_negotiated
+    pub(crate) format: Option<&'router str>,
+}
+
+/// Matches are displayed with their route, the path that was matched,
+/// and their named captures, rather than the position of each capture
+/// in the route.
+///
+/// ```rust
+/// let mut router = routefinder::Router::new();
+/// router.add("/users/:id/*", "user").unwrap();
+/// let m = router.best_match("/users/1/posts").unwrap();
+/// assert_eq!(
+///     format!("{:?}", m),
+///     r#"Match { route: /users/:id/*, path: "/users/1/posts", captures: {"id": "1", "*": "posts"}, handler: "user" }"#
+/// );
+/// ```
+impl<Handler: Debug> Debug for Match<'_, '_, Handler> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Match")
+            .field("route", &format_args!("{}", self.route))
+            .field("path", &self.path())
+            .field("captures", &self.captures())
+            .field("handler", self.handler)
+            .finish()
+    }
 }
 
 impl<'router, 'path, Handler> Match<'router, 'path, Handler> {
+    /// applies the route to the path, which has already been
+    /// prepared from the raw path by the router
+    pub(crate) fn try_new(
+        route: &'router RouteSpec,
+        handler: &'router Handler,
+        path: &Cow<'path, str>,
+        raw: &Cow<'path, str>,
+        options: &MatchOptions,
+    ) -> Option<Self> {
+        Self::try_new_in(route, handler, path, raw, options, None)
+    }
+
+    /// applies the route to the path, checking the route's guard, if
+    /// any, against the context of the match
+    pub(crate) fn try_new_in(
+        route: &'router RouteSpec,
+        handler: &'router Handler,
+        path: &Cow<'path, str>,
+        raw: &Cow<'path, str>,
+        options: &MatchOptions,
+        context: Option<&dyn Any>,
+    ) -> Option<Self> {
+        if !route.is_enabled_in(context) {
+            return None;
+        }
+
+        let captures = match path {
+            Cow::Borrowed(path) => route
+                .matches_with(path, options)?
+                .into_iter()
+                .map(Cow::Borrowed)
+                .collect(),
+            Cow::Owned(path) => route
+                .matches_with(path, options)?
+                .into_iter()
+                .map(|capture| Cow::Owned(capture.to_owned()))
+                .collect(),
+        };
+
+        Some(Self {
+            path: path.clone(),
+            raw: raw.clone(),
+            options: *options,
+            route,
+            captures,
+            handler,
+            format: None,
+        })
+    }
+
+    /// Returns a match that owns its path and captures, so that it
+    /// can outlive the path
+    pub fn into_owned(self) -> Match<'router, 'static, Handler> {
+        Match {
+            path: Cow::Owned(self.path.into_owned()),
+            raw: Cow::Owned(self.raw.into_owned()),
+            options: self.options,
+            route: self.route,
+            captures: self
+                .captures
+                .into_iter()
+                .map(|capture| Cow::Owned(capture.into_owned()))
+                .collect(),
+            handler: self.handler,
+            format: self.format,
+        }
+    }
+
     /// Returns a reference to the handler associated with this route
     pub fn handler(&self) -> &'router Handler {
         self.handler
@@ -25,33 +129,323 @@ impl<'router, 'path, Handler> Match<'router, 'path, Handler> {
         self.route
     }
 
-    /// returns the exact path that was matched
-    pub fn path(&self) -> &'path str {
-        self.path
+    /// Returns the format for routes added with
+    /// [`Router::add_with_formats`][crate::Router::add_with_formats].
+    /// This is the suffix of the path if it had one of the registered
+    /// formats, and otherwise the default format, or the format chosen
+    /// by [`Router::best_match_negotiated`][crate::Router::best_match_negotiated].
+    pub fn format(&self) -> Option<&'router str> {
+        self.format.or_else(|| self.route.format())
+    }
+
+    /// Returns the operational settings of the matched route, if it
+    /// was added with
+    /// [`Router::add_with_config`][crate::Router::add_with_config]
+    pub fn config(&self) -> Option<&'router RouteConfig> {
+        self.route.config()
+    }
+
+    /// returns the exact path that was matched. If the router
+    /// normalizes paths, this is the normalized path.
+    pub fn path(&self) -> &str {
+        &self.path
     }
 
     /// Returns the [`Captures`] for this match
     pub fn captures(&self) -> Captures<'router, 'path> {
-        self.route
-            .segments()
-            .iter()
-            .filter(|s| matches!(s, Segment::Param(_) | Segment::Wildcard))
-            .zip(&self.captures)
-            .fold(
-                Captures::default(),
-                |mut captures, (segment, capture)| match segment {
-                    Segment::Param(name) => {
-                        captures.push(Capture::new(&**name, *capture));
-                        captures
+        let mut captures = self.route.captures(self.captures.iter().cloned());
+        if self.options.collapse_wildcard_slashes {
+            if let Some(wildcard) = captures.wildcard.take() {
+                captures.set_wildcard(collapse_slashes(wildcard));
+            }
+        }
+        captures
+    }
+
+    /// Returns the wildcard of this match, or the rest of the path for
+    /// a prefix route, with no slash at either end and each run of
+    /// slashes within it collapsed to one, whether or not the router
+    /// [collapses wildcard slashes][crate::MatchOptions::collapse_wildcard_slashes].
+    /// This can be appended to a base url after a single slash.
+    ///
+    /// ```rust
+    /// let mut router = routefinder::Router::new();
+    /// router.add("/proxy/*", "proxy").unwrap();
+    /// let m = router.best_match("/proxy//a//b/").unwrap();
+    /// assert_eq!(m.wildcard().unwrap(), "a/b");
+    /// assert_eq!(m.captures().wildcard(), Some("/a//b"));
+    /// assert_eq!(m.wildcard_raw().unwrap(), "/a//b/");
+    /// ```
+    pub fn wildcard(&self) -> Option<Cow<'path, str>> {
+        self.captures().wildcard.map(collapse_slashes)
+    }
+
+    /// Returns the part of the [raw path][Match::raw_path] that the
+    /// wildcard matched, including any slashes at either end, as in
+    /// [`Match::raw_captures`]
+    pub fn wildcard_raw(&self) -> Option<Cow<'_, str>> {
+        match self.raw_captures().wildcard? {
+            SmartCow::Borrowed(wildcard) => Some(Cow::Borrowed(wildcard)),
+            SmartCow::Owned(wildcard) => Some(Cow::Owned(wildcard.into())),
+        }
+    }
+
+    /// Returns the [`Captures`] of this match combined with those of a
+    /// match of its wildcard by another router, such as a router that
+    /// is mounted under `/tenants/:tenant/*`. The params of this match
+    /// are kept where the inner match has a param with the same name,
+    /// and the wildcard is the inner match's. See
+    /// [`Match::chain_with`] to choose how names that collide are
+    /// handled.
+    ///
+    /// ```rust
+    /// let mut outer = routefinder::Router::new();
+    /// outer.add("/tenants/:tenant/*", "tenants").unwrap();
+    /// let mut inner = routefinder::Router::new();
+    /// inner.add("/users/:id/*", "user").unwrap();
+    ///
+    /// let outer_match = outer.best_match("/tenants/acme/users/1/avatar").unwrap();
+    /// let outer_captures = outer_match.captures();
+    /// let inner_match = inner.best_match(outer_captures.wildcard().unwrap()).unwrap();
+    ///
+    /// let captures = outer_match.chain(&inner_match);
+    /// assert_eq!(captures.get("tenant"), Some("acme"));
+    /// assert_eq!(captures.get("id"), Some("1"));
+    /// assert_eq!(captures.wildcard(), Some("avatar"));
+    /// ```
+    pub fn chain<'inner, 'keys, 'values, Inner>(
+        &self,
+        inner: &Match<'inner, 'values, Inner>,
+    ) -> Captures<'keys, 'values>
+    where
+        'router: 'keys,
+        'inner: 'keys,
+        'path: 'values,
+    {
+        self.chain_with(inner, Collision::default())
+    }
+
+    /// Returns the [`Captures`] of this match combined with those of a
+    /// match of its wildcard by another router, resolving params with
+    /// the same name as the [`Collision`] specifies. See
+    /// [`Match::chain`].
+    pub fn chain_with<'inner, 'keys, 'values, Inner>(
+        &self,
+        inner: &Match<'inner, 'values, Inner>,
+        collision: Collision,
+    ) -> Captures<'keys, 'values>
+    where
+        'router: 'keys,
+        'inner: 'keys,
+        'path: 'values,
+    {
+        let mut captures: Captures<'keys, 'values> = self.captures();
+        captures.merge(inner.captures(), collision);
+        captures
+    }
+
+    /// Renders the matched route with this match's captures, as with
+    /// [`RouteSpec::template`], without a trailing slash unless the
+    /// path is `/`. A prefix route is followed by the rest of the
+    /// path. Returns None for routes with globs, which cannot be
+    /// rendered.
+    ///
+    /// The canonical path is always matched by the same route with the
+    /// same captures. It is equal to [`Match::path`] unless the path
+    /// had more than one leading slash, a trailing slash, or exact text
+    /// that only matched with
+    /// [`MatchOptions::case_insensitive`][crate::MatchOptions::case_insensitive],
+    /// so a difference indicates a path that could be redirected to its
+    /// canonical form. Exact text is always spelled as the route was
+    /// registered. See [`Router::canonical_path`][crate::Router::canonical_path].
+    ///
+    /// ```rust
+    /// let mut router = routefinder::Router::new();
+    /// router.add("/users/:id", ()).unwrap();
+    /// let m = router.best_match("/users/1").unwrap();
+    /// assert_eq!(m.canonical_path().unwrap(), m.path());
+    ///
+    /// let m = router.best_match("//users/1/").unwrap();
+    /// assert_eq!(m.canonical_path().unwrap(), "/users/1");
+    /// ```
+    pub fn canonical_path(&self) -> Option<String> {
+        self.canonical_path_from(self.captures())
+    }
+
+    /// the canonical path rendered with the [raw
+    /// captures][Match::raw_captures], which keep their
+    /// percent-encoding, so that it is safe to use as a redirect
+    pub(crate) fn canonical_raw_path(&self) -> Option<String> {
+        self.canonical_path_from(self.raw_captures())
+    }
+
+    fn canonical_path_from(&self, captures: Captures<'_, '_>) -> Option<String> {
+        let mut path = if self.route.mode().is_prefix()
+            && !self.route.segments().contains(&Segment::Wildcard)
+        {
+            let params: Captures = captures.iter().collect();
+            let mut path = self.route.template(&params)?.to_string();
+            if let Some(rest) = captures.wildcard().filter(|rest| !rest.is_empty()) {
+                if !path.ends_with('/') {
+                    path.push('/');
+                }
+                path.push_str(rest);
+            }
+            path
+        } else {
+            self.route.template(&captures)?.to_string()
+        };
+
+        while path.len() > 1 && path.ends_with('/') {
+            path.pop();
+        }
+        Some(path)
+    }
+
+    /// Returns the matched route's template with the params named in
+    /// the mask replaced by their captured values, and every other
+    /// param left as a placeholder. Use `"*"` in the mask for the
+    /// wildcard, which for a prefix route is the rest of the path.
+    /// Because unmasked values never appear, the number of distinct
+    /// keys is bounded by the routes and the values of masked params,
+    /// which makes these suitable as cache or metrics keys.
+    ///
+    /// ```rust
+    /// let mut router = routefinder::Router::new();
+    /// router.add("/orgs/:org/users/:id", ()).unwrap();
+    /// router.add("/assets/*", ()).unwrap();
+    ///
+    /// let m = router.best_match("/orgs/acme/users/42").unwrap();
+    /// assert_eq!(m.cache_key(&[]), "/orgs/:org/users/:id");
+    /// assert_eq!(m.cache_key(&["org"]), "/orgs/acme/users/:id");
+    ///
+    /// let m = router.best_match("/assets/css/site.css").unwrap();
+    /// assert_eq!(m.cache_key(&[]), "/assets/*");
+    /// assert_eq!(m.cache_key(&["*"]), "/assets/css/site.css");
+    /// ```
+    pub fn cache_key(&self, mask: &[&str]) -> String {
+        let captures = self.captures();
+        let value = |segment: &Segment| match segment {
+            Segment::Wildcard if mask.contains(&"*") => {
+                Some(String::from(captures.wildcard().unwrap_or_default()))
+            }
+            _ => segment
+                .name()
+                .filter(|name| segment.is_capture() && mask.contains(name))
+                .map(|name| String::from(captures.get(name).unwrap_or_default())),
+        };
+
+        let segments = self.route.segments();
+        let mut key = String::from("/");
+        for (index, segment) in segments.iter().enumerate() {
+            let next = segments.get(index + 1);
+            // like a reverse match, an absent optional segment takes
+            // the preceding slash with it
+            let next_is_absent = next.is_some_and(|next| {
+                next.may_be_empty() && value(next).is_some_and(|value| value.is_empty())
+            });
+
+            match (segment, value(segment)) {
+                (Segment::Slash, _) if next_is_absent => {}
+                (_, Some(value)) => key.push_str(&value),
+                (_, None) => {
+                    // writing to a string cannot fail
+                    let _ = self.route.write_segment(segment, &mut key);
+                }
+            }
+        }
+
+        if self.route.mode().is_prefix() && !segments.contains(&Segment::Wildcard) {
+            match captures.wildcard().filter(|rest| !rest.is_empty()) {
+                Some(rest) if mask.contains(&"*") => {
+                    if !key.ends_with('/') {
+                        key.push('/');
                     }
+                    key.push_str(rest);
+                }
+                _ => {}
+            }
+        }
+
+        key
+    }
+
+    /// returns the path exactly as it was provided, before any
+    /// normalization or decoding. For a match from
+    /// [`Router::best_match_normalized`][crate::Router::best_match_normalized],
+    /// this is the normalized path.
+    pub fn raw_path(&self) -> &str {
+        &self.raw
+    }
+
+    /// Returns [`Captures`] whose values are the exact substrings of
+    /// the [raw path][Match::raw_path] that each capture was matched
+    /// from, before dot segments were resolved or percent-encoding was
+    /// decoded. A capture that can span several path segments, such as
+    /// the wildcard, also includes any trailing slashes at the end of
+    /// the path, which are otherwise ignored.
+    ///
+    /// ```rust
+    /// use routefinder::{MatchOptions, Router};
+    /// let mut router = Router::new();
+    /// router.add("/proxy/:host/*", ()).unwrap();
+    /// router.set_match_options(MatchOptions::new().decode(true).resolve_dot_segments(true));
+    ///
+    /// let m = router.best_match("/proxy/x/../my%20host/a%2Fb/./c//").unwrap();
+    /// assert_eq!(m.captures().get("host"), Some("my host"));
+    /// assert_eq!(m.captures().wildcard(), Some("a%2Fb/c"));
+    ///
+    /// let raw = m.raw_captures();
+    /// assert_eq!(raw.get("host"), Some("my%20host"));
+    /// assert_eq!(raw.wildcard(), Some("a%2Fb/./c//"));
+    /// ```
+    pub fn raw_captures(&self) -> Captures<'router, '_> {
+        let spans = match self.route.matches_with(&self.path, &self.options) {
+            Some(spans) => spans,
+            None => return self.captures(),
+        };
 
-                    Segment::Wildcard => {
-                        captures.set_wildcard(*capture);
-                        captures
+        let offsets = normalize::raw_offsets(&self.raw, &self.options);
+        let capture_segments = self.route.segments().iter().filter(|s| s.is_capture());
+        let spans_segments = capture_segments
+            .map(|segment| {
+                matches!(
+                    segment,
+                    Segment::Wildcard | Segment::OneOrMore(_) | Segment::ZeroOrMore(_)
+                )
+            })
+            // prefix routes capture the rest of the path
+            .chain(iter::once(true));
+
+        let last = spans.len().saturating_sub(1);
+        let path_start = self.path.as_ptr() as usize;
+        let values =
+            spans
+                .iter()
+                .zip(spans_segments)
+                .enumerate()
+                .map(|(index, (span, spans_segments))| {
+                    let start = span.as_ptr() as usize - path_start;
+                    let (start, mut end) = (offsets[start], offsets[start + span.len()]);
+                    if index == last && spans_segments && self.raw[end..].bytes().all(|b| b == b'/')
+                    {
+                        end = self.raw.len();
                     }
-                    _ => captures,
-                },
-            )
+                    self.raw.get(start..end).unwrap_or(span)
+                });
+
+        self.route.captures(values.collect::<Vec<_>>())
+    }
+}
+
+/// collapses the slashes of a wildcard capture, keeping it borrowed
+/// when it can be
+fn collapse_slashes(wildcard: SmartCow<'_>) -> Cow<'_, str> {
+    match wildcard {
+        SmartCow::Borrowed(wildcard) => normalize::collapse_slashes(wildcard),
+        SmartCow::Owned(wildcard) => {
+            Cow::Owned(normalize::collapse_slashes(&wildcard).into_owned())
+        }
     }
 }
 
@@ -82,3 +476,43 @@ impl<'router, 'path, Handler> Deref for Match<'router, 'path, Handler> {
         self.handler
     }
 }
+
+/// A match that does not borrow the [`Router`][crate::Router], so that
+/// the router can be mutated while the match is held. Returned by
+/// [`Router::lookup`][crate::Router::lookup], and resolved to the
+/// route and handler with [`Router::resolve`][crate::Router::resolve]
+/// or [`Router::resolve_mut`][crate::Router::resolve_mut].
+///
+/// A MatchIndex refers to a route of the router it was looked up from,
+/// so it does not resolve on any other router, including a clone, or
+/// once routes have been added to or removed from the router.
+#[derive(Debug)]
+pub struct MatchIndex<'path> {
+    pub(crate) id: RouteId,
+    pub(crate) generation: u64,
+    pub(crate) path: Cow<'path, str>,
+    pub(crate) captures: Captures<'static, 'path>,
+}
+
+impl<'path> MatchIndex<'path> {
+    /// Returns the [`RouteId`] of the matched route
+    pub fn id(&self) -> RouteId {
+        self.id
+    }
+
+    /// returns the exact path that was matched. If the router
+    /// normalizes paths, this is the normalized path.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the [`Captures`] for this match
+    pub fn captures(&self) -> &Captures<'static, 'path> {
+        &self.captures
+    }
+
+    /// Returns the [`Captures`] for this match, consuming the match
+    pub fn into_captures(self) -> Captures<'static, 'path> {
+        self.captures
+    }
+}
@@ -0,0 +1,50 @@
+/// How a [`RouteSpec`][crate::RouteSpec] is applied to a path, modeled
+/// after nginx's `location` modifiers. See
+/// [`Router::add_with_mode`][crate::Router::add_with_mode].
+///
+/// Routes are first ordered by mode, in the order `Exact`,
+/// `PriorityPrefix`, `Pattern`, `Prefix`, and then by the usual
+/// precedence rules, except that among prefix routes, longer routes
+/// win. Unlike nginx, which only gives a `^~` location priority over
+/// regex locations when it is the longest matching prefix, a
+/// `PriorityPrefix` route here always takes priority over `Pattern`
+/// routes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum MatchMode {
+    /// The spec must match the whole path, ignoring leading and
+    /// trailing slashes. This is how routefinder routes usually
+    /// behave, and is the counterpart to nginx's regex locations.
+    #[default]
+    Pattern,
+
+    /// Like `location = /path`: the spec must match the whole path,
+    /// which may not have a trailing slash unless it is `/`, and
+    /// these routes take priority over all other modes.
+    Exact,
+
+    /// Like `location /path`: the spec only needs to match the
+    /// beginning of the path, up to a slash. Whatever follows is
+    /// available as the [`wildcard`][crate::Captures::wildcard].
+    /// When several prefixes match, the longest wins, but any
+    /// matching `Pattern` route takes priority.
+    Prefix,
+
+    /// Like `location ^~ /path`: a `Prefix` that takes priority over
+    /// `Pattern` routes.
+    PriorityPrefix,
+}
+
+impl MatchMode {
+    pub(crate) fn is_prefix(self) -> bool {
+        matches!(self, MatchMode::Prefix | MatchMode::PriorityPrefix)
+    }
+
+    pub(crate) fn rank(self) -> u8 {
+        match self {
+            MatchMode::Exact => 0,
+            MatchMode::PriorityPrefix => 1,
+            MatchMode::Pattern => 2,
+            MatchMode::Prefix => 3,
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use crate::{normalize, RootPolicy};
+use std::borrow::Cow;
+
+/// Options that control how a [`Router`][crate::Router] applies its
+/// routes to a path. Each router has a set of options, which can be
+/// replaced with [`Router::set_match_options`][crate::Router::set_match_options],
+/// and individual lookups can use different options with
+/// [`Router::best_match_with`][crate::Router::best_match_with]. By
+/// default, trailing slashes are ignored, exact text is compared case
+/// sensitively, and paths are matched as they are, without decoding.
+///
+/// ```rust
+/// use routefinder::{MatchOptions, Router};
+/// let mut router = Router::new();
+/// router.add("/health", "health").unwrap();
+/// router.add("/users/:name", "user").unwrap();
+/// router.set_match_options(MatchOptions::new().case_insensitive(true).decode(true));
+///
+/// let m = router.best_match("/USERS/jane%20doe/").unwrap();
+/// assert_eq!(m.captures().get("name"), Some("jane doe"));
+///
+/// let strict = MatchOptions::new().strict_trailing_slash(true);
+/// assert!(router.best_match_with("/health", &strict).is_some());
+/// assert!(router.best_match_with("/health/", &strict).is_none());
+/// assert!(router.best_match_with("/HEALTH", &strict).is_none());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct MatchOptions {
+    pub(crate) strict_trailing_slash: bool,
+    pub(crate) case_insensitive: bool,
+    pub(crate) decode: bool,
+    pub(crate) plus_as_space: bool,
+    pub(crate) resolve_dot_segments: bool,
+    pub(crate) collapse_wildcard_slashes: bool,
+    pub(crate) root_policy: RootPolicy,
+}
+
+impl MatchOptions {
+    /// Builds the default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// When enabled, a path with a trailing slash does not match a
+    /// route unless the route is a prefix, as with
+    /// [`MatchMode::Exact`][crate::MatchMode::Exact]. The path `/`
+    /// always matches `/`.
+    pub fn strict_trailing_slash(mut self, strict_trailing_slash: bool) -> Self {
+        self.strict_trailing_slash = strict_trailing_slash;
+        self
+    }
+
+    /// When enabled, the exact text and globs of each route are
+    /// compared with the path ignoring ascii case. Captures keep the
+    /// case of the path.
+    pub fn case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive = case_insensitive;
+        self
+    }
+
+    /// When enabled, paths are percent-decoded with
+    /// [`normalize::percent_decode`][crate::normalize::percent_decode]
+    /// before they are matched, so captures are decoded. Paths that
+    /// do not decode to utf-8 do not match any route.
+    pub fn decode(mut self, decode: bool) -> Self {
+        self.decode = decode;
+        self
+    }
+
+    /// When enabled, each `+` in a path is read as a space before the
+    /// path is matched, as in legacy form-encoded urls, so that
+    /// `/search/red+shoes` matches the exact text of
+    /// `/search/red shoes` and captures `red shoes`. An encoded plus,
+    /// `%2B`, is still a plus when [`MatchOptions::decode`] is also
+    /// enabled. [`Match::raw_captures`][crate::Match::raw_captures]
+    /// keep each `+` as it was in the path.
+    ///
+    /// ```rust
+    /// use routefinder::{MatchOptions, Router};
+    /// let mut router = Router::new();
+    /// router.add("/search/red shoes", "red shoes").unwrap();
+    /// router.add("/search/:query", "search").unwrap();
+    /// router.set_match_options(MatchOptions::new().plus_as_space(true).decode(true));
+    ///
+    /// assert_eq!(*router.best_match("/search/red+shoes").unwrap(), "red shoes");
+    /// let m = router.best_match("/search/c%2B%2B+books").unwrap();
+    /// assert_eq!(m.captures().get("query"), Some("c++ books"));
+    /// assert_eq!(m.raw_captures().get("query"), Some("c%2B%2B+books"));
+    /// ```
+    pub fn plus_as_space(mut self, plus_as_space: bool) -> Self {
+        self.plus_as_space = plus_as_space;
+        self
+    }
+
+    /// When enabled, `.` and `..` segments are resolved before paths
+    /// are matched. See
+    /// [`Router::set_resolve_dot_segments`][crate::Router::set_resolve_dot_segments]
+    pub fn resolve_dot_segments(mut self, resolve_dot_segments: bool) -> Self {
+        self.resolve_dot_segments = resolve_dot_segments;
+        self
+    }
+
+    /// When enabled, the [wildcard][crate::Captures::wildcard] of each
+    /// match has no slash at either end, and each run of slashes within
+    /// it is collapsed to one, so that it can be appended to a base url
+    /// after a single slash. By default, the wildcard is the part of
+    /// the path that it matched, without trailing slashes, so that
+    /// `/proxy//a//b` matched by `/proxy/*` captures `/a//b`. This
+    /// does not change which routes match. See [`Match::wildcard`][crate::Match::wildcard]
+    /// and [`Match::wildcard_raw`][crate::Match::wildcard_raw].
+    ///
+    /// ```rust
+    /// use routefinder::{MatchOptions, Router};
+    /// let mut router = Router::new();
+    /// router.add("/proxy/*", "proxy").unwrap();
+    /// assert_eq!(router.best_match("/proxy//a//b").unwrap().captures().wildcard(), Some("/a//b"));
+    ///
+    /// router.set_match_options(MatchOptions::new().collapse_wildcard_slashes(true));
+    /// let m = router.best_match("/proxy//a//b/").unwrap();
+    /// assert_eq!(m.captures().wildcard(), Some("a/b"));
+    /// assert_eq!(m.wildcard_raw().unwrap(), "/a//b/");
+    /// ```
+    pub fn collapse_wildcard_slashes(mut self, collapse_wildcard_slashes: bool) -> Self {
+        self.collapse_wildcard_slashes = collapse_wildcard_slashes;
+        self
+    }
+
+    /// Sets how the root of a site is matched. See [`RootPolicy`].
+    pub fn root_policy(mut self, root_policy: RootPolicy) -> Self {
+        self.root_policy = root_policy;
+        self
+    }
+
+    /// whether a path might match differently than it reads, segment
+    /// by segment
+    pub(crate) fn transforms_path(&self) -> bool {
+        self.case_insensitive || self.decode || self.plus_as_space || self.resolve_dot_segments
+    }
+
+    /// resolves and decodes a path as these options require, before
+    /// routes are applied to it
+    pub(crate) fn prepare<'a>(&self, path: &'a str) -> Option<Cow<'a, str>> {
+        if path.is_empty() && !self.root_policy.empty_path_is_root {
+            return None;
+        }
+
+        let path = if self.resolve_dot_segments {
+            normalize::resolve_dot_segments(path)?
+        } else {
+            Cow::Borrowed(path)
+        };
+
+        // a plus is replaced before decoding, so that an encoded plus
+        // is not read as a space
+        let path = match path {
+            path if !self.plus_as_space || !path.contains('+') => path,
+            path => Cow::Owned(path.replace('+', " ")),
+        };
+
+        if !self.decode {
+            return Some(path);
+        }
+
+        match path {
+            Cow::Borrowed(path) => normalize::percent_decode(path),
+            Cow::Owned(path) => {
+                normalize::percent_decode(&path).map(|decoded| Cow::Owned(decoded.into_owned()))
+            }
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use crate::{Match, RouteSpec, Router, Segment};
+use std::borrow::Cow;
+
+/// A resumable matcher that is fed a path one segment at a time.
+/// Returned by [`Router::matcher`].
+///
+/// Each segment is checked against the routes that could still match
+/// as soon as it arrives, so a path that cannot match any route is
+/// known to be unroutable without waiting for the rest of it. The
+/// matcher keeps a copy of the fed segments in order to provide
+/// captures, and [`Matcher::finish`] applies the same rules as
+/// [`Router::best_match`] to the routes that remain.
+///
+/// ```rust
+/// let mut router = routefinder::Router::new();
+/// router.add("/users/:id", "user").unwrap();
+/// router.add("/users/:id/posts/*", "posts").unwrap();
+///
+/// let mut matcher = router.matcher();
+/// matcher.feed("users").feed("42");
+/// assert!(matcher.is_viable());
+/// let m = matcher.finish().unwrap();
+/// assert_eq!(*m, "user");
+/// assert_eq!(m.captures().get("id"), Some("42"));
+///
+/// let mut matcher = router.matcher();
+/// matcher.feed("posts");
+/// assert!(!matcher.is_viable());
+/// ```
+#[derive(Debug)]
+pub struct Matcher<'router, Handler> {
+    router: &'router Router<Handler>,
+    path: String,
+    depth: usize,
+    candidates: Vec<Candidate<'router, Handler>>,
+}
+
+#[derive(Debug)]
+struct Candidate<'router, Handler> {
+    route: &'router RouteSpec,
+    handler: &'router Handler,
+    // once a route has a segment that might span several path
+    // segments, it can only be checked against the complete path
+    open: bool,
+}
+
+impl<'router, Handler> Matcher<'router, Handler> {
+    pub(crate) fn new(router: &'router Router<Handler>) -> Self {
+        Self {
+            router,
+            path: String::new(),
+            depth: 0,
+            candidates: router
+                .iter()
+                .filter(|(route, _)| route.is_enabled())
+                .map(|(route, handler)| Candidate {
+                    route,
+                    handler,
+                    open: router.match_options().transforms_path(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Appends a path segment. A segment that contains slashes is
+    /// treated as several segments, and empty segments are skipped.
+    pub fn feed(&mut self, segment: &str) -> &mut Self {
+        for segment in segment.split('/').filter(|s| !s.is_empty()) {
+            self.path.push('/');
+            self.path.push_str(segment);
+
+            let depth = self.depth;
+            self.candidates
+                .retain_mut(|candidate| candidate.accepts(depth, segment));
+            self.depth += 1;
+        }
+
+        self
+    }
+
+    /// Returns false if no route can match the path fed so far,
+    /// regardless of what is fed next
+    pub fn is_viable(&self) -> bool {
+        !self.candidates.is_empty()
+    }
+
+    /// Returns the number of routes that could still match
+    pub fn candidates(&self) -> usize {
+        self.candidates.len()
+    }
+
+    /// Returns the path that has been fed so far
+    pub fn path(&self) -> &str {
+        if self.path.is_empty() {
+            "/"
+        } else {
+            &self.path
+        }
+    }
+
+    /// Returns the best match for the complete path. See
+    /// [`Router::best_match`]
+    pub fn finish(self) -> Option<Match<'router, 'static, Handler>> {
+        let options = self.router.match_options();
+        let raw = Cow::Borrowed(self.path());
+        let path = self.router.prepare(self.path())?;
+        let best = self.candidates.iter().find_map(|candidate| {
+            Match::try_new(candidate.route, candidate.handler, &path, &raw, &options)
+        })?;
+        let best = self
+            .router
+            .pick_weighted(best)
+            .filter(|best| self.router.permits(best))
+            .map(|best| best.into_owned());
+        #[cfg(feature = "metrics")]
+        self.router.record(best.as_ref());
+        best
+    }
+}
+
+impl<Handler> Candidate<'_, Handler> {
+    /// whether this candidate could still match after the path
+    /// segment at this depth
+    fn accepts(&mut self, depth: usize, segment: &str) -> bool {
+        if self.open {
+            return true;
+        }
+
+        let mut groups = self.route.segments().split(|s| *s == Segment::Slash);
+        let group = match groups.nth(depth) {
+            Some(group) if !group.is_empty() => group,
+            _ => return self.route.mode().is_prefix(),
+        };
+
+        let fixed = group.iter().all(|s| {
+            matches!(s, Segment::Exact(_) | Segment::Dot | Segment::Glob(_))
+                || matches!(s, Segment::Param(name) if self.route.constraint(name).is_none())
+        });
+
+        if !fixed {
+            self.open = true;
+            return true;
+        }
+
+        RouteSpec::from(group.to_vec()).matches(segment).is_some()
+    }
+}
@@ -0,0 +1,262 @@
+use crate::{AsPath, Match, RouteId, RouteSpec, Router};
+use smartstring::alias::String as SmartString;
+use std::{
+    collections::BTreeMap,
+    convert::TryInto,
+    fmt::{self, Debug, Formatter},
+};
+
+/// A collection of [`Router`]s, one per http method
+///
+/// Methods are compared case-insensitively, and are otherwise opaque
+/// strings, so nonstandard methods work the same as `GET`.
+///
+/// ```rust
+/// let mut router = routefinder::MethodRouter::new();
+/// router.add("get", "/users/:id", "show").unwrap();
+/// router.add("DELETE", "/users/:id", "destroy").unwrap();
+/// assert_eq!(*router.best_match("GET", "/users/1").unwrap(), "show");
+/// assert_eq!(*router.best_match("delete", "/users/1").unwrap(), "destroy");
+/// assert!(router.best_match("POST", "/users/1").is_none());
+/// ```
+pub struct MethodRouter<Handler> {
+    routers: BTreeMap<SmartString, Router<Handler>>,
+    #[cfg(feature = "metrics")]
+    pub(crate) recorder: Option<Box<dyn crate::metrics::MatchRecorder>>,
+}
+
+impl<Handler> Debug for MethodRouter<Handler> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(&self.routers).finish()
+    }
+}
+
+impl<Handler> Default for MethodRouter<Handler> {
+    fn default() -> Self {
+        Self {
+            routers: Default::default(),
+            #[cfg(feature = "metrics")]
+            recorder: None,
+        }
+    }
+}
+
+fn normalize(method: &str) -> SmartString {
+    method.chars().map(|c| c.to_ascii_uppercase()).collect()
+}
+
+impl<Handler> MethodRouter<Handler> {
+    /// Builds a new method router
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a route for the provided method. See [`Router::add`]
+    pub fn add<R>(
+        &mut self,
+        method: &str,
+        route: R,
+        handler: Handler,
+    ) -> Result<RouteId, <R as TryInto<RouteSpec>>::Error>
+    where
+        R: TryInto<RouteSpec>,
+    {
+        self.routers
+            .entry(normalize(method))
+            .or_default()
+            .add(route, handler)
+    }
+
+    /// Returns the best match for this method and path. See
+    /// [`Router::best_match`]. A `HEAD` request that does not match
+    /// any `HEAD` route falls back to the `GET` routes.
+    ///
+    /// ```rust
+    /// let mut router = routefinder::MethodRouter::new();
+    /// router.add("GET", "/users/:id", "show").unwrap();
+    /// assert_eq!(*router.best_match("HEAD", "/users/1").unwrap(), "show");
+    /// ```
+    pub fn best_match<'a, 'b, P>(
+        &'a self,
+        method: &str,
+        path: &'b P,
+    ) -> Option<Match<'a, 'b, Handler>>
+    where
+        P: AsPath + ?Sized,
+    {
+        let method = normalize(method);
+        let mut best_match = self
+            .routers
+            .get(&method)
+            .and_then(|router| router.best_match(path));
+
+        if best_match.is_none() && method == "HEAD" {
+            best_match = self
+                .routers
+                .get("GET")
+                .and_then(|router| router.best_match(path));
+        }
+
+        #[cfg(feature = "metrics")]
+        self.record(&method, best_match.as_ref());
+        best_match
+    }
+
+    /// Returns the methods that this path could be requested with, in
+    /// alphabetical order. This includes `HEAD` if a `GET` route
+    /// matches, and `OPTIONS` if any route matches. This is the
+    /// contents of an `Allow` header.
+    ///
+    /// ```rust
+    /// let mut router = routefinder::MethodRouter::new();
+    /// router.add("GET", "/users/:id", "show").unwrap();
+    /// router.add("DELETE", "/users/:id", "destroy").unwrap();
+    /// router.add("POST", "/users", "create").unwrap();
+    /// assert_eq!(
+    ///     router.allowed_methods("/users/1"),
+    ///     ["DELETE", "GET", "HEAD", "OPTIONS"]
+    /// );
+    /// assert!(router.allowed_methods("/nope").is_empty());
+    /// ```
+    pub fn allowed_methods<P>(&self, path: &P) -> Vec<&str>
+    where
+        P: AsPath + ?Sized,
+    {
+        let mut methods = self
+            .routers
+            .iter()
+            .filter(|(_, router)| router.match_iter(path).next().is_some())
+            .map(|(method, _)| &**method)
+            .collect::<Vec<_>>();
+
+        if methods.contains(&"GET") && !methods.contains(&"HEAD") {
+            methods.push("HEAD");
+        }
+
+        if !methods.is_empty() && !methods.contains(&"OPTIONS") {
+            methods.push("OPTIONS");
+        }
+
+        methods.sort_unstable();
+        methods
+    }
+
+    /// Routes a request by method and path, distinguishing between
+    /// paths that match no route and paths that match routes for other
+    /// methods, and answering `OPTIONS` requests that do not match an
+    /// `OPTIONS` route with the allowed methods. `HEAD` requests fall
+    /// back to `GET` routes as with [`MethodRouter::best_match`].
+    ///
+    /// ```rust
+    /// use routefinder::{MethodMatch, MethodRouter};
+    /// let mut router = MethodRouter::new();
+    /// router.add("GET", "/users/:id", "show").unwrap();
+    ///
+    /// match router.resolve("GET", "/users/1") {
+    ///     MethodMatch::Found(m) => assert_eq!(*m, "show"),
+    ///     _ => panic!(),
+    /// }
+    /// assert_eq!(
+    ///     router.resolve("OPTIONS", "/users/1").allow(),
+    ///     Some(&["GET", "HEAD", "OPTIONS"][..])
+    /// );
+    /// assert!(matches!(
+    ///     router.resolve("PUT", "/users/1"),
+    ///     MethodMatch::MethodNotAllowed(_)
+    /// ));
+    /// assert!(matches!(router.resolve("GET", "/nope"), MethodMatch::NotFound));
+    /// ```
+    pub fn resolve<'a, 'b, P>(&'a self, method: &str, path: &'b P) -> MethodMatch<'a, 'b, Handler>
+    where
+        P: AsPath + ?Sized,
+    {
+        if let Some(m) = self.best_match(method, path) {
+            return MethodMatch::Found(m);
+        }
+
+        let allow = self.allowed_methods(path);
+        if allow.is_empty() {
+            MethodMatch::NotFound
+        } else if normalize(method) == "OPTIONS" {
+            MethodMatch::Options(allow)
+        } else {
+            MethodMatch::MethodNotAllowed(allow)
+        }
+    }
+
+    /// Returns the [`Router`] for the provided method, if any routes
+    /// have been added for it
+    pub fn router(&self, method: &str) -> Option<&Router<Handler>> {
+        self.routers.get(&normalize(method))
+    }
+
+    /// Returns a mutable reference to the [`Router`] for the provided
+    /// method, if any routes have been added for it
+    pub fn router_mut(&mut self, method: &str) -> Option<&mut Router<Handler>> {
+        self.routers.get_mut(&normalize(method))
+    }
+
+    /// Returns an iterator over the methods that have routes, in
+    /// alphabetical order
+    pub fn methods(&self) -> impl Iterator<Item = &str> {
+        self.routers.keys().map(|method| &**method)
+    }
+
+    /// Returns an iterator of `(method, &RouteSpec, &Handler)`, ordered
+    /// by method and then by route precedence
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &RouteSpec, &Handler)> {
+        self.routers.iter().flat_map(|(method, router)| {
+            router
+                .iter()
+                .map(move |(route, handler)| (&**method, route, handler))
+        })
+    }
+
+    /// returns the total number of routes across all methods
+    pub fn len(&self) -> usize {
+        self.routers.values().map(Router::len).sum()
+    }
+
+    /// returns true if no routes have been added
+    pub fn is_empty(&self) -> bool {
+        self.routers.values().all(Router::is_empty)
+    }
+}
+
+/// The outcome of [`MethodRouter::resolve`]
+#[derive(Debug)]
+pub enum MethodMatch<'router, 'path, Handler> {
+    /// A route matched this method and path
+    Found(Match<'router, 'path, Handler>),
+
+    /// This is an `OPTIONS` request that did not match an `OPTIONS`
+    /// route. Contains the allowed methods for this path.
+    Options(Vec<&'router str>),
+
+    /// Routes matched this path, but not for this method. Contains the
+    /// allowed methods for this path, and would generally be
+    /// represented as a 405 response.
+    MethodNotAllowed(Vec<&'router str>),
+
+    /// No route matched this path for any method
+    NotFound,
+}
+
+impl<'router, 'path, Handler> MethodMatch<'router, 'path, Handler> {
+    /// Returns the match, if a route matched
+    pub fn into_match(self) -> Option<Match<'router, 'path, Handler>> {
+        match self {
+            MethodMatch::Found(m) => Some(m),
+            _ => None,
+        }
+    }
+
+    /// Returns the allowed methods for an `OPTIONS` or method not
+    /// allowed outcome
+    pub fn allow(&self) -> Option<&[&'router str]> {
+        match self {
+            MethodMatch::Options(allow) | MethodMatch::MethodNotAllowed(allow) => Some(allow),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,297 @@
+//! Labels for route metrics, and a hook for recording them
+//!
+//! This is available with the `metrics` feature. [`Match::labels`]
+//! describes a match with the route's template rather than the path,
+//! and a [`MatchRecorder`] set with [`Router::set_recorder`] or
+//! [`MethodRouter::set_recorder`] is called with those labels for
+//! every best match and every miss. Labels never include captured
+//! values, so the number of distinct label sets is bounded by the
+//! number of routes and methods, regardless of the paths requested.
+//!
+//! Routers can also count the best matches of each route with
+//! [`Router::set_hit_counts`]. The counts are relaxed atomics stored
+//! alongside the handlers, so counting never takes a lock, and a
+//! panicking reader cannot poison matching for other threads.
+//!
+//! ```rust
+//! use routefinder::{metrics::Labels, Router};
+//! use std::sync::{Arc, Mutex};
+//!
+//! let requests = Arc::new(Mutex::new(vec![]));
+//! let recorded = requests.clone();
+//! let mut router = Router::new();
+//! router.add("/users/:id", ()).unwrap();
+//! router.set_recorder(move |labels: &Labels| {
+//!     recorded.lock().unwrap().push(labels.to_string());
+//! });
+//!
+//! router.best_match("/users/1");
+//! router.best_match("/users/2");
+//! router.best_match("/nothing");
+//! assert_eq!(
+//!     *requests.lock().unwrap(),
+//!     ["route=\"/users/:id\"", "route=\"/users/:id\"", ""]
+//! );
+//! ```
+
+use crate::{Match, MethodRouter, RouteId, Router};
+use std::{
+    fmt::{self, Debug, Display, Formatter},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+/// The labels for a best match or a miss, shaped for metrics
+/// libraries. A miss has no route.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Labels {
+    route: Option<String>,
+    method: Option<String>,
+}
+
+impl Labels {
+    /// The template of the route that matched, such as `/users/:id`,
+    /// or None for a miss
+    pub fn route(&self) -> Option<&str> {
+        self.route.as_deref()
+    }
+
+    /// The method of the request, for labels from a [`MethodRouter`].
+    /// Methods that the router has no routes for are labeled `OTHER`,
+    /// so that arbitrary methods cannot add label values.
+    pub fn method(&self) -> Option<&str> {
+        self.method.as_deref()
+    }
+
+    /// Returns `(name, value)` pairs for the labels that are present,
+    /// named `route` and `method`
+    pub fn pairs(&self) -> Vec<(&'static str, &str)> {
+        let route = self.route().map(|route| ("route", route));
+        let method = self.method().map(|method| ("method", method));
+        route.into_iter().chain(method).collect()
+    }
+
+    /// whether these are the labels of a best match
+    pub fn is_match(&self) -> bool {
+        self.route.is_some()
+    }
+
+    pub(crate) fn with_method(mut self, method: &str) -> Self {
+        self.method = Some(String::from(method));
+        self
+    }
+}
+
+impl Display for Labels {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (index, (name, value)) in self.pairs().into_iter().enumerate() {
+            if index > 0 {
+                f.write_str(",")?;
+            }
+            write!(f, "{}={:?}", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Called with the [`Labels`] of each best match and each miss. See
+/// [`Router::set_recorder`].
+///
+/// This is implemented for any `Fn(&Labels)`.
+pub trait MatchRecorder: Send + Sync {
+    /// records a best match or a miss, which can be distinguished with
+    /// [`Labels::is_match`]
+    fn record(&self, labels: &Labels);
+}
+
+impl<F> MatchRecorder for F
+where
+    F: Fn(&Labels) + Send + Sync,
+{
+    fn record(&self, labels: &Labels) {
+        self(labels)
+    }
+}
+
+impl<Handler> Match<'_, '_, Handler> {
+    /// Returns the [`Labels`] for this match, which contain the
+    /// route's template but never the captured values
+    ///
+    /// ```rust
+    /// let mut router = routefinder::Router::new();
+    /// router.add("/users/:id", ()).unwrap();
+    /// let labels = router.best_match("/users/1").unwrap().labels();
+    /// assert_eq!(labels.pairs(), [("route", "/users/:id")]);
+    /// ```
+    pub fn labels(&self) -> Labels {
+        Labels {
+            route: Some(self.route().to_string()),
+            method: None,
+        }
+    }
+}
+
+impl<Handler> Router<Handler> {
+    /// Sets a [`MatchRecorder`] that is called with the [`Labels`] of
+    /// each best match and each miss from [`Router::best_match`] and
+    /// its variants. Iterating over matches is not recorded.
+    pub fn set_recorder(&mut self, recorder: impl MatchRecorder + 'static) {
+        self.recorder = Some(Arc::new(recorder));
+    }
+
+    pub(crate) fn record(&self, best: Option<&Match<'_, '_, Handler>>) {
+        if let Some(id) = best.and_then(|best| best.route().id()) {
+            self.hits.count(id);
+        }
+
+        if let Some(recorder) = &self.recorder {
+            recorder.record(&best.map(Match::labels).unwrap_or_default());
+        }
+    }
+
+    /// Enables or disables counting the best matches of each route
+    /// from [`Router::best_match`] and its variants. Counts start at
+    /// zero when enabled, and are discarded when disabled.
+    ///
+    /// ```rust
+    /// let mut router = routefinder::Router::new();
+    /// let id = router.add("/users/:id", ()).unwrap();
+    /// router.set_hit_counts(true);
+    ///
+    /// router.best_match("/users/1");
+    /// router.best_match("/users/2");
+    /// router.best_match("/nothing");
+    /// assert_eq!(router.hit_count(id), Some(2));
+    /// assert_eq!(router.hit_counts().collect::<Vec<_>>(), [(id, 2)]);
+    ///
+    /// router.reset_hit_counts();
+    /// assert_eq!(router.hit_count(id), Some(0));
+    /// ```
+    pub fn set_hit_counts(&mut self, enabled: bool) {
+        self.hits = if enabled {
+            HitCounts::enabled(self.next_id().get())
+        } else {
+            HitCounts::default()
+        };
+    }
+
+    /// Returns the number of best matches of the route with this id,
+    /// or None if hit counts are not enabled or there is no such route
+    pub fn hit_count(&self, id: RouteId) -> Option<u64> {
+        self.handler(id)?;
+        self.hits.get(id)
+    }
+
+    /// Returns the [`RouteId`] and number of best matches of each
+    /// route, in order of precedence. This is empty if hit counts are
+    /// not enabled.
+    pub fn hit_counts(&self) -> impl Iterator<Item = (RouteId, u64)> + '_ {
+        self.iter()
+            .filter_map(|(route, _)| route.id())
+            .filter_map(move |id| Some((id, self.hits.get(id)?)))
+    }
+
+    /// Sets the count of every route back to zero, such as after the
+    /// counts are exported
+    pub fn reset_hit_counts(&self) {
+        self.hits.reset();
+    }
+}
+
+/// the number of best matches of each route, indexed by id, if enabled
+#[derive(Default)]
+pub(crate) struct HitCounts(Option<Vec<AtomicU64>>);
+
+impl HitCounts {
+    fn enabled(len: usize) -> Self {
+        Self(Some((0..len).map(|_| AtomicU64::new(0)).collect()))
+    }
+
+    /// makes room for the counts of routes added since, if enabled
+    pub(crate) fn resize(&mut self, len: usize) {
+        if let Some(counts) = &mut self.0 {
+            counts.resize_with(len.max(counts.len()), || AtomicU64::new(0));
+        }
+    }
+
+    fn count(&self, id: RouteId) {
+        if let Some(count) = self.0.as_ref().and_then(|counts| counts.get(id.get())) {
+            count.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn get(&self, id: RouteId) -> Option<u64> {
+        let count = self.0.as_ref()?.get(id.get())?;
+        Some(count.load(Ordering::Relaxed))
+    }
+
+    fn reset(&self) {
+        for count in self.0.iter().flatten() {
+            count.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Clone for HitCounts {
+    fn clone(&self) -> Self {
+        Self(self.0.as_ref().map(|counts| {
+            counts
+                .iter()
+                .map(|count| AtomicU64::new(count.load(Ordering::Relaxed)))
+                .collect()
+        }))
+    }
+}
+
+impl Debug for HitCounts {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Some(counts) => f.debug_list().entries(counts).finish(),
+            None => f.write_str("disabled"),
+        }
+    }
+}
+
+impl<Handler> MethodRouter<Handler> {
+    /// Sets a [`MatchRecorder`] that is called with the [`Labels`],
+    /// including the method, of each best match and each miss from
+    /// [`MethodRouter::best_match`] and [`MethodRouter::resolve`].
+    /// Recorders set on the router for each method are also called.
+    ///
+    /// ```rust
+    /// use routefinder::{metrics::Labels, MethodRouter};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let requests = Arc::new(Mutex::new(vec![]));
+    /// let recorded = requests.clone();
+    /// let mut router = MethodRouter::new();
+    /// router.add("GET", "/users/:id", ()).unwrap();
+    /// router.set_recorder(move |labels: &Labels| {
+    ///     recorded.lock().unwrap().push(labels.to_string());
+    /// });
+    ///
+    /// router.best_match("get", "/users/1");
+    /// router.best_match("BREW", "/coffee");
+    /// assert_eq!(
+    ///     *requests.lock().unwrap(),
+    ///     ["route=\"/users/:id\",method=\"GET\"", "method=\"OTHER\""]
+    /// );
+    /// ```
+    pub fn set_recorder(&mut self, recorder: impl MatchRecorder + 'static) {
+        self.recorder = Some(Box::new(recorder));
+    }
+
+    pub(crate) fn record(&self, method: &str, best: Option<&Match<'_, '_, Handler>>) {
+        if let Some(recorder) = &self.recorder {
+            let method = if method == "HEAD" || self.router(method).is_some() {
+                method
+            } else {
+                "OTHER"
+            };
+            let labels = best.map(Match::labels).unwrap_or_default();
+            recorder.record(&labels.with_method(method));
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::BTreeMap;
+
+/// Chooses between the formats of a route added with
+/// [`Router::add_with_formats`][crate::Router::add_with_formats] from
+/// an HTTP `Accept` header, for paths that do not end in one of the
+/// formats. See
+/// [`Router::best_match_negotiated`][crate::Router::best_match_negotiated].
+///
+/// Each format is a media type, such as `json` for `application/json`.
+/// Common formats are known by default, and others can be added with
+/// [`Negotiator::with_media_type`].
+///
+/// ```rust
+/// use routefinder::Negotiator;
+/// let negotiator = Negotiator::new().with_media_type("ics", "text/calendar");
+/// let formats = ["json", "html", "ics"];
+/// assert_eq!(negotiator.negotiate("text/html,*/*;q=0.8", formats), Some("html"));
+/// assert_eq!(negotiator.negotiate("text/*;q=0.5, application/json", formats), Some("json"));
+/// assert_eq!(negotiator.negotiate("text/calendar", formats), Some("ics"));
+/// assert_eq!(negotiator.negotiate("*/*", formats), Some("json"));
+/// assert_eq!(negotiator.negotiate("", formats), Some("json"));
+/// assert_eq!(negotiator.negotiate("image/png", formats), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Negotiator {
+    media_types: BTreeMap<String, String>,
+}
+
+impl Default for Negotiator {
+    fn default() -> Self {
+        let media_types = [
+            ("atom", "application/atom+xml"),
+            ("css", "text/css"),
+            ("csv", "text/csv"),
+            ("html", "text/html"),
+            ("jpg", "image/jpeg"),
+            ("js", "text/javascript"),
+            ("json", "application/json"),
+            ("md", "text/markdown"),
+            ("pdf", "application/pdf"),
+            ("png", "image/png"),
+            ("rss", "application/rss+xml"),
+            ("svg", "image/svg+xml"),
+            ("txt", "text/plain"),
+            ("xml", "application/xml"),
+            ("yaml", "application/yaml"),
+        ];
+
+        Self {
+            media_types: media_types
+                .iter()
+                .map(|(format, media_type)| (format.to_string(), media_type.to_string()))
+                .collect(),
+        }
+    }
+}
+
+impl Negotiator {
+    /// Builds a new negotiator that knows the media types of common
+    /// formats
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns this negotiator with the media type for a format,
+    /// replacing any it already had
+    pub fn with_media_type(mut self, format: &str, media_type: &str) -> Self {
+        self.media_types
+            .insert(format.to_string(), media_type.to_ascii_lowercase());
+        self
+    }
+
+    /// Returns the media type for a format, if it is known
+    pub fn media_type(&self, format: &str) -> Option<&str> {
+        self.media_types.get(format).map(String::as_str)
+    }
+
+    /// Chooses the format that the `Accept` header prefers. Each format
+    /// has the quality of the most specific media range that includes
+    /// its media type, and the first of the formats with the highest
+    /// quality is chosen. Returns the first format if the header is
+    /// empty, and None if the header accepts none of the formats. A
+    /// format with no known media type is only included by `*/*`.
+    pub fn negotiate<'format>(
+        &self,
+        accept: &str,
+        formats: impl IntoIterator<Item = &'format str>,
+    ) -> Option<&'format str> {
+        let ranges: Vec<MediaRange<'_>> = accept.split(',').filter_map(MediaRange::parse).collect();
+        let mut formats = formats.into_iter();
+        if ranges.is_empty() {
+            return formats.next();
+        }
+
+        let mut best: Option<(u16, &str)> = None;
+        for format in formats {
+            let media_type = self.media_type(format).unwrap_or_default();
+            let quality = ranges
+                .iter()
+                .filter_map(|range| Some((range.specificity(media_type)?, range.quality)))
+                .max_by_key(|(specificity, _)| *specificity)
+                .map(|(_, quality)| quality)
+                .unwrap_or_default();
+
+            if quality > 0 && best.is_none_or(|(best, _)| quality > best) {
+                best = Some((quality, format));
+            }
+        }
+        best.map(|(_, format)| format)
+    }
+}
+
+/// one media range of an accept header, with its quality in
+/// thousandths
+struct MediaRange<'accept> {
+    media_type: &'accept str,
+    quality: u16,
+}
+
+impl<'accept> MediaRange<'accept> {
+    fn parse(range: &'accept str) -> Option<Self> {
+        let mut parts = range.split(';').map(str::trim);
+        let media_type = parts.next().filter(|media_type| media_type.contains('/'))?;
+        let quality = parts
+            .filter_map(|param| param.strip_prefix("q="))
+            .next()
+            .map_or(1000, parse_quality);
+        Some(Self {
+            media_type,
+            quality,
+        })
+    }
+
+    /// how specifically this range includes the media type, or None
+    /// if it does not include it
+    fn specificity(&self, media_type: &str) -> Option<u8> {
+        if self.media_type == "*/*" {
+            return Some(0);
+        }
+
+        if self.media_type.eq_ignore_ascii_case(media_type) {
+            return Some(2);
+        }
+
+        let (kind, subtype) = self.media_type.split_once('/')?;
+        let matches_kind = media_type
+            .split_once('/')
+            .is_some_and(|(other, _)| other.eq_ignore_ascii_case(kind));
+        (subtype == "*" && matches_kind).then_some(1)
+    }
+}
+
+/// parses a quality value such as `0.8` as thousandths, treating
+/// invalid values as zero
+fn parse_quality(quality: &str) -> u16 {
+    let (whole, fraction) = quality.split_once('.').unwrap_or((quality, ""));
+    let digits = fraction.chars().chain("000".chars()).take(3);
+    let fraction = digits
+        .map(|digit| digit.to_digit(10))
+        .try_fold(0, |total, digit| Some(total * 10 + digit?));
+    match (whole, fraction) {
+        ("1", Some(_)) => 1000,
+        ("0", Some(fraction)) | ("", Some(fraction)) => fraction as u16,
+        _ => 0,
+    }
+}
@@ -0,0 +1,299 @@
+//! Path normalization routines that can be applied before matching
+//!
+//! See [`Router::set_resolve_dot_segments`][crate::Router::set_resolve_dot_segments]
+//! to apply these automatically.
+
+use crate::MatchOptions;
+use std::{
+    borrow::Cow,
+    fmt::{self, Display, Formatter},
+    ops::Deref,
+};
+
+/// A path whose dot segments have already been resolved by this
+/// crate. Routers accept a NormalizedPath through methods like
+/// [`Router::best_match_normalized`][crate::Router::best_match_normalized],
+/// which skip resolving dot segments again, so an application that
+/// normalizes each path once can be certain that the router matches
+/// exactly that path.
+///
+/// The only way to build a NormalizedPath is with
+/// [`NormalizedPath::new`], so holding one guarantees that its dot
+/// segments were resolved as the router would resolve them. The router
+/// still applies the rest of its [`MatchOptions`], such as
+/// [decoding][MatchOptions::decode], so a NormalizedPath matches the
+/// same routes with the same captures as the original path does on a
+/// router that resolves dot segments.
+///
+/// ```rust
+/// use routefinder::{normalize::NormalizedPath, Router};
+/// let mut router = Router::new();
+/// router.add("/users/:id", ()).unwrap();
+///
+/// let path = NormalizedPath::new("/posts/../users/1").unwrap();
+/// assert_eq!(path.as_str(), "/users/1");
+/// let m = router.best_match_normalized(&path).unwrap();
+/// assert_eq!(m.captures().get("id"), Some("1"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NormalizedPath<'path>(Cow<'path, str>);
+
+impl<'path> NormalizedPath<'path> {
+    /// Normalizes a path by resolving its dot segments with
+    /// [`resolve_dot_segments`], returning None if the path would
+    /// traverse above the root.
+    pub fn new(path: &'path str) -> Option<Self> {
+        resolve_dot_segments(path).map(Self)
+    }
+
+    /// Returns the normalized path as a str
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns a NormalizedPath that owns its contents
+    pub fn into_owned(self) -> NormalizedPath<'static> {
+        NormalizedPath(Cow::Owned(self.0.into_owned()))
+    }
+}
+
+impl Deref for NormalizedPath<'_> {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl AsRef<str> for NormalizedPath<'_> {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for NormalizedPath<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Resolves `.` and `..` path segments as described in [RFC 3986
+/// section 5.2.4](https://www.rfc-editor.org/rfc/rfc3986#section-5.2.4),
+/// treating percent-encoded dots (`%2e`) as dots. Unlike the RFC, a
+/// `..` that would traverse above the root is an error, and this
+/// returns None. Paths without any dot segments are returned borrowed.
+///
+/// ```rust
+/// use routefinder::normalize::resolve_dot_segments;
+/// assert_eq!(resolve_dot_segments("/a/b/../c/./d").unwrap(), "/a/c/d");
+/// assert_eq!(resolve_dot_segments("/a/b/..").unwrap(), "/a/");
+/// assert_eq!(resolve_dot_segments("/a/%2e%2E/b").unwrap(), "/b");
+/// assert_eq!(resolve_dot_segments("/file.tar.gz").unwrap(), "/file.tar.gz");
+/// assert!(resolve_dot_segments("/a/../../etc/passwd").is_none());
+/// ```
+pub fn resolve_dot_segments(path: &str) -> Option<Cow<'_, str>> {
+    let resolved = match resolve(path)? {
+        Some(resolved) => resolved,
+        None => return Some(Cow::Borrowed(path)),
+    };
+
+    let mut output = String::with_capacity(path.len());
+    output.push_str(resolved.root);
+    output.push_str(&resolved.segments.join("/"));
+    if resolved.trailing_slash {
+        output.push('/');
+    }
+
+    Some(Cow::Owned(output))
+}
+
+/// the parts of a path that remain once its dot segments are resolved,
+/// which are all slices of that path
+struct Resolved<'path> {
+    root: &'path str,
+    segments: Vec<&'path str>,
+    trailing_slash: bool,
+}
+
+/// resolves dot segments, returning Ok(None) if there are none
+fn resolve(path: &str) -> Option<Option<Resolved<'_>>> {
+    if !path
+        .split('/')
+        .any(|segment| dot_segment(segment).is_some())
+    {
+        return Some(None);
+    }
+
+    let (root, relative) = match path.strip_prefix('/') {
+        Some(relative) => ("/", relative),
+        None => ("", path),
+    };
+
+    let mut segments = vec![];
+    let mut trailing_slash = false;
+
+    for segment in relative.split('/') {
+        trailing_slash = true;
+        match dot_segment(segment) {
+            Some(DotSegment::Current) => {}
+            Some(DotSegment::Parent) => {
+                segments.pop()?;
+            }
+            None => {
+                segments.push(segment);
+                trailing_slash = false;
+            }
+        }
+    }
+
+    Some(Some(Resolved {
+        root,
+        trailing_slash: trailing_slash && !segments.is_empty(),
+        segments,
+    }))
+}
+
+/// Decodes the percent-encoded bytes of a path, except for `%2F`,
+/// which is left encoded so that it does not separate path segments.
+/// Percent signs that are not followed by two hex digits are left as
+/// they are. Returns None if the decoded path is not utf-8, and paths
+/// without any percent-encoding are returned borrowed.
+///
+/// ```rust
+/// use routefinder::normalize::percent_decode;
+/// assert_eq!(percent_decode("/users/jane%20doe").unwrap(), "/users/jane doe");
+/// assert_eq!(percent_decode("/caf%C3%A9").unwrap(), "/café");
+/// assert_eq!(percent_decode("/a%2Fb/100%").unwrap(), "/a%2Fb/100%");
+/// assert!(percent_decode("/%FF").is_none());
+/// ```
+pub fn percent_decode(path: &str) -> Option<Cow<'_, str>> {
+    if !path.contains('%') {
+        return Some(Cow::Borrowed(path));
+    }
+
+    String::from_utf8(decode(path, |_| {})).ok().map(Cow::Owned)
+}
+
+/// Trims the slashes at either end of a path and collapses each run
+/// of slashes within it to one, as
+/// [`MatchOptions::collapse_wildcard_slashes`][crate::MatchOptions::collapse_wildcard_slashes]
+/// does to wildcard captures. Paths that are already collapsed are
+/// returned borrowed.
+///
+/// ```rust
+/// use routefinder::normalize::collapse_slashes;
+/// assert_eq!(collapse_slashes("//a//b/c/"), "a/b/c");
+/// assert_eq!(collapse_slashes("a/b"), "a/b");
+/// assert_eq!(collapse_slashes("///"), "");
+/// ```
+pub fn collapse_slashes(path: &str) -> Cow<'_, str> {
+    let trimmed = path.trim_matches('/');
+    if !trimmed.contains("//") {
+        return Cow::Borrowed(trimmed);
+    }
+
+    let mut collapsed = String::with_capacity(trimmed.len());
+    for segment in trimmed.split('/').filter(|segment| !segment.is_empty()) {
+        if !collapsed.is_empty() {
+            collapsed.push('/');
+        }
+        collapsed.push_str(segment);
+    }
+    Cow::Owned(collapsed)
+}
+
+/// decodes a path, calling `record` with the offset in the path of
+/// each decoded byte
+fn decode(path: &str, mut record: impl FnMut(usize)) -> Vec<u8> {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        record(index);
+        let byte = match (bytes[index], bytes.get(index + 1..index + 3)) {
+            (b'%', Some(&[high, low])) => hex(high).zip(hex(low)).map(|(h, l)| h << 4 | l),
+            _ => None,
+        };
+
+        match byte {
+            Some(byte) if byte != b'/' => {
+                decoded.push(byte);
+                index += 3;
+            }
+            _ => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    decoded
+}
+
+/// maps each byte offset of the path that a router would match for
+/// `raw` with these options, and the offset of its end, to the
+/// corresponding offset in `raw`
+pub(crate) fn raw_offsets(raw: &str, options: &MatchOptions) -> Vec<usize> {
+    let mut offsets: Vec<usize> = (0..=raw.len()).collect();
+
+    if options.resolve_dot_segments {
+        if let Some(Some(resolved)) = resolve(raw) {
+            let start = |segment: &str| segment.as_ptr() as usize - raw.as_ptr() as usize;
+            let mut resolved_offsets = vec![];
+            if !resolved.root.is_empty() {
+                resolved_offsets.push(0);
+            }
+            for (index, segment) in resolved.segments.iter().enumerate() {
+                if index > 0 {
+                    let previous = resolved.segments[index - 1];
+                    resolved_offsets.push(start(previous) + previous.len());
+                }
+                resolved_offsets.extend(start(segment)..start(segment) + segment.len());
+            }
+            if let (true, Some(last)) = (resolved.trailing_slash, resolved.segments.last()) {
+                resolved_offsets.push(start(last) + last.len());
+            }
+            resolved_offsets.push(raw.len());
+            offsets = resolved_offsets;
+        }
+    }
+
+    if options.decode {
+        let resolved = match options.resolve_dot_segments {
+            true => resolve_dot_segments(raw),
+            false => Some(Cow::Borrowed(raw)),
+        };
+        if let Some(resolved) = resolved.filter(|resolved| resolved.contains('%')) {
+            let mut decoded_offsets = vec![];
+            decode(&resolved, |index| decoded_offsets.push(offsets[index]));
+            decoded_offsets.push(raw.len());
+            offsets = decoded_offsets;
+        }
+    }
+
+    offsets
+}
+
+fn hex(digit: u8) -> Option<u8> {
+    (digit as char).to_digit(16).map(|digit| digit as u8)
+}
+
+enum DotSegment {
+    Current,
+    Parent,
+}
+
+fn dot_segment(segment: &str) -> Option<DotSegment> {
+    if is_dot(segment) {
+        return Some(DotSegment::Current);
+    }
+
+    let split = if segment.starts_with('.') { 1 } else { 3 };
+    match (segment.get(..split), segment.get(split..)) {
+        (Some(first), Some(second)) if is_dot(first) && is_dot(second) => Some(DotSegment::Parent),
+        _ => None,
+    }
+}
+
+fn is_dot(s: &str) -> bool {
+    s == "." || s.eq_ignore_ascii_case("%2e")
+}
@@ -0,0 +1,119 @@
+//! An importer for the `paths` of an OpenAPI document
+//!
+//! This is available with the `openapi` feature. Because an OpenAPI
+//! path can have a different operation for each http method, the
+//! importer builds a [`MethodRouter`] whose handlers are the
+//! `operationId`s.
+//!
+//! ```rust
+//! let router = routefinder::MethodRouter::from_openapi(r#"{
+//!     "openapi": "3.1.0",
+//!     "paths": {
+//!         "/pets": {
+//!             "get": { "operationId": "listPets" },
+//!             "post": { "operationId": "createPet" }
+//!         },
+//!         "/pets/{petId}": {
+//!             "parameters": [{ "name": "petId", "in": "path", "required": true }],
+//!             "get": { "operationId": "showPetById" }
+//!         }
+//!     }
+//! }"#).unwrap();
+//!
+//! assert_eq!(*router.best_match("POST", "/pets").unwrap(), "createPet");
+//! let m = router.best_match("GET", "/pets/7").unwrap();
+//! assert_eq!(*m, "showPetById");
+//! assert_eq!(m.captures().get("petId"), Some("7"));
+//! ```
+//!
+//! `{param}` templates may be an entire path segment or separated by
+//! dots, as in `/files/{name}.{ext}`, but not embedded in other text,
+//! which routefinder cannot express. Operations without an
+//! `operationId` are named `"METHOD /path"`, as written in the
+//! document. `servers`, `$ref`s, and parameter schemas are ignored.
+
+use crate::MethodRouter;
+use serde_json::Value;
+
+const METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+impl MethodRouter<String> {
+    /// Builds a method router from the `paths` of an OpenAPI (or
+    /// swagger 2) json document. See the [module docs][crate::openapi].
+    pub fn from_openapi(spec: &str) -> Result<Self, String> {
+        let document: Value = serde_json::from_str(spec).map_err(|e| e.to_string())?;
+        let paths = document
+            .get("paths")
+            .and_then(Value::as_object)
+            .ok_or_else(|| String::from("expected an object at `paths`"))?;
+
+        let mut router = Self::new();
+        for (template, item) in paths {
+            let route = convert_template(template)?;
+            let item = item
+                .as_object()
+                .ok_or_else(|| format!("expected an object at `paths.{}`", template))?;
+
+            for (method, operation) in item {
+                if !METHODS.contains(&&**method) {
+                    continue;
+                }
+
+                let operation_id = match operation.get("operationId").and_then(Value::as_str) {
+                    Some(operation_id) => String::from(operation_id),
+                    None => format!("{} {}", method.to_ascii_uppercase(), template),
+                };
+
+                router
+                    .add(method, &*route, operation_id)
+                    .map_err(|e| format!("{}: {}", template, e))?;
+            }
+        }
+
+        Ok(router)
+    }
+}
+
+/// converts an OpenAPI path template such as `/users/{id}` to a
+/// routefinder route such as `/users/:id`
+fn convert_template(template: &str) -> Result<String, String> {
+    let mut route = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let (before, after) = rest.split_at(start);
+        let previous = before.chars().last().or_else(|| route.chars().last());
+        if !matches!(previous, None | Some('/') | Some('.')) {
+            return Err(format!(
+                "`{}` has a param that is not a whole path segment",
+                template
+            ));
+        }
+
+        let end = after
+            .find('}')
+            .ok_or_else(|| format!("`{}` has an unclosed `{{`", template))?;
+        let name = &after[1..end];
+        if name.is_empty() || name.contains(['/', '.', ':', '{']) {
+            return Err(format!(
+                "`{}` has an invalid param name `{}`",
+                template, name
+            ));
+        }
+
+        route.push_str(before);
+        route.push(':');
+        route.push_str(name);
+        rest = &after[end + 1..];
+
+        if !(rest.is_empty() || rest.starts_with(['/', '.'])) {
+            return Err(format!(
+                "`{}` has a param that is not a whole path segment",
+                template
+            ));
+        }
+    }
+    route.push_str(rest);
+    Ok(route)
+}
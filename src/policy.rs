@@ -0,0 +1,195 @@
+use crate::{MatchMode, RouteId, RouteSet, RouteSpec, Router};
+use std::{
+    convert::TryInto,
+    fmt::{self, Debug, Formatter},
+};
+
+/// Whether a [`PolicyRouter`] rule allows or denies the paths it
+/// matches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Effect {
+    /// the path is allowed
+    Allow,
+    /// the path is denied
+    Deny,
+}
+
+/// How a [`PolicyRouter`] combines the rules that match a path
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PolicyMode {
+    /// The highest precedence matching rule decides, with the usual
+    /// routefinder precedence. If an allow and a deny rule have the
+    /// same spec, the deny rule wins.
+    #[default]
+    MostSpecific,
+
+    /// If any matching rule denies the path, it is denied
+    DenyOverrides,
+
+    /// If any matching rule allows the path, it is allowed
+    AllowOverrides,
+}
+
+/// A set of allow and deny rules for paths, such as for path-based
+/// authorization or proxy access control lists.
+///
+/// ```rust
+/// use routefinder::{Effect, PolicyMode, PolicyRouter};
+/// let mut policy = PolicyRouter::new();
+/// policy.deny("/admin/*").unwrap();
+/// policy.allow("/admin/login").unwrap();
+/// policy.allow("/*").unwrap();
+///
+/// assert!(policy.is_allowed("/admin/login"));
+/// assert!(!policy.is_allowed("/admin/users"));
+/// assert!(policy.is_allowed("/posts/1"));
+///
+/// policy.set_mode(PolicyMode::DenyOverrides);
+/// assert!(!policy.is_allowed("/admin/login"));
+/// ```
+pub struct PolicyRouter {
+    rules: Router<Effect>,
+    mode: PolicyMode,
+    default: Effect,
+}
+
+impl Debug for PolicyRouter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PolicyRouter")
+            .field("rules", &self.rules)
+            .field("mode", &self.mode)
+            .field("default", &self.default)
+            .finish()
+    }
+}
+
+impl Default for PolicyRouter {
+    fn default() -> Self {
+        Self {
+            rules: Router::new(),
+            mode: PolicyMode::default(),
+            default: Effect::Deny,
+        }
+    }
+}
+
+impl PolicyRouter {
+    /// Builds a new PolicyRouter that uses [`PolicyMode::MostSpecific`]
+    /// and denies paths that no rule matches
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a rule with the provided effect
+    pub fn add<R>(
+        &mut self,
+        route: R,
+        effect: Effect,
+    ) -> Result<RouteId, <R as TryInto<RouteSpec>>::Error>
+    where
+        R: TryInto<RouteSpec>,
+    {
+        self.rules.add(route, effect)
+    }
+
+    /// Adds a rule with the provided [`MatchMode`] and effect
+    pub fn add_with_mode<R>(
+        &mut self,
+        route: R,
+        mode: MatchMode,
+        effect: Effect,
+    ) -> Result<RouteId, <R as TryInto<RouteSpec>>::Error>
+    where
+        R: TryInto<RouteSpec>,
+    {
+        self.rules.add_with_mode(route, mode, effect)
+    }
+
+    /// Adds a rule that allows the paths it matches
+    pub fn allow<R>(&mut self, route: R) -> Result<RouteId, <R as TryInto<RouteSpec>>::Error>
+    where
+        R: TryInto<RouteSpec>,
+    {
+        self.add(route, Effect::Allow)
+    }
+
+    /// Adds a rule that denies the paths it matches
+    pub fn deny<R>(&mut self, route: R) -> Result<RouteId, <R as TryInto<RouteSpec>>::Error>
+    where
+        R: TryInto<RouteSpec>,
+    {
+        self.add(route, Effect::Deny)
+    }
+
+    /// Sets how matching rules are combined
+    pub fn set_mode(&mut self, mode: PolicyMode) {
+        self.mode = mode;
+    }
+
+    /// Returns how matching rules are combined
+    pub fn mode(&self) -> PolicyMode {
+        self.mode
+    }
+
+    /// Sets the effect for paths that no rule matches
+    pub fn set_default(&mut self, default: Effect) {
+        self.default = default;
+    }
+
+    /// Returns the effect for paths that no rule matches
+    pub fn default_effect(&self) -> Effect {
+        self.default
+    }
+
+    /// Returns the effect of the rules that match this path, or None
+    /// if no rule matches
+    pub fn evaluate(&self, path: &str) -> Option<Effect> {
+        let mut matches = self.rules.match_iter(path).peekable();
+        let best = matches.peek()?.route();
+
+        let (overriding, found) = match self.mode {
+            PolicyMode::MostSpecific => (
+                Effect::Deny,
+                matches
+                    .filter(|m| m.route().is_same_route(best))
+                    .any(|m| *m == Effect::Deny),
+            ),
+            PolicyMode::DenyOverrides => (Effect::Deny, matches.any(|m| *m == Effect::Deny)),
+            PolicyMode::AllowOverrides => (Effect::Allow, matches.any(|m| *m == Effect::Allow)),
+        };
+
+        Some(if found {
+            overriding
+        } else {
+            overriding.opposite()
+        })
+    }
+
+    /// whether this path is allowed, using the default effect if no
+    /// rule matches
+    pub fn is_allowed(&self, path: &str) -> bool {
+        self.evaluate(path).unwrap_or(self.default) == Effect::Allow
+    }
+
+    /// Returns a [`RouteSet`] of the specs for rules with this effect
+    pub fn route_set(&self, effect: Effect) -> RouteSet {
+        self.rules()
+            .filter(|(_, e)| *e == effect)
+            .map(|(route, _)| route.clone())
+            .collect()
+    }
+
+    /// Returns the rules, in precedence order
+    pub fn rules(&self) -> impl Iterator<Item = (&RouteSpec, Effect)> {
+        self.rules.iter().map(|(route, effect)| (route, *effect))
+    }
+}
+
+impl Effect {
+    fn opposite(self) -> Self {
+        match self {
+            Effect::Allow => Effect::Deny,
+            Effect::Deny => Effect::Allow,
+        }
+    }
+}
@@ -0,0 +1,359 @@
+//! A loader for a minimal subset of the rails routes dsl
+//!
+//! ```rust
+//! let router = routefinder::rails::load(r#"
+//!     Rails.application.routes.draw do
+//!       root "pages#home"
+//!       get "/about" => "pages#about"
+//!       resources :posts, only: [:index, :show] do
+//!         resources :comments
+//!       end
+//!     end
+//! "#).unwrap();
+//!
+//! assert_eq!(*router.best_match("GET", "/").unwrap(), "pages#home");
+//! assert_eq!(*router.best_match("GET", "/posts/10").unwrap(), "posts#show");
+//! let m = router.best_match("DELETE", "/posts/10/comments/2").unwrap();
+//! assert_eq!(*m, "comments#destroy");
+//! assert_eq!(m.captures().get("post_id"), Some("10"));
+//! assert!(router.best_match("DELETE", "/posts/10").is_none());
+//! ```
+//!
+//! The supported subset is:
+//! * `get`, `post`, `put`, `patch`, and `delete` with either
+//!   `"/path" => "controller#action"` or `"/path", to: "controller#action"`
+//! * `root "controller#action"` and `root to: "controller#action"`
+//! * `resources` and `resource`, with `only:` and `except:`, nested
+//!   with `do ... end`. Nested resources use the singular form of the
+//!   parent as the param name (`/posts/:post_id/comments`), using
+//!   naive english inflection rules.
+//! * `namespace :name do ... end` and `scope "/path" do ... end`
+//! * `*name` path globs, which must match at least one segment
+//!
+//! Optional segments such as `(.:format)` and constraints are not
+//! supported. As with everything in routefinder, routes are ordered
+//! by precedence rather than by the order they appear in the file.
+
+use crate::{MethodRouter, RouteSpec, Syntax};
+
+const ACTIONS: [&str; 7] = [
+    "index", "new", "create", "show", "edit", "update", "destroy",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Str(String),
+    Symbol(String),
+    Word(String),
+    Key(String),
+    Arrow,
+    Comma,
+    Open,
+    Close,
+}
+
+#[derive(Debug, Default, Clone)]
+struct Scope {
+    path: String,
+    controller: String,
+}
+
+/// Parses a rails routes file into a [`MethodRouter`], where each
+/// handler is the `"controller#action"` string for that route. See
+/// the [module-level docs](crate::rails) for the supported subset.
+pub fn load(source: &str) -> Result<MethodRouter<String>, String> {
+    let mut router = MethodRouter::new();
+    let mut scopes = vec![Scope::default()];
+
+    for (index, line) in source.lines().enumerate() {
+        load_line(line, &mut scopes, &mut router)
+            .map_err(|e| format!("line {}: {}", index + 1, e))?;
+    }
+
+    if scopes.len() > 1 {
+        return Err(String::from("unexpected end of input, expected `end`"));
+    }
+
+    Ok(router)
+}
+
+fn load_line(
+    line: &str,
+    scopes: &mut Vec<Scope>,
+    router: &mut MethodRouter<String>,
+) -> Result<(), String> {
+    let mut tokens = tokenize(line)?;
+    let opens_block = tokens.last() == Some(&Token::Word(String::from("do")));
+    if opens_block {
+        tokens.pop();
+    }
+
+    let scope = scopes.last().cloned().unwrap_or_default();
+    let mut nested = scope.clone();
+
+    match tokens.split_first() {
+        None => {}
+
+        Some((Token::Word(word), [])) if word == "end" => {
+            if scopes.len() == 1 {
+                return Err(String::from("unexpected `end`"));
+            }
+            scopes.pop();
+        }
+
+        Some((Token::Word(word), [])) if word.ends_with("routes.draw") && opens_block => {}
+
+        Some((Token::Word(verb), rest))
+            if matches!(&**verb, "get" | "post" | "put" | "patch" | "delete") =>
+        {
+            let (path, target, options) = match rest {
+                [Token::Str(path), Token::Arrow, Token::Str(target), options @ ..] => {
+                    (path, target, options)
+                }
+                [Token::Str(path), Token::Comma, Token::Key(key), Token::Str(target), options @ ..]
+                    if key == "to" =>
+                {
+                    (path, target, options)
+                }
+                _ => {
+                    return Err(format!(
+                        "expected `{} \"/path\" => \"controller#action\"`",
+                        verb
+                    ))
+                }
+            };
+
+            ignore_options(options)?;
+            add(router, verb, &scope, path, &scoped_target(&scope, target))?;
+        }
+
+        Some((Token::Word(word), rest)) if word == "root" => {
+            let target = match rest {
+                [Token::Str(target)] => target,
+                [Token::Key(key), Token::Str(target)] if key == "to" => target,
+                _ => return Err(String::from("expected `root \"controller#action\"`")),
+            };
+
+            add(router, "get", &scope, "/", &scoped_target(&scope, target))?;
+        }
+
+        Some((Token::Word(word), rest)) if word == "resources" || word == "resource" => {
+            let singular = word == "resource";
+            let (name, options) = match rest {
+                [Token::Symbol(name), options @ ..] => (name, options),
+                _ => return Err(format!("expected `{} :name`", word)),
+            };
+
+            let actions = filter_actions(options)?;
+            let controller = format!(
+                "{}{}",
+                scope.controller,
+                if singular {
+                    pluralize(name)
+                } else {
+                    name.clone()
+                }
+            );
+            let base = format!("{}/{}", scope.path, name);
+            let member = if singular {
+                base.clone()
+            } else {
+                format!("{}/:id", base)
+            };
+
+            for action in actions {
+                let target = format!("{}#{}", controller, action);
+                let routes: &[(&str, &str)] = match action {
+                    "index" if singular => &[],
+                    "index" => &[("get", &base)],
+                    "new" => &[("get", "/new")],
+                    "create" => &[("post", &base)],
+                    "show" => &[("get", &member)],
+                    "edit" => &[("get", "/edit")],
+                    "update" => &[("patch", &member), ("put", &member)],
+                    _ => &[("delete", &member)],
+                };
+
+                for (verb, path) in routes {
+                    let path = match *path {
+                        "/new" => format!("{}/new", base),
+                        "/edit" => format!("{}/edit", member),
+                        path => String::from(path),
+                    };
+                    add(router, verb, &Scope::default(), &path, &target)?;
+                }
+            }
+
+            nested.path = if singular {
+                base
+            } else {
+                format!("{}/:{}_id", base, singularize(name))
+            };
+        }
+
+        Some((Token::Word(word), [Token::Symbol(name)])) if word == "namespace" => {
+            nested.path = format!("{}/{}", scope.path, name);
+            nested.controller = format!("{}{}/", scope.controller, name);
+        }
+
+        Some((Token::Word(word), [Token::Str(path)])) if word == "scope" => {
+            nested.path = join(&scope.path, path);
+        }
+
+        _ => return Err(format!("unsupported route definition `{}`", line.trim())),
+    }
+
+    if opens_block {
+        scopes.push(nested);
+    }
+
+    Ok(())
+}
+
+fn add(
+    router: &mut MethodRouter<String>,
+    verb: &str,
+    scope: &Scope,
+    path: &str,
+    target: &str,
+) -> Result<(), String> {
+    if path.contains(['(', ')']) {
+        return Err(format!("optional segments are not supported: `{}`", path));
+    }
+
+    let path = join(&scope.path, path);
+    let express: String = path
+        .split('/')
+        .map(|section| match section.strip_prefix('*') {
+            Some(name) => format!(":{}+", name),
+            None => String::from(section),
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+
+    let spec = RouteSpec::parse_with(&express, Syntax::Express)?;
+    router
+        .add(verb, spec, String::from(target))
+        .unwrap_or_else(|never| match never {});
+    Ok(())
+}
+
+fn join(prefix: &str, path: &str) -> String {
+    format!("{}/{}", prefix, path.trim_start_matches('/'))
+}
+
+fn scoped_target(scope: &Scope, target: &str) -> String {
+    format!("{}{}", scope.controller, target)
+}
+
+fn ignore_options(options: &[Token]) -> Result<(), String> {
+    match options {
+        [] => Ok(()),
+        [Token::Comma, Token::Key(key), Token::Str(_) | Token::Symbol(_), rest @ ..]
+            if key == "as" =>
+        {
+            ignore_options(rest)
+        }
+        _ => Err(String::from("unsupported route options")),
+    }
+}
+
+fn filter_actions(options: &[Token]) -> Result<Vec<&'static str>, String> {
+    let (key, listed) = match options {
+        [] => return Ok(ACTIONS.to_vec()),
+        [Token::Comma, Token::Key(key), Token::Symbol(action)] => (key, vec![action]),
+        [Token::Comma, Token::Key(key), Token::Open, list @ .., Token::Close] => (
+            key,
+            list.iter()
+                .filter(|token| **token != Token::Comma)
+                .map(|token| match token {
+                    Token::Symbol(action) => Ok(action),
+                    _ => Err(String::from("expected a list of actions")),
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+        ),
+        _ => return Err(String::from("unsupported resource options")),
+    };
+
+    if let Some(unknown) = listed.iter().find(|a| !ACTIONS.contains(&a.as_str())) {
+        return Err(format!("unknown action `:{}`", unknown));
+    }
+
+    match &**key {
+        "only" => Ok(ACTIONS
+            .iter()
+            .copied()
+            .filter(|a| listed.iter().any(|l| l == a))
+            .collect()),
+        "except" => Ok(ACTIONS
+            .iter()
+            .copied()
+            .filter(|a| !listed.iter().any(|l| l == a))
+            .collect()),
+        _ => Err(format!("unsupported option `{}:`", key)),
+    }
+}
+
+pub(crate) fn singularize(name: &str) -> String {
+    if let Some(stem) = name.strip_suffix("ies") {
+        format!("{}y", stem)
+    } else if name.ends_with("ss") {
+        String::from(name)
+    } else {
+        String::from(name.strip_suffix('s').unwrap_or(name))
+    }
+}
+
+fn pluralize(name: &str) -> String {
+    match name.strip_suffix('y') {
+        Some(stem) if !stem.ends_with(['a', 'e', 'i', 'o', 'u']) => format!("{}ies", stem),
+        _ if name.ends_with('s') => format!("{}es", name),
+        _ => format!("{}s", name),
+    }
+}
+
+fn tokenize(line: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '#' => break,
+            c if c.is_whitespace() => {}
+            ',' => tokens.push(Token::Comma),
+            '[' => tokens.push(Token::Open),
+            ']' => tokens.push(Token::Close),
+            '=' if chars.next_if_eq(&'>').is_some() => tokens.push(Token::Arrow),
+            '"' | '\'' => {
+                let mut string = String::new();
+                loop {
+                    match chars.next() {
+                        Some(end) if end == c => break,
+                        Some(c) => string.push(c),
+                        None => return Err(String::from("unterminated string")),
+                    }
+                }
+                tokens.push(Token::Str(string));
+            }
+            ':' => tokens.push(Token::Symbol(word(&mut chars, None))),
+            c if c.is_alphanumeric() || c == '_' => {
+                let word = word(&mut chars, Some(c));
+                if chars.peek() == Some(&':') {
+                    chars.next();
+                    tokens.push(Token::Key(word));
+                } else {
+                    tokens.push(Token::Word(word));
+                }
+            }
+            c => return Err(format!("unexpected `{}`", c)),
+        }
+    }
+    Ok(tokens)
+}
+
+fn word(chars: &mut std::iter::Peekable<std::str::Chars<'_>>, first: Option<char>) -> String {
+    let mut word: String = first.into_iter().collect();
+    while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || matches!(c, '_' | '.')) {
+        word.push(c);
+    }
+    word
+}
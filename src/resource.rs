@@ -0,0 +1,190 @@
+use crate::{rails::singularize, MethodRouter, RouteSpec};
+use smartstring::alias::String as SmartString;
+
+/// The handlers of a REST resource, for
+/// [`MethodRouter::add_resource`]. Each handler is optional, and only
+/// the routes of the handlers that are set are added. Resources can
+/// be nested under a member of their parent, such as the comments of
+/// a post.
+///
+/// | handler  | method         | path         | name           |
+/// |----------|----------------|--------------|----------------|
+/// | `index`  | `GET`          | `/posts`     | `posts.index`  |
+/// | `create` | `POST`         | `/posts`     | `posts.create` |
+/// | `show`   | `GET`          | `/posts/:id` | `posts.show`   |
+/// | `update` | `PUT`, `PATCH` | `/posts/:id` | `posts.update` |
+/// | `delete` | `DELETE`       | `/posts/:id` | `posts.delete` |
+#[derive(Debug, Clone)]
+pub struct Resource<Handler> {
+    index: Option<Handler>,
+    create: Option<Handler>,
+    show: Option<Handler>,
+    update: Option<Handler>,
+    delete: Option<Handler>,
+    nested: Vec<(SmartString, Resource<Handler>)>,
+}
+
+impl<Handler> Default for Resource<Handler> {
+    fn default() -> Self {
+        Self {
+            index: None,
+            create: None,
+            show: None,
+            update: None,
+            delete: None,
+            nested: vec![],
+        }
+    }
+}
+
+impl<Handler> Resource<Handler> {
+    /// Builds a new Resource with no handlers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the handler that lists the resource
+    pub fn with_index(mut self, handler: Handler) -> Self {
+        self.index = Some(handler);
+        self
+    }
+
+    /// Sets the handler that creates a member of the resource
+    pub fn with_create(mut self, handler: Handler) -> Self {
+        self.create = Some(handler);
+        self
+    }
+
+    /// Sets the handler that shows a member of the resource
+    pub fn with_show(mut self, handler: Handler) -> Self {
+        self.show = Some(handler);
+        self
+    }
+
+    /// Sets the handler that updates a member of the resource, which
+    /// is added for both `PUT` and `PATCH`
+    pub fn with_update(mut self, handler: Handler) -> Self {
+        self.update = Some(handler);
+        self
+    }
+
+    /// Sets the handler that deletes a member of the resource
+    pub fn with_delete(mut self, handler: Handler) -> Self {
+        self.delete = Some(handler);
+        self
+    }
+
+    /// Nests a resource under each member of this one, so that
+    /// `comments` nested under `/posts` is at
+    /// `/posts/:post_id/comments`. The param is named for the
+    /// singular of this resource, using naive english inflection
+    /// rules.
+    pub fn with_nested(mut self, name: &str, resource: Resource<Handler>) -> Self {
+        self.nested.push((SmartString::from(name), resource));
+        self
+    }
+}
+
+impl<Handler: Clone> MethodRouter<Handler> {
+    /// Adds the conventional routes of a REST resource at this path,
+    /// along with the routes of its nested resources. The resource is
+    /// named for the last segment of the path, and each route is
+    /// named for its resource and handler, such as `posts.show` or
+    /// `posts.comments.index`, which is available from
+    /// [`RouteSpec::name`]. See [`Resource`] for the routes of each
+    /// handler. As with every route, the routes are ordered by
+    /// precedence, so a literal route such as `/posts/new` takes
+    /// precedence over `/posts/:id`. If the path or the name of any
+    /// nested resource is invalid, no routes are added.
+    ///
+    /// ```rust
+    /// use routefinder::{MethodRouter, Resource};
+    /// let mut router = MethodRouter::new();
+    /// router
+    ///     .add_resource(
+    ///         "/posts",
+    ///         Resource::new()
+    ///             .with_index("list posts")
+    ///             .with_show("show post")
+    ///             .with_create("create post")
+    ///             .with_update("update post")
+    ///             .with_delete("delete post")
+    ///             .with_nested("comments", Resource::new().with_index("list comments")),
+    ///     )
+    ///     .unwrap();
+    ///
+    /// assert_eq!(*router.best_match("POST", "/posts").unwrap(), "create post");
+    /// assert_eq!(*router.best_match("PATCH", "/posts/1").unwrap(), "update post");
+    /// let m = router.best_match("GET", "/posts/1/comments").unwrap();
+    /// assert_eq!(*m, "list comments");
+    /// assert_eq!(m.captures().get("post_id"), Some("1"));
+    /// assert_eq!(m.route().name(), Some("posts.comments.index"));
+    /// assert!(router.best_match("DELETE", "/posts").is_none());
+    /// ```
+    pub fn add_resource(&mut self, path: &str, resource: Resource<Handler>) -> Result<(), String> {
+        let path = path.trim_end_matches('/');
+        let name = path.rsplit('/').next().unwrap_or_default();
+        if !is_resource_name(name) {
+            return Err(format!(
+                "a resource path must end in the resource's name: `{}`",
+                path
+            ));
+        }
+
+        // every route is parsed before any is added, so that an error
+        // leaves the router unchanged
+        let mut routes = vec![];
+        resource_routes(path, name, resource, &mut routes)?;
+        for (method, spec, handler) in routes {
+            self.add(method, spec, handler)
+                .unwrap_or_else(|never| match never {});
+        }
+        Ok(())
+    }
+}
+
+/// the routes of this resource and its nested resources, with their
+/// methods and handlers
+fn resource_routes<Handler: Clone>(
+    base: &str,
+    name: &str,
+    resource: Resource<Handler>,
+    routes: &mut Vec<(&'static str, RouteSpec, Handler)>,
+) -> Result<(), String> {
+    let member = format!("{}/:id", base);
+    let actions = [
+        ("GET", base, "index", resource.index),
+        ("POST", base, "create", resource.create),
+        ("GET", &member, "show", resource.show),
+        ("PUT", &member, "update", resource.update.clone()),
+        ("PATCH", &member, "update", resource.update),
+        ("DELETE", &member, "delete", resource.delete),
+    ];
+
+    for (method, path, action, handler) in actions {
+        if let Some(handler) = handler {
+            let spec = path
+                .parse::<RouteSpec>()?
+                .with_name(&format!("{}.{}", name, action));
+            routes.push((method, spec, handler));
+        }
+    }
+
+    let singular = singularize(name.rsplit('.').next().unwrap_or(name));
+    for (nested_name, nested) in resource.nested {
+        if !is_resource_name(&nested_name) {
+            return Err(format!("invalid nested resource name: `{}`", nested_name));
+        }
+        resource_routes(
+            &format!("{}/:{}_id/{}", base, singular, nested_name),
+            &format!("{}.{}", name, nested_name),
+            nested,
+            routes,
+        )?;
+    }
+    Ok(())
+}
+
+fn is_resource_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(['/', ':', '*', '.'])
+}
@@ -1,10 +1,12 @@
-use crate::{Captures, RouteSpec, Segment};
+use crate::{Captures, RouteSpec, Segment, SeparatorPolicy, TemplateError};
+use std::fmt::{self, Display, Formatter, Write};
 /// This struct represents the result of a reverse lookup from
 /// [`Captures`] to a [`RouteSpec`]
 #[derive(Debug, Clone, Copy)]
 pub struct ReverseMatch<'keys, 'values, 'captures, 'route> {
     route: &'route RouteSpec,
     captures: &'captures Captures<'keys, 'values>,
+    separators: SeparatorPolicy,
 }
 
 impl<'keys, 'values, 'captures, 'route> ReverseMatch<'keys, 'values, 'captures, 'route> {
@@ -14,26 +16,12 @@ impl<'keys, 'values, 'captures, 'route> ReverseMatch<'keys, 'values, 'captures,
         captures: &'captures Captures<'keys, 'values>,
         route: &'route RouteSpec,
     ) -> Option<Self> {
-        let all_params_matched = route
-            .segments()
-            .iter()
-            .filter_map(|s| match s {
-                Segment::Param(s) => Some(s),
-                _ => None,
-            })
-            .eq(captures.params().iter().map(|c| c.name()));
-
-        if !all_params_matched {
-            return None;
-        }
-
-        if captures.wildcard().is_some()
-            && !matches!(route.segments().last(), Some(Segment::Wildcard))
-        {
-            return None;
-        }
-
-        Some(Self { route, captures })
+        route.check_captures(captures).ok()?;
+        Some(Self {
+            route,
+            captures,
+            separators: SeparatorPolicy::default(),
+        })
     }
 
     /// Returns the [`RouteSpec`] for this ReverseMatch
@@ -42,25 +30,141 @@ impl<'keys, 'values, 'captures, 'route> ReverseMatch<'keys, 'values, 'captures,
     }
 
     /// Returns the [`Captures`] for this ReverseMatch
-    pub fn captures(&self) -> &Captures {
+    pub fn captures(&self) -> &Captures<'_, '_> {
         self.captures
     }
-}
 
-impl<'keys, 'values, 'captures, 'route> std::fmt::Display
-    for ReverseMatch<'keys, 'values, 'captures, 'route>
-{
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str("/")?;
-        for segment in self.route.segments() {
+    /// Sets what this ReverseMatch does with param values that contain
+    /// a separator. Returns an error if the policy is
+    /// [`SeparatorPolicy::Reject`] and a value contains a separator.
+    pub fn with_separators(mut self, policy: SeparatorPolicy) -> Result<Self, TemplateError> {
+        if policy == SeparatorPolicy::Reject {
+            let segments = self.route.segments();
+            for (index, segment) in segments.iter().enumerate() {
+                let value = match segment.name().and_then(|name| self.captures.get(name)) {
+                    Some(value) => value,
+                    None => continue,
+                };
+
+                if value.contains(separators(segments, index)) {
+                    return Err(TemplateError::Separator {
+                        name: segment.name().unwrap_or_default().to_string(),
+                        value: value.to_string(),
+                    });
+                }
+            }
+        }
+
+        self.separators = policy;
+        Ok(self)
+    }
+
+    /// Returns the [`SeparatorPolicy`] for this ReverseMatch
+    pub fn separators(&self) -> SeparatorPolicy {
+        self.separators
+    }
+
+    /// Writes the path for this ReverseMatch into an existing buffer,
+    /// such as a reused String, rather than allocating a new one as
+    /// `to_string` does
+    ///
+    /// ```rust
+    /// use routefinder::{Captures, RouteSpec};
+    /// let spec: RouteSpec = "/users/:id".parse().unwrap();
+    /// let captures: Captures = [("id", "1")].into();
+    /// let mut buffer = String::from("https://example.com");
+    /// spec.template(&captures).unwrap().write_to(&mut buffer).unwrap();
+    /// assert_eq!(buffer, "https://example.com/users/1");
+    /// ```
+    pub fn write_to(&self, buffer: &mut impl Write) -> fmt::Result {
+        let segments = self.route.segments();
+        let is_absent = |segment: &Segment| match segment {
+            Segment::OptionalParam(name) | Segment::ZeroOrMore(name) => {
+                self.captures.get(name).is_none()
+            }
+            Segment::Wildcard => self.captures.wildcard().is_none(),
+            _ => false,
+        };
+
+        // as when matching, an absent segment stands in for the slash
+        // after it, or for the slash before it at the end of the route
+        let skip_slash = |index: usize| {
+            index
+                .checked_sub(1)
+                .is_some_and(|previous| is_absent(&segments[previous]))
+                || (index + 2 == segments.len() && is_absent(&segments[index + 1]))
+        };
+
+        buffer.write_str("/")?;
+        for (index, segment) in segments.iter().enumerate() {
             match segment {
-                Segment::Slash => f.write_str("/")?,
-                Segment::Dot => f.write_str(".")?,
-                Segment::Exact(s) => f.write_str(s)?,
-                Segment::Param(p) => f.write_str(self.captures.get(p).unwrap())?,
-                Segment::Wildcard => f.write_str(self.captures.wildcard().unwrap_or_default())?,
+                Segment::Slash if skip_slash(index) => {}
+                Segment::Slash => buffer.write_str("/")?,
+                Segment::Dot => buffer.write_str(".")?,
+                Segment::Exact(s) => buffer.write_str(s)?,
+                Segment::Param(p) | Segment::OptionalParam(p)
+                    if self.separators == SeparatorPolicy::Escape =>
+                {
+                    let mut rest = self.captures.get(p).unwrap_or_default();
+                    while let Some(position) = rest.find(separators(segments, index)) {
+                        buffer.write_str(&rest[..position])?;
+                        buffer.write_str(if rest.as_bytes()[position] == b'/' {
+                            "%2F"
+                        } else {
+                            "%2E"
+                        })?;
+                        rest = &rest[position + 1..];
+                    }
+                    buffer.write_str(rest)?
+                }
+                Segment::Param(p) | Segment::OneOrMore(p) => {
+                    buffer.write_str(self.captures.get(p).unwrap())?
+                }
+                Segment::OptionalParam(p) | Segment::ZeroOrMore(p) => {
+                    buffer.write_str(self.captures.get(p).unwrap_or_default())?
+                }
+                Segment::Glob(g) => buffer.write_str(g)?,
+                Segment::Wildcard => {
+                    buffer.write_str(self.captures.wildcard().unwrap_or_default())?
+                }
             };
         }
         Ok(())
     }
+
+    /// Returns the length in bytes of the path for this ReverseMatch,
+    /// so a buffer can be reserved before [`ReverseMatch::write_to`]
+    pub fn len_hint(&self) -> usize {
+        let mut counter = Counter(0);
+        let _ = self.write_to(&mut counter);
+        counter.0
+    }
+}
+
+impl<'keys, 'values, 'captures, 'route> Display
+    for ReverseMatch<'keys, 'values, 'captures, 'route>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.write_to(f)
+    }
+}
+
+/// the separators that the value of the segment at this index cannot
+/// contain
+fn separators(segments: &[Segment], index: usize) -> &'static [char] {
+    match (segments.get(index), segments.get(index + 1)) {
+        (Some(Segment::Param(_) | Segment::OptionalParam(_)), Some(Segment::Dot)) => &['/', '.'],
+        (Some(Segment::Param(_) | Segment::OptionalParam(_)), _) => &['/'],
+        _ => &[],
+    }
+}
+
+/// counts the bytes written to it
+struct Counter(usize);
+
+impl Write for Counter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.len();
+        Ok(())
+    }
 }
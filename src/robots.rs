@@ -0,0 +1,220 @@
+//! A parser and evaluator for robots.txt files
+//!
+//! ```rust
+//! use routefinder::robots::Robots;
+//! let robots = Robots::parse(r#"
+//!     User-agent: *
+//!     Disallow: /private/
+//!     Allow: /private/press-kit
+//!
+//!     User-agent: ExampleBot
+//!     Disallow: /*.pdf$
+//!
+//!     Sitemap: https://example.com/sitemap.xml
+//! "#);
+//!
+//! assert!(robots.is_allowed("AnyBot/1.0", "/posts/1"));
+//! assert!(!robots.is_allowed("AnyBot/1.0", "/private/notes"));
+//! assert!(robots.is_allowed("AnyBot/1.0", "/private/press-kit/logo.png"));
+//!
+//! assert!(robots.is_allowed("ExampleBot/2.1", "/private/notes"));
+//! assert!(!robots.is_allowed("ExampleBot/2.1", "/papers/routing.pdf"));
+//! assert!(robots.is_allowed("ExampleBot/2.1", "/papers/routing.pdf?download"));
+//!
+//! assert_eq!(robots.sitemaps(), ["https://example.com/sitemap.xml"]);
+//! ```
+//!
+//! This follows [RFC 9309](https://www.rfc-editor.org/rfc/rfc9309):
+//! * crawlers are matched to groups by product token, case
+//!   insensitively, falling back to the `*` group. Groups for the same
+//!   user agent are merged.
+//! * a rule matches any path that starts with its pattern, where `*`
+//!   matches any run of characters including slashes and a trailing
+//!   `$` anchors the pattern to the end of the path
+//! * the longest matching pattern wins, and allow wins ties
+//! * `/robots.txt` is always allowed
+//!
+//! Because robots.txt patterns are character prefixes rather than
+//! path segments (`Disallow: /priv` also disallows `/private`), rules
+//! are not routefinder [`RouteSpec`][crate::RouteSpec]s and ignore
+//! the usual precedence rules. Parsing is lenient: unknown fields and
+//! malformed lines are skipped, and percent-encoding is compared as
+//! written.
+
+use crate::{glob, Effect};
+use smartstring::alias::String as SmartString;
+
+/// A single `Allow` or `Disallow` line from a robots.txt file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    effect: Effect,
+    pattern: SmartString,
+}
+
+impl Rule {
+    /// whether this rule allows or denies the paths it matches
+    pub fn effect(&self) -> Effect {
+        self.effect
+    }
+
+    /// the pattern as written in the robots.txt file
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// whether this rule applies to `path`, which should include the
+    /// query string if there is one
+    pub fn matches(&self, path: &str) -> bool {
+        match self.pattern.strip_suffix('$') {
+            Some(anchored) => glob::matches_stars(anchored, path),
+            None => {
+                let mut pattern = self.pattern.clone();
+                pattern.push('*');
+                glob::matches_stars(&pattern, path)
+            }
+        }
+    }
+
+    fn specificity(&self) -> usize {
+        self.pattern.len()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Group {
+    user_agents: Vec<SmartString>,
+    rules: Vec<Rule>,
+}
+
+/// A parsed robots.txt file. See the [module docs][self] for details.
+#[derive(Debug, Clone, Default)]
+pub struct Robots {
+    groups: Vec<Group>,
+    sitemaps: Vec<String>,
+}
+
+impl Robots {
+    /// parses the contents of a robots.txt file
+    pub fn parse(source: &str) -> Self {
+        let mut robots = Self::default();
+        let mut current: Option<Group> = None;
+
+        for line in source.lines() {
+            let line = line.split('#').next().unwrap_or_default();
+            let (field, value) = match line.split_once(':') {
+                Some((field, value)) => (field.trim().to_ascii_lowercase(), value.trim()),
+                None => continue,
+            };
+
+            match &*field {
+                "user-agent" => {
+                    let mut group = match current.take() {
+                        Some(group) if group.rules.is_empty() => group,
+                        Some(group) => {
+                            robots.groups.push(group);
+                            Group::default()
+                        }
+                        None => Group::default(),
+                    };
+                    group
+                        .user_agents
+                        .push(SmartString::from(value.to_ascii_lowercase().as_str()));
+                    current = Some(group);
+                }
+
+                "allow" | "disallow" => {
+                    let effect = if field == "allow" {
+                        Effect::Allow
+                    } else {
+                        Effect::Deny
+                    };
+
+                    if let Some(group) = &mut current {
+                        // an empty disallow allows everything, which is
+                        // the same as having no rule at all
+                        if !value.is_empty() {
+                            group.rules.push(Rule {
+                                effect,
+                                pattern: SmartString::from(value),
+                            });
+                        }
+                    }
+                }
+
+                "sitemap" => robots.sitemaps.push(String::from(value)),
+
+                _ => {}
+            }
+        }
+
+        if let Some(group) = current {
+            robots.groups.push(group);
+        }
+
+        robots
+    }
+
+    /// the rules that apply to a crawler with this user agent, such as
+    /// `ExampleBot` or `ExampleBot/2.1 (+https://example.com/bot)`
+    pub fn rules_for(&self, user_agent: &str) -> Vec<&Rule> {
+        let token = product_token(user_agent).to_ascii_lowercase();
+        if self.has_group(&token) {
+            self.rules_for_token(&token)
+        } else {
+            self.rules_for_token("*")
+        }
+    }
+
+    /// the rule that decides whether `user_agent` may crawl `path`, if
+    /// any rule applies
+    pub fn matching_rule(&self, user_agent: &str, path: &str) -> Option<&Rule> {
+        self.rules_for(user_agent)
+            .into_iter()
+            .filter(|rule| rule.matches(path))
+            .max_by(|a, b| {
+                a.specificity()
+                    .cmp(&b.specificity())
+                    .then_with(|| (a.effect == Effect::Allow).cmp(&(b.effect == Effect::Allow)))
+            })
+    }
+
+    /// whether `user_agent` may crawl `path`. Paths that no rule
+    /// matches are allowed.
+    pub fn is_allowed(&self, user_agent: &str, path: &str) -> bool {
+        if path == "/robots.txt" {
+            return true;
+        }
+
+        match self.matching_rule(user_agent, path) {
+            Some(rule) => rule.effect == Effect::Allow,
+            None => true,
+        }
+    }
+
+    /// the urls listed in `Sitemap` lines, which apply to every user
+    /// agent
+    pub fn sitemaps(&self) -> &[String] {
+        &self.sitemaps
+    }
+
+    fn has_group(&self, token: &str) -> bool {
+        self.groups
+            .iter()
+            .any(|group| group.user_agents.iter().any(|agent| agent == token))
+    }
+
+    fn rules_for_token(&self, token: &str) -> Vec<&Rule> {
+        self.groups
+            .iter()
+            .filter(|group| group.user_agents.iter().any(|agent| agent == token))
+            .flat_map(|group| group.rules.iter())
+            .collect()
+    }
+}
+
+fn product_token(user_agent: &str) -> &str {
+    let end = user_agent
+        .find(|c: char| !(c.is_ascii_alphabetic() || c == '-' || c == '_'))
+        .unwrap_or(user_agent.len());
+    &user_agent[..end]
+}
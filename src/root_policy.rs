@@ -0,0 +1,89 @@
+/// How the root of a site, the path `/`, is matched, since frameworks
+/// disagree about it. The policy is part of the [`MatchOptions`][crate::MatchOptions]
+/// of a router, set with
+/// [`MatchOptions::root_policy`][crate::MatchOptions::root_policy].
+///
+/// The root is any path made only of slashes, such as `/` or `//`, and
+/// by default also the empty path. By default, the route `/` and a
+/// route that begins with a wildcard, such as `*` or `/*`, match the
+/// root, and a route made of a single param, such as `/:page`, does
+/// not, because params do not match empty text.
+///
+/// ```rust
+/// use routefinder::{MatchOptions, RootPolicy, Router};
+/// let mut router = Router::new();
+/// router.add("/:page", "page").unwrap();
+/// router.add("*", "fallback").unwrap();
+/// assert_eq!(*router.best_match("/").unwrap(), "fallback");
+/// assert_eq!(*router.best_match("").unwrap(), "fallback");
+///
+/// let policy = RootPolicy::new()
+///     .empty_path_is_root(false)
+///     .param_matches_root(true);
+/// router.set_match_options(MatchOptions::new().root_policy(policy));
+/// let m = router.best_match("/").unwrap();
+/// assert_eq!(*m, "page");
+/// assert_eq!(m.captures().get("page"), Some(""));
+/// assert!(router.best_match("").is_none());
+///
+/// router.set_match_options(MatchOptions::new().root_policy(
+///     RootPolicy::new().wildcard_matches_root(false),
+/// ));
+/// assert!(router.best_match("/").is_none());
+/// assert_eq!(*router.best_match("/about").unwrap(), "page");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RootPolicy {
+    pub(crate) empty_path_is_root: bool,
+    pub(crate) wildcard_matches_root: bool,
+    pub(crate) param_matches_root: bool,
+}
+
+impl Default for RootPolicy {
+    fn default() -> Self {
+        Self {
+            empty_path_is_root: true,
+            wildcard_matches_root: true,
+            param_matches_root: false,
+        }
+    }
+}
+
+impl RootPolicy {
+    /// Builds the default policy
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// When disabled, the empty path does not match any route. Enabled
+    /// by default.
+    pub fn empty_path_is_root(mut self, empty_path_is_root: bool) -> Self {
+        self.empty_path_is_root = empty_path_is_root;
+        self
+    }
+
+    /// When disabled, a route with a wildcard does not match the root,
+    /// even though the wildcard could match nothing. Enabled by
+    /// default.
+    pub fn wildcard_matches_root(mut self, wildcard_matches_root: bool) -> Self {
+        self.wildcard_matches_root = wildcard_matches_root;
+        self
+    }
+
+    /// When enabled, a route made of a single param, such as
+    /// `/:page`, matches the root, and the param captures empty text.
+    /// Disabled by default.
+    pub fn param_matches_root(mut self, param_matches_root: bool) -> Self {
+        self.param_matches_root = param_matches_root;
+        self
+    }
+
+    /// whether this path is the root
+    pub(crate) fn is_root(&self, path: &str) -> bool {
+        if path.is_empty() {
+            self.empty_path_is_root
+        } else {
+            path.bytes().all(|byte| byte == b'/')
+        }
+    }
+}
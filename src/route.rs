@@ -0,0 +1,121 @@
+use crate::{Captures, Match, MatchOptions, RouteSpec};
+use std::{
+    borrow::Cow,
+    convert::{TryFrom, TryInto},
+    str::FromStr,
+};
+
+/// A single route, for matching paths against one pattern without
+/// building a [`Router`][crate::Router]
+///
+/// Matching a path with a `Route` is the same as matching it with a
+/// router that holds only this route, and produces the same
+/// [`Match`]. A `Route` without a handler can be parsed directly from
+/// a str.
+///
+/// ```rust
+/// use routefinder::Route;
+/// # pub fn main() -> Result<(), String> {
+/// let route: Route = "/users/:id".parse()?;
+/// assert!(route.is_match("/users/1"));
+/// assert!(!route.is_match("/users"));
+/// assert_eq!(route.captures("/users/1").unwrap().get("id"), Some("1"));
+///
+/// let route = Route::new("/hello/:planet", "greeting")?;
+/// let m = route.matches("/hello/earth").unwrap();
+/// assert_eq!(*m, "greeting");
+/// assert_eq!(m.captures().get("planet"), Some("earth"));
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Route<Handler = ()> {
+    spec: RouteSpec,
+    handler: Handler,
+    options: MatchOptions,
+}
+
+impl<Handler> Route<Handler> {
+    /// Builds a new route from a [`RouteSpec`], or anything that can
+    /// be converted into one, and a handler
+    pub fn new<R>(route: R, handler: Handler) -> Result<Self, <R as TryInto<RouteSpec>>::Error>
+    where
+        R: TryInto<RouteSpec>,
+    {
+        Ok(Self {
+            spec: route.try_into()?,
+            handler,
+            options: MatchOptions::default(),
+        })
+    }
+
+    /// Sets the [`MatchOptions`] used to match paths, in place of the
+    /// defaults
+    pub fn with_match_options(mut self, options: MatchOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Returns the [`MatchOptions`] used to match paths
+    pub fn match_options(&self) -> MatchOptions {
+        self.options
+    }
+
+    /// Returns the [`RouteSpec`] for this route
+    pub fn spec(&self) -> &RouteSpec {
+        &self.spec
+    }
+
+    /// Returns the handler for this route
+    pub fn handler(&self) -> &Handler {
+        &self.handler
+    }
+
+    /// Returns the [`RouteSpec`] and handler for this route
+    pub fn into_parts(self) -> (RouteSpec, Handler) {
+        (self.spec, self.handler)
+    }
+
+    /// Returns a [`Match`] if this route matches the path
+    pub fn matches<'a, 'b>(&'a self, path: &'b str) -> Option<Match<'a, 'b, Handler>> {
+        let prepared = self.options.prepare(path)?;
+        let raw = Cow::Borrowed(path);
+        Match::try_new(&self.spec, &self.handler, &prepared, &raw, &self.options)
+    }
+
+    /// Returns true if this route matches the path
+    pub fn is_match(&self, path: &str) -> bool {
+        self.matches(path).is_some()
+    }
+
+    /// Returns the [`Captures`] for the path, if this route matches
+    /// it
+    pub fn captures<'a, 'b>(&'a self, path: &'b str) -> Option<Captures<'a, 'b>> {
+        self.matches(path).map(|m| m.captures())
+    }
+}
+
+impl FromStr for Route {
+    type Err = String;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        Self::new(source, ())
+    }
+}
+
+impl TryFrom<&str> for Route {
+    type Error = String;
+
+    fn try_from(source: &str) -> Result<Self, Self::Error> {
+        source.parse()
+    }
+}
+
+impl From<RouteSpec> for Route {
+    fn from(spec: RouteSpec) -> Self {
+        Self {
+            spec,
+            handler: (),
+            options: MatchOptions::default(),
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use smartstring::alias::String as SmartString;
+use std::time::Duration;
+
+/// Operational settings for a route, such as a timeout, a limit on the
+/// size of request bodies, and a rate limiting class, for a gateway or
+/// server to apply to the requests that the route matches. A config is
+/// attached with
+/// [`Router::add_with_config`][crate::Router::add_with_config] and
+/// retrieved with [`Match::config`][crate::Match::config]. Routefinder
+/// does not apply these settings itself, and they do not affect
+/// matching. Nothing is set by default.
+///
+/// ```rust
+/// use routefinder::{RouteConfig, Router};
+/// use std::time::Duration;
+/// let mut router = Router::new();
+/// router
+///     .add_with_config(
+///         "/uploads",
+///         RouteConfig::new()
+///             .with_timeout(Duration::from_secs(60))
+///             .with_max_body_bytes(10 << 20)
+///             .with_rate_class("uploads"),
+///         "upload",
+///     )
+///     .unwrap();
+/// router.add("/health", "health").unwrap();
+///
+/// let config = router.best_match("/uploads").unwrap().config().unwrap();
+/// assert_eq!(config.timeout(), Some(Duration::from_secs(60)));
+/// assert_eq!(config.max_body_bytes(), Some(10 << 20));
+/// assert_eq!(config.rate_class(), Some("uploads"));
+/// assert!(router.best_match("/health").unwrap().config().is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct RouteConfig {
+    timeout: Option<Duration>,
+    max_body_bytes: Option<u64>,
+    rate_class: Option<SmartString>,
+}
+
+impl RouteConfig {
+    /// Builds a new RouteConfig with nothing set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how long a request to this route may take
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the largest request body, in bytes, that this route
+    /// accepts
+    pub fn with_max_body_bytes(mut self, max_body_bytes: u64) -> Self {
+        self.max_body_bytes = Some(max_body_bytes);
+        self
+    }
+
+    /// Sets the name of the rate limiting class that requests to this
+    /// route count against, which can be shared by several routes
+    pub fn with_rate_class(mut self, rate_class: &str) -> Self {
+        self.rate_class = Some(SmartString::from(rate_class));
+        self
+    }
+
+    /// Returns the timeout for this route, if one was set
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Returns the largest request body for this route, in bytes, if
+    /// a limit was set
+    pub fn max_body_bytes(&self) -> Option<u64> {
+        self.max_body_bytes
+    }
+
+    /// Returns the name of the rate limiting class for this route, if
+    /// one was set
+    pub fn rate_class(&self) -> Option<&str> {
+        self.rate_class.as_deref()
+    }
+}
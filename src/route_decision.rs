@@ -0,0 +1,192 @@
+use crate::{explanation::Rejection, AsPath, Match, RouteSpec, Router};
+use std::fmt::{self, Debug, Formatter};
+
+/// What an HTTP layer should do with a path, returned by
+/// [`Router::route_decision`]: serve the match, redirect to a path
+/// that would match, or respond that nothing is found, suggesting the
+/// routes that came closest.
+///
+/// ```rust
+/// use routefinder::{MatchOptions, RouteDecision, Router};
+/// let mut router = Router::new();
+/// router.add("/Users/:id", "user").unwrap();
+/// router.add("/docs", "docs").unwrap();
+/// router.set_match_options(MatchOptions::new().strict_trailing_slash(true));
+///
+/// let response = match router.route_decision("/docs/") {
+///     RouteDecision::Matched(m) => format!("200 {}", m.handler()),
+///     RouteDecision::WouldMatchWithSlash(location)
+///     | RouteDecision::WouldMatchOtherCase(location) => format!("308 {}", location),
+///     RouteDecision::NoMatch { .. } => String::from("404"),
+/// };
+/// assert_eq!(response, "308 /docs");
+/// ```
+pub enum RouteDecision<'router, 'path, Handler> {
+    /// A route matches the path
+    Matched(Match<'router, 'path, Handler>),
+
+    /// No route matches the path, but one would without its trailing
+    /// slash. Contains the path to redirect to, including any query.
+    WouldMatchWithSlash(String),
+
+    /// No route matches the path, but one would if the router were
+    /// [case insensitive][crate::MatchOptions::case_insensitive].
+    /// Contains the [canonical path][Match::canonical_path] to
+    /// redirect to, with its captures still percent-encoded as they
+    /// were in the path, including any query.
+    WouldMatchOtherCase(String),
+
+    /// No route matches the path
+    NoMatch {
+        /// The routes that agree with the most leading segments of
+        /// the path, in order of precedence, or none if no route
+        /// agrees with the first segment
+        nearest: Vec<&'router RouteSpec>,
+    },
+}
+
+impl<Handler: Debug> Debug for RouteDecision<'_, '_, Handler> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Matched(m) => f.debug_tuple("Matched").field(m).finish(),
+            Self::WouldMatchWithSlash(location) => f
+                .debug_tuple("WouldMatchWithSlash")
+                .field(location)
+                .finish(),
+            Self::WouldMatchOtherCase(location) => f
+                .debug_tuple("WouldMatchOtherCase")
+                .field(location)
+                .finish(),
+            Self::NoMatch { nearest } => f
+                .debug_struct("NoMatch")
+                .field(
+                    "nearest",
+                    &nearest.iter().map(ToString::to_string).collect::<Vec<_>>(),
+                )
+                .finish(),
+        }
+    }
+}
+
+impl<'router, 'path, Handler> RouteDecision<'router, 'path, Handler> {
+    /// Returns the match, if a route matches the path
+    pub fn matched(&self) -> Option<&Match<'router, 'path, Handler>> {
+        match self {
+            Self::Matched(m) => Some(m),
+            _ => None,
+        }
+    }
+
+    /// Returns the path to redirect to, if the path would match
+    /// without its trailing slash or with different casing
+    pub fn redirect_to(&self) -> Option<&str> {
+        match self {
+            Self::WouldMatchWithSlash(location) | Self::WouldMatchOtherCase(location) => {
+                Some(location)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<Handler> Router<Handler> {
+    /// Decides what to do with a path in one call, for an HTTP layer
+    /// to translate directly into a response. Returns the
+    /// [best match][Router::best_match] if there is one. Otherwise, if
+    /// the path would match without its trailing slash, such as under
+    /// [`MatchOptions::strict_trailing_slash`], returns that path.
+    /// Otherwise, if the path would match ignoring case, returns its
+    /// [canonical path][Match::canonical_path], with its captures
+    /// spelled as they were in the path rather than decoded. Redirects
+    /// keep the query of the path, and always begin with a single
+    /// slash, so that a path such as `//example.com/` cannot redirect
+    /// to another host. Otherwise, returns the routes nearest to matching, which
+    /// can be suggested in a not found response. See
+    /// [`RouteDecision`].
+    ///
+    /// [`MatchOptions::strict_trailing_slash`]: crate::MatchOptions::strict_trailing_slash
+    ///
+    /// ```rust
+    /// use routefinder::{RouteDecision, Router};
+    /// let mut router = Router::new();
+    /// router.add("/users/:id", "user").unwrap();
+    /// router.add("/users/:id/Posts", "posts").unwrap();
+    ///
+    /// assert_eq!(**router.route_decision("/users/1").matched().unwrap(), "user");
+    /// assert_eq!(
+    ///     router.route_decision("/users/1/POSTS").redirect_to(),
+    ///     Some("/users/1/Posts")
+    /// );
+    /// match router.route_decision("/users/1/Posts/2") {
+    ///     RouteDecision::NoMatch { nearest } => {
+    ///         assert_eq!(nearest[0].to_string(), "/users/:id/Posts");
+    ///     }
+    ///     _ => panic!("expected no match"),
+    /// }
+    /// ```
+    pub fn route_decision<'a, 'b, P>(&'a self, path: &'b P) -> RouteDecision<'a, 'b, Handler>
+    where
+        P: AsPath + ?Sized,
+    {
+        if let Some(m) = self.best_match(path) {
+            return RouteDecision::Matched(m);
+        }
+
+        let trimmed = path.trimmed();
+        let query = path.raw().strip_prefix(trimmed).unwrap_or_default();
+        let options = self.match_options();
+
+        // routes never require a trailing slash, so only a path that
+        // has one can match with a different slash
+        let without_slash = trimmed.trim_end_matches('/');
+        if !without_slash.is_empty()
+            && without_slash.len() < trimmed.len()
+            && self
+                .match_iter_with(without_slash, &options)
+                .next()
+                .is_some()
+        {
+            if let Some(location) = location(without_slash, query) {
+                return RouteDecision::WouldMatchWithSlash(location);
+            }
+        }
+
+        if !options.case_insensitive {
+            let location = self
+                .match_iter_with(trimmed, &options.case_insensitive(true))
+                .best()
+                .and_then(|m| location(&m.canonical_raw_path()?, query));
+            if let Some(location) = location {
+                return RouteDecision::WouldMatchOtherCase(location);
+            }
+        }
+
+        let prepared = self.prepare(trimmed).unwrap_or_default();
+        let segments: Vec<&str> = prepared.split('/').filter(|s| !s.is_empty()).collect();
+        let mut nearest = vec![];
+        let mut agreement = 1;
+        for (route, _) in self.iter() {
+            let segment = Rejection::new(route, &segments).segment();
+            if segment > agreement {
+                agreement = segment;
+                nearest.clear();
+            }
+            if segment == agreement {
+                nearest.push(route);
+            }
+        }
+        RouteDecision::NoMatch { nearest }
+    }
+}
+
+/// a redirect location for this path, beginning with a single slash,
+/// or None if a browser would read it as another host or it contains
+/// control characters, which could end the `Location` header
+fn location(path: &str, query: &str) -> Option<String> {
+    let location = format!("/{}{}", path.trim_start_matches('/'), query);
+    if location.starts_with("/\\") || location.chars().any(char::is_control) {
+        None
+    } else {
+        Some(location)
+    }
+}
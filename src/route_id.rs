@@ -0,0 +1,47 @@
+use std::fmt::{self, Display, Formatter};
+
+/// A stable identifier for a route within a [`Router`][crate::Router]
+///
+/// Each route is assigned the next id when it is added, and keeps that
+/// id until it is removed. Ids are never reused by the same router, so
+/// they can be logged or used as keys for per-route statistics without
+/// comparing route templates. Ids from one router have no meaning to
+/// another.
+///
+/// ```rust
+/// let mut router = routefinder::Router::new();
+/// let users = router.add("/users/:id", "users").unwrap();
+/// let posts = router.add("/posts/:id", "posts").unwrap();
+/// assert_ne!(users, posts);
+///
+/// let m = router.best_match("/users/1").unwrap();
+/// assert_eq!(m.route().id(), Some(users));
+/// assert_eq!(users.get(), 0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RouteId(usize);
+
+impl RouteId {
+    pub(crate) fn new(id: usize) -> Self {
+        Self(id)
+    }
+
+    /// Returns this id as an integer. Ids are assigned sequentially
+    /// from zero in the order that routes are added.
+    pub fn get(self) -> usize {
+        self.0
+    }
+}
+
+impl Display for RouteId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl From<RouteId> for usize {
+    fn from(id: RouteId) -> Self {
+        id.0
+    }
+}
@@ -0,0 +1,142 @@
+use crate::{RouteSpec, Router};
+use std::{
+    convert::TryInto,
+    fmt::{self, Debug, Formatter},
+    iter::FromIterator,
+};
+
+/// A set of [`RouteSpec`]s without handlers, for allow and deny lists
+/// and other uses that only need to know whether a path matches.
+///
+/// Two specs are the same member of the set if they have the same
+/// segments, constraints and [`MatchMode`][crate::MatchMode], so
+/// `/users/:id` and `users/:id` are the same, but `/users/:id` and
+/// `/users/:user_id` are not.
+///
+/// ```rust
+/// let mut public = routefinder::RouteSet::new();
+/// public.insert("/").unwrap();
+/// public.insert("/assets/*").unwrap();
+/// public.insert("/posts/:id").unwrap();
+///
+/// assert!(public.contains_matching("/assets/site.css"));
+/// assert!(!public.contains_matching("/admin"));
+/// assert!(!public.insert("/assets/*").unwrap());
+/// ```
+#[derive(Default)]
+pub struct RouteSet {
+    router: Router<()>,
+}
+
+impl Debug for RouteSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.router, f)
+    }
+}
+
+impl RouteSet {
+    /// Builds a new empty route set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a spec to the set, returning false if the set already
+    /// contained it
+    pub fn insert<R>(&mut self, route: R) -> Result<bool, <R as TryInto<RouteSpec>>::Error>
+    where
+        R: TryInto<RouteSpec>,
+    {
+        let route = route.try_into()?;
+        if self.contains_spec(&route) {
+            Ok(false)
+        } else {
+            self.router.add(route, ()).ok();
+            Ok(true)
+        }
+    }
+
+    /// whether this set contains the provided spec
+    pub fn contains<R: TryInto<RouteSpec>>(&self, route: R) -> bool {
+        route
+            .try_into()
+            .is_ok_and(|route| self.contains_spec(&route))
+    }
+
+    fn contains_spec(&self, route: &RouteSpec) -> bool {
+        self.iter().any(|member| member.is_same_route(route))
+    }
+
+    /// whether any spec in this set matches the path
+    pub fn contains_matching(&self, path: &str) -> bool {
+        self.router.best_match(path).is_some()
+    }
+
+    /// Returns the highest precedence spec in this set that matches
+    /// the path, if any
+    pub fn best_match(&self, path: &str) -> Option<&RouteSpec> {
+        self.router.best_match(path).map(|m| m.route())
+    }
+
+    /// Returns an iterator over the specs in this set, in precedence
+    /// order
+    pub fn iter(&self) -> impl Iterator<Item = &RouteSpec> {
+        self.router.iter().map(|(route, _)| route)
+    }
+
+    /// returns the number of specs in this set
+    pub fn len(&self) -> usize {
+        self.router.len()
+    }
+
+    /// returns true if this set has no specs
+    pub fn is_empty(&self) -> bool {
+        self.router.is_empty()
+    }
+
+    /// Returns a set of the specs that are in either set
+    ///
+    /// ```rust
+    /// use routefinder::RouteSet;
+    /// let a: RouteSet = ["/a", "/b"].iter().map(|s| s.parse().unwrap()).collect();
+    /// let b: RouteSet = ["/b", "/c"].iter().map(|s| s.parse().unwrap()).collect();
+    /// assert_eq!(a.union(&b).len(), 3);
+    /// assert_eq!(a.intersection(&b).iter().next().unwrap().to_string(), "/b");
+    /// assert_eq!(a.difference(&b).iter().next().unwrap().to_string(), "/a");
+    /// ```
+    pub fn union(&self, other: &RouteSet) -> RouteSet {
+        self.iter().chain(other.iter()).cloned().collect()
+    }
+
+    /// Returns a set of the specs that are in both sets
+    pub fn intersection(&self, other: &RouteSet) -> RouteSet {
+        self.iter()
+            .filter(|route| other.contains_spec(route))
+            .cloned()
+            .collect()
+    }
+
+    /// Returns a set of the specs that are in this set but not the
+    /// other
+    pub fn difference(&self, other: &RouteSet) -> RouteSet {
+        self.iter()
+            .filter(|route| !other.contains_spec(route))
+            .cloned()
+            .collect()
+    }
+}
+
+impl FromIterator<RouteSpec> for RouteSet {
+    fn from_iter<T: IntoIterator<Item = RouteSpec>>(iter: T) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<RouteSpec> for RouteSet {
+    fn extend<T: IntoIterator<Item = RouteSpec>>(&mut self, iter: T) {
+        for route in iter {
+            self.insert(route).ok();
+        }
+    }
+}
@@ -1,9 +1,17 @@
-use crate::{Captures, ReverseMatch, Segment};
+use crate::{
+    condition::RouteCondition, constraint::Constraint, express, glob, http_rule, normalize, split,
+    Capture, Captures, Match, MatchMode, MatchOptions, ReverseMatch, RouteConfig, RouteId, Segment,
+    SortKey, Syntax, TemplateError, Transform,
+};
 use smartstring::alias::String as SmartString;
 use std::{
+    any::Any,
+    borrow::Cow,
     cmp::Ordering,
+    collections::BTreeMap,
     convert::TryFrom,
     fmt::{self, Debug, Display, Formatter},
+    hash::{Hash, Hasher},
     iter,
     str::FromStr,
 };
@@ -16,30 +24,78 @@ use std::{
 pub struct RouteSpec {
     source: Option<SmartString>,
     segments: Vec<Segment>,
+    constraints: Vec<Constraint>,
+    mode: MatchMode,
+    weight: Option<u32>,
+    condition: Option<RouteCondition>,
+    format: Option<SmartString>,
+    // the formats that an unsuffixed spec negotiates between
+    formats: Vec<SmartString>,
+    exclusions: Vec<RouteSpec>,
+    description: Option<SmartString>,
+    config: Option<RouteConfig>,
+    name: Option<SmartString>,
+    id: Option<RouteId>,
+}
+
+/// Specs are hashed by their segments, constraints, mode, weight,
+/// format, and exclusions. Hashes are not stable between versions of
+/// routefinder; see [`Router::fingerprint`][crate::Router::fingerprint]
+/// for a stable hash of a route table.
+impl Hash for RouteSpec {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.segments.hash(state);
+        self.constraints.hash(state);
+        self.mode.hash(state);
+        self.weight.hash(state);
+        self.format.hash(state);
+        self.exclusions.hash(state);
+    }
 }
 
 impl Display for RouteSpec {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.write_str("/")?;
         for segment in &self.segments {
-            match segment {
-                Segment::Slash => f.write_str("/")?,
-                Segment::Dot => f.write_str(".")?,
-                Segment::Exact(s) => f.write_str(s)?,
-                Segment::Param(p) => f.write_fmt(format_args!(":{}", p))?,
-                Segment::Wildcard => f.write_str("*")?,
-            };
+            self.write_segment(segment, f)?;
         }
         Ok(())
     }
 }
 
 impl RouteSpec {
-    fn dots(&self) -> usize {
+    /// the precedence of each segment, followed by the end of the
+    /// route, for comparing routes of the same mode. exact text that
+    /// contains dots takes a position for each dot-separated part, as
+    /// it would if the text were split after each dot, so that
+    /// `/a.json/b` and `/a.:ext/:b` compare part by part
+    fn positions(&self) -> impl Iterator<Item = u8> + '_ {
+        let end = if self.mode.is_prefix() { 0 } else { 9 };
         self.segments
             .iter()
-            .filter(|c| matches!(c, Segment::Dot))
-            .count()
+            .flat_map(|segment| match segment {
+                Segment::Slash => iter::repeat_n(1, 1),
+                Segment::Dot => iter::repeat_n(10, 1),
+                Segment::Exact(e) => iter::repeat_n(
+                    segment.precedence() + 1,
+                    e.split('.').filter(|part| !part.is_empty()).count().max(1),
+                ),
+                segment => iter::repeat_n(segment.precedence() + 1, 1),
+            })
+            .chain(iter::once(end))
+    }
+
+    /// writes a segment as it appears in this spec's Display
+    pub(crate) fn write_segment(&self, segment: &Segment, f: &mut impl fmt::Write) -> fmt::Result {
+        let constraint = segment.name().and_then(|name| self.constraint(name));
+        match (segment, constraint) {
+            (_, None) => write!(f, "{}", segment),
+            (Segment::Param(p), Some(c)) => write!(f, ":{}({})", p, c.source()),
+            (Segment::OptionalParam(p), Some(c)) => write!(f, ":{}({})?", p, c.source()),
+            (Segment::OneOrMore(p), Some(c)) => write!(f, ":{}({})+", p, c.source()),
+            (Segment::ZeroOrMore(p), Some(c)) => write!(f, ":{}({})*", p, c.source()),
+            (_, Some(_)) => write!(f, "{}", segment),
+        }
     }
 
     /// Retrieve a reference to the original route definition, if this
@@ -49,79 +105,617 @@ impl RouteSpec {
         self.source.as_deref()
     }
 
+    /// Parses a route definition written in the provided [`Syntax`].
+    /// `RouteSpec::parse_with(source, Syntax::Routefinder)` is
+    /// equivalent to `source.parse()`.
+    ///
+    /// ```rust
+    /// use routefinder::{RouteSpec, Router, Syntax};
+    /// let mut router = Router::new();
+    /// router.add(RouteSpec::parse_with("content/**/*.md", Syntax::Glob).unwrap(), ()).unwrap();
+    /// let m = router.best_match("/content/posts/2021/hello.md").unwrap();
+    /// assert_eq!(m.captures().wildcard(), Some("posts/2021"));
+    /// assert!(router.best_match("/content/hello.md").is_some());
+    /// assert!(router.best_match("/content/hello.txt").is_none());
+    /// ```
+    pub fn parse_with(source: &str, syntax: Syntax) -> Result<Self, String> {
+        let (segments, constraints) = match syntax {
+            Syntax::Routefinder => return source.parse(),
+            Syntax::Glob => (glob::parse(source)?, vec![]),
+            Syntax::Express => express::parse(source)?,
+            Syntax::HttpRule => http_rule::parse(source)?,
+        };
+
+        Ok(Self {
+            source: Some(SmartString::from(source)),
+            segments,
+            constraints,
+            mode: MatchMode::default(),
+            weight: None,
+            condition: None,
+            format: None,
+            formats: vec![],
+            exclusions: vec![],
+            description: None,
+            config: None,
+            name: None,
+            id: None,
+        })
+    }
+
+    /// Builds a spec from segments, checking that they describe a route
+    /// that can be matched. Unlike `From<Vec<Segment>>`, the segments
+    /// are arranged as they would be if the spec were parsed: slashes
+    /// at either end are dropped, and exact text is joined with any dot
+    /// or exact text that follows it, so `Exact("min")`, `Dot`,
+    /// `Exact("js")` becomes `Exact("min.js")`. A dot only separates
+    /// segments after a param or glob. The resulting spec is displayed
+    /// and parsed back to the same segments.
+    ///
+    /// ```rust
+    /// use routefinder::{RouteSpec, Segment};
+    /// let spec = RouteSpec::from_segments(vec![
+    ///     Segment::Slash,
+    ///     Segment::Param("name".into()),
+    ///     Segment::Dot,
+    ///     Segment::Exact("min".into()),
+    ///     Segment::Dot,
+    ///     Segment::Exact("js".into()),
+    /// ])
+    /// .unwrap();
+    /// assert_eq!(spec.to_string(), "/:name.min.js");
+    /// assert_eq!(spec.segments()[2], Segment::Exact("min.js".into()));
+    /// assert_eq!(spec.matches("/app.min.js"), Some(vec!["app"]));
+    /// assert_eq!(spec.to_string().parse::<RouteSpec>().unwrap().segments(), spec.segments());
+    ///
+    /// assert!(RouteSpec::from_segments(vec![Segment::Param("a".into()), Segment::Wildcard]).is_err());
+    /// ```
+    pub fn from_segments(segments: impl IntoIterator<Item = Segment>) -> Result<Self, String> {
+        let segments: Vec<Segment> = segments.into_iter().collect();
+        for pair in segments.windows(2) {
+            match pair {
+                [Segment::Slash, Segment::Slash] => {
+                    return Err(String::from("segments cannot contain consecutive slashes"));
+                }
+                [Segment::Dot, Segment::Dot] => {
+                    return Err(String::from("segments cannot contain consecutive dots"));
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(Segment::Exact(e)) = segments
+            .iter()
+            .find(|segment| matches!(segment, Segment::Exact(e) if e.is_empty() || e.contains('/')))
+        {
+            return Err(format!("`{}` is not valid exact text", e));
+        }
+
+        let joined = join_segments(segments);
+        let mut wildcards = 0;
+        for (index, segment) in joined.iter().enumerate() {
+            let next = joined.get(index + 1);
+            match segment {
+                Segment::Wildcard => wildcards += 1,
+                Segment::Glob(g) if g.is_empty() || g.contains('/') => {
+                    return Err(format!("`{}` is not a valid glob", g));
+                }
+                Segment::Glob(g) if !matches!(next, None | Some(Segment::Slash | Segment::Dot)) => {
+                    return Err(format!(
+                        "`{}` must be followed by a dot, a slash, or the end",
+                        g
+                    ));
+                }
+                _ => {}
+            }
+
+            if segment.name() == Some("") {
+                return Err(String::from("params must be named"));
+            }
+
+            if matches!(segment, Segment::Param(_))
+                && !matches!(
+                    next,
+                    None | Some(Segment::Slash | Segment::Dot | Segment::Exact(_))
+                )
+            {
+                return Err(format!(
+                    "`{}` must be followed by a dot, a slash, exact text, or the end",
+                    segment
+                ));
+            }
+        }
+
+        if wildcards > 1 {
+            return Err(String::from("there can only be one wildcard"));
+        }
+
+        Ok(Self::from(joined))
+    }
+
+    /// Parses this spec's [`Display`] output, returning an equal spec if
+    /// the routefinder syntax can express it. Exact text on either side
+    /// of a dot is compared as it would be parsed, as with
+    /// [`RouteSpec::from_segments`]. Specs that can only be written in
+    /// another [`Syntax`], such as those with globs or param patterns,
+    /// or a param directly followed by exact text, return an error.
+    /// The mode, weight, and other settings of this spec are not part
+    /// of its display, and are not compared.
+    ///
+    /// ```rust
+    /// use routefinder::{RouteSpec, Segment, Syntax};
+    /// let spec: RouteSpec = "/files/:path+/raw.:format?".parse().unwrap();
+    /// assert_eq!(spec.reparse().unwrap().segments(), spec.segments());
+    ///
+    /// let spec = RouteSpec::from(vec![
+    ///     Segment::Exact("min".into()),
+    ///     Segment::Dot,
+    ///     Segment::Exact("js".into()),
+    /// ]);
+    /// assert_eq!(spec.reparse().unwrap().segments(), &[Segment::Exact("min.js".into())]);
+    ///
+    /// let glob = RouteSpec::parse_with("assets/*.css", Syntax::Glob).unwrap();
+    /// assert!(glob.reparse().is_err());
+    /// ```
+    pub fn reparse(&self) -> Result<Self, String> {
+        let displayed = self.to_string();
+        let reparsed: Self = displayed.parse()?;
+        if reparsed.segments != join_segments(self.segments.clone())
+            || reparsed.constraints != self.constraints
+        {
+            return Err(format!(
+                "`{}` cannot be parsed back to the same route",
+                displayed
+            ));
+        }
+
+        Ok(reparsed)
+    }
+
+    /// Returns this spec with the provided [`MatchMode`]
+    ///
+    /// ```rust
+    /// use routefinder::{MatchMode, RouteSpec};
+    /// let spec = "/assets".parse::<RouteSpec>().unwrap().with_mode(MatchMode::Prefix);
+    /// assert_eq!(spec.matches("/assets/css/site.css"), Some(vec!["css/site.css"]));
+    /// assert!(spec.matches("/assetsandmore").is_none());
+    /// ```
+    pub fn with_mode(mut self, mode: MatchMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Returns the [`MatchMode`] for this spec
+    pub fn mode(&self) -> MatchMode {
+        self.mode
+    }
+
+    /// Returns this spec's weight among the other handlers registered
+    /// for the same route with [`Router::add_weighted`][crate::Router::add_weighted],
+    /// if any
+    pub fn weight(&self) -> Option<u32> {
+        self.weight
+    }
+
+    pub(crate) fn with_weight(mut self, weight: u32) -> Self {
+        self.weight = Some(weight);
+        self
+    }
+
+    pub(crate) fn with_condition(mut self, condition: RouteCondition) -> Self {
+        self.condition = Some(condition);
+        self
+    }
+
+    /// the single path that this spec matches, without slashes at
+    /// either end, if it has no params, wildcards, or globs and is not
+    /// a prefix
+    pub(crate) fn literal_path(&self) -> Option<SmartString> {
+        if self.mode.is_prefix() {
+            return None;
+        }
+
+        let mut path = SmartString::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Slash => path.push('/'),
+                Segment::Dot => path.push('.'),
+                Segment::Exact(exact) => path.push_str(exact),
+                _ => return None,
+            }
+        }
+        Some(SmartString::from(path.trim_matches('/')))
+    }
+
+    /// whether this spec has a literal path and is always tried, so
+    /// that it can be found by that path without being applied to it
+    pub(crate) fn is_static(&self) -> bool {
+        self.condition.is_none()
+            && self.weight.is_none()
+            && self.exclusions.is_empty()
+            && self.literal_path().is_some()
+    }
+
+    pub(crate) fn with_exclusions(mut self, exclusions: Vec<RouteSpec>) -> Self {
+        self.exclusions = exclusions;
+        self
+    }
+
+    /// Returns the specs of the paths that this spec does not match,
+    /// if it was added with
+    /// [`Router::add_excluding`][crate::Router::add_excluding]
+    pub fn exclusions(&self) -> &[RouteSpec] {
+        &self.exclusions
+    }
+
+    pub(crate) fn with_description(mut self, description: &str) -> Self {
+        self.description = Some(SmartString::from(description));
+        self
+    }
+
+    /// Returns the description of this spec, if it was added with
+    /// [`Router::add_documented`][crate::Router::add_documented]
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub(crate) fn with_config(mut self, config: RouteConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Returns the operational settings of this spec, if it was added
+    /// with [`Router::add_with_config`][crate::Router::add_with_config]
+    pub fn config(&self) -> Option<&RouteConfig> {
+        self.config.as_ref()
+    }
+
+    pub(crate) fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(SmartString::from(name));
+        self
+    }
+
+    /// Returns the name of this spec, such as `posts.show`, if it was
+    /// added with
+    /// [`MethodRouter::add_resource`][crate::MethodRouter::add_resource]
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns false if this spec was added with
+    /// [`Router::add_conditional`][crate::Router::add_conditional] and
+    /// its [`Condition`][crate::Condition] is not currently met, or
+    /// was added with [`Router::add_guarded`][crate::Router::add_guarded]
+    pub fn is_enabled(&self) -> bool {
+        self.is_enabled_in(None)
+    }
+
+    /// whether this spec is enabled for the context of a match, if
+    /// any. see [`Router::add_guarded`][crate::Router::add_guarded]
+    pub(crate) fn is_enabled_in(&self, context: Option<&dyn Any>) -> bool {
+        self.condition
+            .as_ref()
+            .is_none_or(|condition| condition.is_enabled(context))
+    }
+
+    /// Returns true if this spec has a [`Segment::Wildcard`], such as
+    /// `/users/*`
+    pub fn has_wildcard(&self) -> bool {
+        self.segments.contains(&Segment::Wildcard)
+    }
+
+    /// Returns true if this spec was added with
+    /// [`Router::add_guarded`][crate::Router::add_guarded], so that it
+    /// only matches with a context
+    pub fn is_guarded(&self) -> bool {
+        self.condition
+            .as_ref()
+            .is_some_and(RouteCondition::is_guard)
+    }
+
+    /// Returns the format that this spec was registered for with
+    /// [`Router::add_with_formats`][crate::Router::add_with_formats], if any
+    pub fn format(&self) -> Option<&str> {
+        self.format.as_deref()
+    }
+
+    /// Returns a description of everything about this spec that
+    /// affects which paths it matches and how it is ordered: its
+    /// display, followed by its mode, weight, format, and exclusions if
+    /// it has them, and whether it is conditional. Specs that route the
+    /// same way have the same canonical form, regardless of how they
+    /// were written or built, so it is suitable for golden files. See
+    /// [`Router::fingerprint`][crate::Router::fingerprint].
+    ///
+    /// ```rust
+    /// use routefinder::{MatchMode, RouteSpec};
+    /// let spec: RouteSpec = "users/:id/".parse().unwrap();
+    /// assert_eq!(spec.canonical(), "/users/:id");
+    /// let spec = spec.with_mode(MatchMode::Prefix);
+    /// assert_eq!(spec.canonical(), "/users/:id mode=Prefix");
+    /// ```
+    pub fn canonical(&self) -> String {
+        let mut canonical = self.to_string();
+        if self.mode != MatchMode::default() {
+            canonical.push_str(&format!(" mode={:?}", self.mode));
+        }
+
+        if let Some(weight) = self.weight {
+            canonical.push_str(&format!(" weight={}", weight));
+        }
+
+        if let Some(format) = &self.format {
+            canonical.push_str(&format!(" format={}", format));
+        }
+
+        for exclusion in &self.exclusions {
+            canonical.push_str(&format!(" except={}", exclusion.canonical()));
+        }
+
+        if self.is_guarded() {
+            canonical.push_str(" guarded");
+        } else if self.condition.is_some() {
+            canonical.push_str(" conditional");
+        }
+
+        canonical
+    }
+
+    /// Returns a [`SortKey`] that orders routes as a
+    /// [`Router`][crate::Router] does, for storing routes outside of
+    /// the router in order of precedence. The key encodes the mode,
+    /// each segment's precedence, whether the route is conditional,
+    /// and its [`RouteId`], in the order they are compared.
+    ///
+    /// ```rust
+    /// let mut router = routefinder::Router::new();
+    /// router.add("/*", ()).unwrap();
+    /// router.add("/users/:id", ()).unwrap();
+    /// router.add("/users/new", ()).unwrap();
+    ///
+    /// let mut routes: Vec<_> = router.iter().map(|(route, _)| route).collect();
+    /// routes.reverse();
+    /// routes.sort_by_key(|route| route.sort_key());
+    /// let in_router: Vec<_> = router.iter().map(|(route, _)| route).collect();
+    /// assert_eq!(routes, in_router);
+    /// ```
+    pub fn sort_key(&self) -> SortKey {
+        let mut key = String::with_capacity(self.segments.len() + 24);
+        key.push(char::from(b'0' + self.mode.rank()));
+        // the greater position is tried first, so it is the lesser digit
+        for position in self.positions() {
+            key.push(char::from_digit(u32::from(10 - position), 16).unwrap_or('0'));
+        }
+        key.push('~');
+        key.push(if self.condition.is_some() { '0' } else { '1' });
+        match self.id {
+            Some(id) => key.push_str(&format!("{:020}", id.get())),
+            None => key.push('~'),
+        }
+        SortKey::new(key)
+    }
+
+    /// Returns the [`RouteId`] that this spec was assigned when it was
+    /// added to a [`Router`][crate::Router], or None if it has not been
+    /// added to one
+    pub fn id(&self) -> Option<RouteId> {
+        self.id
+    }
+
+    pub(crate) fn with_id(mut self, id: RouteId) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Returns this spec for the provided format. If `suffixed`, the
+    /// spec only matches paths ending in `.{format}`
+    pub(crate) fn with_format(mut self, format: &str, suffixed: bool) -> Self {
+        if suffixed {
+            self.segments.push(Segment::Dot);
+            self.segments.push(Segment::Exact(format.into()));
+            self.source = self.source.map(|source| {
+                let mut source = SmartString::from(source.trim_end_matches('/'));
+                source.push('.');
+                source.push_str(format);
+                source
+            });
+        }
+        self.format = Some(format.into());
+        self
+    }
+
+    /// Returns this spec with the formats that it negotiates between,
+    /// the first of which is the default
+    pub(crate) fn with_formats(mut self, formats: Vec<SmartString>) -> Self {
+        self.formats = formats;
+        self
+    }
+
+    /// the formats that this unsuffixed spec negotiates between, as
+    /// registered by
+    /// [`Router::add_with_formats`][crate::Router::add_with_formats]
+    pub(crate) fn formats(&self) -> impl Iterator<Item = &str> {
+        self.formats.iter().map(|format| &**format)
+    }
+
+    /// whether this and the other spec are weighted alternatives for
+    /// the same route
+    pub(crate) fn is_alternative_of(&self, other: &Self) -> bool {
+        self.weight.is_some()
+            && other.weight.is_some()
+            && self.is_same_route(other)
+            && self.condition == other.condition
+    }
+
+    /// whether this and the other spec match the same paths with the
+    /// same captures and precedence, regardless of how they were
+    /// written or registered
+    pub(crate) fn is_same_route(&self, other: &Self) -> bool {
+        self.mode == other.mode
+            && self.segments == other.segments
+            && self.constraints == other.constraints
+            && self.exclusions == other.exclusions
+    }
+
+    /// whether this spec is written under the prefix, so that its
+    /// segments begin with the segments of the prefix, up to a slash
+    pub(crate) fn is_under(&self, prefix: &Self) -> bool {
+        let prefix = prefix.segments();
+        self.segments.starts_with(prefix)
+            && matches!(self.segments.get(prefix.len()), None | Some(Segment::Slash))
+    }
+
+    /// the name of the only segment of this spec, if it is a param
+    /// that can match the root. See [`RootPolicy`][crate::RootPolicy].
+    fn root_param(&self) -> Option<&str> {
+        let mut segments = self
+            .segments
+            .iter()
+            .filter(|segment| **segment != Segment::Slash);
+        match (segments.next(), segments.next()) {
+            (Some(Segment::Param(name)), None)
+                if self
+                    .constraint(name)
+                    .is_none_or(|constraint| constraint.is_match("")) =>
+            {
+                Some(name)
+            }
+            _ => None,
+        }
+    }
+
+    /// whether the remaining unmatched path means that this spec has
+    /// matched
+    fn is_complete(&self, remaining: &str) -> bool {
+        remaining.is_empty()
+            || remaining == "/"
+            || (self.mode.is_prefix() && (remaining.starts_with('/') || self.segments.is_empty()))
+    }
+
     /// Slice accessor for the component [`Segment`]s in this RouteSpec
     pub fn segments(&self) -> &[Segment] {
         self.segments.as_slice()
     }
 
+    pub(crate) fn constraint(&self, name: &str) -> Option<&Constraint> {
+        self.constraints.iter().find(|c| c.name() == name)
+    }
+
     #[inline]
     fn inner_match<'path>(
         &self,
+        mut index: usize,
         mut path: &'path str,
         captures: &mut Vec<&'path str>,
+        failures: &mut Failures,
+        options: &MatchOptions,
     ) -> Option<&'path str> {
-        let mut peek = self.segments.iter().peekable();
-        while let Some(segment) = peek.next() {
+        while let Some(segment) = self.segments.get(index) {
+            let next = self.segments.get(index + 1);
             path = match segment {
                 Segment::Exact(e) => {
-                    if path.starts_with(&**e) {
+                    #[cfg(feature = "simd")]
+                    let prefixed = crate::simd::starts_with(path, e);
+                    #[cfg(not(feature = "simd"))]
+                    let prefixed = path.starts_with(&**e);
+
+                    let matched = prefixed
+                        || (options.case_insensitive
+                            && path.get(..e.len())?.eq_ignore_ascii_case(e));
+                    if matched {
                         &path[e.len()..]
                     } else {
                         return None;
                     }
                 }
 
+                Segment::Param(name)
+                    if !self.constraints.is_empty() && self.constraint(name).is_some() =>
+                {
+                    return self.inner_match_span(index, path, captures, failures, options);
+                }
+
                 Segment::Param(_) => {
                     if path.is_empty() {
                         return None;
                     }
-                    match peek.peek() {
+                    match next {
                         None | Some(Segment::Slash) => {
-                            #[cfg(feature = "memchr")]
-                            let capture = memchr::memchr(b'/', path.as_bytes())
-                                .map(|index| &path[..index])
-                                .unwrap_or(path);
-                            #[cfg(not(feature = "memchr"))]
-                            let capture = path.split('/').next()?;
-
+                            let capture = &path[..split::find(path, b'/').unwrap_or(path.len())];
                             captures.push(capture);
                             &path[capture.len()..]
                         }
 
                         Some(Segment::Dot) => {
-                            #[cfg(feature = "memchr")]
-                            let index = memchr::memchr2(b'.', b'/', path.as_bytes())?;
-                            #[cfg(not(feature = "memchr"))]
-                            let index = path.find(|c| c == '.' || c == '/')?;
-
-                            if path.chars().nth(index) == Some('.') {
+                            let index = split::find2(path, b'.', b'/')?;
+                            if path.as_bytes()[index] == b'.' {
                                 captures.push(&path[..index]);
                                 &path[index..] // we leave the dot so it can be matched by the Segment::Dot
                             } else {
                                 return None;
                             }
                         }
+
+                        Some(Segment::Exact(e)) => {
+                            // the param takes at least one character and
+                            // ends at the first occurrence of the exact
+                            // text within this path segment
+                            let end = path.find('/').unwrap_or(path.len());
+                            let start = split::first_len(&path[..end])?;
+                            let index = start + find(&path[start..end], e, options)?;
+                            captures.push(&path[..index]);
+                            &path[index..]
+                        }
+
                         _ => panic!(
-                            "param must be followed by a dot, a slash, or the end of the route"
+                            "param must be followed by a dot, a slash, exact text, or the end of the route"
                         ),
                     }
                 }
 
-                Segment::Wildcard => match peek.peek() {
-                    Some(_) => panic!(concat!(
-                        "wildcard must currently be the terminal segment, ",
-                        "please file an issue if you have a use case for a mid-route *"
-                    )),
-                    None => {
-                        captures.push(path);
-                        ""
+                Segment::Glob(pattern) => {
+                    let end = match next {
+                        None | Some(Segment::Slash) => path.find('/'),
+                        Some(Segment::Dot) => path.find(['.', '/']),
+                        _ => panic!(
+                            "glob must be followed by a dot, a slash, or the end of the route"
+                        ),
+                    }
+                    .unwrap_or(path.len());
+
+                    let candidate = &path[..end];
+                    let matched = if options.case_insensitive {
+                        glob::matches(
+                            &pattern.to_ascii_lowercase(),
+                            &candidate.to_ascii_lowercase(),
+                        )
+                    } else {
+                        glob::matches(pattern, candidate)
+                    };
+
+                    if matched {
+                        &path[end..]
+                    } else {
+                        return None;
                     }
-                },
+                }
 
-                Segment::Slash => match (path.chars().next(), peek.peek()) {
+                Segment::Wildcard if next.is_none() => {
+                    captures.push(path);
+                    ""
+                }
+
+                Segment::Wildcard
+                | Segment::OptionalParam(_)
+                | Segment::OneOrMore(_)
+                | Segment::ZeroOrMore(_) => {
+                    return self.inner_match_span(index, path, captures, failures, options)
+                }
+
+                Segment::Slash => match (path.chars().next(), next) {
                     (Some('/'), Some(_)) => &path[1..],
                     (None, None) => path,
-                    (None, Some(Segment::Wildcard)) => path,
+                    (None, Some(next)) if next.may_be_empty() => path,
                     _ => return None,
                 },
 
@@ -129,23 +723,213 @@ impl RouteSpec {
                     Some('.') => &path[1..],
                     _ => return None,
                 },
-            }
+            };
+
+            index += 1;
         }
 
         Some(path)
     }
 
+    /// Segments that can match a variable amount of the path are
+    /// tried against each candidate span, longest first, until the
+    /// rest of the route matches what remains of the path. A segment
+    /// that matches nothing also stands in for the slash that follows
+    /// it, so that `a/*/b` matches `a/b`. Each position in the route
+    /// and the path that fails to match is only tried once, so that
+    /// several of these segments in one route cannot backtrack
+    /// exponentially.
+    fn inner_match_span<'path>(
+        &self,
+        index: usize,
+        path: &'path str,
+        captures: &mut Vec<&'path str>,
+        failures: &mut Failures,
+        options: &MatchOptions,
+    ) -> Option<&'path str> {
+        let segment = &self.segments[index];
+        let next = self.segments.get(index + 1);
+        let constraint = segment.name().and_then(|name| self.constraint(name));
+
+        // a constrained param, like path-to-regexp, may span several
+        // path segments if its pattern allows slashes
+        let (multiple_segments, allow_empty) = match segment {
+            Segment::Wildcard | Segment::ZeroOrMore(_) => (true, true),
+            Segment::OneOrMore(_) => (true, false),
+            Segment::OptionalParam(_) => (false, true),
+            _ => (true, false),
+        };
+        let check_each_segment = matches!(segment, Segment::OneOrMore(_) | Segment::ZeroOrMore(_));
+
+        let limit = if multiple_segments {
+            path.len()
+        } else {
+            path.find('/').unwrap_or(path.len())
+        };
+
+        let len = captures.len();
+        let mut attempt = |end: usize, next_index: usize, remaining: &'path str| {
+            let capture = &path[..end];
+            if capture.is_empty() && !allow_empty {
+                return None;
+            }
+
+            if let Some(constraint) = constraint {
+                let satisfied = capture.is_empty()
+                    || if check_each_segment {
+                        capture.split('/').all(|s| constraint.is_match(s))
+                    } else {
+                        constraint.is_match(capture)
+                    };
+
+                if !satisfied {
+                    return None;
+                }
+            }
+
+            if failures.contains(next_index, remaining.len()) {
+                return None;
+            }
+
+            captures.push(capture);
+            match self.inner_match(next_index, remaining, captures, failures, options) {
+                Some(remaining) if self.is_complete(remaining) => Some(remaining),
+                _ => {
+                    captures.truncate(len);
+                    failures.insert(next_index, remaining.len());
+                    None
+                }
+            }
+        };
+
+        match next {
+            None => attempt(limit, index + 1, &path[limit..]),
+
+            Some(Segment::Slash) => iter::once(limit)
+                .chain(path[..limit].rmatch_indices('/').map(|(i, _)| i))
+                .filter(|end| *end > 0)
+                .find_map(|end| attempt(end, index + 1, &path[end..]))
+                .or_else(|| attempt(0, index + 2, path)),
+
+            Some(_) => iter::once(limit)
+                .chain(path[..limit].char_indices().rev().map(|(i, _)| i))
+                .find_map(|end| attempt(end, index + 1, &path[end..])),
+        }
+    }
+
     /// Returns a vec of captured str slices for this routespec
     #[inline]
     pub fn matches<'path>(&self, path: &'path str) -> Option<Vec<&'path str>> {
+        self.matches_with(path, &MatchOptions::default())
+    }
+
+    /// Returns a vec of captured str slices for this routespec, applying
+    /// the trailing slash and case sensitivity of the [`MatchOptions`].
+    /// Paths are not decoded or otherwise normalized.
+    ///
+    /// ```rust
+    /// use routefinder::{MatchOptions, RouteSpec};
+    /// let spec: RouteSpec = "/Users/:id".parse().unwrap();
+    /// let options = MatchOptions::new().case_insensitive(true);
+    /// assert_eq!(spec.matches_with("/users/Jane", &options), Some(vec!["Jane"]));
+    /// assert!(spec.matches("/users/Jane").is_none());
+    /// ```
+    pub fn matches_with<'path>(
+        &self,
+        path: &'path str,
+        options: &MatchOptions,
+    ) -> Option<Vec<&'path str>> {
+        let strict = self.mode == MatchMode::Exact
+            || (options.strict_trailing_slash && !self.mode.is_prefix());
+        if strict && path.len() > 1 && path.ends_with('/') {
+            return None;
+        }
+
         let mut p = path.trim_start_matches('/').trim_end_matches('/');
         let mut captures = vec![];
-        p = self.inner_match(p, &mut captures)?;
-        if p.is_empty() || p == "/" {
-            Some(captures)
+        let root = options.root_policy.is_root(path);
+        if root && options.root_policy.param_matches_root && self.root_param().is_some() {
+            captures.push(p);
         } else {
-            None
+            let mut failures = Failures::new(self.segments.len() + 1, p.len() + 1);
+            p = self.inner_match(0, p, &mut captures, &mut failures, options)?;
+            if !self.is_complete(p) {
+                return None;
+            }
         }
+
+        if root
+            && !options.root_policy.wildcard_matches_root
+            && self
+                .segments
+                .iter()
+                .any(|segment| matches!(segment, Segment::Wildcard | Segment::ZeroOrMore(_)))
+        {
+            return None;
+        }
+
+        if self.mode.is_prefix() && !self.segments.contains(&Segment::Wildcard) {
+            captures.push(p.trim_start_matches('/'));
+        }
+
+        if self
+            .exclusions
+            .iter()
+            .any(|exclusion| exclusion.matches_with(path, options).is_some())
+        {
+            return None;
+        }
+
+        Some(captures)
+    }
+
+    /// names the positional captures returned by
+    /// [`RouteSpec::matches`]
+    pub(crate) fn captures<'route, 'path, V>(
+        &'route self,
+        values: impl IntoIterator<Item = V>,
+    ) -> Captures<'route, 'path>
+    where
+        V: Into<Cow<'path, str>>,
+    {
+        let mut values = values.into_iter().map(Into::into);
+        // names are borrowed from the route, so the params are the only
+        // allocation
+        let params = self.segments.iter().filter(|s| s.name().is_some()).count();
+        let mut captures = self
+            .segments
+            .iter()
+            .filter(|s| s.is_capture())
+            .zip(&mut values)
+            .fold(
+                Captures::with_capacity(params),
+                |mut captures, (segment, value)| match segment {
+                    Segment::Param(name) | Segment::OneOrMore(name) => {
+                        captures.push(Capture::new(&**name, value));
+                        captures
+                    }
+
+                    Segment::OptionalParam(name) | Segment::ZeroOrMore(name) => {
+                        if !value.is_empty() {
+                            captures.push(Capture::new(&**name, value));
+                        }
+                        captures
+                    }
+
+                    Segment::Wildcard => {
+                        captures.set_wildcard(value);
+                        captures
+                    }
+                    _ => captures,
+                },
+            );
+
+        // prefix routes capture the rest of the path as a wildcard
+        if let Some(rest) = values.next() {
+            captures.set_wildcard(rest);
+        }
+
+        captures
     }
 
     /// populate this route spec with the params and/or wildcard from
@@ -156,34 +940,399 @@ impl RouteSpec {
     ) -> Option<ReverseMatch<'keys, 'values, 'captures, 'route>> {
         ReverseMatch::new(captures, self)
     }
+
+    /// Populates this route spec with the params and/or wildcard from
+    /// a [`Captures`], after applying each [`Transform`] to the param
+    /// of the same name, in order. The transformed values are
+    /// [validated][RouteSpec::validate], and a required param whose
+    /// value is transformed to nothing is
+    /// [invalid][TemplateError::Invalid], so the resulting path
+    /// matches this route. An optional param whose value is
+    /// transformed to nothing is left out.
+    ///
+    /// ```rust
+    /// use routefinder::{RouteSpec, TemplateError, Transform};
+    /// let spec: RouteSpec = "/posts/:slug".parse().unwrap();
+    /// assert_eq!(
+    ///     spec.url_for(&[("slug", "Hello World")].into(), &[("slug", Transform::Slugify)]),
+    ///     Ok(String::from("/posts/hello-world"))
+    /// );
+    /// assert_eq!(
+    ///     spec.url_for(&[("slug", "!!")].into(), &[("slug", Transform::Slugify)]),
+    ///     Err(TemplateError::Invalid { name: "slug".into(), value: "!!".into() })
+    /// );
+    /// ```
+    pub fn url_for(
+        &self,
+        captures: &Captures,
+        transforms: &[(&str, Transform)],
+    ) -> Result<String, TemplateError> {
+        let mut values = Captures::with_capacity(captures.len());
+        for capture in captures.params() {
+            let value = transforms
+                .iter()
+                .filter(|(name, _)| *name == capture.name())
+                .fold(
+                    Cow::Borrowed(capture.value()),
+                    |value, (_, transform)| match value {
+                        Cow::Borrowed(value) => transform.apply(value),
+                        Cow::Owned(value) => Cow::Owned(transform.apply(&value).into_owned()),
+                    },
+                );
+
+            if value.is_empty() {
+                let required = self.segments.iter().any(|segment| {
+                    matches!(segment, Segment::Param(_) | Segment::OneOrMore(_))
+                        && segment.name() == Some(capture.name())
+                });
+                if required {
+                    return Err(TemplateError::Invalid {
+                        name: capture.name().to_string(),
+                        value: capture.value().to_string(),
+                    });
+                }
+            } else {
+                values.push(Capture::new(capture.name(), value));
+            }
+        }
+
+        if let Some(wildcard) = captures.wildcard() {
+            values.set_wildcard(wildcard);
+        }
+
+        self.validate(&values)?;
+        Ok(self
+            .template(&values)
+            .expect("validated captures populate the route")
+            .to_string())
+    }
+
+    /// Checks that matching the path templated from these captures
+    /// gives back the same captures. An optional param or wildcard
+    /// that is absent is the same as one that is empty.
+    ///
+    /// This holds for routes with at most one optional param,
+    /// multi-segment param, or wildcard, and captures that
+    /// [validate][RouteSpec::validate] in which no value is empty, no
+    /// `:param` or `:param?` value contains a slash, or a dot when the
+    /// param is next to a dot (see
+    /// [`SeparatorPolicy`][crate::SeparatorPolicy]), and no
+    /// multi-segment param or wildcard value begins or ends with a
+    /// slash or contains an empty segment. Otherwise, more than one
+    /// set of captures may template the same path. Returns a
+    /// description of the first difference when the captures do not
+    /// round trip.
+    ///
+    /// ```rust
+    /// use routefinder::RouteSpec;
+    /// let spec: RouteSpec = "/:name.:ext".parse().unwrap();
+    /// assert!(spec.roundtrip_check(&[("name", "report"), ("ext", "tar.gz")].into()).is_ok());
+    /// assert_eq!(
+    ///     spec.roundtrip_check(&[("name", "report.tar"), ("ext", "gz")].into()).unwrap_err(),
+    ///     "`name` is \"report.tar\", but /report.tar.gz captures \"report\""
+    /// );
+    /// ```
+    pub fn roundtrip_check(&self, captures: &Captures) -> Result<(), String> {
+        self.validate(captures).map_err(|error| error.to_string())?;
+        let path = self
+            .template(captures)
+            .expect("validated captures populate the route")
+            .to_string();
+        let values = self
+            .matches(&path)
+            .ok_or_else(|| format!("{} does not match {}", self, path))?;
+        let matched = self.captures(values);
+
+        for name in self.segments.iter().filter_map(Segment::name) {
+            let expected = captures.get(name).unwrap_or_default();
+            let found = matched.get(name).unwrap_or_default();
+            if expected != found {
+                return Err(format!(
+                    "`{}` is {:?}, but {} captures {:?}",
+                    name, expected, path, found
+                ));
+            }
+        }
+
+        let expected = captures.wildcard().unwrap_or_default();
+        let found = matched.wildcard().unwrap_or_default();
+        if expected != found {
+            return Err(format!(
+                "the wildcard is {:?}, but {} captures {:?}",
+                expected, path, found
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Checks that these captures can populate this route spec, and
+    /// that the route would match the resulting path, without
+    /// building a [`ReverseMatch`]
+    ///
+    /// ```rust
+    /// use routefinder::{RouteSpec, TemplateError};
+    /// let spec: RouteSpec = "/users/:id/posts/:post".parse().unwrap();
+    /// assert!(spec.validate(&[("id", "1"), ("post", "2")].into()).is_ok());
+    /// assert_eq!(
+    ///     spec.validate(&[("post", "2")].into()),
+    ///     Err(TemplateError::Missing("id".into()))
+    /// );
+    /// assert_eq!(
+    ///     spec.validate(&[("id", "1"), ("post", "2"), ("user", "3")].into()),
+    ///     Err(TemplateError::Unexpected("user".into()))
+    /// );
+    /// ```
+    pub fn validate(&self, captures: &Captures) -> Result<(), TemplateError> {
+        self.check_captures(captures)?;
+        for capture in captures.params() {
+            let constraint = match self.constraint(capture.name()) {
+                Some(constraint) => constraint,
+                None => continue,
+            };
+
+            let value = capture.value();
+            let each_segment = self.segments.iter().any(|segment| {
+                matches!(segment, Segment::OneOrMore(_) | Segment::ZeroOrMore(_))
+                    && segment.name() == Some(capture.name())
+            });
+            let satisfied = value.is_empty()
+                || if each_segment {
+                    value.split('/').all(|s| constraint.is_match(s))
+                } else {
+                    constraint.is_match(value)
+                };
+
+            if !satisfied {
+                return Err(TemplateError::Invalid {
+                    name: capture.name().to_string(),
+                    value: value.to_string(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Resolves a route relative to the path of a match, as a link
+    /// would be resolved relative to a page's url, filling in the
+    /// params and wildcard of the relative route from the match's
+    /// captures
+    ///
+    /// As in [RFC 3986 section
+    /// 5.2](https://www.rfc-editor.org/rfc/rfc3986#section-5.2), the
+    /// relative route replaces the last segment of the matched path,
+    /// and any leading `.` and `..` segments are resolved against the
+    /// rest. A relative route that starts with a slash replaces the
+    /// whole path. The match's path is taken without a trailing
+    /// slash, as in [`Match::canonical_path`][crate::Match::canonical_path].
+    ///
+    /// Returns an error if the relative route does not parse, if a
+    /// param of the relative route is not captured by the match, or if
+    /// the result would traverse above the root.
+    ///
+    /// ```rust
+    /// use routefinder::{RouteSpec, Router};
+    /// let mut router = Router::new();
+    /// router.add("/users/:user/posts/:post", ()).unwrap();
+    /// let base = router.best_match("/users/1/posts/2").unwrap();
+    ///
+    /// assert_eq!(RouteSpec::resolve_relative(&base, "comments").unwrap(), "/users/1/posts/comments");
+    /// assert_eq!(RouteSpec::resolve_relative(&base, "../followers").unwrap(), "/users/1/followers");
+    /// assert_eq!(RouteSpec::resolve_relative(&base, "../../:user").unwrap(), "/users/1");
+    /// assert_eq!(RouteSpec::resolve_relative(&base, "/posts/:post").unwrap(), "/posts/2");
+    /// assert!(RouteSpec::resolve_relative(&base, "./:missing").is_err());
+    /// ```
+    pub fn resolve_relative<Handler>(
+        base: &Match<'_, '_, Handler>,
+        relative: &str,
+    ) -> Result<String, String> {
+        let mut rest = relative;
+        let mut resolved = String::from("/");
+        if !relative.starts_with('/') {
+            // the matched path without its last segment
+            let path = base
+                .canonical_path()
+                .unwrap_or_else(|| base.path().to_string());
+            let directory = path
+                .trim_end_matches('/')
+                .rsplit_once('/')
+                .map_or("", |(directory, _)| directory.trim_matches('/'));
+            if !directory.is_empty() {
+                resolved.push_str(directory);
+                resolved.push('/');
+            }
+
+            loop {
+                let dots = rest.split('/').next().unwrap_or_default();
+                if dots != "." && dots != ".." {
+                    break;
+                }
+                resolved.push_str(dots);
+                resolved.push('/');
+                rest = rest.get(dots.len() + 1..).unwrap_or_default();
+            }
+        }
+
+        if !rest.is_empty() {
+            let spec: RouteSpec = rest.parse()?;
+            let captures = base.captures();
+            let mut values: Captures = spec
+                .segments
+                .iter()
+                .filter_map(Segment::name)
+                .map(|name| match captures.get(name) {
+                    Some(value) => Ok(Capture::new(name, value)),
+                    None => Err(TemplateError::Missing(name.to_string()).to_string()),
+                })
+                .collect::<Result<_, _>>()?;
+            if spec.segments.contains(&Segment::Wildcard) {
+                if let Some(wildcard) = captures.wildcard() {
+                    values.set_wildcard(wildcard);
+                }
+            }
+
+            spec.validate(&values).map_err(|error| error.to_string())?;
+            if let Some(reverse_match) = spec.template(&values) {
+                resolved.push_str(reverse_match.to_string().trim_start_matches('/'));
+            }
+        }
+
+        let resolved = normalize::resolve_dot_segments(&resolved)
+            .ok_or_else(|| format!("`{}` traverses above the root", relative))?
+            .into_owned();
+        match resolved.trim_end_matches('/') {
+            "" => Ok(String::from("/")),
+            trimmed => Ok(trimmed.to_string()),
+        }
+    }
+
+    /// checks that these captures name the params of this route in
+    /// order, with a value for each required param
+    pub(crate) fn check_captures(&self, captures: &Captures) -> Result<(), TemplateError> {
+        let mut params = captures.params().iter().peekable();
+        for segment in &self.segments {
+            match (segment, segment.name()) {
+                (_, Some(name)) if params.peek().map(|c| c.name()) == Some(name) => {
+                    params.next();
+                }
+                (Segment::OptionalParam(_), _) | (Segment::ZeroOrMore(_), _) => {}
+                (_, Some(name)) if captures.get(name).is_some() => {
+                    return Err(TemplateError::OutOfOrder(name.to_string()))
+                }
+                (_, Some(name)) => return Err(TemplateError::Missing(name.to_string())),
+                _ => {}
+            }
+        }
+
+        if let Some(capture) = params.next() {
+            let name = capture.name();
+            return Err(if self.segments.iter().any(|s| s.name() == Some(name)) {
+                TemplateError::OutOfOrder(name.to_string())
+            } else {
+                TemplateError::Unexpected(name.to_string())
+            });
+        }
+
+        if captures.wildcard().is_some()
+            && !self.segments.iter().any(|s| matches!(s, Segment::Wildcard))
+        {
+            return Err(TemplateError::UnexpectedWildcard);
+        }
+
+        if self.segments.iter().any(|s| matches!(s, Segment::Glob(_))) {
+            return Err(TemplateError::Glob);
+        }
+
+        Ok(())
+    }
+
+    /// every path this spec can produce from the provided values for
+    /// each param, with `"*"` naming the wildcard. Optional params
+    /// and wildcards are also expanded without a value. Paths that
+    /// this spec would not match, such as values that fail a
+    /// constraint, are skipped.
+    pub(crate) fn expand(&self, values: &BTreeMap<String, Vec<String>>) -> Vec<String> {
+        let mut slots: Vec<(&str, Vec<Option<&str>>)> = vec![];
+        for segment in &self.segments {
+            let (name, optional) = match segment {
+                Segment::Param(name) | Segment::OneOrMore(name) => (&**name, false),
+                Segment::OptionalParam(name) | Segment::ZeroOrMore(name) => (&**name, true),
+                Segment::Wildcard => ("*", true),
+                Segment::Glob(_) => return vec![],
+                _ => continue,
+            };
+
+            if slots.iter().any(|(existing, _)| *existing == name) {
+                continue;
+            }
+
+            let mut options: Vec<Option<&str>> = values
+                .get(name)
+                .into_iter()
+                .flatten()
+                .map(|value| Some(&**value))
+                .collect();
+            if optional {
+                options.push(None);
+            }
+            if options.is_empty() {
+                return vec![];
+            }
+            slots.push((name, options));
+        }
+
+        let mut paths = vec![];
+        let mut choices = vec![0; slots.len()];
+        loop {
+            let mut captures = Captures::new();
+            for ((name, options), choice) in slots.iter().zip(&choices) {
+                match (*name, options[*choice]) {
+                    (_, None) => {}
+                    ("*", Some(value)) => captures.set_wildcard(value),
+                    (name, Some(value)) => captures.push(Capture::from((name, value))),
+                }
+            }
+
+            if let Some(reverse_match) = self.template(&captures) {
+                let mut path = reverse_match.to_string();
+                // an absent wildcard leaves a trailing slash behind
+                if path.len() > 1 && path.ends_with('/') {
+                    path.pop();
+                }
+                if self.matches(&path).is_some() {
+                    paths.push(path);
+                }
+            }
+
+            // advance to the next combination, like an odometer
+            let mut slot = 0;
+            loop {
+                match slots.get(slot) {
+                    None => return paths,
+                    Some((_, options)) if choices[slot] + 1 < options.len() => {
+                        choices[slot] += 1;
+                        break;
+                    }
+                    Some(_) => {
+                        choices[slot] = 0;
+                        slot += 1;
+                    }
+                }
+            }
+        }
+    }
 }
 
 impl FromStr for RouteSpec {
     type Err = String;
 
     fn from_str(source: &str) -> Result<Self, Self::Err> {
-        let mut last_index = 0;
+        let mut pending_slash = false;
         let source_trimmed = source.trim_start_matches('/').trim_end_matches('/');
-        #[cfg(feature = "memchr")]
-        let index_iter = memchr::memchr2_iter(b'.', b'/', source_trimmed.as_bytes());
-
-        #[cfg(not(feature = "memchr"))]
-        let index_iter = source_trimmed
-            .match_indices(|c| c == '.' || c == '/')
-            .map(|(i, _)| i);
-
-        let segments = index_iter
-            .chain(iter::once_with(|| source_trimmed.len()))
-            .try_fold(vec![], |mut acc, index| {
-                let first_char = if last_index == 0 {
-                    None
-                } else {
-                    source_trimmed.chars().nth(last_index - 1)
-                };
-
-                let section = &source_trimmed[last_index..index];
-                last_index = index + 1;
-
+        let segments = split::sections(source_trimmed).try_fold(
+            vec![],
+            |mut acc, (first_char, section)| {
                 let segment = match (section.chars().next(), section.len()) {
                     (Some('*'), 1) => Some(Segment::Wildcard),
                     (Some('*'), _) => {
@@ -195,39 +1344,109 @@ impl FromStr for RouteSpec {
                             section
                         ));
                     }
-                    (Some(':'), 1) => {
-                        return Err(String::from("params must be named"));
+                    (Some(':'), _) => {
+                        let name = &section[1..];
+                        let (name, segment): (_, fn(SmartString) -> Segment) =
+                            match name.as_bytes().last() {
+                                Some(b'?') => (&name[..name.len() - 1], Segment::OptionalParam),
+                                Some(b'+') => (&name[..name.len() - 1], Segment::OneOrMore),
+                                Some(b'*') => (&name[..name.len() - 1], Segment::ZeroOrMore),
+                                _ => (name, Segment::Param),
+                            };
+
+                        if name.is_empty() {
+                            return Err(String::from("params must be named"));
+                        }
+                        Some(segment(SmartString::from(name)))
                     }
-                    (Some(':'), _) => Some(Segment::Param(SmartString::from(&section[1..]))),
                     (None, 0) => None,
                     (_, _) => Some(Segment::Exact(SmartString::from(section))),
                 };
 
+                // a slash before an empty section still separates the
+                // sections on either side of it
+                pending_slash |= first_char == Some('/');
                 if first_char == Some('.') {
-                    if let Some(Segment::Exact(s)) = acc.last_mut() {
+                    if pending_slash {
+                        acc.push(Segment::Slash);
+                        acc.push(Segment::Dot);
+                        pending_slash = false;
+                    } else if let Some(Segment::Exact(s)) = acc.last_mut() {
                         s.push('.');
+                        // exact text on both sides of a dot is one segment
+                        if let Some(Segment::Exact(next)) = &segment {
+                            s.push_str(next);
+                            return Ok(acc);
+                        }
                     } else {
                         acc.push(Segment::Dot);
                     }
                 }
 
                 if let Some(segment) = segment {
-                    if first_char == Some('/') {
+                    if pending_slash {
                         acc.push(Segment::Slash);
+                        pending_slash = false;
                     }
                     acc.push(segment);
                 }
 
                 Ok(acc)
-            })?;
+            },
+        )?;
 
         Ok(Self {
             source: Some(SmartString::from(source)),
             segments,
+            constraints: vec![],
+            mode: MatchMode::default(),
+            weight: None,
+            condition: None,
+            format: None,
+            formats: vec![],
+            exclusions: vec![],
+            description: None,
+            config: None,
+            name: None,
+            id: None,
         })
     }
 }
 
+/// the positions in a route and a path, counted from the end of the
+/// path, from which the rest of the route is known not to match,
+/// allocated on the first failure
+struct Failures {
+    width: usize,
+    height: usize,
+    bits: Vec<u64>,
+}
+
+impl Failures {
+    fn new(height: usize, width: usize) -> Self {
+        Self {
+            width,
+            height,
+            bits: vec![],
+        }
+    }
+
+    fn contains(&self, index: usize, offset: usize) -> bool {
+        let bit = index * self.width + offset;
+        self.bits
+            .get(bit / 64)
+            .is_some_and(|word| word & (1 << (bit % 64)) != 0)
+    }
+
+    fn insert(&mut self, index: usize, offset: usize) {
+        if self.bits.is_empty() {
+            self.bits = vec![0; (self.width * self.height).div_ceil(64)];
+        }
+        let bit = index * self.width + offset;
+        self.bits[bit / 64] |= 1 << (bit % 64);
+    }
+}
+
 impl TryFrom<&str> for RouteSpec {
     type Error = String;
 
@@ -243,13 +1462,65 @@ impl TryFrom<String> for RouteSpec {
     }
 }
 
+/// Builds a spec from segments as they are, without checking them. See
+/// [`RouteSpec::from_segments`].
 impl From<Vec<Segment>> for RouteSpec {
     fn from(segments: Vec<Segment>) -> Self {
         Self {
             segments,
             source: None,
+            constraints: vec![],
+            mode: MatchMode::default(),
+            weight: None,
+            condition: None,
+            format: None,
+            formats: vec![],
+            exclusions: vec![],
+            description: None,
+            config: None,
+            name: None,
+            id: None,
+        }
+    }
+}
+
+/// arranges segments as they would be parsed, dropping slashes at
+/// either end, repeated slashes, and empty text, and joining exact text with any dot or
+/// exact text that follows it
+fn join_segments(segments: Vec<Segment>) -> Vec<Segment> {
+    let mut joined: Vec<Segment> = vec![];
+    for segment in segments {
+        match (joined.last_mut(), segment) {
+            (None, Segment::Slash) | (Some(Segment::Slash), Segment::Slash) => {}
+            (_, Segment::Exact(e)) if e.is_empty() => {}
+            (Some(Segment::Exact(previous)), Segment::Exact(e)) => previous.push_str(&e),
+            (Some(Segment::Exact(previous)), Segment::Dot) => previous.push('.'),
+            (_, segment) => joined.push(segment),
         }
     }
+
+    if joined.last() == Some(&Segment::Slash) {
+        joined.pop();
+    }
+
+    joined
+}
+
+/// finds exact text within a path segment, ignoring ascii case if
+/// the options are case insensitive
+fn find(haystack: &str, needle: &str, options: &MatchOptions) -> Option<usize> {
+    if !options.case_insensitive {
+        return haystack.find(needle);
+    }
+
+    haystack
+        .char_indices()
+        .map(|(index, _)| index)
+        .find(|index| {
+            haystack
+                .get(*index..index + needle.len())
+                .is_some_and(|candidate| candidate.eq_ignore_ascii_case(needle))
+        })
 }
 
 impl PartialOrd for RouteSpec {
@@ -258,18 +1529,96 @@ impl PartialOrd for RouteSpec {
     }
 }
 
-impl Ord for RouteSpec {
-    fn cmp(&self, other: &Self) -> Ordering {
+/// Routes are ordered by precedence, where the route that is tried
+/// first is the least. Routes are compared by their mode, then segment
+/// by segment with [`Segment::precedence`], then by whether they have
+/// a condition, and finally by the order in which they were added to a
+/// router.
+///
+/// Where one route's segments begin with all of another's, the end of
+/// the shorter route is compared with the next segment of the longer
+/// one: a route ends before a slash, so `/users` is tried before
+/// `/users/*`, but after any other segment, so `/:name.:ext` is tried
+/// before `/:name`. Prefix routes are the opposite, and the longest
+/// prefix is tried first. A dot also comes before a slash in the same
+/// position. Routes of equal precedence are ordered by id, and then
+/// arbitrarily, so that routes only compare equal when they are equal.
+/// This is a total order, as [`BTreeSet`][std::collections::BTreeSet]
+/// and sorting require.
+impl RouteSpec {
+    /// compares the precedence of two routes, regardless of the order
+    /// in which they were added. the greater route is tried first
+    pub(crate) fn cmp_precedence(&self, other: &Self) -> Ordering {
+        other
+            .mode
+            .rank()
+            .cmp(&self.mode.rank())
+            .then_with(|| self.positions().cmp(other.positions()))
+            .then_with(|| self.condition.is_some().cmp(&other.condition.is_some()))
+    }
+
+    /// whether this route is tried before the other, regardless of
+    /// the order in which they were added
+    pub(crate) fn outranks(&self, other: &Self) -> bool {
+        self.cmp_precedence(other) == Ordering::Greater
+    }
+
+    /// an arbitrary order of routes of equal precedence and id, which
+    /// is only equal when the routes are, so that distinct routes are
+    /// kept apart in a [`BTreeSet`][std::collections::BTreeSet]
+    fn cmp_identity(&self, other: &Self) -> Ordering {
+        fn segment_key(segment: &Segment) -> (u8, &str) {
+            let text = match segment {
+                Segment::Slash => "/",
+                Segment::Dot => ".",
+                Segment::Wildcard => "*",
+                Segment::Exact(text) | Segment::Glob(text) => text,
+                segment => segment.name().unwrap_or_default(),
+            };
+            (segment.precedence(), text)
+        }
+
+        fn constraint_key(constraint: &Constraint) -> (&str, &str) {
+            (constraint.name(), constraint.source())
+        }
+
         self.segments
             .iter()
-            .zip(&other.segments)
-            .map(|(mine, theirs)| mine.cmp(theirs))
-            .chain(iter::once_with(|| self.dots().cmp(&other.dots())))
-            .chain(iter::once_with(|| {
-                other.segments.len().cmp(&self.segments.len())
-            }))
-            .find(|c| *c != Ordering::Equal)
-            .unwrap_or(Ordering::Less)
+            .map(segment_key)
+            .cmp(other.segments.iter().map(segment_key))
+            .then_with(|| {
+                self.constraints
+                    .iter()
+                    .map(constraint_key)
+                    .cmp(other.constraints.iter().map(constraint_key))
+            })
+            .then_with(|| self.weight.cmp(&other.weight))
+            .then_with(|| match (&self.condition, &other.condition) {
+                (Some(mine), Some(theirs)) => mine.cmp_identity(theirs),
+                (mine, theirs) => mine.is_some().cmp(&theirs.is_some()),
+            })
+            .then_with(|| self.format.cmp(&other.format))
+            .then_with(|| self.formats.cmp(&other.formats))
+            .then_with(|| self.exclusions.cmp(&other.exclusions))
+            .then_with(|| self.source.cmp(&other.source))
+            .then_with(|| self.description.cmp(&other.description))
+            .then_with(|| self.config.cmp(&other.config))
+            .then_with(|| self.name.cmp(&other.name))
+    }
+}
+
+impl Ord for RouteSpec {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cmp_precedence(other)
+            .then_with(|| match (self.id, other.id) {
+                // routes of equal precedence are tried in the order
+                // they were added
+                (Some(mine), Some(theirs)) => theirs.cmp(&mine),
+                (Some(_), None) => Ordering::Greater,
+                (None, Some(_)) => Ordering::Less,
+                (None, None) => Ordering::Equal,
+            })
             .reverse()
+            .then_with(|| self.cmp_identity(other))
     }
 }
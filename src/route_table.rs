@@ -0,0 +1,565 @@
+use crate::{Engine, MatchOptions, RouteId, RouteSpec, Segment, Storage};
+use smartstring::alias::String as SmartString;
+use std::{
+    collections::{btree_set, BTreeSet, HashMap},
+    slice,
+    sync::{Arc, OnceLock},
+    vec,
+};
+
+/// The routes of a [`Router`][crate::Router] in order of precedence,
+/// along with an index of them for the [`Engine`] in use
+///
+/// A route whose leading segments are entirely exact text, such as
+/// `/api/users` in `/api/users/:id` or `/feed.xml`, can only match a
+/// path that starts with those same segments, and a route that is
+/// entirely exact text can only match that path. Matching a path only visits the
+/// routes indexed under it and the routes that are not indexed, still
+/// in order of precedence. The index is built the first time it is
+/// needed, and patched as routes are added and removed unless the
+/// change calls for a different engine.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct RouteTable {
+    routes: Routes,
+    pinned: Option<Engine>,
+    index: OnceLock<Index>,
+    lookup: Lookup,
+}
+
+/// tables of up to this many routes are matched linearly
+const LINEAR_LIMIT: usize = 16;
+
+/// tables of more than this many routes are indexed as a trie
+const TRIE_LIMIT: usize = 64;
+
+/// the most leading segments of a route that the trie is keyed by
+const TRIE_DEPTH: usize = 4;
+
+#[derive(Debug, Clone)]
+enum Routes {
+    Tree(BTreeSet<Arc<RouteSpec>>),
+    Sorted(Vec<Arc<RouteSpec>>),
+}
+
+impl Default for Routes {
+    fn default() -> Self {
+        Routes::Tree(BTreeSet::new())
+    }
+}
+
+/// positions in the order of precedence, indexed by part of the path
+#[derive(Debug, Clone)]
+struct Index {
+    engine: Engine,
+    // the routes of a tree, in order. sorted routes are indexed in place
+    tree: Vec<Arc<RouteSpec>>,
+    keys: HashMap<SmartString, Vec<usize>>,
+    rest: Vec<usize>,
+    // the number of routes without a literal path
+    dynamic: usize,
+}
+
+impl RouteTable {
+    /// builds a table from routes that are already in order of
+    /// precedence, without inserting them one at a time
+    pub(crate) fn from_sorted(routes: Vec<RouteSpec>) -> Self {
+        debug_assert!(
+            routes.windows(2).all(|pair| pair[0] < pair[1]),
+            "routes are not in order of precedence"
+        );
+
+        let routes: Vec<_> = routes.into_iter().map(Arc::new).collect();
+        let mut lookup = Lookup::default();
+        for route in &routes {
+            lookup.insert(route);
+        }
+
+        Self {
+            // collecting sorted routes builds the tree in bulk
+            routes: Routes::Tree(routes.into_iter().collect()),
+            lookup,
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn insert(&mut self, route: RouteSpec) {
+        let route = Arc::new(route);
+        self.lookup.insert(&route);
+        let position = match &mut self.routes {
+            Routes::Tree(routes) => {
+                routes.insert(Arc::clone(&route));
+                None
+            }
+            Routes::Sorted(routes) => {
+                let position = routes.partition_point(|other| *other < route);
+                routes.insert(position, Arc::clone(&route));
+                Some(position)
+            }
+        };
+
+        let len = self.len();
+        let pinned = self.pinned;
+        if let Some(index) = self.index.get_mut() {
+            if !index.insert(route, position, len, pinned) {
+                self.index = OnceLock::new();
+            }
+        }
+    }
+
+    pub(crate) fn retain(&mut self, mut f: impl FnMut(&RouteSpec) -> bool) {
+        let mut kept = vec![];
+        let mut dynamic = 0;
+        let lookup = &mut self.lookup;
+        let mut f = |route: &Arc<RouteSpec>| {
+            let keep = f(route);
+            if !keep {
+                if route.literal_path().is_none() {
+                    dynamic += 1;
+                }
+                lookup.remove(route);
+            }
+            kept.push(keep);
+            keep
+        };
+
+        match &mut self.routes {
+            Routes::Tree(routes) => routes.retain(&mut f),
+            Routes::Sorted(routes) => routes.retain(&mut f),
+        }
+
+        let len = self.len();
+        let pinned = self.pinned;
+        if let Some(index) = self.index.get_mut() {
+            if !index.retain(&kept, dynamic, len, pinned) {
+                self.index = OnceLock::new();
+            }
+        }
+    }
+
+    pub(crate) fn storage(&self) -> Storage {
+        match self.routes {
+            Routes::Tree(_) => Storage::Tree,
+            Routes::Sorted(_) => Storage::Sorted,
+        }
+    }
+
+    /// moves the routes into this kind of storage, keeping their order
+    pub(crate) fn set_storage(&mut self, storage: Storage) {
+        if storage == self.storage() {
+            return;
+        }
+
+        let pinned = self.pinned;
+        let lookup = std::mem::take(&mut self.lookup);
+        let routes = std::mem::take(self).into_routes();
+        self.pinned = pinned;
+        self.lookup = lookup;
+        self.routes = match storage {
+            Storage::Tree => Routes::Tree(routes.collect()),
+            Storage::Sorted => Routes::Sorted(routes.collect()),
+        };
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        match &self.routes {
+            Routes::Tree(routes) => routes.len(),
+            Routes::Sorted(routes) => routes.len(),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// the route with this id, without searching the routes
+    pub(crate) fn get(&self, id: RouteId) -> Option<&RouteSpec> {
+        self.lookup.ids.get(id.get())?.as_deref()
+    }
+
+    /// the weighted alternatives of this route, including itself, in
+    /// order of precedence, without searching the routes
+    pub(crate) fn alternatives<'a>(
+        &'a self,
+        route: &'a RouteSpec,
+    ) -> impl Iterator<Item = &'a RouteSpec> + 'a {
+        self.lookup
+            .weighted
+            .get(route.segments())
+            .into_iter()
+            .flatten()
+            .map(|alternative| &**alternative)
+            .filter(move |alternative| alternative.is_alternative_of(route))
+    }
+
+    /// every route, in order of precedence
+    pub(crate) fn iter(&self) -> Iter<'_> {
+        match &self.routes {
+            Routes::Tree(routes) => Iter::Tree(routes.iter()),
+            Routes::Sorted(routes) => Iter::Sorted(routes.iter()),
+        }
+    }
+
+    /// the routes that might match this path, in order of precedence
+    pub(crate) fn candidates(&self, path: &str, options: &MatchOptions) -> Iter<'_> {
+        let index = self.index();
+        let path = path.trim_start_matches('/');
+        let heads = match index.engine {
+            _ if options.case_insensitive => return self.iter(),
+            Engine::Linear => return self.iter(),
+            Engine::Partitioned => index.heads(path.split('/').take(1)),
+            Engine::Trie => index.heads(
+                path.match_indices('/')
+                    .map(|(end, _)| &path[..end])
+                    .chain(std::iter::once(path)),
+            ),
+            Engine::Hashed => index.heads(std::iter::once(path.trim_end_matches('/'))),
+        };
+
+        Iter::Partitioned {
+            routes: match &self.routes {
+                Routes::Tree(_) => &index.tree,
+                Routes::Sorted(routes) => routes,
+            },
+            heads,
+            rest: &index.rest,
+        }
+    }
+
+    fn index(&self) -> &Index {
+        self.index
+            .get_or_init(|| Index::new(&self.routes, self.pinned))
+    }
+
+    /// the engine that is pinned, or else chosen for these routes
+    pub(crate) fn engine(&self) -> Engine {
+        self.index().engine
+    }
+
+    pub(crate) fn pin_engine(&mut self, engine: Option<Engine>) {
+        self.index = OnceLock::new();
+        self.pinned = engine;
+    }
+
+    /// every route, in order of precedence
+    pub(crate) fn into_routes(self) -> IntoRoutes {
+        // without the index and the lookup, each route has a single owner
+        match self.routes {
+            Routes::Tree(routes) => routes.into_iter().collect::<Vec<_>>().into_iter(),
+            Routes::Sorted(routes) => routes.into_iter(),
+        }
+    }
+}
+
+pub(crate) type IntoRoutes = vec::IntoIter<Arc<RouteSpec>>;
+
+/// the routes by id, and the weighted routes by their segments, so
+/// that neither has to be found by searching the routes
+#[derive(Debug, Default, Clone)]
+struct Lookup {
+    ids: Vec<Option<Arc<RouteSpec>>>,
+    // each group is in order of precedence
+    weighted: HashMap<Vec<Segment>, Vec<Arc<RouteSpec>>>,
+}
+
+impl Lookup {
+    fn insert(&mut self, route: &Arc<RouteSpec>) {
+        if let Some(id) = route.id() {
+            let id = id.get();
+            if self.ids.len() <= id {
+                self.ids.resize(id + 1, None);
+            }
+            self.ids[id] = Some(Arc::clone(route));
+        }
+
+        if route.weight().is_some() {
+            let group = self.weighted.entry(route.segments().to_vec()).or_default();
+            let position = group.partition_point(|other| other < route);
+            group.insert(position, Arc::clone(route));
+        }
+    }
+
+    fn remove(&mut self, route: &Arc<RouteSpec>) {
+        if let Some(slot) = route.id().and_then(|id| self.ids.get_mut(id.get())) {
+            *slot = None;
+        }
+
+        if let Some(group) = self.weighted.get_mut(route.segments()) {
+            group.retain(|other| !Arc::ptr_eq(other, route));
+            if group.is_empty() {
+                self.weighted.remove(route.segments());
+            }
+        }
+    }
+}
+
+/// takes a route back from the table
+pub(crate) fn unwrap(route: Arc<RouteSpec>) -> RouteSpec {
+    Arc::try_unwrap(route).unwrap_or_else(|route| RouteSpec::clone(&route))
+}
+
+impl Index {
+    fn new(routes: &Routes, pinned: Option<Engine>) -> Self {
+        let (len, dynamic) = match routes {
+            Routes::Tree(routes) => (routes.len(), count_dynamic(routes.iter())),
+            Routes::Sorted(routes) => (routes.len(), count_dynamic(routes.iter())),
+        };
+        let engine = pinned.unwrap_or_else(|| choose(len, dynamic));
+
+        let tree: Vec<_> = match routes {
+            Routes::Tree(routes) if engine != Engine::Linear => routes.iter().cloned().collect(),
+            _ => vec![],
+        };
+        let ordered = match routes {
+            Routes::Tree(_) => &tree,
+            Routes::Sorted(routes) => routes,
+        };
+
+        let mut index = Self {
+            engine,
+            tree: vec![],
+            keys: HashMap::new(),
+            rest: vec![],
+            dynamic,
+        };
+        for (position, route) in ordered.iter().enumerate() {
+            index.partition(route).push(position);
+        }
+
+        index.tree = tree;
+        index
+    }
+
+    /// the positions of the partitions for each of these keys, up to
+    /// the depth of the trie
+    fn heads<'a>(&self, keys: impl Iterator<Item = &'a str>) -> [&[usize]; TRIE_DEPTH] {
+        let mut heads = [<&[usize]>::default(); TRIE_DEPTH];
+        for (head, key) in heads.iter_mut().zip(keys) {
+            *head = self.keys.get(key).map(Vec::as_slice).unwrap_or_default();
+        }
+        heads
+    }
+
+    /// the positions of the partition that this route is indexed in
+    fn partition(&mut self, route: &RouteSpec) -> &mut Vec<usize> {
+        let key = match self.engine {
+            Engine::Linear => None,
+            Engine::Partitioned => prefix(route, 1),
+            Engine::Trie => prefix(route, TRIE_DEPTH),
+            Engine::Hashed => route.literal_path(),
+        };
+
+        match key {
+            Some(key) => self.keys.entry(key).or_default(),
+            None => &mut self.rest,
+        }
+    }
+
+    /// whether a table of this many routes can keep this index,
+    /// rather than choosing a different engine
+    fn is_current(&self, len: usize, pinned: Option<Engine>) -> bool {
+        self.engine != Engine::Linear
+            && pinned.unwrap_or_else(|| choose(len, self.dynamic)) == self.engine
+    }
+
+    /// patches the index for a route that was inserted at this
+    /// position, or into the tree. Returns false if the index must be
+    /// rebuilt instead.
+    fn insert(
+        &mut self,
+        route: Arc<RouteSpec>,
+        position: Option<usize>,
+        len: usize,
+        pinned: Option<Engine>,
+    ) -> bool {
+        if route.literal_path().is_none() {
+            self.dynamic += 1;
+        }
+
+        if !self.is_current(len, pinned) {
+            return false;
+        }
+
+        let position = position.unwrap_or_else(|| {
+            let position = self.tree.partition_point(|other| *other < route);
+            self.tree.insert(position, Arc::clone(&route));
+            position
+        });
+
+        for positions in self.keys.values_mut().chain([&mut self.rest]) {
+            let start = positions.partition_point(|other| *other < position);
+            for other in &mut positions[start..] {
+                *other += 1;
+            }
+        }
+
+        let positions = self.partition(&route);
+        let at = positions.partition_point(|other| *other < position);
+        positions.insert(at, position);
+        true
+    }
+
+    /// patches the index for the removal of the routes that were not
+    /// kept, of which `dynamic` had no literal path. Returns false if
+    /// the index must be rebuilt instead.
+    fn retain(
+        &mut self,
+        kept: &[bool],
+        dynamic: usize,
+        len: usize,
+        pinned: Option<Engine>,
+    ) -> bool {
+        if kept.iter().all(|keep| *keep) {
+            return true;
+        }
+
+        self.dynamic -= dynamic;
+        if !self.is_current(len, pinned) {
+            return false;
+        }
+
+        let mut next = 0;
+        let moved: Vec<Option<usize>> = kept
+            .iter()
+            .map(|keep| {
+                next += usize::from(*keep);
+                keep.then(|| next - 1)
+            })
+            .collect();
+
+        for positions in self.keys.values_mut().chain([&mut self.rest]) {
+            *positions = positions
+                .iter()
+                .filter_map(|position| moved[*position])
+                .collect();
+        }
+        self.keys.retain(|_, positions| !positions.is_empty());
+
+        if !self.tree.is_empty() {
+            let mut kept = kept.iter();
+            self.tree.retain(|_| *kept.next().unwrap_or(&true));
+        }
+        true
+    }
+}
+
+/// the number of these routes without a literal path
+fn count_dynamic<'a>(routes: impl Iterator<Item = &'a Arc<RouteSpec>>) -> usize {
+    routes
+        .filter(|route| route.literal_path().is_none())
+        .count()
+}
+
+/// the engine for a table of this many routes, of which `dynamic` have
+/// no literal path
+fn choose(len: usize, dynamic: usize) -> Engine {
+    if dynamic == 0 {
+        Engine::Hashed
+    } else if len <= LINEAR_LIMIT {
+        Engine::Linear
+    } else if len > TRIE_LIMIT {
+        Engine::Trie
+    } else {
+        Engine::Partitioned
+    }
+}
+
+/// the literal text of up to `depth` leading path segments of this
+/// route, such as `api/users` in `/api/users/:id`, up to the first
+/// segment with params, globs, or wildcards
+fn prefix(route: &RouteSpec, mut depth: usize) -> Option<SmartString> {
+    let mut prefix = SmartString::new();
+    // where the current segment starts, and where the last complete
+    // segment ends
+    let (mut start, mut end) = (0, 0);
+    for segment in route.segments().iter().map(Some).chain([None]) {
+        match segment {
+            Some(Segment::Slash) | None => {
+                if prefix.len() == start {
+                    break;
+                }
+                end = prefix.len();
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+                prefix.push('/');
+                start = prefix.len();
+            }
+            Some(Segment::Dot) => prefix.push('.'),
+            Some(Segment::Exact(exact)) if !exact.contains('/') => prefix.push_str(exact),
+            Some(_) => break,
+        }
+    }
+
+    prefix.truncate(end);
+    if prefix.is_empty() {
+        None
+    } else {
+        Some(prefix)
+    }
+}
+
+/// an iterator over some or all of the routes of a table, in order of
+/// precedence
+#[derive(Debug)]
+pub(crate) enum Iter<'a> {
+    Tree(btree_set::Iter<'a, Arc<RouteSpec>>),
+    Sorted(slice::Iter<'a, Arc<RouteSpec>>),
+    Partitioned {
+        routes: &'a [Arc<RouteSpec>],
+        heads: [&'a [usize]; TRIE_DEPTH],
+        rest: &'a [usize],
+    },
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a RouteSpec;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Iter::Tree(routes) => routes.next().map(|route| &**route),
+            Iter::Sorted(routes) => routes.next().map(|route| &**route),
+            Iter::Partitioned {
+                routes,
+                heads,
+                rest,
+            } => {
+                let partition = heads
+                    .iter_mut()
+                    .chain([rest])
+                    .filter(|partition| !partition.is_empty())
+                    .min_by_key(|partition| partition[0])?;
+                let (position, remaining) = partition.split_first()?;
+                *partition = remaining;
+                routes.get(*position).map(|route| &**route)
+            }
+        }
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        match self {
+            Iter::Sorted(routes) => routes.nth(n).map(|route| &**route),
+            _ => {
+                for _ in 0..n {
+                    self.next()?;
+                }
+                self.next()
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            Iter::Tree(routes) => routes.size_hint(),
+            Iter::Sorted(routes) => routes.size_hint(),
+            Iter::Partitioned { heads, rest, .. } => {
+                let len = heads
+                    .iter()
+                    .chain([rest])
+                    .map(|partition| partition.len())
+                    .sum();
+                (len, Some(len))
+            }
+        }
+    }
+}
@@ -1,12 +1,24 @@
-use crate::{Match, RouteSpec};
+use crate::{
+    change_event::Observer,
+    condition::RouteCondition,
+    generation::Generation,
+    normalize::NormalizedPath,
+    route_table::{self, IntoRoutes, RouteTable},
+    transaction::Change,
+    AsPath, ChangeEvent, Completion, Condition, DenyMode, Engine, Explanation, Limits, Match,
+    MatchIndex, MatchMode, MatchOptions, Matcher, Negotiator, RandomWeights, RouteConfig, RouteId,
+    RouteScope, RouteSpec, Scope, ScopeParams, Segment, ShadowComparison, Storage, Transaction,
+    TypedScope, Unrouted, WeightStrategy,
+};
+use smartstring::alias::String as SmartString;
 use std::{
-    collections::{
-        btree_map::{IntoIter, Iter, IterMut},
-        BTreeMap,
-    },
-    convert::TryInto,
+    any::Any,
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+    convert::{Infallible, TryInto},
     fmt::{self, Debug, Formatter},
     iter::FromIterator,
+    sync::Arc,
 };
 
 /// The top level struct for routefinder
@@ -14,15 +26,34 @@ use std::{
 /// A router represents an ordered set of routes which can be applied
 /// to a given request path, and any handler T that is associated with
 /// each route
-
+///
+/// Routes are tried and iterated in order of precedence, and routes of
+/// equal precedence in the order that they were added, so the order is
+/// the same for the same sequence of additions. Each route is assigned
+/// a [`RouteId`] when it is added.
+#[derive(Clone)]
 pub struct Router<Handler> {
-    routes: BTreeMap<RouteSpec, Handler>,
+    routes: RouteTable,
+    handlers: Vec<Option<Handler>>,
+    denies: RouteTable,
+    deny_mode: DenyMode,
+    reserved: Vec<RouteSpec>,
+    weight_strategy: Arc<dyn WeightStrategy>,
+    limits: Limits,
+    options: MatchOptions,
+    negotiator: Negotiator,
+    observers: Vec<Arc<Observer>>,
+    #[cfg(feature = "metrics")]
+    pub(crate) recorder: Option<Arc<dyn crate::metrics::MatchRecorder>>,
+    #[cfg(feature = "metrics")]
+    pub(crate) hits: crate::metrics::HitCounts,
+    generation: Generation,
 }
 
 impl<Handler> Debug for Router<Handler> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         let mut debug_set = f.debug_set();
-        for route in self.routes.keys() {
+        for route in self.routes.iter() {
             debug_set.entry(&format_args!("{}", route));
         }
         debug_set.finish()
@@ -33,44 +64,69 @@ impl<Handler> Default for Router<Handler> {
     fn default() -> Self {
         Self {
             routes: Default::default(),
+            handlers: vec![],
+            denies: Default::default(),
+            deny_mode: DenyMode::default(),
+            reserved: vec![],
+            weight_strategy: Arc::new(RandomWeights::default()),
+            limits: Limits::default(),
+            options: MatchOptions::default(),
+            negotiator: Negotiator::default(),
+            observers: vec![],
+            #[cfg(feature = "metrics")]
+            recorder: None,
+            #[cfg(feature = "metrics")]
+            hits: Default::default(),
+            generation: Generation::new(),
         }
     }
 }
 
 impl<Handler> IntoIterator for Router<Handler> {
     type Item = (RouteSpec, Handler);
-    type IntoIter = IntoIter<RouteSpec, Handler>;
+    type IntoIter = IntoIter<Handler>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.routes.into_iter()
+        IntoIter {
+            routes: self.routes.into_routes(),
+            handlers: self.handlers,
+        }
     }
 }
 
 impl<'a, Handler: 'a> IntoIterator for &'a Router<Handler> {
     type Item = (&'a RouteSpec, &'a Handler);
 
-    type IntoIter = Iter<'a, RouteSpec, Handler>;
+    type IntoIter = Iter<'a, Handler>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.routes.iter()
+        Iter {
+            routes: self.routes.iter(),
+            handlers: &self.handlers,
+        }
     }
 }
 
 impl<'a, Handler: 'a> IntoIterator for &'a mut Router<Handler> {
     type Item = (&'a RouteSpec, &'a mut Handler);
 
-    type IntoIter = IterMut<'a, RouteSpec, Handler>;
+    type IntoIter = IterMut<'a, Handler>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.routes.iter_mut()
+        IterMut {
+            routes: self.routes.iter(),
+            handlers: self.handlers.iter_mut().map(Option::as_mut).collect(),
+        }
     }
 }
 
 impl<Handler> FromIterator<(RouteSpec, Handler)> for Router<Handler> {
     fn from_iter<T: IntoIterator<Item = (RouteSpec, Handler)>>(iter: T) -> Self {
-        Self {
-            routes: iter.into_iter().collect(),
+        let mut router = Self::default();
+        for (route, handler) in iter {
+            router.insert(route, handler);
         }
+        router
     }
 }
 
@@ -86,7 +142,46 @@ impl<Handler> Router<Handler> {
         Self::default()
     }
 
+    /// Builds a router from routes that are already in order of
+    /// precedence, such as routes stored by their
+    /// [`RouteSpec::sort_key`], without inserting them one at a time.
+    /// Routes are assigned ids in the order of the iterator, so routes
+    /// of equal precedence keep their order. Routes that are out of
+    /// order are a panic in debug builds, and are sorted in release
+    /// builds.
+    ///
+    /// ```rust
+    /// use routefinder::{RouteSpec, Router};
+    /// let mut router = Router::new();
+    /// router.add("/*", "fallback").unwrap();
+    /// router.add("/users/:id", "user").unwrap();
+    /// router.add("/users/new", "new user").unwrap();
+    ///
+    /// let rows: Vec<(RouteSpec, &str)> = router
+    ///     .iter()
+    ///     .map(|(route, handler)| (route.clone(), *handler))
+    ///     .collect();
+    /// let loaded = Router::from_sorted_iter(rows);
+    /// assert_eq!(loaded.fingerprint(), router.fingerprint());
+    /// assert_eq!(*loaded.best_match("/users/new").unwrap(), "new user");
+    /// ```
+    pub fn from_sorted_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (RouteSpec, Handler)>,
+    {
+        let mut router = Self::default();
+        let mut routes = vec![];
+        for (route, handler) in iter {
+            routes.push(route.with_id(router.next_id()));
+            router.push_handler(Some(handler));
+        }
+
+        router.routes = RouteTable::from_sorted(routes);
+        router
+    }
+
     /// Adds a route to the router, accepting any type that implements TryInto<[`RouteSpec`]>. In most circumstances, this will be a &str or a String.
+    /// Returns the [`RouteId`] assigned to the route.
     ///
     /// ```rust
     /// let mut router = routefinder::Router::new();
@@ -98,148 +193,2030 @@ impl<Handler> Router<Handler> {
         &mut self,
         route: R,
         handler: Handler,
-    ) -> Result<(), <R as TryInto<RouteSpec>>::Error>
+    ) -> Result<RouteId, <R as TryInto<RouteSpec>>::Error>
     where
         R: TryInto<RouteSpec>,
     {
-        self.routes.insert(route.try_into()?, handler);
-        Ok(())
+        Ok(self.insert(route.try_into()?, handler))
     }
 
-    /// Returns the single best route match as defined by the sorting
-    /// rules. To compare any two routes, step through each
-    /// [`Segment`][crate::Segment] and find the first pair that are not equal,
-    /// according to: `Exact > Param > Wildcard > (dots and slashes)`
-    /// As a result, `/hello` > `/:param` > `/*`.  Because we can sort
-    /// the routes before encountering a path, we evaluate them from
-    /// highest to lowest weight and an early return as soon as we
-    /// find a match.
+    /// the id that the next route added will be assigned
+    pub(crate) fn next_id(&self) -> RouteId {
+        RouteId::new(self.handlers.len())
+    }
+
+    fn insert(&mut self, route: RouteSpec, handler: Handler) -> RouteId {
+        let id = self.next_id();
+        let route = route.with_id(id);
+        self.notify(ChangeEvent::Added { id, route: &route });
+        self.generation.bump();
+        self.routes.insert(route);
+        self.push_handler(Some(handler));
+        id
+    }
+
+    /// stores the handler for the next id
+    fn push_handler(&mut self, handler: Option<Handler>) {
+        self.handlers.push(handler);
+        #[cfg(feature = "metrics")]
+        self.hits.resize(self.handlers.len());
+    }
+
+    /// Adds an observer that is called after each route is added,
+    /// replaced, or removed, with a [`ChangeEvent`] describing the
+    /// change, so that caches and indexes built from the routes can be
+    /// updated incrementally. Observers are called in the order they
+    /// were added, and are kept when the router is cloned or its
+    /// handlers are mapped.
+    pub fn on_change(&mut self, observer: impl Fn(&ChangeEvent<'_>) + Send + Sync + 'static) {
+        self.observers.push(Arc::new(observer));
+    }
+
+    fn notify(&self, event: ChangeEvent<'_>) {
+        for observer in &self.observers {
+            observer(&event);
+        }
+    }
+
+    /// Replaces the handler of the route with this [`RouteId`],
+    /// returning the previous handler, or None if there is no such
+    /// route, in which case the handler is dropped
     ///
     /// ```rust
     /// let mut router = routefinder::Router::new();
-    /// router.add("*", 0).unwrap();
-    /// router.add("/:param", 1).unwrap();
-    /// router.add("/hello", 2).unwrap();
-    /// assert_eq!(*router.best_match("/hello").unwrap(), 2);
-    /// assert_eq!(*router.best_match("/hey").unwrap(), 1);
-    /// assert_eq!(router.best_match("/hey").unwrap().captures().get("param"), Some("hey"));
-    /// assert_eq!(*router.best_match("/hey/there").unwrap(), 0);
-    /// assert_eq!(*router.best_match("/").unwrap(), 0);
+    /// let id = router.add("/users/:id", "v1").unwrap();
+    /// assert_eq!(router.replace(id, "v2"), Some("v1"));
+    /// assert_eq!(*router.best_match("/users/1").unwrap(), "v2");
     /// ```
-    pub fn best_match<'a, 'b>(&'a self, path: &'b str) -> Option<Match<'a, 'b, Handler>> {
-        self.match_iter(path).next()
+    pub fn replace(&mut self, id: RouteId, handler: Handler) -> Option<Handler> {
+        let previous = self.handlers.get_mut(id.get())?.as_mut()?;
+        let previous = std::mem::replace(previous, handler);
+        if let Some(route) = self.spec(id) {
+            self.notify(ChangeEvent::Replaced { id, route });
+        }
+        Some(previous)
     }
 
-    /// Returns _all_ of the matching routes for a given path. This is
-    /// probably not what you want, as [`Router::best_match`] is more
-    /// efficient. The primary reason you'd want to use `matches` is
-    /// to implement different route precedence rules or for
-    /// testing.
+    /// Removes the route with this [`RouteId`], returning its handler,
+    /// or None if there is no such route. Routes added with
+    /// [`Router::deny`] can also be removed, and have no handler to
+    /// return.
     ///
     /// ```rust
     /// let mut router = routefinder::Router::new();
-    /// router.add("*", ()).unwrap();
-    /// router.add("/:param", ()).unwrap();
-    /// router.add("/hello", ()).unwrap();
-    /// assert_eq!(router.matches("/").len(), 1);
-    /// assert_eq!(router.matches("/hello").len(), 3);
-    /// assert_eq!(router.matches("/hey").len(), 2);
-    /// assert_eq!(router.matches("/hey/there").len(), 1);
+    /// let id = router.add("/users/:id", "users").unwrap();
+    /// router.add("/*", "fallback").unwrap();
+    /// assert_eq!(router.remove(id), Some("users"));
+    /// assert_eq!(router.remove(id), None);
+    /// assert_eq!(*router.best_match("/users/1").unwrap(), "fallback");
     /// ```
-    pub fn matches<'a, 'b>(&'a self, path: &'b str) -> Vec<Match<'a, 'b, Handler>> {
-        self.match_iter(path).collect()
+    pub fn remove(&mut self, id: RouteId) -> Option<Handler> {
+        let handler = match self.handlers.get_mut(id.get())?.take() {
+            Some(handler) => handler,
+            None => {
+                self.remove_deny(id);
+                return None;
+            }
+        };
+        self.generation.bump();
+        if let Some(route) = self.routes.get(id) {
+            self.notify(ChangeEvent::Removed { id, route });
+        }
+        self.routes.retain(|route| route.id() != Some(id));
+        Some(handler)
     }
 
-    /// Returns an iterator over the possible matches for this
-    /// particular path. Because rust iterators are lazy, this is
-    /// useful for some filtering operations that might otherwise use
-    /// [`Router::matches`], which is this iterator collected into a
-    /// vec.
-    pub fn match_iter<'a, 'b>(&'a self, path: &'b str) -> MatchIter<'a, 'b, Handler> {
-        MatchIter {
-            iter: self.routes.iter(),
-            path,
+    /// removes the deny route with this id, if there is one
+    fn remove_deny(&mut self, id: RouteId) {
+        if let Some(route) = self.denies.get(id) {
+            self.generation.bump();
+            self.notify(ChangeEvent::Removed { id, route });
+            self.denies.retain(|route| route.id() != Some(id));
         }
     }
 
-    /// Returns an iterator of references to `(&RouteSpec, &Handler)`
+    /// Applies the changes staged by `f` on a [`Transaction`] if it
+    /// returns Ok, and none of them if it returns an error, which is
+    /// returned. Staging a route that does not parse, or removing a
+    /// route that does not exist, is an error that can be propagated
+    /// with `?`, so a reload of many routes cannot leave the router
+    /// with only some of them. Changes are applied in the order they
+    /// were staged.
+    ///
+    /// ```rust
+    /// let mut router = routefinder::Router::new();
+    /// let old = router.add("/users/:id", "old users").unwrap();
+    ///
+    /// let result = router.transaction(|txn| {
+    ///     txn.remove(old)?;
+    ///     txn.add("/users/:id", "new users")?;
+    ///     txn.add("/posts/*named_wildcard", "posts")?;
+    ///     Ok::<_, String>(())
+    /// });
+    /// assert!(result.is_err());
+    /// assert_eq!(*router.best_match("/users/1").unwrap(), "old users");
+    ///
+    /// let posts = router
+    ///     .transaction(|txn| {
+    ///         txn.remove(old)?;
+    ///         txn.add("/users/:id", "new users")?;
+    ///         txn.add("/posts/*", "posts")
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(*router.best_match("/users/1").unwrap(), "new users");
+    /// assert_eq!(router.handler(posts), Some(&"posts"));
+    /// ```
+    pub fn transaction<T, E>(
+        &mut self,
+        f: impl FnOnce(&mut Transaction<'_, Handler>) -> Result<T, E>,
+    ) -> Result<T, E> {
+        let mut transaction = Transaction::new(self);
+        let value = f(&mut transaction)?;
+        for change in transaction.into_changes() {
+            match change {
+                Change::Add(route, handler) => {
+                    self.insert(*route, handler);
+                }
+                Change::Remove(id) => {
+                    self.remove(id);
+                }
+            }
+        }
+        Ok(value)
+    }
+
+    /// Adds a group of routes under a prefix with params, such as the
+    /// organization of a multi-tenant api. Every route added to the
+    /// [`Scope`] begins with the prefix, so each captures the params of
+    /// the prefix, which must all be required. The returned
+    /// [`RouteScope`] fills those params once for reverse lookups of
+    /// the routes in the scope. As with [`Router::transaction`], the
+    /// routes are only added if `f` returns Ok, and an error from `f`
+    /// or an invalid prefix is returned.
+    ///
+    /// ```rust
+    /// use routefinder::{Captures, Router};
+    /// let mut router = Router::new();
+    /// let mut repos = None;
+    /// let orgs = router
+    ///     .scope_with_param("/orgs/:org", |scope| {
+    ///         repos = Some(scope.add("/repos/:repo", "repo")?);
+    ///         scope.add("/members", "members")?;
+    ///         Ok::<_, String>(())
+    ///     })
+    ///     .unwrap();
+    ///
+    /// let m = router.best_match("/orgs/acme/members").unwrap();
+    /// assert_eq!(*m, "members");
+    /// assert_eq!(m.captures().get("org"), Some("acme"));
     ///
+    /// let acme = orgs.bind([("org", "acme")].into());
+    /// let url = acme.url_for(&router, repos.unwrap(), &[("repo", "routefinder")].into());
+    /// assert_eq!(url.as_deref(), Some("/orgs/acme/repos/routefinder"));
+    ///
+    /// assert!(router.scope_with_param("/orgs", |_| Ok::<_, String>(())).is_err());
+    /// assert!(router.scope_with_param("/orgs/:org?", |_| Ok::<_, String>(())).is_err());
     /// ```
+    pub fn scope_with_param<E>(
+        &mut self,
+        prefix: &str,
+        f: impl FnOnce(&mut Scope<'_, '_, Handler>) -> Result<(), E>,
+    ) -> Result<RouteScope, E>
+    where
+        E: From<String>,
+    {
+        let route_scope = RouteScope::new(prefix)?;
+        let ids = self.transaction(|transaction| {
+            let mut scope = route_scope.scope(transaction, prefix);
+            f(&mut scope)?;
+            Ok::<_, E>(scope.into_ids())
+        })?;
+        Ok(route_scope.with_ids(ids))
+    }
+
+    /// Adds a group of routes under a prefix, as
+    /// [`Router::scope_with_param`] does, where the params of the
+    /// prefix are the fields of a struct defined with
+    /// [`scope_params!`][crate::scope_params]. The returned
+    /// [`TypedScope`] reads the params of a match of a route in the
+    /// scope as that struct. Returns an error if the params of the
+    /// prefix are not the fields of the struct.
+    ///
+    /// ```rust
+    /// routefinder::scope_params! {
+    ///     pub struct OrgParams { org }
+    /// }
+    ///
     /// let mut router = routefinder::Router::new();
-    /// router.add("*", 1).unwrap();
-    /// router.add("/:param", 2).unwrap();
-    /// router.add("/hello", 3).unwrap();
-    /// let (route, handler) = router.iter().next().unwrap();
-    /// assert_eq!(route.to_string(), "/hello");
-    /// assert_eq!(handler, &3);
+    /// let orgs = router
+    ///     .typed_scope::<OrgParams, String>("/orgs/:org", |scope| {
+    ///         scope.add("/repos", "repos")?;
+    ///         Ok(())
+    ///     })
+    ///     .unwrap();
+    ///
+    /// let m = router.best_match("/orgs/acme/repos").unwrap();
+    /// let captures = m.captures();
+    /// let params = orgs.params(m.route(), &captures).unwrap();
+    /// assert_eq!(params.org, "acme");
+    ///
+    /// let wrong = router.typed_scope::<OrgParams, String>("/users/:user", |_| Ok(()));
+    /// assert!(wrong.is_err());
     /// ```
-    pub fn iter(&self) -> impl Iterator<Item = (&RouteSpec, &Handler)> {
-        self.into_iter()
+    pub fn typed_scope<P, E>(
+        &mut self,
+        prefix: &str,
+        f: impl FnOnce(&mut Scope<'_, '_, Handler>) -> Result<(), E>,
+    ) -> Result<TypedScope<P>, E>
+    where
+        P: ScopeParams,
+        E: From<String>,
+    {
+        // the prefix is checked before any routes are added
+        TypedScope::<P>::new(RouteScope::new(prefix)?)?;
+        let scope = self.scope_with_param(prefix, f)?;
+        Ok(TypedScope::new(scope)?)
     }
 
-    /// returns an iterator of `(&RouteSpec, &mut Handler)`
+    /// removes every route, keeping this router's settings
+    pub(crate) fn take_routes(&mut self) -> IntoIter<Handler> {
+        self.generation.bump();
+        IntoIter {
+            routes: std::mem::take(&mut self.routes).into_routes(),
+            handlers: std::mem::take(&mut self.handlers),
+        }
+    }
+
+    /// the routes that might match this path, in order of precedence,
+    /// regardless of their conditions
+    pub(crate) fn candidates<'a>(&'a self, path: &str) -> impl Iterator<Item = &'a RouteSpec> {
+        self.routes.candidates(path, &self.options)
+    }
+
+    /// adds back a route from [`Router::take_routes`] with its id
+    pub(crate) fn restore(&mut self, route: RouteSpec, handler: Handler) {
+        if let Some(id) = route.id() {
+            if self.handlers.len() <= id.get() {
+                self.handlers.resize_with(id.get() + 1, || None);
+            }
+            self.handlers[id.get()] = Some(handler);
+            #[cfg(feature = "metrics")]
+            self.hits.resize(self.handlers.len());
+            self.generation.bump();
+            self.routes.insert(route);
+        }
+    }
+
+    /// Adds a route with the provided [`MatchMode`]. See [`MatchMode`]
+    /// for how modes affect precedence.
+    ///
+    /// ```rust
+    /// use routefinder::{MatchMode, Router};
+    /// let mut router = Router::new();
+    /// router.add_with_mode("/", MatchMode::Exact, "home").unwrap();
+    /// router.add_with_mode("/", MatchMode::Prefix, "fallback").unwrap();
+    /// router.add_with_mode("/static", MatchMode::PriorityPrefix, "static").unwrap();
+    /// router.add_with_mode("/api", MatchMode::Prefix, "api").unwrap();
+    /// router.add_with_mode("/api/v2", MatchMode::Prefix, "api v2").unwrap();
+    /// router.add("/:anything/:id.png", "images").unwrap();
+    ///
+    /// assert_eq!(*router.best_match("/").unwrap(), "home");
+    /// assert_eq!(*router.best_match("/about").unwrap(), "fallback");
+    /// assert_eq!(*router.best_match("/static/logo.png").unwrap(), "static");
+    /// assert_eq!(*router.best_match("/api/v2/users").unwrap(), "api v2");
+    /// assert_eq!(*router.best_match("/api/logo.png").unwrap(), "images");
     ///
+    /// let m = router.best_match("/api/v1/users").unwrap();
+    /// assert_eq!(*m, "api");
+    /// assert_eq!(m.captures().wildcard(), Some("v1/users"));
     /// ```
+    pub fn add_with_mode<R>(
+        &mut self,
+        route: R,
+        mode: MatchMode,
+        handler: Handler,
+    ) -> Result<RouteId, <R as TryInto<RouteSpec>>::Error>
+    where
+        R: TryInto<RouteSpec>,
+    {
+        Ok(self.insert(route.try_into()?.with_mode(mode), handler))
+    }
+
+    /// Adds a route with a description of what it does, which is
+    /// available from [`RouteSpec::description`] and is included in
+    /// the route references of the `export` feature, such as
+    /// `Router::export_markdown`. The description does not affect
+    /// matching.
+    ///
+    /// ```rust
     /// let mut router = routefinder::Router::new();
-    /// router.add("*", 1).unwrap();
-    /// router.add("/:param", 2).unwrap();
-    /// router.add("/hello", 3).unwrap();
+    /// router.add_documented("/users/:id", "user", "Returns a user by id").unwrap();
+    /// let m = router.best_match("/users/1").unwrap();
+    /// assert_eq!(m.route().description(), Some("Returns a user by id"));
+    /// ```
+    pub fn add_documented<R>(
+        &mut self,
+        route: R,
+        handler: Handler,
+        description: &str,
+    ) -> Result<RouteId, <R as TryInto<RouteSpec>>::Error>
+    where
+        R: TryInto<RouteSpec>,
+    {
+        Ok(self.insert(route.try_into()?.with_description(description), handler))
+    }
+
+    /// Adds a route with operational settings, such as a timeout, for
+    /// a gateway or server to apply to the requests it matches. The
+    /// config is available from [`Match::config`] and
+    /// [`RouteSpec::config`], and does not affect matching. See
+    /// [`RouteConfig`].
     ///
-    /// assert_eq!(*router.best_match("/hello").unwrap(), 3);
-    /// let (route, handler) = router.iter_mut().next().unwrap();
-    /// assert_eq!(route.to_string(), "/hello");
-    /// *handler = 10;
-    /// assert_eq!(*router.best_match("/hello").unwrap(), 10);
+    /// ```rust
+    /// use routefinder::{RouteConfig, Router};
+    /// let mut router = Router::new();
+    /// let config = RouteConfig::new().with_rate_class("search");
+    /// router.add_with_config("/search", config, "search").unwrap();
+    /// let m = router.best_match("/search").unwrap();
+    /// assert_eq!(m.config().and_then(RouteConfig::rate_class), Some("search"));
     /// ```
-    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&RouteSpec, &mut Handler)> {
-        self.into_iter()
+    pub fn add_with_config<R>(
+        &mut self,
+        route: R,
+        config: RouteConfig,
+        handler: Handler,
+    ) -> Result<RouteId, <R as TryInto<RouteSpec>>::Error>
+    where
+        R: TryInto<RouteSpec>,
+    {
+        Ok(self.insert(route.try_into()?.with_config(config), handler))
     }
 
-    /// returns the number of routes that have been added
-    pub fn len(&self) -> usize {
-        self.routes.len()
+    /// Adds a route that does not match any path matched by one of the
+    /// exclusions, so those paths fall through to lower precedence
+    /// routes. The exclusions are not routes themselves, and do not
+    /// affect any other route.
+    ///
+    /// ```rust
+    /// let mut router = routefinder::Router::new();
+    /// router.add("*", "not found").unwrap();
+    /// router
+    ///     .add_excluding("/assets/*", ["/assets/private/*", "/assets/:name.map"], "assets")
+    ///     .unwrap();
+    ///
+    /// assert_eq!(*router.best_match("/assets/app.js").unwrap(), "assets");
+    /// assert_eq!(*router.best_match("/assets/private/key").unwrap(), "not found");
+    /// assert_eq!(*router.best_match("/assets/app.map").unwrap(), "not found");
+    /// ```
+    pub fn add_excluding<R, E>(
+        &mut self,
+        route: R,
+        exclusions: E,
+        handler: Handler,
+    ) -> Result<RouteId, <R as TryInto<RouteSpec>>::Error>
+    where
+        R: TryInto<RouteSpec>,
+        E: IntoIterator<Item = R>,
+    {
+        let exclusions = exclusions
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect::<Result<_, _>>()?;
+        Ok(self.insert(route.try_into()?.with_exclusions(exclusions), handler))
     }
 
-    /// returns true if no routes have been added
-    pub fn is_empty(&self) -> bool {
-        self.routes.is_empty()
+    /// Adds a route that blocks the other routes of this router for
+    /// the paths it matches, according to the router's [`DenyMode`].
+    /// By default, a deny route blocks the routes it takes precedence
+    /// over, so more specific routes still match. A deny route has no
+    /// handler, and is not one of the router's routes when iterating.
+    ///
+    /// ```rust
+    /// use routefinder::{DenyMode, Router};
+    /// let mut router = Router::new();
+    /// router.add("*", "proxy").unwrap();
+    /// router.add("/admin/login", "login").unwrap();
+    /// router.deny("/admin/*").unwrap();
+    ///
+    /// assert_eq!(*router.best_match("/posts/1").unwrap(), "proxy");
+    /// assert!(router.best_match("/admin/users").is_none());
+    /// assert_eq!(*router.best_match("/admin/login").unwrap(), "login");
+    ///
+    /// router.set_deny_mode(DenyMode::Always);
+    /// assert!(router.best_match("/admin/login").is_none());
+    /// ```
+    pub fn deny<R>(&mut self, route: R) -> Result<RouteId, <R as TryInto<RouteSpec>>::Error>
+    where
+        R: TryInto<RouteSpec>,
+    {
+        let route = route.try_into()?;
+        let id = self.next_id();
+        self.push_handler(None);
+        let route = route.with_id(id);
+        self.notify(ChangeEvent::Added { id, route: &route });
+        self.generation.bump();
+        self.denies.insert(route);
+        Ok(id)
     }
 
-    /// get a reference to the handler for the given route spec
-    pub fn get_handler(&self, spec: impl TryInto<RouteSpec>) -> Option<&Handler> {
-        spec.try_into().ok().and_then(|sp| self.routes.get(&sp))
+    /// Sets how the routes added with [`Router::deny`] block other
+    /// routes. See [`DenyMode`].
+    pub fn set_deny_mode(&mut self, deny_mode: DenyMode) {
+        self.deny_mode = deny_mode;
     }
 
-    /// get a mut reference to the handler for the given route spec
-    pub fn get_handler_mut(&mut self, spec: impl TryInto<RouteSpec>) -> Option<&mut Handler> {
-        spec.try_into()
-            .ok()
-            .and_then(move |sp| self.routes.get_mut(&sp))
+    /// Returns how the routes added with [`Router::deny`] block other
+    /// routes
+    pub fn deny_mode(&self) -> DenyMode {
+        self.deny_mode
     }
-}
 
-/// an iterator over matches for a given path. returned by [`Router::match_iter`]
-#[derive(Debug)]
-pub struct MatchIter<'a, 'b, Handler> {
-    iter: Iter<'a, RouteSpec, Handler>,
-    path: &'b str,
-}
-impl<'a, 'b, Handler> Iterator for MatchIter<'a, 'b, Handler> {
-    type Item = Match<'a, 'b, Handler>;
+    /// Returns an iterator over the routes added with
+    /// [`Router::deny`], in order of precedence
+    pub fn denied(&self) -> impl Iterator<Item = &RouteSpec> {
+        self.denies.iter()
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let path = self.path;
-        self.iter.find_map(|(route, handler)| {
-            route.matches(path).map(|captures| Match {
-                path,
-                route,
-                captures,
-                handler,
-            })
-        })
+    /// the highest precedence deny route that matches this prepared
+    /// path
+    fn first_deny(&self, path: &str, options: &MatchOptions) -> Option<&RouteSpec> {
+        if self.denies.is_empty() {
+            return None;
+        }
+
+        self.denies
+            .candidates(path, options)
+            .find(|deny| deny.matches_with(path, options).is_some())
     }
 
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        (0, self.iter.size_hint().1)
+    /// whether the deny routes and reserved prefixes allow this
+    /// match, for matches that were not found with
+    /// [`Router::match_iter`]
+    pub(crate) fn permits(&self, m: &Match<'_, '_, Handler>) -> bool {
+        let reservation = self.reservation(m.path(), &m.options);
+        if reservation.is_some_and(|prefix| !m.route.is_under(prefix)) {
+            return false;
+        }
+
+        match self.first_deny(m.path(), &m.options) {
+            None => true,
+            Some(deny) => self.deny_mode == DenyMode::Precedence && m.route.outranks(deny),
+        }
+    }
+
+    /// Reserves the paths under this prefix, such as `/.well-known`,
+    /// for the routes that are written under it. Routes outside the
+    /// prefix, such as a catch-all `*` or a `/:page` fallback, do not
+    /// match these paths, even when no route under the prefix does, so
+    /// that the routes of an application cannot shadow paths that a
+    /// protocol or framework requires. [`Router::try_best_match`]
+    /// tells a reserved path that no route matches apart from a path
+    /// that is not found. When reserved prefixes are nested, the
+    /// longest prefix that matches a path applies.
+    ///
+    /// ```rust
+    /// let mut router = routefinder::Router::new();
+    /// router.add("*", "fallback").unwrap();
+    /// router.add("/.well-known/acme-challenge/:token", "acme").unwrap();
+    /// router.reserve_prefix("/.well-known").unwrap();
+    ///
+    /// assert_eq!(*router.best_match("/about").unwrap(), "fallback");
+    /// assert_eq!(*router.best_match("/.well-known/acme-challenge/x").unwrap(), "acme");
+    /// assert!(router.best_match("/.well-known/security.txt").is_none());
+    /// ```
+    pub fn reserve_prefix<R>(&mut self, prefix: R) -> Result<(), <R as TryInto<RouteSpec>>::Error>
+    where
+        R: TryInto<RouteSpec>,
+    {
+        let prefix = prefix.try_into()?.with_mode(MatchMode::Prefix);
+        if !self
+            .reserved
+            .iter()
+            .any(|reserved| reserved.is_same_route(&prefix))
+        {
+            self.generation.bump();
+            self.reserved.push(prefix);
+            self.reserved
+                .sort_by_key(|reserved| std::cmp::Reverse(reserved.segments().len()));
+        }
+        Ok(())
+    }
+
+    /// Returns an iterator over the prefixes added with
+    /// [`Router::reserve_prefix`], longest first
+    pub fn reserved_prefixes(&self) -> impl Iterator<Item = &RouteSpec> {
+        self.reserved.iter()
+    }
+
+    /// the longest reserved prefix that matches this prepared path
+    fn reservation(&self, path: &str, options: &MatchOptions) -> Option<&RouteSpec> {
+        self.reserved
+            .iter()
+            .find(|prefix| prefix.matches_with(path, options).is_some())
+    }
+
+    /// Adds a route along with a variant for each format suffix, such
+    /// as `/reports/:id.json`. The first format is the default, and is
+    /// used when the path does not end in one of these suffixes. The
+    /// matched format is available as [`Match::format`]. Returns the
+    /// [`RouteId`] of each variant.
+    ///
+    /// Note that a path with an unlisted suffix matches the unsuffixed
+    /// route, so `/reports/1.xml` captures `1.xml` as the id below.
+    ///
+    /// ```rust
+    /// let mut router = routefinder::Router::new();
+    /// router.add_with_formats("/reports/:id", ["json", "csv", "html"], "report").unwrap();
+    ///
+    /// let m = router.best_match("/reports/1.csv").unwrap();
+    /// assert_eq!(*m, "report");
+    /// assert_eq!(m.format(), Some("csv"));
+    /// assert_eq!(m.captures().get("id"), Some("1"));
+    ///
+    /// let m = router.best_match("/reports/1").unwrap();
+    /// assert_eq!(m.format(), Some("json"));
+    /// assert_eq!(m.captures().get("id"), Some("1"));
+    /// ```
+    pub fn add_with_formats<R, F>(
+        &mut self,
+        route: R,
+        formats: F,
+        handler: Handler,
+    ) -> Result<Vec<RouteId>, <R as TryInto<RouteSpec>>::Error>
+    where
+        R: TryInto<RouteSpec>,
+        F: IntoIterator,
+        F::Item: AsRef<str>,
+        Handler: Clone,
+    {
+        let route = route.try_into()?;
+        let formats: Vec<SmartString> = formats
+            .into_iter()
+            .map(|format| SmartString::from(format.as_ref()))
+            .collect();
+        let (default, others) = match formats.split_first() {
+            Some((default, others)) => (default.clone(), others),
+            None => return Ok(vec![self.insert(route, handler)]),
+        };
+
+        let mut ids = vec![];
+        for format in others {
+            ids.push(self.insert(route.clone().with_format(format, true), handler.clone()));
+        }
+
+        ids.push(self.insert(route.clone().with_format(&default, true), handler.clone()));
+
+        // the unsuffixed route keeps the formats to negotiate between,
+        // so that they are not searched for on each request
+        let mut negotiable: Vec<SmartString> = vec![];
+        for format in formats {
+            if !negotiable.contains(&format) {
+                negotiable.push(format);
+            }
+        }
+        let route = route.with_format(&default, false).with_formats(negotiable);
+        ids.push(self.insert(route, handler));
+        Ok(ids)
+    }
+
+    /// Adds several handlers for the same route, each with a weight.
+    /// When this route is the best match, [`Router::best_match`]
+    /// selects one of these handlers in proportion to its weight,
+    /// using the router's [`WeightStrategy`]. Each handler is a
+    /// separate entry for [`Router::matches`] and [`Router::iter`],
+    /// with its weight available as [`RouteSpec::weight`] and its own
+    /// [`RouteId`], which are returned in the order of the handlers.
+    ///
+    /// ```rust
+    /// let mut router = routefinder::Router::new();
+    /// router.add_weighted("/checkout", [(90, "stable"), (10, "canary")]).unwrap();
+    /// router.set_weight_strategy(|_total| 95);
+    /// assert_eq!(*router.best_match("/checkout").unwrap(), "canary");
+    /// router.set_weight_strategy(|_total| 0);
+    /// assert_eq!(*router.best_match("/checkout").unwrap(), "stable");
+    /// assert_eq!(router.matches("/checkout").len(), 2);
+    /// ```
+    pub fn add_weighted<R, I>(
+        &mut self,
+        route: R,
+        handlers: I,
+    ) -> Result<Vec<RouteId>, <R as TryInto<RouteSpec>>::Error>
+    where
+        R: TryInto<RouteSpec>,
+        I: IntoIterator<Item = (u32, Handler)>,
+    {
+        let route = route.try_into()?;
+        Ok(handlers
+            .into_iter()
+            .map(|(weight, handler)| self.insert(route.clone().with_weight(weight), handler))
+            .collect())
+    }
+
+    /// Adds a route that is only considered while the provided
+    /// [`Condition`] is met. The condition is checked each time the
+    /// route would otherwise be tried, so when it is not met, lower
+    /// precedence routes match instead. A conditional route takes
+    /// precedence over an otherwise identical unconditional one. See
+    /// [`Flag`][crate::Flag] for a condition that can be toggled from
+    /// elsewhere.
+    ///
+    /// ```rust
+    /// let mut router = routefinder::Router::new();
+    /// router.add("/reports/:id", "old reports").unwrap();
+    /// router.add_conditional("/reports/:id", "new reports", || false).unwrap();
+    /// assert_eq!(*router.best_match("/reports/1").unwrap(), "old reports");
+    /// assert_eq!(router.matches("/reports/1").len(), 1);
+    /// ```
+    pub fn add_conditional<R>(
+        &mut self,
+        route: R,
+        handler: Handler,
+        condition: impl Condition + 'static,
+    ) -> Result<RouteId, <R as TryInto<RouteSpec>>::Error>
+    where
+        R: TryInto<RouteSpec>,
+    {
+        Ok(self.insert(
+            route
+                .try_into()?
+                .with_condition(RouteCondition::new(condition)),
+            handler,
+        ))
+    }
+
+    /// Adds a route that only matches in [`Router::best_match_in`] and
+    /// [`Router::match_iter_in`], when the guard returns true for the
+    /// context of the match, such as the request's headers. Guards are
+    /// checked in order of precedence, so when one is not met, lower
+    /// precedence routes match instead. As with
+    /// [`Router::add_conditional`], a guarded route takes precedence
+    /// over an otherwise identical unguarded one. A guarded route never
+    /// matches without a context, or with a context of another type.
+    ///
+    /// ```rust
+    /// struct Headers { accept: &'static str }
+    /// let mut router = routefinder::Router::new();
+    /// router.add("/reports/:id", "html").unwrap();
+    /// router
+    ///     .add_guarded("/reports/:id", "json", |headers: &Headers| headers.accept == "application/json")
+    ///     .unwrap();
+    ///
+    /// let json = Headers { accept: "application/json" };
+    /// let html = Headers { accept: "text/html" };
+    /// assert_eq!(*router.best_match_in("/reports/1", &json).unwrap(), "json");
+    /// assert_eq!(*router.best_match_in("/reports/1", &html).unwrap(), "html");
+    /// assert_eq!(*router.best_match("/reports/1").unwrap(), "html");
+    /// ```
+    pub fn add_guarded<R, C: 'static>(
+        &mut self,
+        route: R,
+        handler: Handler,
+        guard: impl Fn(&C) -> bool + Send + Sync + 'static,
+    ) -> Result<RouteId, <R as TryInto<RouteSpec>>::Error>
+    where
+        R: TryInto<RouteSpec>,
+    {
+        Ok(self.insert(
+            route
+                .try_into()?
+                .with_condition(RouteCondition::guard(guard)),
+            handler,
+        ))
+    }
+
+    /// Sets the [`WeightStrategy`] used to choose between the handlers
+    /// of routes added with [`Router::add_weighted`]. This defaults to
+    /// [`RandomWeights`].
+    pub fn set_weight_strategy(&mut self, weight_strategy: impl WeightStrategy + 'static) {
+        self.weight_strategy = Arc::new(weight_strategy);
+    }
+
+    /// Sets the [`Limits`] for paths that this router will attempt to
+    /// match. Paths outside of these limits do not match any route.
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// Returns the [`Limits`] for this router
+    pub fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    /// Sets the [`Negotiator`] that [`Router::best_match_negotiated`]
+    /// uses to choose between formats
+    pub fn set_negotiator(&mut self, negotiator: Negotiator) {
+        self.negotiator = negotiator;
+    }
+
+    /// Returns the [`Negotiator`] for this router
+    pub fn negotiator(&self) -> &Negotiator {
+        &self.negotiator
+    }
+
+    /// Sets how this router stores its routes, moving any routes that
+    /// have already been added. Routes keep their order of precedence.
+    /// See [`Storage`].
+    ///
+    /// ```rust
+    /// use routefinder::{Router, Storage};
+    /// let mut router = Router::new();
+    /// router.add("/users/:id", "user").unwrap();
+    /// router.add("/users/new", "new user").unwrap();
+    /// router.set_storage(Storage::Sorted);
+    /// router.add("/*", "fallback").unwrap();
+    ///
+    /// assert_eq!(router.storage(), Storage::Sorted);
+    /// assert_eq!(*router.best_match("/users/new").unwrap(), "new user");
+    /// assert_eq!(*router.best_match("/users/1").unwrap(), "user");
+    /// assert_eq!(*router.best_match("/about").unwrap(), "fallback");
+    /// ```
+    pub fn set_storage(&mut self, storage: Storage) {
+        self.routes.set_storage(storage);
+    }
+
+    /// Returns how this router stores its routes
+    pub fn storage(&self) -> Storage {
+        self.routes.storage()
+    }
+
+    /// Returns the [`Engine`] that this router uses to find the routes
+    /// to try for a path, which is either pinned with
+    /// [`Router::pin_engine`] or chosen from the shape of the routes
+    ///
+    /// ```rust
+    /// use routefinder::{Engine, Router};
+    /// let mut router = Router::new();
+    /// router.add("/", ()).unwrap();
+    /// router.add("/about", ()).unwrap();
+    /// assert_eq!(router.engine(), Engine::Hashed);
+    ///
+    /// router.add("/users/:id", ()).unwrap();
+    /// assert_eq!(router.engine(), Engine::Linear);
+    ///
+    /// for resource in 0..20 {
+    ///     router.add(format!("/resource{}/:id", resource), ()).unwrap();
+    /// }
+    /// assert_eq!(router.engine(), Engine::Partitioned);
+    ///
+    /// for resource in 0..50 {
+    ///     router.add(format!("/api/resource{}/:id", resource), ()).unwrap();
+    /// }
+    /// assert_eq!(router.engine(), Engine::Trie);
+    ///
+    /// router.pin_engine(Engine::Linear);
+    /// assert_eq!(router.engine(), Engine::Linear);
+    /// ```
+    pub fn engine(&self) -> Engine {
+        self.routes.engine()
+    }
+
+    /// Pins the [`Engine`] that this router uses to find the routes to
+    /// try for a path, so that it is no longer chosen from the shape
+    /// of the routes. Every engine finds the same matches.
+    pub fn pin_engine(&mut self, engine: Engine) {
+        self.routes.pin_engine(Some(engine));
+    }
+
+    /// Unpins the [`Engine`], so that it is chosen from the shape of
+    /// the routes again
+    pub fn unpin_engine(&mut self) {
+        self.routes.pin_engine(None);
+    }
+
+    /// Enables or disables resolving `.` and `..` segments in paths
+    /// before they are matched, with
+    /// [`normalize::resolve_dot_segments`][crate::normalize::resolve_dot_segments].
+    /// Paths that would traverse above the root do not match any
+    /// route, and [`Match::path`] is the resolved path. This is
+    /// disabled by default.
+    ///
+    /// ```rust
+    /// let mut router = routefinder::Router::new();
+    /// router.add("/static/*", "static").unwrap();
+    /// router.add("/secrets/*", "secrets").unwrap();
+    /// assert_eq!(*router.best_match("/static/../secrets/key").unwrap(), "static");
+    ///
+    /// router.set_resolve_dot_segments(true);
+    /// let m = router.best_match("/static/../secrets/key").unwrap();
+    /// assert_eq!(*m, "secrets");
+    /// assert_eq!(m.path(), "/secrets/key");
+    /// assert!(router.best_match("/static/../../etc/passwd").is_none());
+    /// ```
+    pub fn set_resolve_dot_segments(&mut self, resolve_dot_segments: bool) {
+        self.options = self.options.resolve_dot_segments(resolve_dot_segments);
+    }
+
+    /// Enables or disables reading each `+` in a path as a space, for
+    /// legacy form-encoded urls. This is disabled by default. See
+    /// [`MatchOptions::plus_as_space`].
+    pub fn set_plus_as_space(&mut self, plus_as_space: bool) {
+        self.options = self.options.plus_as_space(plus_as_space);
+    }
+
+    /// Sets the [`MatchOptions`] that this router uses to match paths,
+    /// which replaces any earlier call to
+    /// [`Router::set_resolve_dot_segments`] or
+    /// [`Router::set_plus_as_space`]
+    pub fn set_match_options(&mut self, options: MatchOptions) {
+        self.options = options;
+    }
+
+    /// Returns the [`MatchOptions`] that this router uses to match
+    /// paths, as a starting point for options passed to
+    /// [`Router::best_match_with`]
+    pub fn match_options(&self) -> MatchOptions {
+        self.options
+    }
+
+    /// Returns a [`Matcher`] that is fed a path one segment at a
+    /// time, for paths that arrive incrementally
+    pub fn matcher(&self) -> Matcher<'_, Handler> {
+        Matcher::new(self)
+    }
+
+    /// Returns the single best route match as defined by the sorting
+    /// rules. To compare any two routes, step through each
+    /// [`Segment`][crate::Segment] and find the first pair that are not equal,
+    /// according to: `Exact > Param > Wildcard > (dots and slashes)`
+    /// As a result, `/hello` > `/:param` > `/*`.  Because we can sort
+    /// the routes before encountering a path, we evaluate them from
+    /// highest to lowest weight and an early return as soon as we
+    /// find a match. The path may be any borrowed string, such as a
+    /// `&str`, `&String`, or `&Cow<str>`.
+    ///
+    /// ```rust
+    /// let mut router = routefinder::Router::new();
+    /// router.add("*", 0).unwrap();
+    /// router.add("/:param", 1).unwrap();
+    /// router.add("/hello", 2).unwrap();
+    /// assert_eq!(*router.best_match("/hello").unwrap(), 2);
+    /// assert_eq!(*router.best_match("/hey").unwrap(), 1);
+    /// assert_eq!(router.best_match("/hey").unwrap().captures().get("param"), Some("hey"));
+    /// assert_eq!(*router.best_match("/hey/there").unwrap(), 0);
+    /// assert_eq!(*router.best_match("/").unwrap(), 0);
+    /// ```
+    pub fn best_match<'a, 'b, P>(&'a self, path: &'b P) -> Option<Match<'a, 'b, Handler>>
+    where
+        P: AsPath + ?Sized,
+    {
+        self.best_of(self.match_iter(path))
+    }
+
+    /// Returns the single best route match for this path, or why there
+    /// is none: either no route matches, or the path is under a prefix
+    /// reserved with [`Router::reserve_prefix`] and none of the routes
+    /// under that prefix match it. See [`Router::best_match`] and
+    /// [`Unrouted`].
+    ///
+    /// ```rust
+    /// use routefinder::{Router, Unrouted};
+    /// let mut router = Router::new();
+    /// router.add("/users/:id", "user").unwrap();
+    /// router.reserve_prefix("/_internal").unwrap();
+    ///
+    /// assert_eq!(*router.try_best_match("/users/1").unwrap(), "user");
+    /// assert_eq!(router.try_best_match("/posts/1").unwrap_err(), Unrouted::NotFound);
+    /// assert!(matches!(
+    ///     router.try_best_match("/_internal/health"),
+    ///     Err(Unrouted::Reserved(_))
+    /// ));
+    /// ```
+    pub fn try_best_match<'a, 'b, P>(
+        &'a self,
+        path: &'b P,
+    ) -> Result<Match<'a, 'b, Handler>, Unrouted<'a>>
+    where
+        P: AsPath + ?Sized,
+    {
+        let matches = self.match_iter(path);
+        let unrouted = match matches.reservation {
+            Some(prefix) => Unrouted::Reserved(prefix),
+            None => Unrouted::NotFound,
+        };
+        self.best_of(matches).ok_or(unrouted)
+    }
+
+    /// Returns the [canonical path][Match::canonical_path] of the best
+    /// match for this path, which spells the exact text of the route
+    /// as it was registered, even when the path only matched with
+    /// [`MatchOptions::case_insensitive`]. A path that differs from its
+    /// canonical path can be redirected to it, so that each route is
+    /// served under one casing. Returns None if no route matches, or
+    /// if the best match has a glob.
+    ///
+    /// ```rust
+    /// use routefinder::{MatchOptions, Router};
+    /// let mut router = Router::new();
+    /// router.add("/Users/:name/Profile", ()).unwrap();
+    /// router.set_match_options(MatchOptions::new().case_insensitive(true));
+    ///
+    /// assert_eq!(
+    ///     router.canonical_path("/USERS/Jane/profile/").unwrap(),
+    ///     "/Users/Jane/Profile"
+    /// );
+    /// assert_eq!(router.canonical_path("/Users/Jane/Profile").unwrap(), "/Users/Jane/Profile");
+    /// assert!(router.canonical_path("/users").is_none());
+    /// ```
+    pub fn canonical_path<P>(&self, path: &P) -> Option<String>
+    where
+        P: AsPath + ?Sized,
+    {
+        self.match_iter(path).best()?.canonical_path()
+    }
+
+    /// Returns the single best route match for this path, choosing
+    /// its format from an HTTP `Accept` header. When the best match is
+    /// the unsuffixed route of [`Router::add_with_formats`], its
+    /// [`Match::format`] is the registered format that the router's
+    /// [`Negotiator`] chooses, or the default format if the header
+    /// accepts none of them. A path that ends in one of the formats
+    /// takes precedence over the header, as do routes that outrank
+    /// the unsuffixed route, such as a literal `/reports/1.json`.
+    ///
+    /// ```rust
+    /// let mut router = routefinder::Router::new();
+    /// router.add_with_formats("/reports/:id", ["json", "html"], "report").unwrap();
+    ///
+    /// let m = router.best_match_negotiated("/reports/1", "text/html").unwrap();
+    /// assert_eq!(m.format(), Some("html"));
+    /// let m = router.best_match_negotiated("/reports/1", "image/png").unwrap();
+    /// assert_eq!(m.format(), Some("json"));
+    /// let m = router.best_match_negotiated("/reports/1.json", "text/html").unwrap();
+    /// assert_eq!(m.format(), Some("json"));
+    /// ```
+    pub fn best_match_negotiated<'a, 'b, P>(
+        &'a self,
+        path: &'b P,
+        accept: &str,
+    ) -> Option<Match<'a, 'b, Handler>>
+    where
+        P: AsPath + ?Sized,
+    {
+        let mut best = self.best_match(path)?;
+        if best.route.formats().next().is_some() {
+            best.format = self.negotiator.negotiate(accept, best.route.formats());
+        }
+        Some(best)
+    }
+
+    /// Returns the single best route match for this path, checking
+    /// the guards of routes added with [`Router::add_guarded`] against
+    /// this context. See [`Router::best_match`].
+    pub fn best_match_in<'a, 'b, P, C: 'static>(
+        &'a self,
+        path: &'b P,
+        context: &'b C,
+    ) -> Option<Match<'a, 'b, Handler>>
+    where
+        P: AsPath + ?Sized,
+    {
+        self.best_of(self.match_iter_in(path, context))
+    }
+
+    /// Returns an iterator over the possible matches for this path,
+    /// checking the guards of routes added with [`Router::add_guarded`]
+    /// against this context. See [`Router::match_iter`].
+    pub fn match_iter_in<'a, 'b, P, C: 'static>(
+        &'a self,
+        path: &'b P,
+        context: &'b C,
+    ) -> MatchIter<'a, 'b, Handler>
+    where
+        P: AsPath + ?Sized,
+    {
+        MatchIter {
+            context: Some(context),
+            ..self.match_iter(path)
+        }
+    }
+
+    /// the best of these matches, which is recorded if there is a
+    /// recorder
+    fn best_of<'a, 'b>(
+        &'a self,
+        matches: MatchIter<'a, 'b, Handler>,
+    ) -> Option<Match<'a, 'b, Handler>> {
+        let best = matches.best();
+        #[cfg(feature = "metrics")]
+        self.record(best.as_ref());
+        best
+    }
+
+    /// chooses between the handlers of a weighted route
+    pub(crate) fn pick_weighted<'a, 'b>(
+        &'a self,
+        best: Match<'a, 'b, Handler>,
+    ) -> Option<Match<'a, 'b, Handler>> {
+        if best.route.weight().is_none() {
+            return Some(best);
+        }
+
+        let alternatives = || {
+            self.routes
+                .alternatives(best.route)
+                .filter(|route| route.is_enabled())
+                .filter_map(|route| Some((route, self.handler(route.id()?)?)))
+        };
+        let total = alternatives()
+            .map(|(route, _)| route.weight().unwrap_or_default())
+            .fold(0u32, u32::saturating_add);
+        let mut choice = self.weight_strategy.choose(total);
+
+        for (route, handler) in alternatives() {
+            let weight = route.weight().unwrap_or_default();
+            if choice < weight {
+                return Some(Match {
+                    route,
+                    handler,
+                    ..best
+                });
+            }
+            choice -= weight;
+        }
+
+        Some(best)
+    }
+
+    /// Returns the best match for this path as a [`MatchIndex`], which
+    /// does not borrow the router. This allows the router to be
+    /// mutated between finding a match and using its handler.
+    ///
+    /// ```rust
+    /// let mut router = routefinder::Router::new();
+    /// router.add("/users/:id", 0).unwrap();
+    /// router.add("/posts/:id", 0).unwrap();
+    ///
+    /// let index = router.lookup("/users/1").unwrap();
+    /// let (route, hits) = router.resolve_mut(&index).unwrap();
+    /// *hits += 1;
+    /// assert_eq!(route.to_string(), "/users/:id");
+    /// assert_eq!(index.captures().get("id"), Some("1"));
+    /// assert_eq!(*router.best_match("/users/2").unwrap(), 1);
+    /// ```
+    pub fn lookup<'b, P>(&self, path: &'b P) -> Option<MatchIndex<'b>>
+    where
+        P: AsPath + ?Sized,
+    {
+        let best = self.best_match(path)?;
+        Some(MatchIndex {
+            id: best.route.id()?,
+            generation: self.generation.get(),
+            captures: best.captures().into_owned_keys(),
+            path: best.path,
+        })
+    }
+
+    /// Returns the route and handler for a [`MatchIndex`] from this
+    /// router, or None if the index is from another router or routes
+    /// have been added or removed since the lookup
+    pub fn resolve(&self, index: &MatchIndex<'_>) -> Option<(&RouteSpec, &Handler)> {
+        if index.generation != self.generation.get() {
+            return None;
+        }
+        Some((self.routes.get(index.id)?, self.handler(index.id)?))
+    }
+
+    /// Returns the route and a mutable reference to the handler for a
+    /// [`MatchIndex`] from this router, or None if the index is from
+    /// another router or routes have been added or removed since the
+    /// lookup
+    pub fn resolve_mut(&mut self, index: &MatchIndex<'_>) -> Option<(&RouteSpec, &mut Handler)> {
+        if index.generation != self.generation.get() {
+            return None;
+        }
+        let route = self.routes.get(index.id)?;
+        let handler = self.handlers.get_mut(index.id.get())?.as_mut()?;
+        Some((route, handler))
+    }
+
+    /// Returns _all_ of the matching routes for a given path. This is
+    /// probably not what you want, as [`Router::best_match`] is more
+    /// efficient. The primary reason you'd want to use `matches` is
+    /// to implement different route precedence rules or for
+    /// testing.
+    ///
+    /// ```rust
+    /// let mut router = routefinder::Router::new();
+    /// router.add("*", ()).unwrap();
+    /// router.add("/:param", ()).unwrap();
+    /// router.add("/hello", ()).unwrap();
+    /// assert_eq!(router.matches("/").len(), 1);
+    /// assert_eq!(router.matches("/hello").len(), 3);
+    /// assert_eq!(router.matches("/hey").len(), 2);
+    /// assert_eq!(router.matches("/hey/there").len(), 1);
+    /// ```
+    pub fn matches<'a, 'b, P>(&'a self, path: &'b P) -> Vec<Match<'a, 'b, Handler>>
+    where
+        P: AsPath + ?Sized,
+    {
+        self.match_iter(path).collect()
+    }
+
+    /// Returns an iterator over the possible matches for this
+    /// particular path. Because rust iterators are lazy, this is
+    /// useful for some filtering operations that might otherwise use
+    /// [`Router::matches`], which is this iterator collected into a
+    /// vec.
+    pub fn match_iter<'a, 'b, P>(&'a self, path: &'b P) -> MatchIter<'a, 'b, Handler>
+    where
+        P: AsPath + ?Sized,
+    {
+        self.match_iter_with(path, &self.options)
+    }
+
+    /// Returns an iterator over the possible matches for this path
+    /// among the routes for which the predicate returns true. Other
+    /// routes are skipped without being applied to the path. See
+    /// [`MatchIter::filter_routes`].
+    ///
+    /// ```rust
+    /// let mut router = routefinder::Router::new();
+    /// router.add("/users/:id", "user").unwrap();
+    /// router.add("/users/*", "users").unwrap();
+    /// router.add("*", "fallback").unwrap();
+    ///
+    /// let matches: Vec<_> = router
+    ///     .match_iter_where("/users/1", |route| !route.has_wildcard())
+    ///     .map(|m| *m)
+    ///     .collect();
+    /// assert_eq!(matches, ["user"]);
+    /// ```
+    pub fn match_iter_where<'a, 'b, P>(
+        &'a self,
+        path: &'b P,
+        predicate: impl FnMut(&RouteSpec) -> bool,
+    ) -> impl Iterator<Item = Match<'a, 'b, Handler>>
+    where
+        P: AsPath + ?Sized,
+    {
+        self.match_iter(path).filter_routes(predicate)
+    }
+
+    /// Returns the single best route match for this path, using the
+    /// provided [`MatchOptions`] in place of the router's own. See
+    /// [`Router::best_match`].
+    ///
+    /// ```rust
+    /// let mut router = routefinder::Router::new();
+    /// router.add("/health", "health").unwrap();
+    /// let strict = router.match_options().strict_trailing_slash(true);
+    /// assert!(router.best_match("/health/").is_some());
+    /// assert!(router.best_match_with("/health/", &strict).is_none());
+    /// ```
+    pub fn best_match_with<'a, 'b, P>(
+        &'a self,
+        path: &'b P,
+        options: &MatchOptions,
+    ) -> Option<Match<'a, 'b, Handler>>
+    where
+        P: AsPath + ?Sized,
+    {
+        self.best_of(self.match_iter_with(path, options))
+    }
+
+    /// Returns an iterator over the possible matches for this path,
+    /// using the provided [`MatchOptions`] in place of the router's
+    /// own. See [`Router::match_iter`].
+    pub fn match_iter_with<'a, 'b, P>(
+        &'a self,
+        path: &'b P,
+        options: &MatchOptions,
+    ) -> MatchIter<'a, 'b, Handler>
+    where
+        P: AsPath + ?Sized,
+    {
+        let path = path.trimmed();
+        self.match_iter_cow(
+            self.prepare_with(path, options),
+            Cow::Borrowed(path),
+            options,
+        )
+    }
+
+    /// applies this router's limits and normalization to a path,
+    /// returning None if the path cannot match any route
+    pub(crate) fn prepare<'b>(&self, path: &'b str) -> Option<Cow<'b, str>> {
+        self.prepare_with(path, &self.options)
+    }
+
+    fn prepare_with<'b>(&self, path: &'b str, options: &MatchOptions) -> Option<Cow<'b, str>> {
+        if !self.limits.allows(path) {
+            return None;
+        }
+
+        options.prepare(path)
+    }
+
+    /// Returns the single best route match for a path that has
+    /// already been normalized, skipping the resolution of its dot
+    /// segments. See [`Router::best_match`] and [`NormalizedPath`].
+    pub fn best_match_normalized<'a, 'b>(
+        &'a self,
+        path: &'b NormalizedPath<'_>,
+    ) -> Option<Match<'a, 'b, Handler>> {
+        self.best_of(self.match_iter_normalized(path))
+    }
+
+    /// Returns an iterator over the possible matches for a path that
+    /// has already been normalized, skipping the resolution of its dot
+    /// segments. See [`Router::match_iter`] and [`NormalizedPath`].
+    pub fn match_iter_normalized<'a, 'b>(
+        &'a self,
+        path: &'b NormalizedPath<'_>,
+    ) -> MatchIter<'a, 'b, Handler> {
+        // the dot segments have already been resolved, so the
+        // normalized path is the raw path
+        let path = path.as_str();
+        let options = self.options.resolve_dot_segments(false);
+        self.match_iter_cow(
+            self.prepare_with(path, &options),
+            Cow::Borrowed(path),
+            &options,
+        )
+    }
+
+    fn match_iter_cow<'a, 'b>(
+        &'a self,
+        path: Option<Cow<'b, str>>,
+        raw: Cow<'b, str>,
+        options: &MatchOptions,
+    ) -> MatchIter<'a, 'b, Handler> {
+        let candidates = path.as_deref().unwrap_or_default();
+        let deny = path
+            .as_deref()
+            .and_then(|path| self.first_deny(path, options));
+        let reservation = path
+            .as_deref()
+            .and_then(|path| self.reservation(path, options));
+        MatchIter {
+            router: self,
+            iter: Iter {
+                routes: self.routes.candidates(candidates, options),
+                handlers: &self.handlers,
+            },
+            raw,
+            options: *options,
+            rejected: path.is_none() || (deny.is_some() && self.deny_mode == DenyMode::Always),
+            deny,
+            reservation,
+            path: path.unwrap_or_default(),
+            context: None,
+        }
+    }
+
+    /// Returns an iterator of references to `(&RouteSpec, &Handler)`
+    ///
+    /// ```
+    /// let mut router = routefinder::Router::new();
+    /// router.add("*", 1).unwrap();
+    /// router.add("/:param", 2).unwrap();
+    /// router.add("/hello", 3).unwrap();
+    /// let (route, handler) = router.iter().next().unwrap();
+    /// assert_eq!(route.to_string(), "/hello");
+    /// assert_eq!(handler, &3);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (&RouteSpec, &Handler)> {
+        self.into_iter()
+    }
+
+    /// returns an iterator of `(&RouteSpec, &mut Handler)`
+    ///
+    /// ```
+    /// let mut router = routefinder::Router::new();
+    /// router.add("*", 1).unwrap();
+    /// router.add("/:param", 2).unwrap();
+    /// router.add("/hello", 3).unwrap();
+    ///
+    /// assert_eq!(*router.best_match("/hello").unwrap(), 3);
+    /// let (route, handler) = router.iter_mut().next().unwrap();
+    /// assert_eq!(route.to_string(), "/hello");
+    /// *handler = 10;
+    /// assert_eq!(*router.best_match("/hello").unwrap(), 10);
+    /// ```
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&RouteSpec, &mut Handler)> {
+        self.into_iter()
+    }
+
+    /// Builds a router with the same routes and settings, with each
+    /// handler replaced by the result of calling `f` with its route,
+    /// in order of precedence. Mapping stops at the first error, which
+    /// is returned.
+    ///
+    /// ```rust
+    /// let mut router = routefinder::Router::new();
+    /// router.add("/users/:id", "42").unwrap();
+    /// router.add("/about", "7").unwrap();
+    ///
+    /// let numbers = router.try_map(|_, handler| handler.parse::<u32>()).unwrap();
+    /// assert_eq!(*numbers.best_match("/about").unwrap(), 7);
+    ///
+    /// let mut router = routefinder::Router::new();
+    /// router.add("/about", "seven").unwrap();
+    /// let error = router
+    ///     .try_map(|route, handler| handler.parse::<u32>().map_err(|_| route.to_string()))
+    ///     .unwrap_err();
+    /// assert_eq!(error, "/about");
+    /// ```
+    pub fn try_map<U, E>(
+        self,
+        mut f: impl FnMut(&RouteSpec, Handler) -> Result<U, E>,
+    ) -> Result<Router<U>, E> {
+        self.map_routes(|route, handler| f(route, handler).map(Some))
+    }
+
+    /// Builds a router with the same routes and settings, with each
+    /// handler paired with the next item of `meta`, in order of
+    /// precedence. As with [`Iterator::zip`], routes beyond the end of
+    /// `meta` are removed.
+    ///
+    /// ```rust
+    /// let mut router = routefinder::Router::new();
+    /// router.add("/users/:id", "user").unwrap();
+    /// router.add("/about", "about").unwrap();
+    ///
+    /// let numbered = router.zip_meta(1..);
+    /// assert_eq!(*numbered.best_match("/about").unwrap(), ("about", 1));
+    /// assert_eq!(*numbered.best_match("/users/1").unwrap(), ("user", 2));
+    /// ```
+    pub fn zip_meta<M>(self, meta: impl IntoIterator<Item = M>) -> Router<(Handler, M)> {
+        let mut meta = meta.into_iter();
+        let zipped = self
+            .map_routes(|_, handler| Ok::<_, Infallible>(meta.next().map(|meta| (handler, meta))));
+
+        match zipped {
+            Ok(router) => router,
+            Err(never) => match never {},
+        }
+    }
+
+    /// replaces each handler in order of precedence, removing the
+    /// routes for which `f` returns None
+    fn map_routes<U, E>(
+        mut self,
+        mut f: impl FnMut(&RouteSpec, Handler) -> Result<Option<U>, E>,
+    ) -> Result<Router<U>, E> {
+        let mut handlers: Vec<Option<U>> = self.handlers.iter().map(|_| None).collect();
+        for route in self.routes.iter() {
+            let id = match route.id() {
+                Some(id) => id.get(),
+                None => continue,
+            };
+
+            if let Some(handler) = self.handlers[id].take() {
+                handlers[id] = f(route, handler)?;
+            }
+        }
+
+        let removed = self.routes.len() != handlers.iter().flatten().count();
+        if removed {
+            for route in self.routes.iter() {
+                if let Some(id) = route.id().filter(|id| handlers[id.get()].is_none()) {
+                    self.notify(ChangeEvent::Removed { id, route });
+                }
+            }
+
+            self.routes
+                .retain(|route| route.id().is_some_and(|id| handlers[id.get()].is_some()));
+        }
+
+        Ok(Router {
+            routes: self.routes,
+            handlers,
+            denies: self.denies,
+            deny_mode: self.deny_mode,
+            reserved: self.reserved,
+            weight_strategy: self.weight_strategy,
+            limits: self.limits,
+            options: self.options,
+            negotiator: self.negotiator,
+            observers: self.observers,
+            #[cfg(feature = "metrics")]
+            recorder: self.recorder,
+            #[cfg(feature = "metrics")]
+            hits: self.hits,
+            generation: if removed {
+                Generation::new()
+            } else {
+                self.generation
+            },
+        })
+    }
+
+    /// returns the number of routes that have been added
+    pub fn len(&self) -> usize {
+        self.routes.len()
+    }
+
+    /// returns true if no routes have been added
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+
+    /// Returns a hash of every route, deny route, and reserved prefix
+    /// of this router, in order of precedence, that is the same for the same routes on
+    /// every platform and version of routefinder. Handlers, ids, and
+    /// match options are not included. A test can compare the
+    /// fingerprint to a known value, so that any change to the routes
+    /// of an application is noticed and reviewed. The routes are
+    /// hashed by their [`RouteSpec::canonical`] form, which can be
+    /// written to a golden file to see what changed.
+    ///
+    /// ```rust
+    /// use routefinder::Router;
+    /// let mut router = Router::new();
+    /// router.add("/users/:id", 1).unwrap();
+    /// router.add("/", 2).unwrap();
+    ///
+    /// let mut same = Router::new();
+    /// same.add("/", "home").unwrap();
+    /// same.add("users/:id/", "user").unwrap();
+    /// assert_eq!(router.fingerprint(), same.fingerprint());
+    ///
+    /// router.add("/users/:id/posts", 3).unwrap();
+    /// assert_ne!(router.fingerprint(), same.fingerprint());
+    /// ```
+    pub fn fingerprint(&self) -> u64 {
+        // 64 bit fnv-1a, which unlike the std hashers is specified
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let mut write = |bytes: &[u8]| {
+            for byte in bytes {
+                hash ^= u64::from(*byte);
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        };
+
+        for route in self.routes.iter() {
+            write(route.canonical().as_bytes());
+            write(b"\n");
+        }
+
+        for deny in self.denies.iter() {
+            write(b"deny ");
+            write(deny.canonical().as_bytes());
+            write(b"\n");
+        }
+
+        for prefix in &self.reserved {
+            write(b"reserve ");
+            write(prefix.canonical().as_bytes());
+            write(b"\n");
+        }
+
+        hash
+    }
+
+    /// get a reference to the handler for the first route, in order of
+    /// precedence, that matches the same paths as the given route spec
+    ///
+    /// ```rust
+    /// let mut router = routefinder::Router::new();
+    /// router.add("/users/:id", "users").unwrap();
+    /// assert_eq!(router.get_handler("/users/:id"), Some(&"users"));
+    /// assert_eq!(router.get_handler("/users/:user_id"), None);
+    /// ```
+    pub fn get_handler(&self, spec: impl TryInto<RouteSpec>) -> Option<&Handler> {
+        let id = self.find_same_route(spec.try_into().ok()?)?;
+        self.handler(id)
+    }
+
+    /// get a mut reference to the handler for the first route, in order
+    /// of precedence, that matches the same paths as the given route spec
+    pub fn get_handler_mut(&mut self, spec: impl TryInto<RouteSpec>) -> Option<&mut Handler> {
+        let id = self.find_same_route(spec.try_into().ok()?)?;
+        self.handler_mut(id)
+    }
+
+    fn find_same_route(&self, spec: RouteSpec) -> Option<RouteId> {
+        self.routes
+            .iter()
+            .find(|route| route.is_same_route(&spec))?
+            .id()
+    }
+
+    /// Returns the handler for the route with this [`RouteId`], without
+    /// searching the routes
+    ///
+    /// ```rust
+    /// let mut router = routefinder::Router::new();
+    /// let id = router.add("/users/:id", 1).unwrap();
+    /// *router.handler_mut(id).unwrap() += 1;
+    /// assert_eq!(router.handler(id), Some(&2));
+    /// ```
+    pub fn handler(&self, id: RouteId) -> Option<&Handler> {
+        self.handlers.get(id.get())?.as_ref()
+    }
+
+    /// Returns a mutable reference to the handler for the route with
+    /// this [`RouteId`], without searching the routes
+    pub fn handler_mut(&mut self, id: RouteId) -> Option<&mut Handler> {
+        self.handlers.get_mut(id.get())?.as_mut()
+    }
+
+    /// Returns the [`RouteSpec`] for the route with this [`RouteId`]
+    ///
+    /// ```rust
+    /// let mut router = routefinder::Router::new();
+    /// let id = router.add("/users/:id", ()).unwrap();
+    /// assert_eq!(router.spec(id).unwrap().to_string(), "/users/:id");
+    /// ```
+    pub fn spec(&self, id: RouteId) -> Option<&RouteSpec> {
+        self.handler(id)?;
+        self.routes.get(id)
+    }
+
+    /// Returns the [`RouteId`] of the first route, in order of
+    /// precedence, that was parsed from this source string. Leading and
+    /// trailing slashes are ignored, as they are when parsing, but the
+    /// source is otherwise compared as written, without parsing it. Routes
+    /// that were not parsed from a string have no source and are never
+    /// found.
+    ///
+    /// ```rust
+    /// let mut router = routefinder::Router::new();
+    /// let id = router.add("/users/:id", "users").unwrap();
+    /// assert_eq!(router.find_by_source("/users/:id"), Some(id));
+    /// assert_eq!(router.find_by_source("users/:id/"), Some(id));
+    /// assert_eq!(router.find_by_source("/users/:user_id"), None);
+    /// ```
+    pub fn find_by_source(&self, source: &str) -> Option<RouteId> {
+        let source = trim_slashes(source);
+        self.routes
+            .iter()
+            .find(|route| route.source().map(trim_slashes) == Some(source))?
+            .id()
+    }
+
+    /// returns the concrete path for every route that has no params,
+    /// wildcards, or globs, in precedence order. This is useful for
+    /// pre-rendering every page that a static site can serve.
+    ///
+    /// ```
+    /// let mut router = routefinder::Router::new();
+    /// router.add("/", ()).unwrap();
+    /// router.add("/about", ()).unwrap();
+    /// router.add("/posts/:slug", ()).unwrap();
+    /// router.add("/feed.xml", ()).unwrap();
+    /// assert_eq!(router.static_paths(), ["/", "/about", "/feed.xml"]);
+    /// ```
+    pub fn static_paths(&self) -> Vec<String> {
+        let no_values = BTreeMap::new();
+        self.expand_routes(
+            self.routes.iter().filter(|route| {
+                route.segments().iter().all(|segment| {
+                    matches!(segment, Segment::Slash | Segment::Dot | Segment::Exact(_))
+                })
+            }),
+            &no_values,
+        )
+    }
+
+    /// returns every path that the routes in this router can produce
+    /// from the provided values for each param, in precedence order.
+    /// Values for the wildcard are provided as `"*"`. Routes with a
+    /// required param that has no values are skipped, optional params
+    /// and wildcards are also expanded without a value, and values
+    /// that a route would not match, such as those that fail a
+    /// constraint, are skipped.
+    ///
+    /// ```
+    /// let mut router = routefinder::Router::new();
+    /// router.add("/", ()).unwrap();
+    /// router.add("/posts/:slug", ()).unwrap();
+    /// router.add("/posts/:slug/comments/:id", ()).unwrap();
+    /// router.add("/docs/*", ()).unwrap();
+    ///
+    /// let paths = router.expand_with([
+    ///     ("slug", vec!["hello", "world"]),
+    ///     ("*", vec!["intro/setup"]),
+    /// ]);
+    /// assert_eq!(
+    ///     paths,
+    ///     ["/", "/posts/hello", "/posts/world", "/docs/intro/setup", "/docs"]
+    /// );
+    /// ```
+    pub fn expand_with<I, K, V, S>(&self, values: I) -> Vec<String>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut value_sets: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (name, values) in values {
+            value_sets
+                .entry(String::from(name.as_ref()))
+                .or_default()
+                .extend(values.into_iter().map(|value| String::from(value.as_ref())));
+        }
+        self.expand_routes(self.routes.iter(), &value_sets)
+    }
+
+    /// Describes what can follow a partial path in the routes of this
+    /// router: the literal text of the next path segment, and whether
+    /// a param or wildcard could continue from there. See
+    /// [`Completion`].
+    ///
+    /// ```rust
+    /// let mut router = routefinder::Router::new();
+    /// router.add("/api/v1/users", ()).unwrap();
+    /// router.add("/api/v2/:resource", ()).unwrap();
+    /// let literals: Vec<_> = router.complete("/api/").literals().map(String::from).collect();
+    /// assert_eq!(literals, ["v1", "v2"]);
+    /// assert!(router.complete("/api/v2/").param());
+    /// assert!(router.complete("/api/v3/").is_empty());
+    /// ```
+    pub fn complete(&self, prefix: &str) -> Completion {
+        Completion::new(self.routes.iter(), prefix)
+    }
+
+    /// Describes how each route of this router applies to a path,
+    /// whether it is selected, matches but is outranked, or does not
+    /// match and why, as structured data for tooling. The
+    /// [`Display`][std::fmt::Display] of an [`Explanation`] lists
+    /// every route, and [`testing::explain`][crate::testing::explain]
+    /// lists only the closest of the routes that do not match.
+    ///
+    /// ```rust
+    /// use routefinder::{Outcome, Router};
+    /// let mut router = Router::new();
+    /// router.add("/users/:id", ()).unwrap();
+    /// router.add("/users/new", ()).unwrap();
+    /// router.add("/posts/:id", ()).unwrap();
+    ///
+    /// let explanation = router.explain("/users/new");
+    /// assert_eq!(explanation.selected().unwrap().route(), "/users/new");
+    /// assert_eq!(
+    ///     explanation.to_string(),
+    ///     concat!(
+    ///         "routes for /users/new:\n",
+    ///         "  > /users/new  selected\n",
+    ///         "    /users/:id  matches, but a route above takes precedence\n",
+    ///         "    /posts/:id  does not match: `users` does not match `posts`",
+    ///     )
+    /// );
+    /// ```
+    pub fn explain<P>(&self, path: &P) -> Explanation
+    where
+        P: AsPath + ?Sized,
+    {
+        Explanation::new(self, path.trimmed())
+    }
+
+    /// Compares the routes that this router and a pending router
+    /// select for each of these paths, such as a sample of real
+    /// traffic, so that the effect of a change to the routes can be
+    /// reviewed before it is deployed. Each router selects its highest
+    /// precedence match without choosing between weighted handlers or
+    /// recording metrics, and the comparisons are in the order of the
+    /// paths.
+    ///
+    /// ```rust
+    /// let mut current = routefinder::Router::new();
+    /// current.add("/users/:id", "user").unwrap();
+    /// current.add("/*", "fallback").unwrap();
+    ///
+    /// let mut pending = current.clone();
+    /// pending.add("/users/me", "me").unwrap();
+    ///
+    /// let comparisons = current.shadow_compare(&pending, ["/users/1", "/users/me", "/about"]);
+    /// let differences: Vec<String> = comparisons
+    ///     .iter()
+    ///     .filter(|comparison| comparison.is_different())
+    ///     .map(ToString::to_string)
+    ///     .collect();
+    /// assert_eq!(differences, ["/users/me: /users/:id -> /users/me"]);
+    /// ```
+    pub fn shadow_compare<Other, P>(
+        &self,
+        pending: &Router<Other>,
+        paths: impl IntoIterator<Item = P>,
+    ) -> Vec<ShadowComparison>
+    where
+        P: AsRef<str>,
+    {
+        paths
+            .into_iter()
+            .map(|path| {
+                let path = path.as_ref();
+                ShadowComparison::new(
+                    path,
+                    self.match_iter(path).next().map(|m| m.route.canonical()),
+                    pending.match_iter(path).next().map(|m| m.route.canonical()),
+                )
+            })
+            .collect()
+    }
+
+    /// Generates `count` random paths shaped like the routes of this
+    /// router, for load-testing and fuzzing an application with
+    /// realistic traffic. Most paths are built from a route with
+    /// random values for its params and wildcard, so they match that
+    /// route. About three in ten are near misses, built the same way
+    /// and then altered by changing a character, dropping or adding a
+    /// segment, or doubling a slash, so they usually do not match. One
+    /// in ten is garbage, made of random segments that may include
+    /// dot segments, percent-encoding, and non-ascii text.
+    ///
+    /// `rng` returns a number in `0..bound` for the bound it is
+    /// called with, as a [`WeightStrategy`] does, so the same paths
+    /// can be generated again from a seeded rng.
+    ///
+    /// ```
+    /// use routefinder::{RandomWeights, Router, WeightStrategy};
+    /// let mut router = Router::new();
+    /// router.add("/users/:id", ()).unwrap();
+    /// router.add("/files/*", ()).unwrap();
+    ///
+    /// let weights = RandomWeights::default();
+    /// let paths = router.sample_paths(|bound| weights.choose(bound), 100);
+    /// assert_eq!(paths.len(), 100);
+    /// assert!(paths.iter().any(|path| router.best_match(path).is_some()));
+    /// ```
+    pub fn sample_paths(&self, mut rng: impl FnMut(u32) -> u32, count: usize) -> Vec<String> {
+        const VALUES: [&str; 8] = ["1", "42", "2024", "a", "abc", "hello-world", "x_y", "Z"];
+        const SPANS: [&str; 3] = ["a/b", "x/1/y", "docs/intro.md"];
+        const GARBAGE: [&str; 10] = [
+            "..",
+            ".",
+            "%zz",
+            "%2F",
+            "caf\u{e9}",
+            "\u{1f600}",
+            "a b",
+            "~",
+            "",
+            "index.php",
+        ];
+
+        let mut next = |bound: usize| rng(bound as u32) as usize % bound.max(1);
+        let routes: Vec<&RouteSpec> = self.routes.iter().collect();
+        let mut paths = Vec::with_capacity(count);
+        while paths.len() < count {
+            let kind = if routes.is_empty() { 9 } else { next(10) };
+            if kind == 9 {
+                let mut path = String::new();
+                for _ in 0..=next(4) {
+                    path.push('/');
+                    path.push_str(GARBAGE[next(GARBAGE.len())]);
+                }
+                paths.push(path);
+                continue;
+            }
+
+            let route = routes[next(routes.len())];
+            let mut values: BTreeMap<String, Vec<String>> = BTreeMap::new();
+            for segment in route.segments() {
+                let (name, pool) = match segment {
+                    Segment::Param(name) | Segment::OptionalParam(name) => (&**name, &VALUES[..]),
+                    Segment::OneOrMore(name) | Segment::ZeroOrMore(name) => (&**name, &SPANS[..]),
+                    Segment::Wildcard => ("*", &SPANS[..]),
+                    _ => continue,
+                };
+                // a number and a word, since constraints often want one
+                let candidates = [pool[next(pool.len())], VALUES[next(3)]];
+                values.insert(
+                    String::from(name),
+                    candidates
+                        .iter()
+                        .map(|value| String::from(*value))
+                        .collect(),
+                );
+            }
+
+            let expanded = route.expand(&values);
+            let mut path = match expanded.len() {
+                0 => route.to_string(),
+                len => expanded[next(len)].clone(),
+            };
+            if route.mode().is_prefix() && next(2) == 0 {
+                path = format!(
+                    "{}/{}",
+                    path.trim_end_matches('/'),
+                    VALUES[next(VALUES.len())]
+                );
+            }
+
+            if kind >= 6 {
+                match next(4) {
+                    0 => {
+                        let positions: Vec<usize> = path
+                            .char_indices()
+                            .filter(|(_, c)| *c != '/')
+                            .map(|(index, _)| index)
+                            .collect();
+                        if !positions.is_empty() {
+                            let index = positions[next(positions.len())];
+                            let len = path[index..].chars().next().map_or(0, char::len_utf8);
+                            let replacement = (b'a' + next(26) as u8) as char;
+                            path.replace_range(index..index + len, &replacement.to_string());
+                        }
+                    }
+                    1 => {
+                        let end = path.trim_end_matches('/').rfind('/').unwrap_or(0);
+                        path.truncate(end.max(1));
+                    }
+                    2 => {
+                        path.push('/');
+                        path.push_str(VALUES[next(VALUES.len())]);
+                    }
+                    _ => path = path.replacen('/', "//", 1),
+                }
+            }
+
+            paths.push(path);
+        }
+
+        paths
+    }
+
+    fn expand_routes<'a>(
+        &self,
+        routes: impl Iterator<Item = &'a RouteSpec>,
+        values: &BTreeMap<String, Vec<String>>,
+    ) -> Vec<String> {
+        let mut seen = BTreeSet::new();
+        routes
+            .flat_map(|route| route.expand(values))
+            .filter(|path| seen.insert(path.clone()))
+            .collect()
+    }
+}
+
+fn trim_slashes(source: &str) -> &str {
+    source.trim_start_matches('/').trim_end_matches('/')
+}
+
+/// an iterator over matches for a given path, in order of
+/// precedence. returned by [`Router::match_iter`]
+///
+/// ```rust
+/// let mut router = routefinder::Router::new();
+/// router.add("/users/:id", "user").unwrap();
+/// router.add("/users/*", "users").unwrap();
+/// router.add("*", "fallback").unwrap();
+///
+/// assert_eq!(*router.match_iter("/users/1").best().unwrap(), "user");
+/// let matches: Vec<_> = router
+///     .match_iter("/users/1")
+///     .filter_routes(|route| route.to_string() != "/users/:id")
+///     .map(|m| *m)
+///     .collect();
+/// assert_eq!(matches, ["users", "fallback"]);
+/// ```
+#[derive(Debug)]
+pub struct MatchIter<'a, 'b, Handler> {
+    router: &'a Router<Handler>,
+    iter: Iter<'a, Handler>,
+    // the deny route that blocks the routes it outranks
+    deny: Option<&'a RouteSpec>,
+    // the reserved prefix that this path is under
+    reservation: Option<&'a RouteSpec>,
+    options: MatchOptions,
+    path: Cow<'b, str>,
+    raw: Cow<'b, str>,
+    rejected: bool,
+    // checked by the guards of routes added with Router::add_guarded
+    context: Option<&'b dyn Any>,
+}
+impl<'a, 'b, Handler> MatchIter<'a, 'b, Handler> {
+    /// Returns the best of the remaining matches, choosing between
+    /// the handlers of a weighted route as [`Router::best_match`]
+    /// does
+    pub fn best(mut self) -> Option<Match<'a, 'b, Handler>> {
+        let router = self.router;
+        self.next().and_then(|best| router.pick_weighted(best))
+    }
+
+    /// Returns an iterator over the remaining matches of the routes
+    /// for which the predicate returns true. Other routes are skipped
+    /// without being applied to the path.
+    pub fn filter_routes(
+        self,
+        mut predicate: impl FnMut(&RouteSpec) -> bool,
+    ) -> impl Iterator<Item = Match<'a, 'b, Handler>> {
+        let MatchIter {
+            iter,
+            deny,
+            reservation,
+            options,
+            path,
+            raw,
+            rejected,
+            context,
+            ..
+        } = self;
+
+        iter.take_while(move |(route, _)| !rejected && deny.is_none_or(|deny| route.outranks(deny)))
+            .filter(move |(route, _)| reservation.is_none_or(|prefix| route.is_under(prefix)))
+            .filter(move |(route, _)| predicate(route))
+            .filter_map(move |(route, handler)| {
+                Match::try_new_in(route, handler, &path, &raw, &options, context)
+            })
+    }
+}
+
+impl<'a, 'b, Handler> Iterator for MatchIter<'a, 'b, Handler> {
+    type Item = Match<'a, 'b, Handler>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rejected {
+            return None;
+        }
+
+        let (path, raw, options, context) = (&self.path, &self.raw, &self.options, self.context);
+        for (route, handler) in &mut self.iter {
+            if self.deny.is_some_and(|deny| !route.outranks(deny)) {
+                self.rejected = true;
+                return None;
+            }
+
+            if self
+                .reservation
+                .is_some_and(|prefix| !route.is_under(prefix))
+            {
+                continue;
+            }
+
+            if let Some(m) = Match::try_new_in(route, handler, path, raw, options, context) {
+                return Some(m);
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.rejected {
+            return (0, Some(0));
+        }
+
+        (0, self.iter.size_hint().1)
+    }
+}
+
+/// an iterator over the routes and handlers of a router, in order of
+/// precedence. returned by [`Router::iter`]
+#[derive(Debug)]
+pub struct Iter<'a, Handler> {
+    routes: route_table::Iter<'a>,
+    handlers: &'a [Option<Handler>],
+}
+
+impl<'a, Handler> Iterator for Iter<'a, Handler> {
+    type Item = (&'a RouteSpec, &'a Handler);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let handlers = self.handlers;
+        self.routes
+            .find_map(|route| Some((route, handlers.get(route.id()?.get())?.as_ref()?)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.routes.size_hint()
+    }
+}
+
+/// an iterator over the routes and mutable handlers of a router, in
+/// order of precedence. returned by [`Router::iter_mut`]
+#[derive(Debug)]
+pub struct IterMut<'a, Handler> {
+    routes: route_table::Iter<'a>,
+    handlers: Vec<Option<&'a mut Handler>>,
+}
+
+impl<'a, Handler> Iterator for IterMut<'a, Handler> {
+    type Item = (&'a RouteSpec, &'a mut Handler);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let handlers = &mut self.handlers;
+        self.routes
+            .find_map(|route| Some((route, handlers.get_mut(route.id()?.get())?.take()?)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.routes.size_hint()
+    }
+}
+
+/// an owning iterator over the routes and handlers of a router, in
+/// order of precedence
+#[derive(Debug)]
+pub struct IntoIter<Handler> {
+    routes: IntoRoutes,
+    handlers: Vec<Option<Handler>>,
+}
+
+impl<Handler> Iterator for IntoIter<Handler> {
+    type Item = (RouteSpec, Handler);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let handlers = &mut self.handlers;
+        self.routes.find_map(|route| {
+            let handler = handlers.get_mut(route.id()?.get())?.take()?;
+            Some((route_table::unwrap(route), handler))
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.routes.size_hint()
     }
 }
@@ -0,0 +1,186 @@
+use crate::{AsPath, Match, RouteId, RouteSpec, Router};
+use std::{
+    borrow::Borrow,
+    collections::BTreeMap,
+    convert::TryInto,
+    fmt::{self, Debug, Formatter},
+};
+
+/// A collection of [`Router`]s keyed by anything orderable, such as a
+/// host or a tenant id, with a shared fallback router that is
+/// consulted when the keyed router has no match.
+///
+/// ```rust
+/// let mut routers = routefinder::RouterMap::new();
+/// routers.add_fallback("/", "shared home").unwrap();
+/// routers.add_fallback("/about", "shared about").unwrap();
+/// routers.add("acme.example.com", "/", "acme home").unwrap();
+///
+/// assert_eq!(*routers.best_match("acme.example.com", "/").unwrap(), "acme home");
+/// assert_eq!(*routers.best_match("acme.example.com", "/about").unwrap(), "shared about");
+/// assert_eq!(*routers.best_match("other.example.com", "/").unwrap(), "shared home");
+/// ```
+pub struct RouterMap<Key, Handler> {
+    routers: BTreeMap<Key, Router<Handler>>,
+    fallback: Router<Handler>,
+}
+
+impl<Key: Debug, Handler> Debug for RouterMap<Key, Handler> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RouterMap")
+            .field("routers", &self.routers)
+            .field("fallback", &self.fallback)
+            .finish()
+    }
+}
+
+impl<Key, Handler> Default for RouterMap<Key, Handler> {
+    fn default() -> Self {
+        Self {
+            routers: Default::default(),
+            fallback: Default::default(),
+        }
+    }
+}
+
+impl<Key: Ord, Handler> RouterMap<Key, Handler> {
+    /// Builds a new router map
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a route to the router for this key, creating that router
+    /// if needed. See [`Router::add`]
+    pub fn add<R>(
+        &mut self,
+        key: Key,
+        route: R,
+        handler: Handler,
+    ) -> Result<RouteId, <R as TryInto<RouteSpec>>::Error>
+    where
+        R: TryInto<RouteSpec>,
+    {
+        self.routers.entry(key).or_default().add(route, handler)
+    }
+
+    /// Adds a route to the shared fallback router. See [`Router::add`]
+    pub fn add_fallback<R>(
+        &mut self,
+        route: R,
+        handler: Handler,
+    ) -> Result<RouteId, <R as TryInto<RouteSpec>>::Error>
+    where
+        R: TryInto<RouteSpec>,
+    {
+        self.fallback.add(route, handler)
+    }
+
+    /// Returns the best match from the router for this key, or if that
+    /// router does not exist or has no match, from the fallback router
+    pub fn best_match<'a, 'b, Q, P>(
+        &'a self,
+        key: &Q,
+        path: &'b P,
+    ) -> Option<Match<'a, 'b, Handler>>
+    where
+        Key: Borrow<Q>,
+        Q: Ord + ?Sized,
+        P: AsPath + ?Sized,
+    {
+        self.routers
+            .get(key)
+            .and_then(|router| router.best_match(path))
+            .or_else(|| self.fallback.best_match(path))
+    }
+
+    /// Replaces the entire router for this key, returning the previous
+    /// router, if any
+    pub fn insert(&mut self, key: Key, router: Router<Handler>) -> Option<Router<Handler>> {
+        self.routers.insert(key, router)
+    }
+
+    /// Replaces the entire router for this key with the provided
+    /// routes, returning the previous router, if any. If any of the
+    /// routes fail to parse, the existing router is left in place and
+    /// the first error is returned.
+    ///
+    /// ```rust
+    /// let mut routers = routefinder::RouterMap::new();
+    /// routers.add("acme", "/old", "old").unwrap();
+    ///
+    /// assert!(routers.reload("acme", vec![("/new", "new"), ("*bad", "bad")]).is_err());
+    /// assert!(routers.best_match("acme", "/old").is_some());
+    ///
+    /// let previous = routers.reload("acme", vec![("/new", "new")]).unwrap();
+    /// assert_eq!(previous.unwrap().len(), 1);
+    /// assert!(routers.best_match("acme", "/old").is_none());
+    /// assert!(routers.best_match("acme", "/new").is_some());
+    /// ```
+    pub fn reload<I, R>(
+        &mut self,
+        key: Key,
+        routes: I,
+    ) -> Result<Option<Router<Handler>>, <R as TryInto<RouteSpec>>::Error>
+    where
+        I: IntoIterator<Item = (R, Handler)>,
+        R: TryInto<RouteSpec>,
+    {
+        let mut router = Router::new();
+        for (route, handler) in routes {
+            router.add(route, handler)?;
+        }
+        Ok(self.insert(key, router))
+    }
+
+    /// Removes and returns the router for this key
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<Router<Handler>>
+    where
+        Key: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.routers.remove(key)
+    }
+
+    /// Returns the router for this key, if any
+    pub fn get<Q>(&self, key: &Q) -> Option<&Router<Handler>>
+    where
+        Key: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.routers.get(key)
+    }
+
+    /// Returns a mutable reference to the router for this key, if any
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut Router<Handler>>
+    where
+        Key: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.routers.get_mut(key)
+    }
+
+    /// Returns the shared fallback router
+    pub fn fallback(&self) -> &Router<Handler> {
+        &self.fallback
+    }
+
+    /// Returns a mutable reference to the shared fallback router
+    pub fn fallback_mut(&mut self) -> &mut Router<Handler> {
+        &mut self.fallback
+    }
+
+    /// Returns an iterator over the keys that have routers, in order
+    pub fn keys(&self) -> impl Iterator<Item = &Key> {
+        self.routers.keys()
+    }
+
+    /// returns the number of keyed routers, not including the fallback
+    pub fn len(&self) -> usize {
+        self.routers.len()
+    }
+
+    /// returns true if there are no keyed routers
+    pub fn is_empty(&self) -> bool {
+        self.routers.is_empty()
+    }
+}
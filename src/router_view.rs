@@ -0,0 +1,124 @@
+use crate::{AsPath, Match, Router};
+use std::fmt::{self, Debug, Formatter};
+
+/// A read-only view of several [`Router`]s as if they were one, such
+/// as the routers of each plugin of an application, without copying
+/// their routes
+///
+/// Unlike a [`Stack`][crate::Stack], where the first router with any
+/// match wins, a path is matched by the route of highest precedence
+/// among all of the routers. Routes of equal precedence are tried in
+/// the order of their routers. Each router applies its own match
+/// options, limits, deny routes, and reserved prefixes to the path.
+/// Each router is numbered from zero in the order it was given, and
+/// matches are returned along with that number.
+///
+/// ```rust
+/// use routefinder::{Router, RouterView};
+/// let mut app = Router::new();
+/// app.add("*", "fallback").unwrap();
+/// app.add("/users/:id", "user").unwrap();
+/// let mut admin = Router::new();
+/// admin.add("/users/:id/edit", "edit user").unwrap();
+/// admin.add("/users/me", "me").unwrap();
+///
+/// let view = RouterView::union([&app, &admin]);
+/// assert_eq!(*view.best_match("/users/1").unwrap().1, "user");
+/// assert_eq!(*view.best_match("/users/me").unwrap().1, "me");
+/// assert_eq!(view.best_match("/users/1/edit").unwrap().0, 1);
+/// assert_eq!(*view.best_match("/about").unwrap().1, "fallback");
+/// ```
+pub struct RouterView<'a, Handler> {
+    routers: Vec<&'a Router<Handler>>,
+}
+
+impl<Handler> Debug for RouterView<'_, Handler> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(&self.routers).finish()
+    }
+}
+
+impl<Handler> Clone for RouterView<'_, Handler> {
+    fn clone(&self) -> Self {
+        Self {
+            routers: self.routers.clone(),
+        }
+    }
+}
+
+impl<'a, Handler> RouterView<'a, Handler> {
+    /// Builds a view of these routers, which are numbered in order
+    pub fn union(routers: impl IntoIterator<Item = &'a Router<Handler>>) -> Self {
+        Self {
+            routers: routers.into_iter().collect(),
+        }
+    }
+
+    /// Returns the best match among all of the routers for this path,
+    /// along with the number of the router it is from. See
+    /// [`Router::best_match`].
+    pub fn best_match<'b, P>(&self, path: &'b P) -> Option<(usize, Match<'a, 'b, Handler>)>
+    where
+        P: AsPath + ?Sized,
+    {
+        let mut best: Option<(usize, Match<'a, 'b, Handler>)> = None;
+        for (index, router) in self.routers.iter().enumerate() {
+            let m = match router.match_iter(path).best() {
+                Some(m) => m,
+                None => continue,
+            };
+
+            if best
+                .as_ref()
+                .is_none_or(|(_, best)| m.route().outranks(best.route()))
+            {
+                best = Some((index, m));
+            }
+        }
+
+        #[cfg(feature = "metrics")]
+        if let Some((index, m)) = &best {
+            self.routers[*index].record(Some(m));
+        }
+        best
+    }
+
+    /// Returns every match among all of the routers for this path, in
+    /// order of precedence, along with the number of the router each
+    /// is from. See [`Router::matches`].
+    pub fn matches<'b, P>(&self, path: &'b P) -> Vec<(usize, Match<'a, 'b, Handler>)>
+    where
+        P: AsPath + ?Sized,
+    {
+        let mut matches: Vec<_> = self
+            .routers
+            .iter()
+            .enumerate()
+            .flat_map(|(index, router)| router.match_iter(path).map(move |m| (index, m)))
+            .collect();
+        // a stable sort keeps routes of equal precedence in the order
+        // of their routers
+        matches.sort_by(|(_, a), (_, b)| b.route().cmp_precedence(a.route()));
+        matches
+    }
+
+    /// Returns the router with this number, if any
+    pub fn router(&self, index: usize) -> Option<&'a Router<Handler>> {
+        self.routers.get(index).copied()
+    }
+
+    /// Returns an iterator over the routers, in order
+    pub fn iter(&self) -> impl Iterator<Item = &'a Router<Handler>> + '_ {
+        self.routers.iter().copied()
+    }
+
+    /// returns the number of routers
+    pub fn len(&self) -> usize {
+        self.routers.len()
+    }
+
+    /// returns true if there are no routers
+    pub fn is_empty(&self) -> bool {
+        self.routers.is_empty()
+    }
+}
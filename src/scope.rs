@@ -0,0 +1,191 @@
+use crate::{Capture, Captures, Collision, RouteId, RouteSpec, Router, Segment, Transaction};
+
+/// Adds routes under the prefix of a [`Router::scope_with_param`], in
+/// a [`Transaction`] that is applied when the scope closes.
+#[derive(Debug)]
+pub struct Scope<'scope, 'router, Handler> {
+    transaction: &'scope mut Transaction<'router, Handler>,
+    prefix: &'scope RouteSpec,
+    source: &'scope str,
+    ids: Vec<RouteId>,
+}
+
+impl<'scope, 'router, Handler> Scope<'scope, 'router, Handler> {
+    /// Stages a route under the prefix, returning the [`RouteId`] it
+    /// will be assigned. Returns an error if the route does not parse,
+    /// or if it has a param with the same name as one of the prefix.
+    pub fn add(&mut self, route: &str, handler: Handler) -> Result<RouteId, String> {
+        let spec: RouteSpec = route.parse()?;
+        if let Some(name) = spec
+            .segments()
+            .iter()
+            .filter_map(Segment::name)
+            .find(|name| self.params().any(|param| param == *name))
+        {
+            return Err(format!(
+                "`{}` is already a param of the scope `{}`",
+                name, self.prefix
+            ));
+        }
+
+        let joined = format!(
+            "{}/{}",
+            self.source.trim_end_matches('/'),
+            route.trim_start_matches('/')
+        );
+        let id = self.transaction.add(joined.as_str(), handler)?;
+        self.ids.push(id);
+        Ok(id)
+    }
+
+    /// Returns the prefix of the scope
+    pub fn prefix(&self) -> &RouteSpec {
+        self.prefix
+    }
+
+    pub(crate) fn into_ids(self) -> Vec<RouteId> {
+        self.ids
+    }
+
+    /// Returns the names of the params of the prefix, which every
+    /// route in the scope captures
+    pub fn params(&self) -> impl Iterator<Item = &str> + '_ {
+        self.prefix.segments().iter().filter_map(Segment::name)
+    }
+}
+
+/// The routes added in a [`Router::scope_with_param`], for reverse
+/// lookups that fill the params of the prefix once. See
+/// [`RouteScope::bind`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteScope {
+    prefix: RouteSpec,
+    ids: Vec<RouteId>,
+}
+
+impl RouteScope {
+    /// checks that a prefix has at least one param, and that every
+    /// segment of it is required, so that every route in the scope
+    /// captures its params
+    pub(crate) fn new(prefix: &str) -> Result<Self, String> {
+        let prefix: RouteSpec = prefix.parse()?;
+        let mut params = 0;
+        for segment in prefix.segments() {
+            match segment {
+                Segment::Param(_) => params += 1,
+                Segment::Exact(_) | Segment::Slash | Segment::Dot => {}
+                _ => {
+                    return Err(format!(
+                        "the scope `{}` can only have required params, found `{}`",
+                        prefix, segment
+                    ))
+                }
+            }
+        }
+
+        if params == 0 {
+            return Err(format!("the scope `{}` has no params", prefix));
+        }
+
+        Ok(Self {
+            prefix,
+            ids: vec![],
+        })
+    }
+
+    pub(crate) fn scope<'scope, 'router, Handler>(
+        &'scope self,
+        transaction: &'scope mut Transaction<'router, Handler>,
+        source: &'scope str,
+    ) -> Scope<'scope, 'router, Handler> {
+        Scope {
+            transaction,
+            prefix: &self.prefix,
+            source,
+            ids: vec![],
+        }
+    }
+
+    pub(crate) fn with_ids(mut self, ids: Vec<RouteId>) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    /// Returns the prefix of the scope
+    pub fn prefix(&self) -> &RouteSpec {
+        &self.prefix
+    }
+
+    /// Returns the names of the params of the prefix
+    pub fn params(&self) -> impl Iterator<Item = &str> + '_ {
+        self.prefix.segments().iter().filter_map(Segment::name)
+    }
+
+    /// Returns the ids of the routes in the scope, in the order they
+    /// were added
+    pub fn ids(&self) -> &[RouteId] {
+        &self.ids
+    }
+
+    /// Returns true if the route with this id is in the scope
+    pub fn contains(&self, id: RouteId) -> bool {
+        self.ids.contains(&id)
+    }
+
+    /// Fills the params of the prefix, such as the tenant of a
+    /// multi-tenant api, for reverse lookups of the routes in the
+    /// scope
+    pub fn bind<'scope, 'keys, 'values>(
+        &'scope self,
+        values: Captures<'keys, 'values>,
+    ) -> BoundScope<'scope, 'keys, 'values> {
+        BoundScope {
+            scope: self,
+            values,
+        }
+    }
+}
+
+/// A [`RouteScope`] with values for the params of its prefix. Returned
+/// by [`RouteScope::bind`].
+#[derive(Debug)]
+pub struct BoundScope<'scope, 'keys, 'values> {
+    scope: &'scope RouteScope,
+    values: Captures<'keys, 'values>,
+}
+
+impl BoundScope<'_, '_, '_> {
+    /// Returns the path for the route in the scope with this id, with
+    /// the bound values for the params of the prefix and these
+    /// captures for the rest of the route. Returns None if the route
+    /// is not in the scope, or the captures do not fill it.
+    pub fn url_for<Handler>(
+        &self,
+        router: &Router<Handler>,
+        id: RouteId,
+        captures: &Captures<'_, '_>,
+    ) -> Option<String> {
+        if !self.scope.contains(id) {
+            return None;
+        }
+
+        let mut values = owned(&self.values);
+        values.merge(owned(captures), Collision::KeepFirst);
+        router
+            .spec(id)?
+            .template(&values)
+            .map(|reverse_match| reverse_match.to_string())
+    }
+}
+
+/// copies captures, which are not `Clone`
+fn owned(captures: &Captures<'_, '_>) -> Captures<'static, 'static> {
+    let mut owned: Captures<'static, 'static> = captures
+        .iter()
+        .map(|(name, value)| Capture::new(name.to_string(), value.to_string()))
+        .collect();
+    if let Some(wildcard) = captures.wildcard() {
+        owned.set_wildcard(wildcard.to_string());
+    }
+    owned
+}
@@ -0,0 +1,133 @@
+use crate::{Captures, RouteScope, RouteSpec};
+use std::{
+    fmt::{self, Debug, Formatter},
+    marker::PhantomData,
+    ops::Deref,
+};
+
+/// A typed view of the params of a scope's prefix, so that code for the
+/// routes of a [`Router::typed_scope`][crate::Router::typed_scope]
+/// reads them as fields rather than looking each one up in the
+/// [`Captures`]. This is implemented by the structs that
+/// [`scope_params!`][crate::scope_params] defines.
+pub trait ScopeParams {
+    /// the names of the params, which are the names of the fields
+    const NAMES: &'static [&'static str];
+
+    /// the struct of params, borrowing from the captures
+    type Params<'captures>;
+
+    /// reads the params from captures, returning None if any is absent
+    fn from_captures<'captures>(
+        captures: &'captures Captures<'_, '_>,
+    ) -> Option<Self::Params<'captures>>;
+}
+
+/// Defines a struct with a `&str` field for each param of a scope's
+/// prefix, and implements [`ScopeParams`] for it. See
+/// [`Router::typed_scope`][crate::Router::typed_scope].
+///
+/// ```rust
+/// routefinder::scope_params! {
+///     /// the params of `/orgs/:org/teams/:team`
+///     pub struct TeamParams { org, team }
+/// }
+///
+/// let captures = [("org", "acme"), ("team", "core")].into();
+/// let params = <TeamParams as routefinder::ScopeParams>::from_captures(&captures).unwrap();
+/// assert_eq!(params, TeamParams { org: "acme", team: "core" });
+/// ```
+#[macro_export]
+macro_rules! scope_params {
+    ($(#[$meta:meta])* $vis:vis struct $name:ident { $($field:ident),+ $(,)? }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        $vis struct $name<'captures> {
+            $(
+                #[allow(missing_docs)]
+                pub $field: &'captures str,
+            )+
+        }
+
+        impl $crate::ScopeParams for $name<'static> {
+            const NAMES: &'static [&'static str] = &[$(stringify!($field)),+];
+
+            type Params<'captures> = $name<'captures>;
+
+            fn from_captures<'captures>(
+                captures: &'captures $crate::Captures<'_, '_>,
+            ) -> Option<Self::Params<'captures>> {
+                Some($name {
+                    $($field: captures.get(stringify!($field))?,)+
+                })
+            }
+        }
+    };
+}
+
+/// A [`RouteScope`] whose prefix has exactly the params of a
+/// [`ScopeParams`] struct. Returned by
+/// [`Router::typed_scope`][crate::Router::typed_scope], and
+/// dereferences to the [`RouteScope`].
+pub struct TypedScope<Params> {
+    scope: RouteScope,
+    params: PhantomData<fn() -> Params>,
+}
+
+impl<Params> Debug for TypedScope<Params> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TypedScope").field(&self.scope).finish()
+    }
+}
+
+impl<Params: ScopeParams> TypedScope<Params> {
+    /// checks that the params of the scope's prefix are the fields of
+    /// the struct
+    pub(crate) fn new(scope: RouteScope) -> Result<Self, String> {
+        let mut prefix: Vec<&str> = scope.params().collect();
+        let mut fields = Params::NAMES.to_vec();
+        prefix.sort_unstable();
+        fields.sort_unstable();
+        if prefix != fields {
+            return Err(format!(
+                "the scope `{}` has params {:?}, but the struct has fields {:?}",
+                scope.prefix(),
+                prefix,
+                fields
+            ));
+        }
+
+        Ok(Self {
+            scope,
+            params: PhantomData,
+        })
+    }
+
+    /// Returns the params of the prefix from the captures of a match of
+    /// this route. Every route in the scope captures them, so this
+    /// only returns None if the route is not in the scope.
+    pub fn params<'captures>(
+        &self,
+        route: &RouteSpec,
+        captures: &'captures Captures<'_, '_>,
+    ) -> Option<Params::Params<'captures>> {
+        if !route.id().is_some_and(|id| self.scope.contains(id)) {
+            return None;
+        }
+
+        Params::from_captures(captures)
+    }
+
+    /// Returns the untyped [`RouteScope`]
+    pub fn into_inner(self) -> RouteScope {
+        self.scope
+    }
+}
+
+impl<Params> Deref for TypedScope<Params> {
+    type Target = RouteScope;
+
+    fn deref(&self) -> &Self::Target {
+        &self.scope
+    }
+}
@@ -1,10 +1,19 @@
 use smartstring::alias::String as SmartString;
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
 /// the internal representation of a parsed component of a route
 ///
 /// as an example, `/hello/:planet/*` would be represented as the
 /// following sequence `[Exact("hello"), Slash, Param("planet"),
 /// Slash, Wildcard]`
-#[derive(Debug, PartialEq, Eq, Clone)]
+///
+/// New kinds of segment may be added as the route syntax grows, so
+/// matches on a segment need a wildcard arm.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[non_exhaustive]
 pub enum Segment {
     /// represented by a / in the route spec and matching one /
     Slash,
@@ -17,10 +26,30 @@ pub enum Segment {
     /// available in [`Captures`][crate::Captures]. Param captures up to the next slash
     /// or dot, whichever is next in the spec.
     Param(SmartString),
+    /// represented by :name?, this behaves like a [`Segment::Param`]
+    /// that may also match nothing, along with the slash before it.
+    /// When it matches nothing, it is absent from the
+    /// [`Captures`][crate::Captures].
+    OptionalParam(SmartString),
+    /// represented by :name+, this captures one or more path
+    /// segments, including the slashes between them, as a named param
+    OneOrMore(SmartString),
+    /// represented by :name*, this is like [`Segment::OneOrMore`] but
+    /// may also match nothing, in which case it is absent from the
+    /// [`Captures`][crate::Captures]
+    ZeroOrMore(SmartString),
+    /// represented by a segment containing `*` or `?` in the
+    /// [`Syntax::Glob`][crate::Syntax::Glob] dialect, this matches a
+    /// single path segment against a shell-style pattern, where `*`
+    /// matches any run of characters and `?` matches exactly one
+    /// character. Globs do not capture anything.
+    Glob(SmartString),
     /// represented by * in the spec, this will capture everything up
     /// to the end of the path. a wildcard will also match nothing
-    /// (similar to the regex `(.*)$`). There can only be one wildcard
-    /// per route spec
+    /// (similar to the regex `(.*)$`). A wildcard is usually the last
+    /// segment of a route; when it is not, it matches the longest run
+    /// of path segments that still lets the rest of the route
+    /// match. Only one wildcard per route spec is captured
     Wildcard,
 }
 
@@ -30,26 +59,132 @@ impl PartialOrd for Segment {
     }
 }
 
+impl Segment {
+    /// the name of the capture for this segment, if it is a named
+    /// capture
+    pub(crate) fn name(&self) -> Option<&str> {
+        match self {
+            Segment::Param(name)
+            | Segment::OptionalParam(name)
+            | Segment::OneOrMore(name)
+            | Segment::ZeroOrMore(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    /// whether this segment contributes to the [`Captures`][crate::Captures]
+    pub(crate) fn is_capture(&self) -> bool {
+        self.name().is_some() || matches!(self, Segment::Wildcard)
+    }
+
+    /// whether this segment can match an empty path, in which case a
+    /// slash before it is also optional
+    pub(crate) fn may_be_empty(&self) -> bool {
+        matches!(
+            self,
+            Segment::Wildcard | Segment::OptionalParam(_) | Segment::ZeroOrMore(_)
+        )
+    }
+
+    /// Returns the precedence of this segment compared to other
+    /// segments in the same position of another route, where a
+    /// greater precedence is more specific. This is the order of
+    /// [`Segment`]'s [`Ord`] implementation, which
+    /// [`RouteSpec`][crate::RouteSpec] uses to compare segments.
+    ///
+    /// Exact text is the most specific, followed by globs, params,
+    /// optional params, one-or-more params, zero-or-more params, and
+    /// wildcards. Slashes and dots have the lowest precedence, and are
+    /// equal to each other.
+    ///
+    /// ```rust
+    /// use routefinder::Segment;
+    /// assert!(Segment::Exact("users".into()).precedence() > Segment::Param("id".into()).precedence());
+    /// assert!(Segment::Param("id".into()).precedence() > Segment::Wildcard.precedence());
+    /// assert_eq!(Segment::Slash.precedence(), Segment::Dot.precedence());
+    /// ```
+    pub fn precedence(&self) -> u8 {
+        match self {
+            Segment::Exact(_) => 7,
+            Segment::Glob(_) => 6,
+            Segment::Param(_) => 5,
+            Segment::OptionalParam(_) => 4,
+            Segment::OneOrMore(_) => 3,
+            Segment::ZeroOrMore(_) => 2,
+            Segment::Wildcard => 1,
+            Segment::Slash | Segment::Dot => 0,
+        }
+    }
+}
+
 impl Ord for Segment {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        use std::cmp::Ordering::*;
-        use Segment::*;
-        match (self, other) {
-            (Exact(_), Exact(_))
-            | (Slash, Slash)
-            | (Dot, Slash)
-            | (Slash, Dot)
-            | (Dot, Dot)
-            | (Param(_), Param(_))
-            | (Wildcard, Wildcard) => Equal,
-
-            (Dot, _) => Greater,
-            (Exact(_), _) => Greater,
-            (Param(_), Exact(_)) => Less,
-            (Param(_), _) => Greater,
-            (Wildcard, Exact(_)) | (Wildcard, Param(_)) => Less,
-            (Wildcard, _) => Greater,
-            _ => Less,
+        self.precedence().cmp(&other.precedence())
+    }
+}
+
+/// Segments are displayed as they appear in a route spec, such as
+/// `:name?` for an [`Segment::OptionalParam`]. The constraint of a
+/// param, if any, belongs to the [`RouteSpec`][crate::RouteSpec] and
+/// is not displayed.
+impl Display for Segment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Segment::Slash => f.write_str("/"),
+            Segment::Dot => f.write_str("."),
+            Segment::Exact(s) => f.write_str(s),
+            Segment::Glob(g) => f.write_str(g),
+            Segment::Wildcard => f.write_str("*"),
+            Segment::Param(p) => write!(f, ":{}", p),
+            Segment::OptionalParam(p) => write!(f, ":{}?", p),
+            Segment::OneOrMore(p) => write!(f, ":{}+", p),
+            Segment::ZeroOrMore(p) => write!(f, ":{}*", p),
         }
     }
 }
+
+/// Parses a single segment as it is displayed. Text that contains
+/// `*` or `?` is parsed as a [`Segment::Glob`], and any other text
+/// that is not a slash, dot, wildcard, or param is parsed as a
+/// [`Segment::Exact`].
+///
+/// ```rust
+/// use routefinder::Segment;
+/// assert_eq!("/".parse(), Ok(Segment::Slash));
+/// assert_eq!(":id".parse(), Ok(Segment::Param("id".into())));
+/// assert_eq!(":rest*".parse(), Ok(Segment::ZeroOrMore("rest".into())));
+/// assert_eq!("*.css".parse(), Ok(Segment::Glob("*.css".into())));
+/// assert_eq!("users".parse::<Segment>().unwrap().to_string(), "users");
+/// assert!(":".parse::<Segment>().is_err());
+/// ```
+impl FromStr for Segment {
+    type Err = String;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let segment = match source {
+            "" => return Err(String::from("segments cannot be empty")),
+            "/" => Segment::Slash,
+            "." => Segment::Dot,
+            "*" => Segment::Wildcard,
+            _ if source.starts_with(':') => {
+                let name = &source[1..];
+                let (name, segment): (_, fn(SmartString) -> Segment) = match name.as_bytes().last()
+                {
+                    Some(b'?') => (&name[..name.len() - 1], Segment::OptionalParam),
+                    Some(b'+') => (&name[..name.len() - 1], Segment::OneOrMore),
+                    Some(b'*') => (&name[..name.len() - 1], Segment::ZeroOrMore),
+                    _ => (name, Segment::Param),
+                };
+
+                if name.is_empty() {
+                    return Err(String::from("params must be named"));
+                }
+                segment(SmartString::from(name))
+            }
+            _ if source.contains(['*', '?']) => Segment::Glob(source.into()),
+            _ => Segment::Exact(source.into()),
+        };
+
+        Ok(segment)
+    }
+}
@@ -0,0 +1,41 @@
+/// What a [`ReverseMatch`][crate::ReverseMatch] does with param values
+/// that contain a separator, since the route would not match the
+/// resulting path with the same captures. See
+/// [`ReverseMatch::with_separators`][crate::ReverseMatch::with_separators].
+///
+/// The value of a `:param` or `:param?` cannot contain a `/`, and
+/// when the param is followed by a dot, as in `:name.:ext`, it cannot
+/// contain a `.` either. Multi-segment params and wildcards may
+/// contain slashes.
+///
+/// ```rust
+/// use routefinder::{Captures, RouteSpec, SeparatorPolicy, TemplateError};
+/// let spec: RouteSpec = "/files/:name.:ext".parse().unwrap();
+/// let captures: Captures = [("name", "a/b.c"), ("ext", "txt")].into();
+/// let reverse_match = spec.template(&captures).unwrap();
+/// assert_eq!(reverse_match.to_string(), "/files/a/b.c.txt");
+///
+/// assert_eq!(
+///     reverse_match.with_separators(SeparatorPolicy::Reject).unwrap_err(),
+///     TemplateError::Separator { name: "name".into(), value: "a/b.c".into() }
+/// );
+///
+/// let escaped = reverse_match.with_separators(SeparatorPolicy::Escape).unwrap();
+/// assert_eq!(escaped.to_string(), "/files/a%2Fb%2Ec.txt");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SeparatorPolicy {
+    /// Values are written as they are, even if they contain
+    /// separators. This is the default.
+    #[default]
+    Allow,
+
+    /// Values that contain separators are a
+    /// [`TemplateError::Separator`][crate::TemplateError::Separator]
+    Reject,
+
+    /// Separators in values are percent-encoded, as `%2F` and `%2E`,
+    /// so the path matches the route, and decoding the captures gives
+    /// back the original values
+    Escape,
+}
@@ -0,0 +1,60 @@
+use std::fmt::{self, Display, Formatter};
+
+/// How the current and pending routers route one path. Returned by
+/// [`Router::shadow_compare`][crate::Router::shadow_compare].
+///
+/// Routes are compared by their
+/// [`RouteSpec::canonical`][crate::RouteSpec::canonical] form, so a
+/// route that is added to both routers selects the same route in each,
+/// regardless of its handler or id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShadowComparison {
+    path: String,
+    current: Option<String>,
+    pending: Option<String>,
+}
+
+impl ShadowComparison {
+    pub(crate) fn new(path: &str, current: Option<String>, pending: Option<String>) -> Self {
+        Self {
+            path: path.to_string(),
+            current,
+            pending,
+        }
+    }
+
+    /// Returns the path that was compared
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the canonical form of the route that the current router
+    /// selects for the path, if any
+    pub fn current(&self) -> Option<&str> {
+        self.current.as_deref()
+    }
+
+    /// Returns the canonical form of the route that the pending router
+    /// selects for the path, if any
+    pub fn pending(&self) -> Option<&str> {
+        self.pending.as_deref()
+    }
+
+    /// Returns true if the routers select different routes for the
+    /// path, or only one of them matches it
+    pub fn is_different(&self) -> bool {
+        self.current != self.pending
+    }
+}
+
+impl Display for ShadowComparison {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let current = self.current().unwrap_or("no match");
+        if self.is_different() {
+            let pending = self.pending().unwrap_or("no match");
+            write!(f, "{}: {} -> {}", self.path, current, pending)
+        } else {
+            write!(f, "{}: {}", self.path, current)
+        }
+    }
+}
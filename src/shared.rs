@@ -0,0 +1,45 @@
+use crate::{Match, RouteId, RouteSpec, Router};
+use std::{convert::TryInto, sync::Arc};
+
+impl<Handler: ?Sized> Router<Arc<Handler>> {
+    /// Adds a route whose handler is shared with other routes, or with
+    /// other routers, such as a compiled template or a connection
+    /// pool. The router stores a reference to the handler, so the
+    /// handler itself does not need to implement [`Clone`] for the
+    /// router to be cloned or [snapshotted][Router::snapshot], and it
+    /// can be unsized, such as a `dyn Fn`. See [`Match::shared`].
+    ///
+    /// ```rust
+    /// use std::sync::Arc;
+    /// let mut router = routefinder::Router::new();
+    /// let render: Arc<dyn Fn(&str) -> String + Send + Sync> =
+    ///     Arc::new(|name| format!("hello, {}", name));
+    /// let hello = router.add_shared("/hello/:name", Arc::clone(&render)).unwrap();
+    /// router.add_shared("/hi/:name", render).unwrap();
+    ///
+    /// let snapshot = router.snapshot();
+    /// let m = snapshot.best_match("/hi/world").unwrap();
+    /// let handler = m.shared();
+    /// assert_eq!(handler(m.captures().get("name").unwrap()), "hello, world");
+    /// assert!(Arc::ptr_eq(&handler, router.handler(hello).unwrap()));
+    /// ```
+    pub fn add_shared<R>(
+        &mut self,
+        route: R,
+        handler: Arc<Handler>,
+    ) -> Result<RouteId, <R as TryInto<RouteSpec>>::Error>
+    where
+        R: TryInto<RouteSpec>,
+    {
+        self.add(route, handler)
+    }
+}
+
+impl<Handler: ?Sized> Match<'_, '_, Arc<Handler>> {
+    /// Returns a new reference to the shared handler of this match,
+    /// which can outlive the match and the router. See
+    /// [`Router::add_shared`].
+    pub fn shared(&self) -> Arc<Handler> {
+        Arc::clone(self.handler())
+    }
+}
@@ -0,0 +1,82 @@
+//! Byte comparisons and scans that work on eight bytes at a time
+//!
+//! This is available with the `simd` feature. Rather than platform
+//! intrinsics or nightly `std::simd`, each eight byte chunk is loaded
+//! into a `u64` and compared in a handful of integer operations, so the
+//! crate stays free of unsafe code and builds on stable for every
+//! target.
+
+const ONES: u64 = u64::from_ne_bytes([0x01; 8]);
+const HIGHS: u64 = u64::from_ne_bytes([0x80; 8]);
+
+/// sets the high bit of each zero byte of this word. bytes after the
+/// first zero byte may also be set, so only the lowest set bit is
+/// meaningful
+#[inline]
+fn zero_bytes(word: u64) -> u64 {
+    word.wrapping_sub(ONES) & !word & HIGHS
+}
+
+#[inline]
+fn load(chunk: &[u8]) -> u64 {
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(chunk);
+    u64::from_le_bytes(bytes)
+}
+
+/// finds the first byte for which `found` returns a nonzero mask
+#[inline]
+fn scan(
+    haystack: &[u8],
+    found: impl Fn(u64) -> u64,
+    matches: impl Fn(u8) -> bool,
+) -> Option<usize> {
+    let mut chunks = haystack.chunks_exact(8);
+    for (index, chunk) in chunks.by_ref().enumerate() {
+        let mask = found(load(chunk));
+        if mask != 0 {
+            return Some(index * 8 + mask.trailing_zeros() as usize / 8);
+        }
+    }
+
+    let offset = haystack.len() - chunks.remainder().len();
+    let position = chunks.remainder().iter().position(|byte| matches(*byte));
+    position.map(|position| offset + position)
+}
+
+/// the index of the first occurrence of `needle` in `haystack`
+pub(crate) fn find(needle: u8, haystack: &[u8]) -> Option<usize> {
+    let needles = ONES * u64::from(needle);
+    scan(
+        haystack,
+        |word| zero_bytes(word ^ needles),
+        |byte| byte == needle,
+    )
+}
+
+/// the index of the first occurrence of either byte in `haystack`
+pub(crate) fn find2(first: u8, second: u8, haystack: &[u8]) -> Option<usize> {
+    let (firsts, seconds) = (ONES * u64::from(first), ONES * u64::from(second));
+    scan(
+        haystack,
+        |word| zero_bytes(word ^ firsts) | zero_bytes(word ^ seconds),
+        |byte| byte == first || byte == second,
+    )
+}
+
+/// whether `haystack` begins with `prefix`
+pub(crate) fn starts_with(haystack: &str, prefix: &str) -> bool {
+    let (haystack, prefix) = (haystack.as_bytes(), prefix.as_bytes());
+    let haystack = match haystack.get(..prefix.len()) {
+        Some(haystack) => haystack,
+        None => return false,
+    };
+
+    let mut chunks = haystack.chunks_exact(8).zip(prefix.chunks_exact(8));
+    if !chunks.all(|(mine, theirs)| load(mine) == load(theirs)) {
+        return false;
+    }
+
+    let tail = prefix.len() - prefix.len() % 8;
+    haystack[tail..] == prefix[tail..]
+}
@@ -0,0 +1,39 @@
+use std::fmt::{self, Display, Formatter};
+
+/// An encoding of the precedence of a [`RouteSpec`][crate::RouteSpec]
+/// as a string of ascii characters, returned by
+/// [`RouteSpec::sort_key`][crate::RouteSpec::sort_key]. Sort keys
+/// compare as the routes do, so routes sorted by key, including as
+/// text in a database or config store, are in order of precedence,
+/// with the route that is tried first as the least. With the `serde`
+/// feature, this is serializable.
+///
+/// The key includes the route's [`RouteId`][crate::RouteId], which
+/// orders routes of equal precedence, so keys from different routers
+/// are only ordered by precedence up to the ids.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SortKey(String);
+
+impl SortKey {
+    pub(crate) fn new(key: String) -> Self {
+        Self(key)
+    }
+
+    /// Returns the key as a string
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for SortKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<SortKey> for String {
+    fn from(key: SortKey) -> Self {
+        key.0
+    }
+}
@@ -0,0 +1,70 @@
+//! Splitting of routes and paths at their ascii delimiters
+//!
+//! Routes and paths are split at `/` and `.`, which are single bytes
+//! that never occur within a multi-byte character, so every index
+//! returned here is a char boundary and may be used to slice the str
+//! that it was found in. Everything that splits a route or a path by
+//! position goes through these functions rather than mixing byte
+//! indices with counts of chars.
+
+use std::iter;
+
+/// the sections of a route between its delimiters, each with the
+/// delimiter that precedes it, if any
+pub(crate) fn sections(source: &str) -> impl Iterator<Item = (Option<char>, &str)> {
+    let mut start: usize = 0;
+    delimiters(source)
+        .map(Some)
+        .chain(iter::once(None))
+        .map(move |delimiter| {
+            let end = delimiter.map_or(source.len(), |(index, _)| index);
+            let preceding = start
+                .checked_sub(1)
+                .map(|index| char::from(source.as_bytes()[index]));
+            let section = &source[start..end];
+            start = end + 1;
+            (preceding, section)
+        })
+}
+
+/// the byte index and char of each `.` and `/`
+fn delimiters(source: &str) -> impl Iterator<Item = (usize, char)> + '_ {
+    #[cfg(feature = "memchr")]
+    let indices = memchr::memchr2_iter(b'.', b'/', source.as_bytes());
+
+    #[cfg(not(feature = "memchr"))]
+    let indices = source.match_indices(['.', '/']).map(|(index, _)| index);
+
+    indices.map(move |index| (index, char::from(source.as_bytes()[index])))
+}
+
+/// the length of the first char of a str, if any
+pub(crate) fn first_len(path: &str) -> Option<usize> {
+    path.chars().next().map(char::len_utf8)
+}
+
+/// the index of the first occurrence of an ascii byte
+pub(crate) fn find(path: &str, byte: u8) -> Option<usize> {
+    debug_assert!(byte.is_ascii());
+    #[cfg(feature = "simd")]
+    return crate::simd::find(byte, path.as_bytes());
+
+    #[cfg(all(feature = "memchr", not(feature = "simd")))]
+    return memchr::memchr(byte, path.as_bytes());
+
+    #[cfg(not(any(feature = "memchr", feature = "simd")))]
+    return path.find(char::from(byte));
+}
+
+/// the index of the first occurrence of either of two ascii bytes
+pub(crate) fn find2(path: &str, first: u8, second: u8) -> Option<usize> {
+    debug_assert!(first.is_ascii() && second.is_ascii());
+    #[cfg(feature = "simd")]
+    return crate::simd::find2(first, second, path.as_bytes());
+
+    #[cfg(all(feature = "memchr", not(feature = "simd")))]
+    return memchr::memchr2(first, second, path.as_bytes());
+
+    #[cfg(not(any(feature = "memchr", feature = "simd")))]
+    return path.find(|c| c == char::from(first) || c == char::from(second));
+}
@@ -0,0 +1,123 @@
+use crate::{AsPath, Match, Router};
+use std::{
+    fmt::{self, Debug, Formatter},
+    iter::FromIterator,
+};
+
+/// An ordered list of [`Router`]s that are consulted in turn, such as
+/// redirects, then application routes, then static files, then a
+/// fallback
+///
+/// Each router is a layer of the stack, numbered from zero in the
+/// order they were pushed. A path is matched by the first layer that
+/// has any match for it, regardless of the precedence of routes in
+/// later layers, and the match is returned along with its layer.
+///
+/// ```rust
+/// use routefinder::{Router, Stack};
+/// let mut redirects = Router::new();
+/// redirects.add("/old-blog/*", "redirect").unwrap();
+/// let mut app = Router::new();
+/// app.add("/users/:id", "user").unwrap();
+/// app.add("/old-blog/archive", "archive").unwrap();
+/// let mut fallback = Router::new();
+/// fallback.add("*", "not found").unwrap();
+///
+/// let mut stack = Stack::new();
+/// stack.push(redirects);
+/// let app_layer = stack.push(app);
+/// stack.push(fallback);
+///
+/// let (layer, m) = stack.best_match("/users/1").unwrap();
+/// assert_eq!((layer, *m), (app_layer, "user"));
+/// assert_eq!(*stack.best_match("/old-blog/archive").unwrap().1, "redirect");
+/// assert_eq!(stack.best_match("/nothing").unwrap().0, 2);
+/// ```
+pub struct Stack<Handler> {
+    layers: Vec<Router<Handler>>,
+}
+
+impl<Handler> Debug for Stack<Handler> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(&self.layers).finish()
+    }
+}
+
+impl<Handler> Default for Stack<Handler> {
+    fn default() -> Self {
+        Self { layers: vec![] }
+    }
+}
+
+impl<Handler> FromIterator<Router<Handler>> for Stack<Handler> {
+    fn from_iter<T: IntoIterator<Item = Router<Handler>>>(iter: T) -> Self {
+        Self {
+            layers: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<Handler> Stack<Handler> {
+    /// Builds a new empty stack
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a router after every existing layer, returning the number
+    /// of its layer
+    pub fn push(&mut self, router: Router<Handler>) -> usize {
+        self.layers.push(router);
+        self.layers.len() - 1
+    }
+
+    /// Returns the best match from the first layer that matches this
+    /// path, along with the number of that layer
+    pub fn best_match<'a, 'b, P>(&'a self, path: &'b P) -> Option<(usize, Match<'a, 'b, Handler>)>
+    where
+        P: AsPath + ?Sized,
+    {
+        self.layers
+            .iter()
+            .enumerate()
+            .find_map(|(layer, router)| Some((layer, router.best_match(path)?)))
+    }
+
+    /// Returns every match from every layer, in order of layer and
+    /// then of precedence, along with the number of each layer
+    pub fn matches<'a, 'b, P>(&'a self, path: &'b P) -> Vec<(usize, Match<'a, 'b, Handler>)>
+    where
+        P: AsPath + ?Sized,
+    {
+        self.layers
+            .iter()
+            .enumerate()
+            .flat_map(|(layer, router)| router.match_iter(path).map(move |m| (layer, m)))
+            .collect()
+    }
+
+    /// Returns the router for this layer, if any
+    pub fn layer(&self, layer: usize) -> Option<&Router<Handler>> {
+        self.layers.get(layer)
+    }
+
+    /// Returns a mutable reference to the router for this layer, if
+    /// any
+    pub fn layer_mut(&mut self, layer: usize) -> Option<&mut Router<Handler>> {
+        self.layers.get_mut(layer)
+    }
+
+    /// Returns an iterator over the layers, in order
+    pub fn iter(&self) -> impl Iterator<Item = &Router<Handler>> {
+        self.layers.iter()
+    }
+
+    /// returns the number of layers
+    pub fn len(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// returns true if there are no layers
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+}
@@ -0,0 +1,317 @@
+use crate::{AsPath, Match, RouteSpec, Router};
+use smartstring::alias::String as SmartString;
+use std::{
+    borrow::Cow,
+    cmp::Reverse,
+    collections::{hash_map::DefaultHasher, BTreeMap},
+    fmt::{self, Debug, Formatter},
+    hash::Hasher,
+};
+
+/// A router for tables that are built once, which finds routes without
+/// params, wildcards, or globs with a perfect hash of their paths
+///
+/// A `StaticRouter` is built from a [`Router`] with
+/// [`Router::into_static`]. Each static route is found by hashing the
+/// path, so the cost of matching a static path does not grow with the
+/// number of routes. Any other path is matched by the remaining dynamic
+/// routes as usual, and the result is always the same as the original
+/// router's best match. Conditional and weighted routes, prefix routes,
+/// and every route of a router that matches case insensitively are
+/// treated as dynamic.
+///
+/// ```rust
+/// let mut router = routefinder::Router::new();
+/// router.add("/", "home").unwrap();
+/// router.add("/about", "about").unwrap();
+/// router.add("/users/new", "new user").unwrap();
+/// router.add("/users/:id", "user").unwrap();
+/// let router = router.into_static();
+///
+/// assert_eq!(router.static_len(), 3);
+/// assert_eq!(*router.best_match("/about/").unwrap(), "about");
+/// assert_eq!(*router.best_match("/users/new").unwrap(), "new user");
+/// let m = router.best_match("/users/1").unwrap();
+/// assert_eq!(m.captures().get("id"), Some("1"));
+/// ```
+pub struct StaticRouter<Handler> {
+    hash: PerfectHash,
+    slots: Vec<Slot<Handler>>,
+    static_len: usize,
+    router: Router<Handler>,
+}
+
+/// the static routes for one path, in order of precedence
+struct Slot<Handler> {
+    key: SmartString,
+    entries: Vec<Entry<Handler>>,
+}
+
+struct Entry<Handler> {
+    route: RouteSpec,
+    handler: Handler,
+    // whether a dynamic route that takes precedence could also match
+    contested: bool,
+}
+
+impl<Handler> Debug for StaticRouter<Handler> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let routes = self
+            .slots
+            .iter()
+            .flat_map(|slot| &slot.entries)
+            .map(|entry| entry.route.to_string())
+            .collect::<Vec<_>>();
+        f.debug_struct("StaticRouter")
+            .field("static_routes", &routes)
+            .field("router", &self.router)
+            .finish()
+    }
+}
+
+impl<Handler> From<Router<Handler>> for StaticRouter<Handler> {
+    fn from(router: Router<Handler>) -> Self {
+        Self::new(router)
+    }
+}
+
+impl<Handler> Router<Handler> {
+    /// Builds a [`StaticRouter`] from this router's routes and
+    /// settings
+    pub fn into_static(self) -> StaticRouter<Handler> {
+        StaticRouter::new(self)
+    }
+}
+
+impl<Handler> StaticRouter<Handler> {
+    /// Builds a new StaticRouter from a router's routes and settings.
+    /// See [`Router::into_static`].
+    pub fn new(mut router: Router<Handler>) -> Self {
+        let options = router.match_options();
+        let mut groups: BTreeMap<SmartString, Vec<(RouteSpec, Handler)>> = BTreeMap::new();
+        for (route, handler) in router.take_routes() {
+            match route.literal_path() {
+                Some(path) if route.is_static() && !options.case_insensitive => {
+                    groups.entry(path).or_default().push((route, handler));
+                }
+                _ => router.restore(route, handler),
+            }
+        }
+
+        let keys: Vec<&str> = groups.keys().map(|key| &**key).collect();
+        let (hash, order) = PerfectHash::build(&keys);
+
+        let mut slots: Vec<_> = groups
+            .into_iter()
+            .map(|(key, routes)| {
+                let variants = [format!("/{}", key), format!("/{}/", key)];
+                let entries = routes
+                    .into_iter()
+                    .map(|(route, handler)| Entry {
+                        contested: variants.iter().any(|path| {
+                            router
+                                .candidates(path)
+                                .take_while(|dynamic| **dynamic < route)
+                                .any(|dynamic| dynamic.matches_with(path, &options).is_some())
+                        }),
+                        route,
+                        handler,
+                    })
+                    .collect();
+                Some(Slot { key, entries })
+            })
+            .collect();
+
+        let slots: Vec<_> = order
+            .into_iter()
+            .filter_map(|index| slots[index].take())
+            .collect();
+
+        Self {
+            hash,
+            static_len: slots.iter().map(|slot| slot.entries.len()).sum(),
+            slots,
+            router,
+        }
+    }
+
+    /// Returns the single best route match for this path, which is
+    /// always the same as the original router's. See
+    /// [`Router::best_match`].
+    pub fn best_match<'a, 'b, P>(&'a self, path: &'b P) -> Option<Match<'a, 'b, Handler>>
+    where
+        P: AsPath + ?Sized,
+    {
+        let path = path.trimmed();
+        let best = self.router.prepare(path).and_then(|prepared| {
+            let found = self.find_static(prepared, path);
+            match found {
+                Some((best, false)) => Some(best),
+                found => {
+                    let dynamic = self.router.match_iter(path).next();
+                    let dynamic = dynamic.and_then(|best| self.router.pick_weighted(best));
+                    match (found, dynamic) {
+                        (Some((best, _)), Some(dynamic)) if *dynamic.route < *best.route => {
+                            Some(dynamic)
+                        }
+                        (Some((best, _)), _) => Some(best),
+                        (None, dynamic) => dynamic,
+                    }
+                }
+            }
+        });
+        let best = best.filter(|best| self.router.permits(best));
+
+        #[cfg(feature = "metrics")]
+        self.router.record(best.as_ref());
+        best
+    }
+
+    /// the first static route for this path, and whether a dynamic
+    /// route might take precedence over it
+    fn find_static<'a, 'b>(
+        &'a self,
+        prepared: Cow<'b, str>,
+        raw: &'b str,
+    ) -> Option<(Match<'a, 'b, Handler>, bool)> {
+        if self.slots.is_empty() {
+            return None;
+        }
+
+        let key = prepared.trim_start_matches('/').trim_end_matches('/');
+        let slot = &self.slots[self.hash.index(key)];
+        if slot.key != key {
+            return None;
+        }
+
+        let (raw, options) = (Cow::Borrowed(raw), self.router.match_options());
+        slot.entries.iter().find_map(|entry| {
+            let m = Match::try_new(&entry.route, &entry.handler, &prepared, &raw, &options)?;
+            Some((m, entry.contested))
+        })
+    }
+
+    /// returns the number of routes, static or not
+    pub fn len(&self) -> usize {
+        self.static_len + self.router.len()
+    }
+
+    /// returns true if there are no routes
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// returns the number of routes that are found by hashing
+    pub fn static_len(&self) -> usize {
+        self.static_len
+    }
+
+    /// Returns the router that holds the dynamic routes, which are
+    /// tried when a path is not one of the static routes
+    pub fn dynamic_routes(&self) -> &Router<Handler> {
+        &self.router
+    }
+}
+
+/// the number of keys per displacement
+const LAMBDA: usize = 5;
+
+/// a hash-and-displace perfect hash function over a fixed set of keys,
+/// mapping each of them to a distinct index below the number of keys
+#[derive(Debug, Default)]
+struct PerfectHash {
+    seed: u64,
+    displacements: Vec<(u32, u32)>,
+    len: usize,
+}
+
+impl PerfectHash {
+    /// the hash function for these keys, along with the position of the
+    /// key for each index
+    fn build(keys: &[&str]) -> (Self, Vec<usize>) {
+        if keys.is_empty() {
+            return (Self::default(), vec![]);
+        }
+
+        (0..)
+            .find_map(|seed| Self::try_build(keys, seed))
+            .unwrap_or_default()
+    }
+
+    fn try_build(keys: &[&str], seed: u64) -> Option<(Self, Vec<usize>)> {
+        let len = keys.len();
+        let hashes: Vec<_> = keys.iter().map(|key| hashes(seed, key)).collect();
+        let mut buckets = vec![vec![]; len.div_ceil(LAMBDA)];
+        for (key, (bucket, _, _)) in hashes.iter().enumerate() {
+            let bucket_count = buckets.len();
+            buckets[*bucket as usize % bucket_count].push(key);
+        }
+
+        let mut order: Vec<usize> = (0..buckets.len()).collect();
+        order.sort_by_key(|bucket| Reverse(buckets[*bucket].len()));
+
+        let mut indexes: Vec<Option<usize>> = vec![None; len];
+        let mut displacements = vec![(0, 0); buckets.len()];
+        let mut claimed = vec![0_u64; len];
+        let mut attempt = 0;
+        let mut placed = vec![];
+
+        'buckets: for bucket in order {
+            for d1 in 0..len as u32 {
+                for d2 in 0..len as u32 {
+                    attempt += 1;
+                    placed.clear();
+                    let fits = buckets[bucket].iter().all(|key| {
+                        let (_, f1, f2) = hashes[*key];
+                        let index = displace(f1, f2, d1, d2) as usize % len;
+                        if indexes[index].is_some() || claimed[index] == attempt {
+                            return false;
+                        }
+                        claimed[index] = attempt;
+                        placed.push((index, *key));
+                        true
+                    });
+
+                    if fits {
+                        for (index, key) in &placed {
+                            indexes[*index] = Some(*key);
+                        }
+                        displacements[bucket] = (d1, d2);
+                        continue 'buckets;
+                    }
+                }
+            }
+            return None;
+        }
+
+        let indexes = indexes.into_iter().collect::<Option<Vec<_>>>()?;
+        let hash = Self {
+            seed,
+            displacements,
+            len,
+        };
+        Some((hash, indexes))
+    }
+
+    /// the index for this key, if it is one of the keys. any other key
+    /// also has an index, which must be compared with the key there
+    fn index(&self, key: &str) -> usize {
+        let (bucket, f1, f2) = hashes(self.seed, key);
+        let (d1, d2) = self.displacements[bucket as usize % self.displacements.len()];
+        displace(f1, f2, d1, d2) as usize % self.len
+    }
+}
+
+fn hashes(seed: u64, key: &str) -> (u32, u32, u32) {
+    let mut hasher = DefaultHasher::new();
+    hasher.write_u64(seed);
+    hasher.write(key.as_bytes());
+    let first = hasher.finish();
+    hasher.write_u8(0xff);
+    let second = hasher.finish();
+    ((first >> 32) as u32, first as u32, second as u32)
+}
+
+fn displace(f1: u32, f2: u32, d1: u32, d2: u32) -> u32 {
+    d2.wrapping_add(f1.wrapping_mul(d1)).wrapping_add(f2)
+}
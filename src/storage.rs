@@ -0,0 +1,20 @@
+/// How a [`Router`][crate::Router] stores its routes. See
+/// [`Router::set_storage`][crate::Router::set_storage].
+///
+/// Both kinds of storage keep routes in the same order of precedence
+/// and match paths the same way; they differ only in the cost of
+/// adding and removing routes compared to the cost of matching.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Storage {
+    /// Routes are kept in a balanced tree, so adding or removing a
+    /// route is cheap regardless of the size of the table. This is the
+    /// default, and suits routers that change while they are in use.
+    #[default]
+    Tree,
+
+    /// Routes are kept in a sorted vec, so matching walks contiguous
+    /// memory, at the cost of moving later routes each time a route is
+    /// added or removed. This suits tables that are built once and
+    /// then only read.
+    Sorted,
+}
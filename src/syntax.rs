@@ -0,0 +1,40 @@
+/// The route definition dialects that routefinder can parse. See
+/// [`RouteSpec::parse_with`][crate::RouteSpec::parse_with]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Syntax {
+    /// routefinder's own syntax: `/users/:id/files/*`, where a param
+    /// followed by `?`, as in `/posts/:page?`, is optional, and `+` or
+    /// `*` capture one or more or zero or more path segments. This is
+    /// what `str::parse` and [`Router::add`][crate::Router::add] use.
+    #[default]
+    Routefinder,
+
+    /// file path globs: `assets/**/*.min.?s`. `*` matches within a
+    /// single path segment, `?` matches exactly one character, and
+    /// `**` matches any number of path segments, which are available
+    /// as the [`wildcard`][crate::Captures::wildcard]. Dots and colons
+    /// are ordinary characters in this dialect.
+    Glob,
+
+    /// the dialect of path-to-regexp, as used by express and much of
+    /// the node ecosystem: `/users/:id(\\d+)/:tab?`, `/files/:path*`,
+    /// `/:path+`, and unnamed groups such as `/(.*)`, which are
+    /// captured by position as `"0"`, `"1"`, and so on. A bare `*` is
+    /// a routefinder wildcard, as in express 4.
+    ///
+    /// Parenthesized patterns require the `regex` feature. Unlike
+    /// express, matching is always case sensitive, and routes are
+    /// ordered by routefinder's precedence rules rather than by
+    /// insertion order.
+    Express,
+
+    /// google api http rule path templates, as used for grpc
+    /// transcoding: `/v1/{name=projects/*/locations/*}/datasets:export`.
+    /// A variable binds one path segment unless it has a pattern,
+    /// in which case its capture spans every segment the pattern
+    /// matches. An unbound `*` matches one path segment without
+    /// capturing it, an unbound `**` is a routefinder wildcard, and a
+    /// trailing `:verb` must match exactly. Dotted field paths such
+    /// as `{book.name}` are captured under the whole field path.
+    HttpRule,
+}
@@ -0,0 +1,75 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+/// The reason a [`Captures`][crate::Captures] cannot populate a
+/// [`RouteSpec`][crate::RouteSpec]. See
+/// [`RouteSpec::validate`][crate::RouteSpec::validate].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateError {
+    /// The route has a required param with this name, but the
+    /// captures have no value for it
+    Missing(String),
+
+    /// The captures have a value for this name, but the route has no
+    /// param with this name
+    Unexpected(String),
+
+    /// The captures have a value for this param, but not in the order
+    /// in which the params appear in the route
+    OutOfOrder(String),
+
+    /// The captures have a wildcard, but the route has no wildcard
+    UnexpectedWildcard,
+
+    /// The route has a glob, which cannot be populated from captures
+    Glob,
+
+    /// The value for this param does not satisfy the param's
+    /// constraint, so the route would not match the resulting path
+    Invalid {
+        /// the name of the param
+        name: String,
+        /// the value that was provided
+        value: String,
+    },
+
+    /// The value for this param contains a separator, so the route
+    /// would not match the resulting path with the same captures. See
+    /// [`SeparatorPolicy`][crate::SeparatorPolicy].
+    Separator {
+        /// the name of the param
+        name: String,
+        /// the value that was provided
+        value: String,
+    },
+}
+
+impl Display for TemplateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::Missing(name) => write!(f, "no value for the param `{}`", name),
+            TemplateError::Unexpected(name) => {
+                write!(f, "the route has no param named `{}`", name)
+            }
+            TemplateError::OutOfOrder(name) => {
+                write!(f, "the value for `{}` is out of order", name)
+            }
+            TemplateError::UnexpectedWildcard => f.write_str("the route has no wildcard"),
+            TemplateError::Glob => f.write_str("routes with globs cannot be templated"),
+            TemplateError::Invalid { name, value } => {
+                write!(f, "`{}` is not a valid value for `{}`", value, name)
+            }
+            TemplateError::Separator { name, value } => {
+                write!(
+                    f,
+                    "the value `{}` for `{}` contains a separator",
+                    value, name
+                )
+            }
+        }
+    }
+}
+
+impl Error for TemplateError {}
@@ -0,0 +1,346 @@
+//! Assertions for the routes of a [`Router`], for test suites
+//!
+//! [`assert_matches!`][crate::assert_matches] checks that a path is
+//! matched by a route, and optionally which captures it has, and
+//! [`assert_no_match!`][crate::assert_no_match] checks that no route
+//! matches a path. When an assertion fails, the panic message explains
+//! how the routes of the router apply to the path: which route was
+//! selected, which other routes matched, and the routes that came
+//! closest to matching, with the reason each one does not.
+//! [`matrix`] generates probe paths for each route, paired with the
+//! route each selects, as a fixture for characterization tests.
+//!
+//! ```rust
+//! use routefinder::{assert_matches, assert_no_match, Router};
+//! let mut router = Router::new();
+//! router.add("/users/:id", "user").unwrap();
+//! router.add("/users/new", "new user").unwrap();
+//!
+//! assert_matches!(router, "/users/42", "/users/:id", { "id" => "42" });
+//! assert_matches!(router, "/users/new", "/users/new");
+//! assert_no_match!(router, "/posts/1");
+//! ```
+//!
+//! A failure reads like this:
+//!
+//! ```text
+//! expected /users/new to match /users/:id, but it matched /users/new with {}
+//!
+//! routes for /users/new:
+//!   > /users/new  selected
+//!     /users/:id  matches, but a route above takes precedence
+//! ```
+
+use crate::{Captures, Outcome, RouteSpec, Router, Segment};
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet},
+};
+
+/// the number of routes that do not match to describe
+const CLOSEST: usize = 3;
+
+/// Asserts that a path is matched by a route, and optionally that it
+/// has exactly these captures, with the wildcard under `"*"`. The
+/// route is compared by its display, so `/users/:id/` and `/users/:id`
+/// are the same. See [`testing`][crate::testing].
+///
+/// ```rust
+/// use routefinder::{assert_matches, Router};
+/// let mut router = Router::new();
+/// router.add("/files/:name.:ext/*", ()).unwrap();
+/// assert_matches!(
+///     router,
+///     "/files/report.pdf/raw",
+///     "/files/:name.:ext/*",
+///     { "name" => "report", "ext" => "pdf", "*" => "raw" }
+/// );
+/// ```
+#[macro_export]
+macro_rules! assert_matches {
+    ($router:expr, $path:expr, $route:expr $(,)?) => {
+        if let Err(message) = $crate::testing::check_match(&$router, $path, $route, None) {
+            panic!("{}", message);
+        }
+    };
+
+    ($router:expr, $path:expr, $route:expr, { $($key:expr => $value:expr),* $(,)? } $(,)?) => {
+        if let Err(message) =
+            $crate::testing::check_match(&$router, $path, $route, Some(&[$(($key, $value)),*]))
+        {
+            panic!("{}", message);
+        }
+    };
+}
+
+/// Asserts that no route matches a path. See
+/// [`testing`][crate::testing].
+///
+/// ```rust
+/// use routefinder::{assert_no_match, Router};
+/// let mut router = Router::new();
+/// router.add("/users/:id", ()).unwrap();
+/// assert_no_match!(router, "/users");
+/// assert_no_match!(router, "/users/1/posts");
+/// ```
+#[macro_export]
+macro_rules! assert_no_match {
+    ($router:expr, $path:expr $(,)?) => {
+        if let Err(message) = $crate::testing::check_no_match(&$router, $path) {
+            panic!("{}", message);
+        }
+    };
+}
+
+/// Checks that the best match for this path has this route, and, if
+/// provided, exactly these captures, returning a description of the
+/// routes for the path if it does not. This is what
+/// [`assert_matches!`][crate::assert_matches] calls.
+pub fn check_match<Handler>(
+    router: &Router<Handler>,
+    path: &str,
+    route: &str,
+    captures: Option<&[(&str, &str)]>,
+) -> Result<(), String> {
+    let expected = route
+        .parse::<RouteSpec>()
+        .map_or_else(|_| route.to_string(), |spec| spec.to_string());
+
+    let best = match router.best_match(path) {
+        Some(best) => best,
+        None => {
+            return Err(format!(
+                "expected {} to match {}, but no route matches it\n\n{}",
+                path,
+                expected,
+                explain(router, path)
+            ))
+        }
+    };
+
+    let actual = best.captures();
+    if best.route().to_string() != expected {
+        return Err(format!(
+            "expected {} to match {}, but it matched {} with {:?}\n\n{}",
+            path,
+            expected,
+            best.route(),
+            actual,
+            explain(router, path)
+        ));
+    }
+
+    if let Some(captures) = captures {
+        if !same_captures(&actual, captures) {
+            let params: Captures = captures
+                .iter()
+                .filter(|(key, _)| *key != "*")
+                .copied()
+                .collect();
+            let wildcard = captures
+                .iter()
+                .find(|(key, _)| *key == "*")
+                .map_or_else(String::new, |(_, value)| {
+                    format!(" and the wildcard {:?}", value)
+                });
+            return Err(format!(
+                "expected {} to match {} with {:?}{}, but the captures were {:?}",
+                path, expected, params, wildcard, actual
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that no route matches this path, returning a description of
+/// the routes for the path if any does. This is what
+/// [`assert_no_match!`][crate::assert_no_match] calls.
+pub fn check_no_match<Handler>(router: &Router<Handler>, path: &str) -> Result<(), String> {
+    match router.best_match(path) {
+        None => Ok(()),
+        Some(best) => Err(format!(
+            "expected no route to match {}, but {} matched it with {:?}\n\n{}",
+            path,
+            best.route(),
+            best.captures(),
+            explain(router, path)
+        )),
+    }
+}
+
+/// Describes how the routes of a router apply to this path: the route
+/// that is selected, every other route that matches, and the routes
+/// that come closest to matching, with the reason that each does not.
+/// See [`Router::explain`] for the same as structured data.
+///
+/// ```rust
+/// let mut router = routefinder::Router::new();
+/// router.add("/users/:id", ()).unwrap();
+/// router.add("/posts/:id", ()).unwrap();
+/// let explanation = routefinder::testing::explain(&router, "/users/1/posts");
+/// assert!(explanation.contains("the path continues after the end of the route"));
+/// ```
+pub fn explain<Handler>(router: &Router<Handler>, path: &str) -> String {
+    let explanation = router.explain(path);
+    let (mut lines, mut misses): (Vec<_>, Vec<_>) = explanation
+        .routes()
+        .iter()
+        .partition(|route| !matches!(route.outcome(), Outcome::Rejected(_)));
+
+    // the closest misses, in order of precedence among equally close
+    misses.sort_by_key(|route| match route.outcome() {
+        Outcome::Rejected(rejection) => Reverse(rejection.segment()),
+        _ => Reverse(0),
+    });
+    let omitted = misses.len().saturating_sub(CLOSEST);
+    lines.extend(misses.into_iter().take(CLOSEST));
+
+    let width = lines
+        .iter()
+        .map(|route| route.route().len())
+        .max()
+        .unwrap_or_default();
+    let mut description = format!("routes for {}:", path);
+    for route in lines {
+        let marker = if route.outcome() == &Outcome::Selected {
+            '>'
+        } else {
+            ' '
+        };
+        description.push_str(&format!(
+            "\n  {} {:<width$}  {}",
+            marker,
+            route.route(),
+            route.outcome(),
+            width = width
+        ));
+    }
+
+    match omitted {
+        0 => {}
+        1 => description.push_str("\n    ... and 1 other route that does not match"),
+        n => description.push_str(&format!(
+            "\n    ... and {} other routes that do not match",
+            n
+        )),
+    }
+
+    if router.is_empty() {
+        description.push_str("\n    the router has no routes");
+    }
+
+    description
+}
+
+/// Generates probe paths for each route of a router, paired with the
+/// route that the router selects for each, as a fixture for
+/// characterization tests: record the matrix before changing a route
+/// table, or upgrading routefinder, and compare it afterwards.
+///
+/// For each route, in order of precedence, the probes are the paths
+/// that the route produces with sample values for its params, with and
+/// without its optional params, and variations at the boundaries of
+/// each: with a trailing slash, with an extra segment, without the
+/// last segment, with a `.` suffix added to or removed from the last
+/// segment, and with every param empty. Each path appears once. A path
+/// that matches a weighted route is paired with the first of its
+/// alternatives, so the matrix does not depend on the weight strategy.
+///
+/// ```rust
+/// let mut router = routefinder::Router::new();
+/// router.add("/users/:id", ()).unwrap();
+/// router.add("/files/:name.:ext", ()).unwrap();
+///
+/// let matrix = routefinder::testing::matrix(&router);
+/// let route = |path: &str| {
+///     let (_, route) = matrix.iter().find(|(probe, _)| probe == path).unwrap();
+///     route.as_ref().map(ToString::to_string)
+/// };
+/// assert_eq!(route("/users/1").as_deref(), Some("/users/:id"));
+/// assert_eq!(route("/users/1/"), route("/users/1"));
+/// assert_eq!(route("/users/1/probe"), None);
+/// assert_eq!(route("/files/1.a").as_deref(), Some("/files/:name.:ext"));
+/// assert_eq!(route("/files/1"), None);
+/// ```
+pub fn matrix<Handler>(router: &Router<Handler>) -> Vec<(String, Option<RouteSpec>)> {
+    let mut values: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (route, _) in router.iter() {
+        for segment in route.segments() {
+            let (name, samples) = match segment {
+                Segment::Param(name) | Segment::OptionalParam(name) => (&**name, &["1", "a"][..]),
+                Segment::OneOrMore(name) | Segment::ZeroOrMore(name) => (&**name, &["a/b"][..]),
+                Segment::Wildcard => ("*", &["a/b"][..]),
+                _ => continue,
+            };
+            values.insert(
+                name.to_string(),
+                samples.iter().map(|sample| sample.to_string()).collect(),
+            );
+        }
+    }
+
+    let mut seen = BTreeSet::new();
+    let mut matrix = vec![];
+    for (route, _) in router.iter() {
+        let mut paths = route.expand(&values);
+        if paths.is_empty() {
+            paths.push(route.to_string());
+        }
+
+        let mut probes = vec![];
+        for path in paths {
+            let trimmed = path.trim_end_matches('/').to_string();
+            probes.push(path);
+            if trimmed.is_empty() {
+                probes.push(String::from("/probe"));
+                continue;
+            }
+            probes.push(format!("{}/", trimmed));
+            probes.push(format!("{}/probe", trimmed));
+            probes.push(format!("{}.probe", trimmed));
+            let (parent, last) = trimmed.rsplit_once('/').unwrap_or(("", &trimmed));
+            probes.push(format!("{}/", parent));
+            if let Some((stem, _)) = last.rsplit_once('.') {
+                probes.push(format!("{}/{}", parent, stem));
+            }
+        }
+        probes.push(blank(route));
+
+        for probe in probes {
+            if seen.insert(probe.clone()) {
+                let selected = router.match_iter(&probe).next().map(|m| m.route().clone());
+                matrix.push((probe, selected));
+            }
+        }
+    }
+
+    matrix
+}
+
+/// the path that a route would produce with every param empty
+fn blank(route: &RouteSpec) -> String {
+    let mut path = String::from("/");
+    for segment in route.segments() {
+        match segment {
+            Segment::Slash => path.push('/'),
+            Segment::Dot => path.push('.'),
+            Segment::Exact(exact) => path.push_str(exact),
+            _ => {}
+        }
+    }
+    path
+}
+
+/// whether these captures are exactly the expected pairs, in any
+/// order, with the wildcard under `"*"`
+fn same_captures(actual: &Captures<'_, '_>, expected: &[(&str, &str)]) -> bool {
+    let mut actual: Vec<(&str, &str)> = actual
+        .iter()
+        .chain(actual.wildcard().map(|wildcard| ("*", wildcard)))
+        .collect();
+    let mut expected = expected.to_vec();
+    actual.sort_unstable();
+    expected.sort_unstable();
+    actual == expected
+}
@@ -0,0 +1,102 @@
+use crate::{RouteId, RouteSpec, Router};
+use std::{
+    collections::BTreeSet,
+    convert::TryInto,
+    fmt::{self, Debug, Formatter},
+};
+
+/// A set of changes to a [`Router`] that are applied together, or not
+/// at all. See [`Router::transaction`].
+///
+/// Changes are checked as they are staged, so a route that does not
+/// parse or an id that is not in the router is an error from the
+/// method that staged it, and the router is unchanged until the
+/// transaction commits.
+pub struct Transaction<'router, Handler> {
+    router: &'router Router<Handler>,
+    changes: Vec<Change<Handler>>,
+    added: usize,
+    removed: BTreeSet<RouteId>,
+}
+
+/// a staged change
+pub(crate) enum Change<Handler> {
+    Add(Box<RouteSpec>, Handler),
+    Remove(RouteId),
+}
+
+impl<Handler> Debug for Transaction<'_, Handler> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut debug_list = f.debug_list();
+        for change in &self.changes {
+            match change {
+                Change::Add(route, _) => debug_list.entry(&format_args!("add {}", route)),
+                Change::Remove(id) => debug_list.entry(&format_args!("remove {}", id)),
+            };
+        }
+        debug_list.finish()
+    }
+}
+
+impl<'router, Handler> Transaction<'router, Handler> {
+    pub(crate) fn new(router: &'router Router<Handler>) -> Self {
+        Self {
+            router,
+            changes: vec![],
+            added: 0,
+            removed: BTreeSet::new(),
+        }
+    }
+
+    /// Stages a route to be added, returning the [`RouteId`] it will
+    /// be assigned when the transaction commits. See [`Router::add`].
+    pub fn add<R>(
+        &mut self,
+        route: R,
+        handler: Handler,
+    ) -> Result<RouteId, <R as TryInto<RouteSpec>>::Error>
+    where
+        R: TryInto<RouteSpec>,
+    {
+        let route = route.try_into()?;
+        let id = RouteId::new(self.router.next_id().get() + self.added);
+        self.added += 1;
+        self.changes.push(Change::Add(Box::new(route), handler));
+        Ok(id)
+    }
+
+    /// Stages the route with this [`RouteId`] to be removed. Returns an
+    /// error if there is no such route in the router or staged to be
+    /// added, or if it is already staged to be removed. See
+    /// [`Router::remove`].
+    pub fn remove(&mut self, id: RouteId) -> Result<(), String> {
+        let next = self.router.next_id().get();
+        let exists =
+            self.router.handler(id).is_some() || (next..next + self.added).contains(&id.get());
+        if !exists || !self.removed.insert(id) {
+            return Err(format!("no route with id {} to remove", id));
+        }
+
+        self.changes.push(Change::Remove(id));
+        Ok(())
+    }
+
+    /// Returns the router as it was before the transaction
+    pub fn router(&self) -> &'router Router<Handler> {
+        self.router
+    }
+
+    /// returns the number of changes that have been staged
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// returns true if no changes have been staged
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub(crate) fn into_changes(self) -> Vec<Change<Handler>> {
+        self.changes
+    }
+}
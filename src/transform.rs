@@ -0,0 +1,63 @@
+use std::borrow::Cow;
+
+/// A transformation applied to a param value before it is templated
+/// into a route. See [`RouteSpec::url_for`][crate::RouteSpec::url_for].
+///
+/// ```rust
+/// use routefinder::Transform;
+/// assert_eq!(Transform::Slugify.apply("Hello, World!"), "hello-world");
+/// assert_eq!(Transform::Lowercase.apply("Hello"), "hello");
+/// assert_eq!(Transform::Uppercase.apply("Hello"), "HELLO");
+/// assert_eq!(Transform::Trim.apply("  hello "), "hello");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Transform {
+    /// Lowercases the value
+    Lowercase,
+
+    /// Uppercases the value
+    Uppercase,
+
+    /// Removes leading and trailing whitespace
+    Trim,
+
+    /// Lowercases the value and replaces each run of characters that
+    /// are not alphanumeric with a single `-`, with no leading or
+    /// trailing `-`
+    Slugify,
+}
+
+impl Transform {
+    /// Applies this transformation to a value, borrowing it if it is
+    /// unchanged
+    pub fn apply<'value>(&self, value: &'value str) -> Cow<'value, str> {
+        match self {
+            Transform::Lowercase if value.chars().any(char::is_uppercase) => {
+                Cow::Owned(value.to_lowercase())
+            }
+            Transform::Uppercase if value.chars().any(char::is_lowercase) => {
+                Cow::Owned(value.to_uppercase())
+            }
+            Transform::Trim => Cow::Borrowed(value.trim()),
+            Transform::Slugify => {
+                let mut slug = String::with_capacity(value.len());
+                for word in value.split(|c: char| !c.is_alphanumeric()) {
+                    if word.is_empty() {
+                        continue;
+                    }
+                    if !slug.is_empty() {
+                        slug.push('-');
+                    }
+                    slug.extend(word.chars().flat_map(char::to_lowercase));
+                }
+
+                if slug == value {
+                    Cow::Borrowed(value)
+                } else {
+                    Cow::Owned(slug)
+                }
+            }
+            _ => Cow::Borrowed(value),
+        }
+    }
+}
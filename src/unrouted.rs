@@ -0,0 +1,27 @@
+use crate::RouteSpec;
+
+/// Why [`Router::try_best_match`][crate::Router::try_best_match]
+/// found no route for a path
+///
+/// ```rust
+/// use routefinder::{Router, Unrouted};
+/// let mut router = Router::new();
+/// router.add("*", "fallback").unwrap();
+/// router.reserve_prefix("/.well-known").unwrap();
+///
+/// match router.try_best_match("/.well-known/security.txt") {
+///     Err(Unrouted::Reserved(prefix)) => assert_eq!(prefix.to_string(), "/.well-known"),
+///     _ => panic!("expected a reserved path"),
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unrouted<'router> {
+    /// No route matches the path
+    NotFound,
+
+    /// The path is under a prefix reserved with
+    /// [`Router::reserve_prefix`][crate::Router::reserve_prefix], and
+    /// none of the routes under that prefix match it. Routes outside
+    /// the prefix were not tried.
+    Reserved(&'router RouteSpec),
+}
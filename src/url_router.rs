@@ -0,0 +1,469 @@
+use crate::{Capture, Captures, RouteId, RouteSpec};
+use smartstring::alias::String as SmartString;
+use std::{
+    cmp::Ordering,
+    collections::BTreeMap,
+    convert::{TryFrom, TryInto},
+    fmt::{self, Debug, Display, Formatter},
+    ops::Deref,
+    str::FromStr,
+};
+
+/// A constraint on one query parameter of a [`UrlSpec`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum QueryParam {
+    /// `key`: the key must be present, with any value
+    Present(SmartString),
+    /// `key=value`: the key must be present with exactly this value
+    Exact(SmartString, SmartString),
+    /// `key=:name`: the key must be present, and its value is
+    /// captured as `name`
+    Param(SmartString, SmartString),
+}
+
+impl QueryParam {
+    fn key(&self) -> &str {
+        match self {
+            QueryParam::Present(key) | QueryParam::Exact(key, _) | QueryParam::Param(key, _) => key,
+        }
+    }
+}
+
+/// A pattern for a whole url, composed of an optional scheme, an
+/// optional host pattern, a path [`RouteSpec`], and query parameter
+/// constraints.
+///
+/// The string form is `[scheme:]//host/path[?query]` or `/path[?query]`,
+/// where the host is a [`RouteSpec`] that uses dots as separators, and
+/// the query is a `&`-separated list of `key` (must be present),
+/// `key=value` (must have this value), or `key=:name` (captured as
+/// `name`). Query parameters that are not listed are ignored.
+///
+/// ```rust
+/// use routefinder::UrlSpec;
+/// let spec: UrlSpec = "https://:tenant.example.com/users/:id?tab=:tab".parse().unwrap();
+/// assert_eq!(spec.scheme(), Some("https"));
+/// assert_eq!(spec.host().unwrap().to_string(), "/:tenant.example.com");
+/// assert_eq!(spec.path().to_string(), "/users/:id");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UrlSpec {
+    scheme: Option<SmartString>,
+    host: Option<RouteSpec>,
+    path: RouteSpec,
+    query: Vec<QueryParam>,
+}
+
+impl UrlSpec {
+    /// Builds a UrlSpec that matches this path on any scheme and host
+    pub fn new(path: RouteSpec) -> Self {
+        Self {
+            scheme: None,
+            host: None,
+            path,
+            query: vec![],
+        }
+    }
+
+    /// Returns the scheme that this spec requires, if any
+    pub fn scheme(&self) -> Option<&str> {
+        self.scheme.as_deref()
+    }
+
+    /// Returns the host pattern for this spec, if any
+    pub fn host(&self) -> Option<&RouteSpec> {
+        self.host.as_ref()
+    }
+
+    /// Returns the path pattern for this spec
+    pub fn path(&self) -> &RouteSpec {
+        &self.path
+    }
+
+    /// Attempts to apply this spec to a url, returning the merged
+    /// captures of the host, path, and query, in that order. The
+    /// wildcard is the path's, if it has one, and otherwise the
+    /// host's.
+    pub fn matches<'spec, 'url>(&'spec self, url: &'url str) -> Option<Captures<'spec, 'url>> {
+        let parts = UrlParts::parse(url);
+
+        if let Some(scheme) = &self.scheme {
+            if !parts.scheme?.eq_ignore_ascii_case(scheme) {
+                return None;
+            }
+        }
+
+        let mut captures = match &self.host {
+            Some(host_spec) => {
+                let host = parts.host?;
+                if host.bytes().any(|b| b.is_ascii_uppercase()) {
+                    let host = host.to_ascii_lowercase();
+                    let values = host_spec.matches(&host)?;
+                    host_spec.captures(values).into_owned()
+                } else {
+                    host_spec.captures(host_spec.matches(host)?)
+                }
+            }
+            None => Captures::new(),
+        };
+
+        let path_captures = self.path.captures(self.path.matches(parts.path)?);
+        let host_wildcard = captures.wildcard.take();
+        captures.append(path_captures);
+        if captures.wildcard.is_none() {
+            captures.wildcard = host_wildcard;
+        }
+
+        for param in &self.query {
+            let value = parts.query_pairs().find_map(|(key, value)| {
+                if key == param.key() {
+                    Some(value)
+                } else {
+                    None
+                }
+            })?;
+
+            match param {
+                QueryParam::Present(_) => {}
+                QueryParam::Exact(_, expected) => {
+                    if value != expected {
+                        return None;
+                    }
+                }
+                QueryParam::Param(_, name) => captures.push(Capture::new(&**name, value)),
+            }
+        }
+
+        Some(captures)
+    }
+}
+
+impl Display for UrlSpec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(scheme) = &self.scheme {
+            write!(f, "{}://", scheme)?;
+        } else if self.host.is_some() {
+            f.write_str("//")?;
+        }
+
+        if let Some(host) = &self.host {
+            f.write_str(host.to_string().trim_start_matches('/'))?;
+        }
+
+        Display::fmt(&self.path, f)?;
+
+        for (index, param) in self.query.iter().enumerate() {
+            f.write_str(if index == 0 { "?" } else { "&" })?;
+            match param {
+                QueryParam::Present(key) => f.write_str(key)?,
+                QueryParam::Exact(key, value) => write!(f, "{}={}", key, value)?,
+                QueryParam::Param(key, name) => write!(f, "{}=:{}", key, name)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for UrlSpec {
+    type Err = String;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let (rest, query) = match source.split_once('?') {
+            Some((rest, query)) => (rest, Some(query)),
+            None => (source, None),
+        };
+
+        let (scheme, authority_and_path) = match rest.split_once("://") {
+            Some((scheme, rest)) => (Some(SmartString::from(scheme)), Some(rest)),
+            None => (None, rest.strip_prefix("//")),
+        };
+
+        let (host, path) = match authority_and_path {
+            Some(rest) => {
+                let (host, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+                if host.is_empty() {
+                    (None, path)
+                } else {
+                    (Some(host.to_ascii_lowercase().parse()?), path)
+                }
+            }
+            None => (None, rest),
+        };
+
+        let query = query
+            .into_iter()
+            .flat_map(|query| query.split('&'))
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| match pair.split_once('=') {
+                None => Ok(QueryParam::Present(pair.into())),
+                Some((key, value)) => match value.strip_prefix(':') {
+                    Some("") => Err(String::from("params must be named")),
+                    Some(name) => Ok(QueryParam::Param(key.into(), name.into())),
+                    None => Ok(QueryParam::Exact(key.into(), value.into())),
+                },
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            scheme,
+            host,
+            path: path.parse()?,
+            query,
+        })
+    }
+}
+
+impl TryFrom<&str> for UrlSpec {
+    type Error = String;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl TryFrom<String> for UrlSpec {
+    type Error = String;
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<RouteSpec> for UrlSpec {
+    fn from(path: RouteSpec) -> Self {
+        Self::new(path)
+    }
+}
+
+impl PartialOrd for UrlSpec {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for UrlSpec {
+    /// Routes with a host pattern take precedence over those without,
+    /// then hosts are compared as [`RouteSpec`]s and paths by their
+    /// precedence, and then routes with more query constraints and
+    /// with a scheme win. Routes that are still equal are tried in
+    /// the order they were added.
+    fn cmp(&self, other: &Self) -> Ordering {
+        let host = match (&self.host, &other.host) {
+            (Some(mine), Some(theirs)) => mine.cmp(theirs),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        };
+
+        host.then_with(|| other.path.cmp_precedence(&self.path))
+            .then_with(|| other.query.len().cmp(&self.query.len()))
+            .then_with(|| other.scheme.is_some().cmp(&self.scheme.is_some()))
+            .then_with(|| self.path.cmp(&other.path))
+    }
+}
+
+/// the components of a url, as borrowed slices
+#[derive(Debug, Clone, Copy)]
+struct UrlParts<'url> {
+    scheme: Option<&'url str>,
+    host: Option<&'url str>,
+    path: &'url str,
+    query: Option<&'url str>,
+}
+
+impl<'url> UrlParts<'url> {
+    fn parse(url: &'url str) -> Self {
+        let url = url.split('#').next().unwrap_or_default();
+        let (rest, query) = match url.split_once('?') {
+            Some((rest, query)) => (rest, Some(query)),
+            None => (url, None),
+        };
+
+        let (scheme, authority_and_path) = match rest.split_once("://") {
+            Some((scheme, rest)) => (Some(scheme), Some(rest)),
+            None => (None, rest.strip_prefix("//")),
+        };
+
+        let (host, path) = match authority_and_path {
+            Some(rest) => {
+                let (authority, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+                let host = authority.rsplit('@').next().unwrap_or_default();
+                let host = if host.starts_with('[') {
+                    host.split_inclusive(']').next().unwrap_or_default()
+                } else {
+                    host.split(':').next().unwrap_or_default()
+                };
+                (Some(host), path)
+            }
+            None => (None, rest),
+        };
+
+        Self {
+            scheme,
+            host,
+            path,
+            query,
+        }
+    }
+
+    fn query_pairs(&self) -> impl Iterator<Item = (&'url str, &'url str)> {
+        self.query
+            .into_iter()
+            .flat_map(|query| query.split('&'))
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| pair.split_once('=').unwrap_or((pair, "")))
+    }
+}
+
+/// A router for whole urls, composing scheme constraints, host
+/// patterns, path [`RouteSpec`]s, and query constraints. See
+/// [`UrlSpec`] for the pattern syntax.
+///
+/// ```rust
+/// let mut router = routefinder::UrlRouter::new();
+/// router.add("https://:tenant.example.com/users/:id", "tenant user").unwrap();
+/// router.add("https://api.example.com/users/:id?fields=:fields", "api user").unwrap();
+/// router.add("/users/:id", "any user").unwrap();
+///
+/// let m = router.best_match("https://acme.example.com/users/1").unwrap();
+/// assert_eq!(*m, "tenant user");
+/// assert_eq!(m.captures().get("tenant"), Some("acme"));
+/// assert_eq!(m.captures().get("id"), Some("1"));
+///
+/// let m = router.best_match("https://api.example.com/users/1?fields=name").unwrap();
+/// assert_eq!(*m, "api user");
+/// assert_eq!(m.captures().get("fields"), Some("name"));
+///
+/// assert_eq!(*router.best_match("http://acme.example.com/users/1").unwrap(), "any user");
+/// assert_eq!(*router.best_match("/users/1").unwrap(), "any user");
+/// ```
+pub struct UrlRouter<Handler> {
+    routes: BTreeMap<UrlSpec, Handler>,
+}
+
+impl<Handler> Debug for UrlRouter<Handler> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut debug_set = f.debug_set();
+        for route in self.routes.keys() {
+            debug_set.entry(&format_args!("{}", route));
+        }
+        debug_set.finish()
+    }
+}
+
+impl<Handler> Default for UrlRouter<Handler> {
+    fn default() -> Self {
+        Self {
+            routes: Default::default(),
+        }
+    }
+}
+
+impl<Handler> UrlRouter<Handler> {
+    /// Builds a new url router
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a route to the router, accepting any type that implements
+    /// TryInto<[`UrlSpec`]>, such as a &str, String, or [`RouteSpec`]
+    pub fn add<R>(
+        &mut self,
+        route: R,
+        handler: Handler,
+    ) -> Result<(), <R as TryInto<UrlSpec>>::Error>
+    where
+        R: TryInto<UrlSpec>,
+    {
+        let mut route = route.try_into()?;
+        // routes of equal precedence are tried in the order they were
+        // added
+        route.path = route.path.with_id(RouteId::new(self.routes.len()));
+        self.routes.insert(route, handler);
+        Ok(())
+    }
+
+    /// Returns the single best match for this url
+    pub fn best_match<'a, 'b>(&'a self, url: &'b str) -> Option<UrlMatch<'a, 'b, Handler>> {
+        self.match_iter(url).next()
+    }
+
+    /// Returns all of the matches for this url, in order of precedence
+    pub fn matches<'a, 'b>(&'a self, url: &'b str) -> Vec<UrlMatch<'a, 'b, Handler>> {
+        self.match_iter(url).collect()
+    }
+
+    fn match_iter<'a, 'b>(
+        &'a self,
+        url: &'b str,
+    ) -> impl Iterator<Item = UrlMatch<'a, 'b, Handler>> {
+        self.routes.iter().filter_map(move |(spec, handler)| {
+            spec.matches(url).map(|captures| UrlMatch {
+                url,
+                spec,
+                captures,
+                handler,
+            })
+        })
+    }
+
+    /// Returns an iterator of references to `(&UrlSpec, &Handler)`
+    pub fn iter(&self) -> impl Iterator<Item = (&UrlSpec, &Handler)> {
+        self.routes.iter()
+    }
+
+    /// returns the number of routes that have been added
+    pub fn len(&self) -> usize {
+        self.routes.len()
+    }
+
+    /// returns true if no routes have been added
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+}
+
+/// The output of a successful application of a [`UrlSpec`] to a url.
+/// It dereferences to the contained Handler type.
+#[derive(Debug)]
+pub struct UrlMatch<'router, 'url, Handler> {
+    url: &'url str,
+    spec: &'router UrlSpec,
+    captures: Captures<'router, 'url>,
+    handler: &'router Handler,
+}
+
+impl<'router, 'url, Handler> UrlMatch<'router, 'url, Handler> {
+    /// Returns a reference to the handler associated with this route
+    pub fn handler(&self) -> &'router Handler {
+        self.handler
+    }
+
+    /// Returns the [`UrlSpec`] for this route
+    pub fn spec(&self) -> &'router UrlSpec {
+        self.spec
+    }
+
+    /// returns the exact url that was matched
+    pub fn url(&self) -> &'url str {
+        self.url
+    }
+
+    /// Returns the merged [`Captures`] of the host, path, and query
+    pub fn captures(&self) -> &Captures<'router, 'url> {
+        &self.captures
+    }
+
+    /// Takes ownership of the merged [`Captures`]
+    pub fn into_captures(self) -> Captures<'router, 'url> {
+        self.captures
+    }
+}
+
+impl<'router, 'url, Handler> Deref for UrlMatch<'router, 'url, Handler> {
+    type Target = Handler;
+
+    fn deref(&self) -> &Self::Target {
+        self.handler
+    }
+}
@@ -0,0 +1,121 @@
+//! JavaScript bindings, built with wasm-bindgen
+//!
+//! These are available with the `wasm` feature, and are packaged for
+//! npm from the `js` directory of the repository, where the `wasm`
+//! crate links them into a cdylib. They export a `Router` class whose
+//! handlers can be any JavaScript value:
+//!
+//! ```js
+//! import { Router } from "routefinder";
+//!
+//! const router = new Router();
+//! router.add("/users/:id", "user");
+//! const match = router.bestMatch("/users/1");
+//! // { handler: "user", route: "/users/:id", params: { id: "1" }, wildcard: undefined }
+//! router.urlFor("/users/:id", { id: "2" }); // "/users/2"
+//! ```
+#![allow(unsafe_code)] // wasm-bindgen's generated glue is unsafe
+
+use crate::{Captures, RouteSpec, Router as RustRouter};
+use js_sys::{Object, Reflect};
+use std::fmt::{self, Debug, Formatter};
+use wasm_bindgen::prelude::*;
+
+/// a [`Router`][crate::Router] of JavaScript values, exported to
+/// JavaScript as `Router`
+#[wasm_bindgen(js_name = Router)]
+pub struct JsRouter {
+    router: RustRouter<JsValue>,
+}
+
+impl Debug for JsRouter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsRouter")
+            .field("routes", &self.router.len())
+            .finish()
+    }
+}
+
+impl Default for JsRouter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen(js_class = Router)]
+impl JsRouter {
+    /// builds an empty router
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self {
+            router: RustRouter::new(),
+        }
+    }
+
+    /// adds a route, throwing if it cannot be parsed, and returns its
+    /// numeric id. See [`Router::add`][crate::Router::add]
+    pub fn add(&mut self, route: &str, handler: JsValue) -> Result<usize, JsError> {
+        self.router
+            .add(route, handler)
+            .map(|id| id.get())
+            .map_err(|e| JsError::new(&e))
+    }
+
+    /// the number of routes that have been added
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> usize {
+        self.router.len()
+    }
+
+    /// returns `{ handler, route, params, wildcard }` for the best
+    /// match for this path, or `null`. See
+    /// [`Router::best_match`][crate::Router::best_match]
+    #[wasm_bindgen(js_name = bestMatch)]
+    pub fn best_match(&self, path: &str) -> Result<JsValue, JsValue> {
+        let m = match self.router.best_match(path) {
+            Some(m) => m,
+            None => return Ok(JsValue::NULL),
+        };
+
+        let captures = m.captures();
+        let params = Object::new();
+        for (name, value) in captures.iter() {
+            Reflect::set(&params, &name.into(), &value.into())?;
+        }
+
+        let result = Object::new();
+        Reflect::set(&result, &"handler".into(), m.handler())?;
+        Reflect::set(&result, &"route".into(), &m.route().to_string().into())?;
+        Reflect::set(&result, &"params".into(), &params)?;
+        let wildcard = captures
+            .wildcard()
+            .map_or(JsValue::UNDEFINED, JsValue::from);
+        Reflect::set(&result, &"wildcard".into(), &wildcard)?;
+        Ok(result.into())
+    }
+
+    /// fills in a route with the string values of `params`, using the
+    /// key `"*"` for the wildcard, or returns `undefined` if they do
+    /// not fit the route. See [`RouteSpec::template`]
+    #[wasm_bindgen(js_name = urlFor)]
+    pub fn url_for(&self, route: &str, params: JsValue) -> Result<Option<String>, JsValue> {
+        let spec: RouteSpec = route.parse().map_err(|e: String| JsError::new(&e))?;
+
+        let mut values = vec![];
+        for name in spec.segments().iter().filter_map(|segment| segment.name()) {
+            if let Some(value) = Reflect::get(&params, &name.into())?.as_string() {
+                values.push((name, value));
+            }
+        }
+
+        let mut captures: Captures = values
+            .iter()
+            .map(|(name, value)| (*name, value.as_str()))
+            .collect();
+        if let Some(wildcard) = Reflect::get(&params, &"*".into())?.as_string() {
+            captures.set_wildcard(wildcard);
+        }
+
+        Ok(spec.template(&captures).map(|m| m.to_string()))
+    }
+}
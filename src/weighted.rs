@@ -0,0 +1,52 @@
+use std::{
+    collections::hash_map::RandomState,
+    fmt::{self, Debug, Formatter},
+    hash::{BuildHasher, Hasher},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Chooses between the handlers of a weighted route. See
+/// [`Router::add_weighted`][crate::Router::add_weighted].
+///
+/// This is implemented for any `Fn(u32) -> u32`, so a closure over
+/// the rng of your choice can be used directly.
+pub trait WeightStrategy: Send + Sync {
+    /// Returns a number in `0..total`, where `total` is the sum of the
+    /// weights of the handlers for the route that matched. The handler
+    /// whose share of that range contains the number is selected.
+    fn choose(&self, total: u32) -> u32;
+}
+
+impl<F> WeightStrategy for F
+where
+    F: Fn(u32) -> u32 + Send + Sync,
+{
+    fn choose(&self, total: u32) -> u32 {
+        self(total)
+    }
+}
+
+/// The default [`WeightStrategy`], which uses std's randomly seeded
+/// hasher as a small non-cryptographic random number generator
+#[derive(Default)]
+pub struct RandomWeights {
+    state: RandomState,
+    counter: AtomicU64,
+}
+
+impl Debug for RandomWeights {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RandomWeights").finish()
+    }
+}
+
+impl WeightStrategy for RandomWeights {
+    fn choose(&self, total: u32) -> u32 {
+        if total == 0 {
+            return 0;
+        }
+        let mut hasher = self.state.build_hasher();
+        hasher.write_u64(self.counter.fetch_add(1, Ordering::Relaxed));
+        (hasher.finish() % u64::from(total)) as u32
+    }
+}
@@ -0,0 +1,77 @@
+use routefinder::*;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// counts allocations, so that this file must only have one test
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn allocations<T>(f: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATIONS.load(Ordering::SeqCst);
+    let result = f();
+    let after = ALLOCATIONS.load(Ordering::SeqCst);
+    drop(result);
+    after - before
+}
+
+#[test]
+fn captures_borrow_names() {
+    let mut router = Router::new();
+    router.add("/orgs/:org/repos/:repo/issues/:id", 1).unwrap();
+    router.add("/:a/:b/:c/:d/:e/:f/*", 2).unwrap();
+    router.add("/health", 3).unwrap();
+
+    let m = router
+        .best_match("/orgs/jbr/repos/routefinder/issues/1")
+        .unwrap();
+    assert_eq!(allocations(|| m.captures()), 1);
+
+    let captures = m.captures();
+    let names = m
+        .route()
+        .segments()
+        .iter()
+        .filter_map(|segment| match segment {
+            Segment::Param(name) => Some(name.as_ptr()),
+            _ => None,
+        });
+    assert!(captures.iter().map(|(name, _)| name.as_ptr()).eq(names));
+
+    let m = router.best_match("/1/2/3/4/5/6/7/8").unwrap();
+    assert_eq!(allocations(|| m.captures()), 1);
+    assert_eq!(m.captures().wildcard(), Some("7/8"));
+
+    let m = router.best_match("/health").unwrap();
+    assert_eq!(allocations(|| m.captures()), 0);
+
+    let sized = allocations(|| {
+        let mut captures = Captures::with_capacity(2);
+        captures.push(("a", "1"));
+        captures.push(("b", "2"));
+        captures.reserve(0);
+        captures
+    });
+    assert_eq!(sized, 1);
+}
@@ -0,0 +1,169 @@
+#![cfg(feature = "cli")]
+use std::{
+    env, fs,
+    path::PathBuf,
+    process::{Command, Output},
+};
+
+const ROUTES: &str = "\
+# a small route table
+/                       home
+/users/:id              users.show
+/users/new              users.new
+/users/:name            users.by_name
+/assets/*               assets
+/users/new
+";
+
+fn routes_file(name: &str, contents: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!(
+        "routefinder-cli-{}-{}.txt",
+        name,
+        std::process::id()
+    ));
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+fn run(routes: &str, args: &[&str]) -> Output {
+    let file = routes_file(args[0], routes);
+    let output = Command::new(env!("CARGO_BIN_EXE_routefinder"))
+        .arg(&file)
+        .args(args)
+        .output()
+        .unwrap();
+    fs::remove_file(file).unwrap();
+    output
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn match_path() {
+    let output = run(ROUTES, &["match", "/users/1"]);
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "route: /users/:id (line 3)\nname: users.show\nid = 1\n"
+    );
+
+    let output = run(ROUTES, &["match", "/assets/css/site.css"]);
+    assert!(stdout(&output).ends_with("* = css/site.css\n"));
+
+    let output = run("/a\n", &["match", "/b"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("no route matches /b"));
+}
+
+#[test]
+fn explain() {
+    let output = run(ROUTES, &["explain", "/users/new"]);
+    assert!(output.status.success());
+    let lines: Vec<String> = stdout(&output).lines().map(String::from).collect();
+    assert_eq!(lines.len(), 6);
+    assert!(lines
+        .iter()
+        .any(|l| l.starts_with("> /users/new") && l.contains("users.new")));
+    assert_eq!(
+        lines
+            .iter()
+            .filter(|l| l.contains("takes precedence"))
+            .count(),
+        3
+    );
+}
+
+#[test]
+fn table() {
+    let output = run(ROUTES, &["table"]);
+    let table = stdout(&output);
+    assert_eq!(table.lines().count(), 6);
+    assert!(table.lines().last().unwrap().starts_with("/"));
+    assert!(table.contains("users.show"));
+}
+
+#[test]
+fn lint() {
+    let output = run(ROUTES, &["lint"]);
+    assert!(!output.status.success());
+    let report = stdout(&output);
+    assert!(report.contains("line 7: /users/new duplicates line 4"));
+    assert!(report.contains("/users/:name is shadowed by /users/:id (line 3)"));
+    assert_eq!(report.lines().count(), 2);
+
+    let output = run("/\n/users/:id\n/users/new\n", &["lint"]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "no problems found in 3 routes\n");
+}
+
+#[test]
+fn reverse() {
+    let output = run(ROUTES, &["reverse", "users.show", "id=10"]);
+    assert_eq!(stdout(&output), "/users/10\n");
+
+    let output = run(ROUTES, &["reverse", "assets", "*=js/app.js"]);
+    assert_eq!(stdout(&output), "/assets/js/app.js\n");
+
+    let output = run(ROUTES, &["reverse", "users.show"]);
+    assert!(!output.status.success());
+
+    let output = run(ROUTES, &["reverse", "missing"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("no route is named missing"));
+}
+
+#[test]
+fn errors() {
+    let output = run("/users/:\n", &["table"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("line 1: params must be named"));
+
+    let output = run(ROUTES, &["unknown"]);
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("usage:"));
+}
+
+#[test]
+fn replay() {
+    let log = env::temp_dir().join(format!("routefinder-cli-log-{}.txt", std::process::id()));
+    fs::write(
+        &log,
+        r#"/users/1
+/users/2?tab=posts
+127.0.0.1 - frank [10/Oct/2000:13:55:36 -0700] "GET /users/new HTTP/1.0" 200 2326
+127.0.0.1 - - [10/Oct/2000:13:55:37 -0700] "GET /assets/site.css HTTP/1.1" 200 12 "-" "curl/8.0"
+/missing
+/missing/again
+/missing
+not a request
+"#,
+    )
+    .unwrap();
+
+    let output = run(ROUTES, &["replay", log.to_str().unwrap()]);
+    fs::remove_file(log).unwrap();
+    assert!(output.status.success());
+
+    let report = stdout(&output);
+    let lines: Vec<&str> = report.lines().collect();
+    assert!(lines[0].starts_with("replayed 7 paths in "));
+    assert!(lines[0].ends_with(" paths/s)"));
+    assert_eq!(lines[2], "hits:");
+    assert_eq!(
+        lines[3].split_whitespace().collect::<Vec<_>>(),
+        ["0", "/", "home"]
+    );
+    assert!(lines
+        .iter()
+        .any(|l| l.split_whitespace().collect::<Vec<_>>() == ["2", "/users/:id", "users.show"]));
+    assert!(lines
+        .iter()
+        .any(|l| l.split_whitespace().collect::<Vec<_>>() == ["1", "/assets/*", "assets"]));
+    assert_eq!(
+        &lines[lines.len() - 3..],
+        [
+            "unmatched: 3",
+            "       2  /missing",
+            "       1  /missing/again"
+        ]
+    );
+}
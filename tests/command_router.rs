@@ -0,0 +1,105 @@
+use routefinder::CommandRouter;
+
+fn router() -> CommandRouter<&'static str> {
+    let mut router = CommandRouter::new();
+    for (command, handler) in [
+        ("", "help"),
+        ("remote", "list remotes"),
+        ("remote add :name :url", "add remote"),
+        ("remote remove :name", "remove remote"),
+        ("remote :name", "show remote"),
+        ("checkout :branch :path?", "checkout"),
+        ("commit :files*", "commit"),
+        ("exec *", "exec"),
+    ] {
+        router.add(command, handler).unwrap();
+    }
+    router
+}
+
+#[test]
+fn precedence_and_captures() {
+    let router = router();
+    let none: [&str; 0] = [];
+    assert_eq!(*router.best_match(&none).unwrap(), "help");
+    assert_eq!(*router.best_match(&["remote"]).unwrap(), "list remotes");
+    assert_eq!(
+        *router.best_match(&["remote", "add"]).unwrap(),
+        "show remote"
+    );
+    assert!(router.best_match(&["remote", "add", "origin"]).is_none());
+
+    let m = router.best_match(&["remote", "remove", "origin"]).unwrap();
+    assert_eq!(*m, "remove remote");
+    assert_eq!(m.command(), "remote remove :name");
+    assert_eq!(m.captures().get("name"), Some("origin"));
+
+    let m = router.best_match(&["checkout", "main"]).unwrap();
+    assert_eq!(m.captures().get("path"), None);
+    let m = router
+        .best_match(&["checkout", "main", "src/lib.rs"])
+        .unwrap();
+    assert_eq!(m.captures().get("path"), Some("src/lib.rs"));
+
+    let m = router.best_match(&["commit"]).unwrap();
+    assert_eq!(m.captures().get("files"), None);
+    let m = router.best_match(&["exec", "ls", "-la", "/tmp"]).unwrap();
+    assert_eq!(m.captures().wildcard(), Some("ls -la /tmp"));
+}
+
+#[test]
+fn arguments_are_matched_whole() {
+    let router = router();
+    for arg in [
+        "https://example.com/a/b",
+        "a b",
+        "100%",
+        "%2F",
+        "..",
+        ".",
+        "",
+        "a.b.c",
+    ] {
+        let m = router
+            .best_match(&["remote", "add", "origin", arg])
+            .unwrap();
+        assert_eq!(*m, "add remote", "{:?}", arg);
+        assert_eq!(m.captures().get("url"), Some(arg));
+    }
+
+    let args = vec![String::from("remote"), String::from("upstream")];
+    assert_eq!(
+        router.best_match(&args).unwrap().captures().get("name"),
+        Some("upstream")
+    );
+}
+
+#[test]
+fn suggestions() {
+    let router = router();
+    assert_eq!(
+        router.suggest(&["remot", "add", "origin", "url"]),
+        ["remote add origin url"]
+    );
+    assert_eq!(
+        router.suggest(&["remote", "remov", "origin"]),
+        ["remote remove origin"]
+    );
+    assert_eq!(router.suggest(&["comit", "a.rs"]), ["commit a.rs"]);
+    assert!(router.suggest(&["remote", "origin"]).is_empty());
+    assert!(router.suggest(&["push"]).is_empty());
+}
+
+#[test]
+fn commands() {
+    let mut router = router();
+    assert_eq!(router.len(), 8);
+    assert!(router
+        .commands()
+        .any(|command| command == "remote add :name :url"));
+    assert!(router.add("open a/b", "nope").is_err());
+    assert_eq!(
+        router.router().best_match("/remote/add/a/b").map(|m| *m),
+        Some("add remote")
+    );
+}
@@ -0,0 +1,74 @@
+#![cfg(feature = "dispatch")]
+use routefinder::{Captures, Router};
+use std::{
+    future::Future,
+    pin::Pin,
+    ptr,
+    task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+};
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    fn raw_waker() -> RawWaker {
+        fn clone(_: *const ()) -> RawWaker {
+            raw_waker()
+        }
+        fn noop(_: *const ()) {}
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+        RawWaker::new(ptr::null(), &VTABLE)
+    }
+
+    let waker = unsafe { Waker::from_raw(raw_waker()) };
+    let mut context = Context::from_waker(&waker);
+    let mut future = Box::pin(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+type BoxFuture<'a> = Pin<Box<dyn Future<Output = String> + 'a>>;
+
+#[test]
+fn dispatch_borrowed() {
+    type Handler = Box<dyn for<'a, 'b> Fn(Captures<'a, 'b>, &'static str) -> BoxFuture<'b>>;
+    let mut router: Router<Handler> = Router::new();
+    router
+        .add(
+            "/users/:id",
+            Box::new(|captures, greeting| {
+                let id = captures.get("id").unwrap().to_string();
+                Box::pin(async move { format!("{} user {}", greeting, id) })
+            }),
+        )
+        .unwrap();
+
+    let path = String::from("/users/10");
+    let future = router.dispatch(&path, "hello").ok().unwrap();
+    assert_eq!(block_on(future), "hello user 10");
+
+    let fallback: Handler =
+        Box::new(|_, greeting| Box::pin(async move { format!("{} nobody", greeting) }));
+    assert_eq!(
+        block_on(router.dispatch_or("/nope", "hi", &fallback)),
+        "hi nobody"
+    );
+    assert_eq!(router.dispatch("/nope", "hi").err(), Some("hi"));
+}
+
+#[test]
+fn dispatch_owned() {
+    type Handler = fn(Captures<'static, 'static>, u32) -> BoxFuture<'static>;
+    let mut router: Router<Handler> = Router::new();
+    router
+        .add("/files/*", |captures, n| {
+            Box::pin(async move { format!("{} {}", captures.wildcard().unwrap(), n) })
+        })
+        .unwrap();
+
+    let future = {
+        let path = String::from("/files/a/b.txt");
+        router.dispatch_owned(&path, 1).ok().unwrap()
+    };
+    assert_eq!(block_on(future), "a/b.txt 1");
+}
@@ -0,0 +1,143 @@
+#![cfg(feature = "export")]
+use routefinder::{RouteSpec, Router, Syntax};
+
+fn name(_: &RouteSpec, handler: &&str) -> Option<String> {
+    if handler.is_empty() {
+        None
+    } else {
+        Some(handler.to_string())
+    }
+}
+
+#[test]
+fn json() {
+    let mut router = Router::new();
+    router.add("/files/*", "files").unwrap();
+    router.add("/\"quoted\"", "").unwrap();
+    router
+        .add(
+            RouteSpec::parse_with("/docs/:section/:page?", Syntax::Express).unwrap(),
+            "docs",
+        )
+        .unwrap();
+
+    assert_eq!(
+        router.export_json(name),
+        concat!(
+            "[",
+            r#"{"name":null,"template":"/\"quoted\"","params":[],"optional":[],"wildcard":false},"#,
+            r#"{"name":"docs","template":"/docs/:section/:page?","params":["section","page"],"optional":["page"],"wildcard":false},"#,
+            r#"{"name":"files","template":"/files/*","params":[],"optional":[],"wildcard":true}"#,
+            "]"
+        )
+    );
+
+    assert_eq!(Router::<&str>::new().export_json(name), "[]");
+}
+
+#[test]
+fn typescript() {
+    let mut router = Router::new();
+    router.add("/", "home").unwrap();
+    router.add("/users/:id.:format", "user").unwrap();
+    router.add("/files/*", "files").unwrap();
+    router.add("/hidden", "").unwrap();
+    router.add("/:catchall", "home").unwrap();
+    router
+        .add(
+            RouteSpec::parse_with("/docs/:path*", Syntax::Express).unwrap(),
+            "docs",
+        )
+        .unwrap();
+    router
+        .add(
+            RouteSpec::parse_with("/assets/*.css", Syntax::Glob).unwrap(),
+            "css",
+        )
+        .unwrap();
+
+    let typescript = router.export_typescript(name);
+    assert_eq!(
+        typescript,
+        r#"// generated by routefinder. do not edit.
+
+export const routes = {
+  "home": {
+    template: "/",
+    params: [],
+    url: () => `/`,
+  },
+  "css": {
+    template: "/assets/*.css",
+    params: [],
+  },
+  "user": {
+    template: "/users/:id.:format",
+    params: ["id","format"],
+    url: (params: { id: string; format: string }) => `/users/${encodeURIComponent(params.id)}.${encodeURIComponent(params.format)}`,
+  },
+  "docs": {
+    template: "/docs/:path*",
+    params: ["path"],
+    url: (params: { path?: string }) => `/docs${params.path === undefined || params.path === "" ? "" : "/" + params.path}`,
+  },
+  "files": {
+    template: "/files/*",
+    params: [],
+    url: (params: { "*"?: string }) => `/files${params["*"] === undefined || params["*"] === "" ? "" : "/" + params["*"]}`,
+  },
+} as const;
+"#
+    );
+}
+
+#[test]
+fn route_references() {
+    let mut router = Router::new();
+    router
+        .add_documented("/users/:id", "user", "Returns a user | by id")
+        .unwrap();
+    router.add("/users/new", "new user").unwrap();
+    router
+        .add_documented("/assets/*", "assets", "Static files\nfrom disk")
+        .unwrap();
+    router.add("/", "home").unwrap();
+
+    assert_eq!(
+        router.export_markdown(),
+        concat!(
+            "## /\n\n",
+            "| Route | Description |\n",
+            "| --- | --- |\n",
+            "| `/` |  |\n",
+            "\n",
+            "## /assets\n\n",
+            "| Route | Description |\n",
+            "| --- | --- |\n",
+            "| `/assets/*` | Static files from disk |\n",
+            "\n",
+            "## /users\n\n",
+            "| Route | Description |\n",
+            "| --- | --- |\n",
+            "| `/users/new` |  |\n",
+            "| `/users/:id` | Returns a user \\| by id |\n",
+        )
+    );
+
+    assert_eq!(
+        router.export_text(),
+        concat!(
+            "/\n",
+            "  /\n",
+            "\n",
+            "/assets\n",
+            "  /assets/*    Static files from disk\n",
+            "\n",
+            "/users\n",
+            "  /users/new\n",
+            "  /users/:id   Returns a user | by id\n",
+        )
+    );
+
+    assert_eq!(Router::<()>::new().export_markdown(), "");
+}
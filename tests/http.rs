@@ -0,0 +1,55 @@
+#![cfg(feature = "http")]
+use routefinder::{AsPath, ErasedRouter, MethodMatch, MethodRouter, Router};
+
+#[test]
+fn uri_paths() {
+    let mut router = Router::new();
+    router.add("/users/:id", "user").unwrap();
+    router.add("/", "root").unwrap();
+
+    let uri: http::Uri = "https://example.com/users/1?tab=posts".parse().unwrap();
+    assert_eq!(uri.raw(), "/users/1?tab=posts");
+    assert_eq!(uri.trimmed(), "/users/1");
+    let m = router.best_match(&uri).unwrap();
+    assert_eq!(*m, "user");
+    assert_eq!(m.captures().get("id"), Some("1"));
+
+    let root = http::Uri::from_static("https://example.com");
+    assert_eq!(*router.best_match(&root).unwrap(), "root");
+
+    let mut methods = MethodRouter::new();
+    methods.add("GET", "/users/:id", "show").unwrap();
+    assert_eq!(*methods.best_match("GET", &uri).unwrap(), "show");
+    assert_eq!(methods.allowed_methods(&uri), ["GET", "HEAD", "OPTIONS"]);
+    assert!(matches!(
+        methods.resolve("PUT", &uri),
+        MethodMatch::MethodNotAllowed(_)
+    ));
+}
+
+#[test]
+fn uri_paths_across_matching_methods() {
+    let mut router = Router::new();
+    router.add("/users/:id", "user").unwrap();
+    let uri: http::Uri = "/users/1/?tab=posts".parse().unwrap();
+
+    let strict = router.match_options().strict_trailing_slash(true);
+    assert!(router.best_match_with(&uri, &strict).is_none());
+    assert_eq!(
+        router
+            .match_iter_with(&uri, &router.match_options())
+            .count(),
+        1
+    );
+
+    let index = router.lookup(&uri).unwrap();
+    assert_eq!(index.captures().get("id"), Some("1"));
+
+    let explanation = router.explain(&uri);
+    assert_eq!(explanation.selected().unwrap().route(), "/users/:id");
+
+    let erased: &dyn ErasedRouter = &router;
+    let m = erased.best_match_erased(&uri).unwrap();
+    assert_eq!(m.downcast_handler::<&str>(), Some(&"user"));
+    assert_eq!(m.path(), "/users/1/");
+}
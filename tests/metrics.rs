@@ -0,0 +1,154 @@
+#![cfg(feature = "metrics")]
+use routefinder::{metrics::Labels, MatchMode, MethodRouter, Router};
+use std::sync::{Arc, Mutex};
+
+fn recorder() -> (Arc<Mutex<Vec<Labels>>>, impl Fn(&Labels) + Send + Sync) {
+    let recorded = Arc::new(Mutex::new(vec![]));
+    let sink = recorded.clone();
+    (recorded, move |labels: &Labels| {
+        sink.lock().unwrap().push(labels.clone())
+    })
+}
+
+#[test]
+fn labels_never_include_captures() {
+    let mut router = Router::new();
+    router.add("/users/:id", ()).unwrap();
+    router.add("/files/*", ()).unwrap();
+    router
+        .add_with_mode("/static", MatchMode::Prefix, ())
+        .unwrap();
+
+    let (recorded, recorder) = recorder();
+    router.set_recorder(recorder);
+
+    for path in [
+        "/users/1",
+        "/users/2",
+        "/files/a/b",
+        "/static/x.css",
+        "/nope",
+    ] {
+        router.best_match(path);
+    }
+    router.matches("/users/3");
+    let mut matcher = router.matcher();
+    matcher.feed("users").feed("4");
+    matcher.finish();
+
+    let routes: Vec<_> = recorded
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|labels| labels.route().map(String::from))
+        .collect();
+    assert_eq!(
+        routes,
+        [
+            Some("/users/:id".into()),
+            Some("/users/:id".into()),
+            Some("/files/*".into()),
+            Some("/static".into()),
+            None,
+            Some("/users/:id".into()),
+        ]
+    );
+
+    let miss = &recorded.lock().unwrap()[4];
+    assert!(!miss.is_match());
+    assert!(miss.pairs().is_empty());
+}
+
+#[test]
+fn method_labels() {
+    let mut router = MethodRouter::new();
+    router.add("GET", "/users/:id", ()).unwrap();
+    router.add("DELETE", "/users/:id", ()).unwrap();
+
+    let (recorded, recorder) = recorder();
+    router.set_recorder(recorder);
+
+    router.best_match("get", "/users/1");
+    router.best_match("HEAD", "/users/1");
+    router.resolve("DELETE", "/nope");
+    router.resolve("PUT", "/users/1");
+    router.best_match("X-RANDOM-1234", "/users/1");
+
+    let pairs: Vec<Vec<(&str, String)>> = recorded
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|labels| {
+            labels
+                .pairs()
+                .into_iter()
+                .map(|(name, value)| (name, String::from(value)))
+                .collect()
+        })
+        .collect();
+
+    assert_eq!(
+        pairs,
+        [
+            vec![("route", "/users/:id".into()), ("method", "GET".into())],
+            vec![("route", "/users/:id".into()), ("method", "HEAD".into())],
+            vec![("method", "DELETE".into())],
+            vec![("method", "OTHER".into())],
+            vec![("method", "OTHER".into())],
+        ]
+    );
+}
+
+#[test]
+fn hit_counts() {
+    let mut router = Router::new();
+    let user = router.add("/users/:id", "user").unwrap();
+    let fallback = router.add("/*", "fallback").unwrap();
+    assert_eq!(router.hit_count(user), None);
+    assert_eq!(router.hit_counts().count(), 0);
+
+    router.set_hit_counts(true);
+    let new = router.add("/users/new", "new user").unwrap();
+    router
+        .add_with_mode("/api", MatchMode::Prefix, "api")
+        .unwrap();
+
+    let router = Arc::new(router);
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            let router = Arc::clone(&router);
+            std::thread::spawn(move || {
+                for _ in 0..100 {
+                    router.best_match("/users/1");
+                    router.best_match("/users/new");
+                    router.best_match("/about");
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    assert_eq!(router.hit_count(user), Some(400));
+    assert_eq!(router.hit_count(new), Some(400));
+    assert_eq!(router.hit_count(fallback), Some(400));
+    assert_eq!(
+        router
+            .hit_counts()
+            .map(|(_, count)| count)
+            .collect::<Vec<_>>(),
+        [400, 400, 400, 0]
+    );
+
+    let copy = Router::clone(&router);
+    router.reset_hit_counts();
+    assert_eq!(router.hit_count(user), Some(0));
+    assert_eq!(copy.hit_count(user), Some(400));
+
+    let mut router = copy;
+    router.remove(user);
+    assert_eq!(router.hit_count(user), None);
+    router.set_hit_counts(false);
+    assert_eq!(router.hit_count(new), None);
+}
@@ -0,0 +1,69 @@
+#![cfg(feature = "openapi")]
+use routefinder::MethodRouter;
+
+#[test]
+fn imports_paths() {
+    let router = MethodRouter::from_openapi(
+        r#"{
+            "swagger": "2.0",
+            "basePath": "/api",
+            "paths": {
+                "/": { "get": { "operationId": "root" } },
+                "/files/{name}.{ext}": {
+                    "get": { "operationId": "download" },
+                    "delete": {},
+                    "x-internal": { "operationId": "ignored" }
+                },
+                "/users/{id}/posts/{post_id}": {
+                    "summary": "a post",
+                    "put": { "operationId": "updatePost" }
+                }
+            }
+        }"#,
+    )
+    .unwrap();
+
+    assert_eq!(*router.best_match("GET", "/").unwrap(), "root");
+
+    let m = router.best_match("GET", "/files/notes.txt").unwrap();
+    assert_eq!(*m, "download");
+    assert_eq!(m.captures().get("name"), Some("notes"));
+    assert_eq!(m.captures().get("ext"), Some("txt"));
+    assert_eq!(
+        *router.best_match("DELETE", "/files/a.b").unwrap(),
+        "DELETE /files/{name}.{ext}"
+    );
+
+    let m = router.best_match("put", "/users/1/posts/2").unwrap();
+    assert_eq!(*m, "updatePost");
+    assert_eq!(m.captures().get("post_id"), Some("2"));
+
+    assert_eq!(router.iter().count(), 4);
+    assert!(router.router("X-INTERNAL").is_none());
+}
+
+#[test]
+fn errors() {
+    for document in [
+        "not json",
+        "{}",
+        r#"{"paths": []}"#,
+        r#"{"paths": {"/users": []}}"#,
+        r#"{"paths": {"/v{version}/users": {}}}"#,
+        r#"{"paths": {"/users/{id}s": {}}}"#,
+        r#"{"paths": {"/users/{id": {}}}"#,
+        r#"{"paths": {"/users/{}": {}}}"#,
+    ] {
+        assert!(
+            MethodRouter::from_openapi(document).is_err(),
+            "{} should not import",
+            document
+        );
+    }
+
+    assert!(MethodRouter::from_openapi(r#"{"paths": {}}"#)
+        .unwrap()
+        .methods()
+        .next()
+        .is_none());
+}
@@ -0,0 +1,118 @@
+use routefinder::{rails, MethodRouter};
+
+fn table(router: &MethodRouter<String>) -> Vec<String> {
+    router
+        .iter()
+        .map(|(method, route, handler)| format!("{} {} {}", method, route, handler))
+        .collect()
+}
+
+#[test]
+fn resources_expand_to_conventional_routes() {
+    let router = rails::load("resources :photos").unwrap();
+    assert_eq!(
+        table(&router),
+        [
+            "DELETE /photos/:id photos#destroy",
+            "GET /photos photos#index",
+            "GET /photos/new photos#new",
+            "GET /photos/:id photos#show",
+            "GET /photos/:id/edit photos#edit",
+            "PATCH /photos/:id photos#update",
+            "POST /photos photos#create",
+            "PUT /photos/:id photos#update",
+        ]
+    );
+
+    assert_eq!(
+        *router.best_match("GET", "/photos/new").unwrap(),
+        "photos#new"
+    );
+    assert_eq!(
+        *router.best_match("GET", "/photos/1").unwrap(),
+        "photos#show"
+    );
+}
+
+#[test]
+fn only_except_and_singular_resources() {
+    let router = rails::load(
+        r#"
+        resources :users, only: [:index, :show]
+        resources :sessions, except: :index
+        resource :profile
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(router.router("GET").unwrap().len(), 8);
+    assert!(router.best_match("DELETE", "/users/1").is_none());
+    assert!(router.best_match("GET", "/sessions").is_none());
+    assert_eq!(
+        *router.best_match("GET", "/profile").unwrap(),
+        "profiles#show"
+    );
+    assert_eq!(
+        *router.best_match("GET", "/profile/edit").unwrap(),
+        "profiles#edit"
+    );
+    assert!(router.best_match("GET", "/profile/1").is_none());
+}
+
+#[test]
+fn namespaces_scopes_and_nesting() {
+    let router = rails::load(
+        r#"
+        Rails.application.routes.draw do
+          # the admin area
+          namespace :admin do
+            get "dashboard", to: "dashboard#index", as: :dashboard
+            resources :categories, only: :show do
+              resources :entries, only: [:index]
+            end
+          end
+
+          scope "/api" do
+            get "/files/*path" => "files#show"
+          end
+        end
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        *router.best_match("GET", "/admin/dashboard").unwrap(),
+        "admin/dashboard#index"
+    );
+
+    let m = router
+        .best_match("GET", "/admin/categories/3/entries")
+        .unwrap();
+    assert_eq!(*m, "admin/entries#index");
+    assert_eq!(m.captures().get("category_id"), Some("3"));
+
+    let m = router.best_match("GET", "/api/files/a/b.txt").unwrap();
+    assert_eq!(*m, "files#show");
+    assert_eq!(m.captures().get("path"), Some("a/b.txt"));
+    assert!(router.best_match("GET", "/api/files").is_none());
+}
+
+#[test]
+fn errors_include_line_numbers() {
+    assert_eq!(
+        rails::load("get '/a' => 'a#b'\nend").unwrap_err(),
+        "line 2: unexpected `end`"
+    );
+    assert!(rails::load("resources :users do")
+        .unwrap_err()
+        .contains("expected `end`"));
+    assert!(rails::load("resources :users, only: [:frobnicate]")
+        .unwrap_err()
+        .contains("unknown action `:frobnicate`"));
+    assert!(rails::load("get '/posts(.:format)' => 'posts#index'")
+        .unwrap_err()
+        .contains("optional segments are not supported"));
+    assert!(rails::load("mount Sidekiq::Web => '/sidekiq'")
+        .unwrap_err()
+        .starts_with("line 1:"));
+}
@@ -0,0 +1,56 @@
+#![cfg(feature = "serde")]
+use routefinder::Router;
+use serde_json::json;
+
+#[test]
+fn explanations_serialize() {
+    let mut router = Router::new();
+    router.add("/users/:id", ()).unwrap();
+    router.add("/users/new", ()).unwrap();
+    router.add("/posts/:id", ()).unwrap();
+    let explanation = router.explain("/users/new");
+
+    assert_eq!(
+        serde_json::to_value(&explanation).unwrap(),
+        json!({
+            "path": "/users/new",
+            "routes": [
+                {
+                    "id": 1,
+                    "route": "/users/new",
+                    "skipped": false,
+                    "outcome": "Selected"
+                },
+                {
+                    "id": 0,
+                    "route": "/users/:id",
+                    "skipped": false,
+                    "outcome": "Shadowed"
+                },
+                {
+                    "id": 2,
+                    "route": "/posts/:id",
+                    "skipped": false,
+                    "outcome": {
+                        "Rejected": {
+                            "Mismatch": {
+                                "segment": 0,
+                                "expected": "posts",
+                                "found": "users"
+                            }
+                        }
+                    }
+                }
+            ]
+        })
+    );
+}
+
+#[test]
+fn sort_keys_serialize() {
+    let mut router = Router::new();
+    router.add("/users/:id", ()).unwrap();
+    let (route, _) = router.iter().next().unwrap();
+    let key = route.sort_key();
+    assert_eq!(serde_json::to_value(&key).unwrap(), json!(key.as_str()));
+}
@@ -0,0 +1,62 @@
+#![cfg(feature = "simd")]
+use routefinder::Router;
+
+type Result = std::result::Result<(), String>;
+
+#[test]
+fn long_exact_segments() -> Result {
+    let mut router = Router::new();
+    router.add("/static/js/vendor/react-dom.production.min.js", "exact")?;
+    router.add("/static/js/vendor/:file", "vendor")?;
+    router.add("/static/*", "static")?;
+
+    let path = "/static/js/vendor/react-dom.production.min.js";
+    assert_eq!(*router.best_match(path).unwrap(), "exact");
+
+    // differences in every position of an eight byte chunk, and in the
+    // remainder after the last chunk
+    for index in 1..path.len() {
+        if path.as_bytes()[index] == b'/' {
+            continue;
+        }
+        let mut changed = String::from(path);
+        changed.replace_range(index..=index, "_");
+        let best = router.best_match(&changed).map(|m| *m);
+        assert_ne!(best, Some("exact"), "{}", changed);
+    }
+
+    assert_eq!(
+        *router
+            .best_match("/static/js/vendor/react-dom.production.min.j")
+            .unwrap(),
+        "vendor"
+    );
+    Ok(())
+}
+
+#[test]
+fn delimiters() -> Result {
+    let mut router = Router::new();
+    router.add("/files/:name.:ext", "file")?;
+    router.add("/assets/:name", "name")?;
+    router.add("/assets/:name/*", "nested")?;
+
+    for name in ["a", "abcdefg", "abcdefgh", "abcdefghi", "ünïcödé-fïlé-nämé"] {
+        let file = format!("/files/{}.js", name);
+        let m = router.best_match(&file).unwrap();
+        assert_eq!(*m, "file");
+        assert_eq!(m.captures().get("name"), Some(name));
+        assert_eq!(m.captures().get("ext"), Some("js"));
+
+        let bare = format!("/assets/{}", name);
+        let m = router.best_match(&bare).unwrap();
+        assert_eq!(*m, "name");
+        assert_eq!(m.captures().get("name"), Some(name));
+
+        let nested = format!("/assets/{}/more/path", name);
+        let m = router.best_match(&nested).unwrap();
+        assert_eq!(*m, "nested");
+        assert_eq!(m.captures().get("name"), Some(name));
+    }
+    Ok(())
+}
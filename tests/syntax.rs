@@ -0,0 +1,593 @@
+type Result = std::result::Result<(), Box<dyn std::error::Error>>;
+use routefinder::*;
+
+fn glob(source: &str) -> RouteSpec {
+    RouteSpec::parse_with(source, Syntax::Glob).unwrap()
+}
+
+#[test]
+fn glob_single_segment() {
+    assert!(glob("*.md").matches("README.md").is_some());
+    assert!(glob("*.md").matches("docs/README.md").is_none());
+    assert!(glob("docs/*").matches("docs/README.md").is_some());
+    assert!(glob("docs/*/index.html")
+        .matches("docs/api/index.html")
+        .is_some());
+    assert!(glob("docs/*/index.html")
+        .matches("docs/index.html")
+        .is_none());
+    assert!(glob("img-??.png").matches("img-01.png").is_some());
+    assert!(glob("img-??.png").matches("img-1.png").is_none());
+    assert!(glob("a*b*c").matches("abbbbc").is_some());
+    assert!(glob("a*b*c").matches("abcb").is_none());
+    assert!(glob("über-?.txt").matches("über-ß.txt").is_some());
+}
+
+#[test]
+fn glob_multi_segment() {
+    let spec = glob("src/**/*.rs");
+    assert_eq!(spec.matches("src/lib.rs"), Some(vec![""]));
+    assert_eq!(spec.matches("src/a/b/c.rs"), Some(vec!["a/b"]));
+    assert!(spec.matches("src/a/b/c.txt").is_none());
+    assert!(spec.matches("tests/a.rs").is_none());
+
+    assert_eq!(glob("**/target").matches("a/b/target"), Some(vec!["a/b"]));
+    assert_eq!(glob("**/target").matches("target"), Some(vec![""]));
+    assert_eq!(glob("assets/**").matches("assets"), Some(vec![""]));
+    assert_eq!(glob("assets/**").matches("assets/a/b"), Some(vec!["a/b"]));
+}
+
+#[test]
+fn glob_colons_and_dots_are_literal() {
+    let spec = glob("notes/:draft.txt");
+    assert_eq!(
+        spec.segments(),
+        &[
+            Segment::Exact("notes".into()),
+            Segment::Slash,
+            Segment::Exact(":draft.txt".into())
+        ]
+    );
+    assert!(spec.matches("notes/:draft.txt").is_some());
+    assert!(spec.matches("notes/final.txt").is_none());
+}
+
+#[test]
+fn glob_errors() {
+    assert!(RouteSpec::parse_with("a/**b", Syntax::Glob)
+        .unwrap_err()
+        .contains("`**` must be an entire path segment"));
+}
+
+#[test]
+fn glob_router_precedence() -> Result {
+    let mut router = Router::new();
+    router.add(glob("assets/**"), 1)?;
+    router.add(glob("assets/*.css"), 2)?;
+    router.add(glob("assets/site.css"), 3)?;
+    router.add("/assets/:file", 4)?;
+
+    assert_eq!(*router.best_match("/assets/site.css").unwrap(), 3);
+    assert_eq!(*router.best_match("/assets/other.css").unwrap(), 2);
+    assert_eq!(*router.best_match("/assets/app.js").unwrap(), 4);
+    assert_eq!(*router.best_match("/assets/js/app.js").unwrap(), 1);
+    assert!(glob("assets/*.css").template(&Captures::new()).is_none());
+    Ok(())
+}
+
+fn express(source: &str) -> RouteSpec {
+    RouteSpec::parse_with(source, Syntax::Express).unwrap()
+}
+
+/// (route, path, expected captures or None if the path should not match)
+type Case = (
+    &'static str,
+    &'static str,
+    Option<&'static [(&'static str, &'static str)]>,
+);
+
+fn assert_cases(cases: &[Case]) {
+    for (route, path, expected) in cases {
+        let mut router = Router::new();
+        router.add(express(route), ()).unwrap();
+        let captures = router.best_match(path).map(|m| {
+            m.captures()
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<Vec<_>>()
+        });
+        let expected = expected.map(|e| {
+            e.iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(captures, expected, "{} against {}", route, path);
+    }
+}
+
+#[test]
+fn express_compatibility() {
+    assert_cases(&[
+        (
+            "/:foo/:bar",
+            "/test/route",
+            Some(&[("foo", "test"), ("bar", "route")]),
+        ),
+        ("/:foo/:bar", "/test", None),
+        ("/:foo/:bar?", "/test", Some(&[("foo", "test")])),
+        (
+            "/:foo/:bar?",
+            "/test/route",
+            Some(&[("foo", "test"), ("bar", "route")]),
+        ),
+        ("/:foo/:bar?", "/test/route/again", None),
+        ("/:foo?/edit", "/edit", Some(&[])),
+        ("/:foo?/edit", "/post/edit", Some(&[("foo", "post")])),
+        ("/:foo*", "/", Some(&[])),
+        ("/:foo*", "/bar/baz", Some(&[("foo", "bar/baz")])),
+        ("/:foo+", "/", None),
+        ("/:foo+", "/bar/baz", Some(&[("foo", "bar/baz")])),
+        ("/files/:path*/raw", "/files/raw", Some(&[])),
+        (
+            "/files/:path*/raw",
+            "/files/a/b/raw",
+            Some(&[("path", "a/b")]),
+        ),
+        ("/files/:path+/raw", "/files/raw", None),
+        (
+            "/:file.:ext",
+            "/photo.tar.gz",
+            Some(&[("file", "photo"), ("ext", "tar.gz")]),
+        ),
+        (
+            "/:from-:to",
+            "/a-b-c",
+            Some(&[("from", "a"), ("to", "b-c")]),
+        ),
+        (
+            "/flights/:from-:to",
+            "/flights/LAX-SFO",
+            Some(&[("from", "LAX"), ("to", "SFO")]),
+        ),
+        ("/test", "/test/", Some(&[])),
+        ("/test/", "/test", Some(&[])),
+        ("/\\:literal", "/:literal", Some(&[])),
+        ("/\\:literal", "/anything", None),
+    ]);
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn express_compatibility_patterns() {
+    assert_cases(&[
+        ("/:foo(\\d+)", "/123", Some(&[("foo", "123")])),
+        ("/:foo(\\d+)", "/abc", None),
+        (
+            "/:foo/(.*)",
+            "/test/route",
+            Some(&[("foo", "test"), ("0", "route")]),
+        ),
+        (
+            "/:foo/(.*)",
+            "/test/a/b",
+            Some(&[("foo", "test"), ("0", "a/b")]),
+        ),
+        ("/(user|u)/:id", "/u/1", Some(&[("0", "u"), ("id", "1")])),
+        ("/(user|u)/:id", "/users/1", None),
+        (
+            "/icon-:foo(\\d+).png",
+            "/icon-123.png",
+            Some(&[("foo", "123")]),
+        ),
+        ("/icon-:foo(\\d+).png", "/icon-abc.png", None),
+        ("/:page(\\d+)?", "/", Some(&[])),
+        ("/:page(\\d+)?", "/2", Some(&[("page", "2")])),
+        ("/:page(\\d+)?", "/two", None),
+        ("/:ids(\\d+)+", "/1/2/3", Some(&[("ids", "1/2/3")])),
+        ("/:ids(\\d+)+", "/1/b/3", None),
+    ]);
+}
+
+#[test]
+fn express_errors() {
+    let error = |source| RouteSpec::parse_with(source, Syntax::Express).unwrap_err();
+    assert_eq!(error("/:"), "params must be named");
+    assert!(error("/:a:b").contains("must be separated"));
+    assert!(error("/:a(\\d+").contains("unterminated"));
+    assert!(error("/a?").contains("unexpected `?`"));
+    #[cfg(not(feature = "regex"))]
+    assert!(error("/:a(\\d+)").contains("requires the `regex` feature"));
+}
+
+#[test]
+fn express_display_and_templating() {
+    let spec = express("/posts/:slug/:page?");
+    assert_eq!(spec.to_string(), "/posts/:slug/:page?");
+    assert_eq!(
+        spec.template(&[("slug", "hello")].into())
+            .unwrap()
+            .to_string(),
+        "/posts/hello"
+    );
+    assert_eq!(
+        spec.template(&[("slug", "hello"), ("page", "2")].into())
+            .unwrap()
+            .to_string(),
+        "/posts/hello/2"
+    );
+    assert!(spec.template(&[("page", "2")].into()).is_none());
+}
+
+#[test]
+fn express_precedence() -> Result {
+    let mut router = Router::new();
+    router.add(express("/posts/:id?"), 1)?;
+    router.add(express("/posts/:id"), 2)?;
+    router.add(express("/posts/:rest*"), 3)?;
+    router.add(express("/posts/latest"), 4)?;
+    assert_eq!(*router.best_match("/posts/latest").unwrap(), 4);
+    assert_eq!(*router.best_match("/posts/10").unwrap(), 2);
+    assert_eq!(*router.best_match("/posts").unwrap(), 1);
+    assert_eq!(*router.best_match("/posts/a/b").unwrap(), 3);
+    Ok(())
+}
+
+fn http_rule(source: &str) -> RouteSpec {
+    RouteSpec::parse_with(source, Syntax::HttpRule).unwrap()
+}
+
+fn http_rule_captures(route: &str, path: &str) -> Option<Vec<(String, String)>> {
+    let mut router = Router::new();
+    router.add(http_rule(route), ()).unwrap();
+    router.best_match(path).map(|m| {
+        let captures = m.captures();
+        captures
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .chain(
+                captures
+                    .wildcard()
+                    .map(|w| (String::from("**"), w.to_string())),
+            )
+            .collect()
+    })
+}
+
+#[test]
+fn validating_constraints() {
+    let spec = RouteSpec::parse_with(
+        "/v1/{name=projects/*/locations/*}/datasets",
+        Syntax::HttpRule,
+    )
+    .unwrap();
+    assert_eq!(
+        spec.validate(&[("name", "projects/p1/locations/us")].into()),
+        Ok(())
+    );
+    assert_eq!(
+        spec.validate(&[("name", "projects/p1")].into()),
+        Err(TemplateError::Invalid {
+            name: "name".into(),
+            value: "projects/p1".into()
+        })
+    );
+    assert_eq!(
+        glob("assets/*.css").validate(&Captures::new()),
+        Err(TemplateError::Glob)
+    );
+}
+
+#[test]
+fn http_rule_templates() {
+    let cases: &[Case] = &[
+        (
+            "/v1/messages/{message_id}",
+            "/v1/messages/123",
+            Some(&[("message_id", "123")]),
+        ),
+        ("/v1/messages/{message_id}", "/v1/messages/1/2", None),
+        (
+            "/v1/{name=projects/*/locations/*}/datasets",
+            "/v1/projects/p1/locations/us/datasets",
+            Some(&[("name", "projects/p1/locations/us")]),
+        ),
+        (
+            "/v1/{name=projects/*/locations/*}/datasets",
+            "/v1/projects/p1/datasets",
+            None,
+        ),
+        (
+            "/v1/{name=projects/*/locations/*}/datasets",
+            "/v1/projects/p1/regions/us/datasets",
+            None,
+        ),
+        (
+            "/v1/{book.name=shelves/*/books/*}",
+            "/v1/shelves/1/books/2",
+            Some(&[("book.name", "shelves/1/books/2")]),
+        ),
+        (
+            "/v1/{name=messages/*}:cancel",
+            "/v1/messages/7:cancel",
+            Some(&[("name", "messages/7")]),
+        ),
+        ("/v1/{name=messages/*}:cancel", "/v1/messages/7", None),
+        (
+            "/v1/{id}:undelete",
+            "/v1/42:undelete",
+            Some(&[("id", "42")]),
+        ),
+        ("/v1/*:batchGet", "/v1/books:batchGet", Some(&[])),
+        ("/v1/*:batchGet", "/v1/books", None),
+        ("/v1/*/books", "/v1/shelf/books", Some(&[])),
+        (
+            "/v1/{name=shelves/**}",
+            "/v1/shelves/a/b/c",
+            Some(&[("name", "shelves/a/b/c")]),
+        ),
+        ("/v1/{path=**}", "/v1/a/b", Some(&[("path", "a/b")])),
+        ("/v1/files/**", "/v1/files/a/b", Some(&[("**", "a/b")])),
+    ];
+
+    for (route, path, expected) in cases {
+        let expected = expected.map(|e| {
+            e.iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(
+            http_rule_captures(route, path),
+            expected,
+            "{} against {}",
+            route,
+            path
+        );
+    }
+}
+
+#[test]
+fn http_rule_errors() {
+    let error = |source| RouteSpec::parse_with(source, Syntax::HttpRule).unwrap_err();
+    assert!(error("v1/books").contains("must start with a slash"));
+    assert!(error("/v1//books").contains("empty path segment"));
+    assert!(error("/v1/{name").contains("unclosed variable"));
+    assert!(error("/v1/{a={b}}").contains("cannot be nested"));
+    assert!(error("/v1/{1name}").contains("not a valid field path"));
+    assert!(error("/v1/{name=**/books}").contains("not a valid variable pattern"));
+    assert!(error("/v1/{id}/{id}").contains("more than once"));
+    assert!(error("/v1/books*").contains("unexpected"));
+    assert!(error("/v1/**/**").contains("more than one"));
+    assert!(error("/v1/books:").contains("invalid verb"));
+}
+
+#[test]
+fn http_rule_precedence() -> Result {
+    let mut router = Router::new();
+    router.add(http_rule("/v1/{name=shelves/*}"), 1)?;
+    router.add(http_rule("/v1/shelves/{shelf}:archive"), 2)?;
+    router.add(http_rule("/v1/**"), 3)?;
+    assert_eq!(*router.best_match("/v1/shelves/1").unwrap(), 1);
+    assert_eq!(*router.best_match("/v1/shelves/1:archive").unwrap(), 2);
+    assert_eq!(*router.best_match("/v1/books/1").unwrap(), 3);
+    Ok(())
+}
+
+#[test]
+fn segments_display_and_parse() {
+    for (source, syntax) in [
+        ("/users/:id/posts/:post?", Syntax::Express),
+        ("/files/:path+", Syntax::Express),
+        ("/docs/:path*.:format", Syntax::Express),
+        ("assets/*.css", Syntax::Glob),
+        ("/hello/:planet/*", Syntax::Routefinder),
+    ] {
+        let spec = RouteSpec::parse_with(source, syntax).unwrap();
+        let mut rendered = String::from("/");
+        for segment in spec.segments() {
+            let displayed = segment.to_string();
+            let parsed: Segment = displayed.parse().unwrap();
+            assert_eq!(parsed.to_string(), displayed);
+            if !matches!(segment, Segment::Exact(_)) {
+                assert_eq!(&parsed, segment);
+            }
+            rendered.push_str(&displayed);
+        }
+        assert_eq!(rendered, spec.to_string());
+    }
+}
+
+#[test]
+fn optional_params() -> Result {
+    let spec: RouteSpec = "/posts/:page?".parse()?;
+    assert_eq!(
+        spec.segments(),
+        &[
+            Segment::Exact("posts".into()),
+            Segment::Slash,
+            Segment::OptionalParam("page".into()),
+        ]
+    );
+    assert_eq!(spec.to_string(), "/posts/:page?");
+    assert_eq!(spec.matches("/posts/2"), Some(vec!["2"]));
+    assert_eq!(spec.matches("/posts/"), Some(vec![""]));
+    assert_eq!(spec.matches("/posts"), Some(vec![""]));
+    assert!(spec.matches("/posts/2/3").is_none());
+
+    let mut router = Router::new();
+    router.add("/posts/:page?", "posts")?;
+    router.add("/posts/new", "new post")?;
+    assert_eq!(*router.best_match("/posts/new").unwrap(), "new post");
+    let m = router.best_match("/posts/").unwrap();
+    assert_eq!(*m, "posts");
+    assert_eq!(m.captures().get("page"), None);
+    assert_eq!(
+        router
+            .best_match("/posts/2")
+            .unwrap()
+            .captures()
+            .get("page"),
+        Some("2")
+    );
+
+    assert!("/posts/:?".parse::<RouteSpec>().is_err());
+    Ok(())
+}
+
+#[test]
+fn dotted_exact_segments() -> Result {
+    let spec: RouteSpec = "/assets/app.min.js".parse()?;
+    assert_eq!(
+        spec.segments(),
+        &[
+            Segment::Exact("assets".into()),
+            Segment::Slash,
+            Segment::Exact("app.min.js".into()),
+        ]
+    );
+
+    let spec: RouteSpec = "/:name.:version.:ext".parse()?;
+    assert_eq!(spec.matches("/app.1.js"), Some(vec!["app", "1", "js"]));
+
+    for segments in [
+        vec![Segment::Exact("min.js".into())],
+        vec![
+            Segment::Slash,
+            Segment::Exact("min".into()),
+            Segment::Dot,
+            Segment::Exact("js".into()),
+            Segment::Slash,
+        ],
+        vec![
+            Segment::Exact("v1.".into()),
+            Segment::Param("name".into()),
+            Segment::Dot,
+            Segment::Exact("tar".into()),
+            Segment::Dot,
+            Segment::Exact("gz".into()),
+        ],
+        vec![
+            Segment::Param("name".into()),
+            Segment::Dot,
+            Segment::Param("ext".into()),
+            Segment::Slash,
+            Segment::Wildcard,
+        ],
+    ] {
+        let spec = RouteSpec::from_segments(segments)?;
+        let parsed: RouteSpec = spec.to_string().parse()?;
+        assert_eq!(parsed.segments(), spec.segments(), "{}", spec);
+    }
+
+    let spec = RouteSpec::from_segments(vec![Segment::Slash, Segment::Exact("min.js".into())])?;
+    assert_eq!(spec.to_string(), "/min.js");
+    assert!(spec.matches("/min.js").is_some());
+    assert!(spec.matches("/min").is_none());
+
+    for invalid in [
+        vec![Segment::Exact("a".into()), Segment::Slash, Segment::Slash],
+        vec![Segment::Param("a".into()), Segment::Dot, Segment::Dot],
+        vec![Segment::Exact("".into())],
+        vec![Segment::Exact("a/b".into())],
+        vec![Segment::Param("".into())],
+        vec![Segment::Param("a".into()), Segment::Param("b".into())],
+        vec![Segment::Glob("*.c".into()), Segment::Exact("d".into())],
+        vec![Segment::Wildcard, Segment::Slash, Segment::Wildcard],
+    ] {
+        assert!(RouteSpec::from_segments(invalid).is_err());
+    }
+    Ok(())
+}
+
+#[test]
+fn dotted_exact_precedence() -> Result {
+    let mut router = Router::new();
+    router.add("/a.:e/:x", "param")?;
+    router.add("/a.json/b", "exact")?;
+    router.add("/a.:e/:x.json", "param then json")?;
+    router.add("/a.json/:x.json", "exact then json")?;
+
+    assert_eq!(*router.best_match("/a.json/b").unwrap(), "exact");
+    assert_eq!(
+        *router.best_match("/a.json/c.json").unwrap(),
+        "exact then json"
+    );
+    assert_eq!(
+        *router.best_match("/a.xml/c.json").unwrap(),
+        "param then json"
+    );
+    assert_eq!(*router.best_match("/a.xml/b").unwrap(), "param");
+
+    let exact: RouteSpec = "/a.json/b".parse()?;
+    let param: RouteSpec = "/a.:e/:x".parse()?;
+    assert!(exact < param);
+    Ok(())
+}
+
+#[test]
+fn display_reparses() -> Result {
+    let mut state: u64 = 0x2940;
+    let mut next = |bound: usize| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state as usize % bound
+    };
+
+    let values = [
+        Segment::Exact("a".into()),
+        Segment::Exact("b.c".into()),
+        Segment::Exact("".into()),
+        Segment::Param("p".into()),
+        Segment::OptionalParam("q".into()),
+        Segment::OneOrMore("r".into()),
+        Segment::ZeroOrMore("s".into()),
+        Segment::Wildcard,
+    ];
+    let separators = [Segment::Slash, Segment::Dot];
+
+    let mut checked = 0;
+    while checked < 300 {
+        let mut segments = vec![];
+        if next(4) == 0 {
+            segments.push(separators[next(2)].clone());
+        }
+        for _ in 0..=next(4) {
+            segments.push(values[next(values.len())].clone());
+            segments.push(separators[next(2)].clone());
+        }
+        if next(2) == 0 {
+            segments.pop();
+        }
+
+        let raw = RouteSpec::from(segments.clone());
+        let reparsed = raw.reparse()?;
+        assert_eq!(reparsed.reparse()?.segments(), reparsed.segments());
+
+        if let Ok(spec) = RouteSpec::from_segments(segments) {
+            let reparsed = spec.reparse()?;
+            assert_eq!(reparsed.segments(), spec.segments(), "{}", spec);
+            checked += 1;
+        }
+    }
+
+    for source in [
+        "/",
+        "/a/.hidden",
+        "/.well-known/:name",
+        "/a//b",
+        "/:name.:version.:ext",
+        "/files/:path*/raw.:format?",
+        "/archive.tar.gz",
+    ] {
+        let spec: RouteSpec = source.parse()?;
+        assert_eq!(spec.reparse()?.segments(), spec.segments(), "{}", source);
+    }
+
+    let spec: RouteSpec = "/a/.hidden".parse()?;
+    assert!(spec.matches("/a/.hidden").is_some());
+    assert!(spec.matches("/a.hidden").is_none());
+
+    let express = RouteSpec::parse_with("/:from-:to", Syntax::Express)?;
+    assert!(express.reparse().is_err());
+    Ok(())
+}
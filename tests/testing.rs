@@ -0,0 +1,90 @@
+type Result = std::result::Result<(), Box<dyn std::error::Error>>;
+use routefinder::{assert_matches, assert_no_match, testing, Router};
+
+fn router() -> Router<&'static str> {
+    let mut router = Router::new();
+    for (route, handler) in [
+        ("/users/:id", "user"),
+        ("/users/new", "new user"),
+        ("/users/:id/posts/*", "posts"),
+        ("/about", "about"),
+        ("/contact", "contact"),
+        ("/admin", "admin"),
+    ] {
+        router.add(route, handler).unwrap();
+    }
+    router
+}
+
+#[test]
+fn passing_assertions() -> Result {
+    let router = router();
+    assert_matches!(router, "/users/42", "/users/:id");
+    assert_matches!(router, "/users/42/", "/users/:id/", { "id" => "42" });
+    assert_matches!(
+        router,
+        "/users/1/posts/a/b",
+        "/users/:id/posts/*",
+        { "*" => "a/b", "id" => "1" },
+    );
+    assert_no_match!(router, "/posts");
+    Ok(())
+}
+
+#[test]
+fn failure_messages() -> Result {
+    let router = router();
+
+    let message = testing::check_match(&router, "/users/new", "/users/:id", None).unwrap_err();
+    assert_eq!(
+        message,
+        "expected /users/new to match /users/:id, but it matched /users/new with {}
+
+routes for /users/new:
+  > /users/new  selected
+    /users/:id  matches, but a route above takes precedence
+    /users/:id/posts/*  does not match: the path ends before `posts`
+    /about      does not match: `users` does not match `about`
+    /contact    does not match: `users` does not match `contact`
+    ... and 1 other route that does not match"
+            .replace("/users/new  selected", "/users/new          selected")
+            .replace("/users/:id  matches", "/users/:id          matches")
+            .replace("/about      does", "/about              does")
+            .replace("/contact    does", "/contact            does")
+    );
+
+    let message =
+        testing::check_match(&router, "/users/1", "/users/:id", Some(&[("id", "2")])).unwrap_err();
+    assert_eq!(
+        message,
+        r#"expected /users/1 to match /users/:id with {"id": "2"}, but the captures were {"id": "1"}"#
+    );
+
+    let message = testing::check_match(&router, "/users/1/posts", "/users/:id", None).unwrap_err();
+    assert!(message.starts_with("expected /users/1/posts to match /users/:id, but it matched"));
+
+    let message = testing::check_match(&router, "/users", "/users/:id", None).unwrap_err();
+    assert!(message.starts_with("expected /users to match /users/:id, but no route matches it"));
+    assert!(message.contains("/users/:id          does not match: the path ends before `:id`"));
+
+    let message = testing::check_no_match(&router, "/about").unwrap_err();
+    assert!(message.starts_with("expected no route to match /about, but /about matched it with {}"));
+
+    let explanation = testing::explain(&Router::<()>::new(), "/");
+    assert_eq!(explanation, "routes for /:\n    the router has no routes");
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "expected /about to match /contact")]
+fn panicking_assertion() {
+    let router = router();
+    assert_matches!(router, "/about", "/contact");
+}
+
+#[test]
+#[should_panic(expected = "expected no route to match /admin")]
+fn panicking_no_match() {
+    let router = router();
+    assert_no_match!(router, "/admin");
+}
@@ -185,6 +185,258 @@ fn templating() -> Result {
         "/users/jbr.txt"
     );
 
+    let mut buffer = String::new();
+    for (source, captures) in [
+        (
+            ":a/:b.:c",
+            Captures::from([("a", "users"), ("b", "jbr"), ("c", "txt")]),
+        ),
+        ("/files/*", Captures::new()),
+        ("/", Captures::new()),
+    ] {
+        let spec = RouteSpec::from_str(source)?;
+        let reverse_match = spec.template(&captures).unwrap();
+        buffer.clear();
+        reverse_match.write_to(&mut buffer)?;
+        assert_eq!(buffer, reverse_match.to_string());
+        assert_eq!(reverse_match.len_hint(), buffer.len());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn validation() -> Result {
+    let spec = RouteSpec::from_str("/:a/:b/*")?;
+    assert_eq!(spec.validate(&[("a", "1"), ("b", "2")].into()), Ok(()));
+    assert_eq!(
+        spec.validate(&[("a", "1")].into()),
+        Err(TemplateError::Missing("b".into()))
+    );
+    assert_eq!(
+        spec.validate(&[("b", "2"), ("a", "1")].into()),
+        Err(TemplateError::OutOfOrder("a".into()))
+    );
+    assert_eq!(
+        spec.validate(&[("a", "1"), ("b", "2"), ("d", "4")].into()),
+        Err(TemplateError::Unexpected("d".into()))
+    );
+
+    let mut captures: Captures = [("a", "1")].into();
+    captures.set_wildcard("rest");
+    let spec = RouteSpec::from_str("/:a")?;
+    assert_eq!(
+        spec.validate(&captures),
+        Err(TemplateError::UnexpectedWildcard)
+    );
+    assert!(spec.template(&captures).is_none());
+
+    // every error agrees with templating
+    for captures in [
+        Captures::from([("a", "1")]),
+        Captures::from([("a", "1"), ("b", "2")]),
+        Captures::new(),
+    ] {
+        assert_eq!(
+            spec.validate(&captures).is_ok(),
+            spec.template(&captures).is_some()
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn transformed_templating() -> Result {
+    let spec = RouteSpec::from_str("/:category/:slug/:format?")?;
+    let captures = Captures::from([
+        ("category", " News "),
+        ("slug", "Hello, World!"),
+        ("format", "HTML"),
+    ]);
+    assert_eq!(
+        spec.url_for(
+            &captures,
+            &[
+                ("category", Transform::Trim),
+                ("category", Transform::Lowercase),
+                ("slug", Transform::Slugify),
+                ("format", Transform::Lowercase),
+            ]
+        )?,
+        "/news/hello-world/html"
+    );
+    assert_eq!(spec.url_for(&captures, &[])?, "/ News /Hello, World!/HTML");
+
+    // an optional param may be transformed to nothing
+    let captures = Captures::from([("category", "news"), ("slug", "a"), ("format", "!")]);
+    assert_eq!(
+        spec.url_for(&captures, &[("format", Transform::Slugify)])?,
+        "/news/a"
+    );
+
+    // the transformed value must still satisfy the constraint
+    #[cfg(feature = "regex")]
+    {
+        let spec = RouteSpec::parse_with("/users/:name([a-z]+)", Syntax::Express)?;
+        let captures = Captures::from([("name", "JBR")]);
+        assert_eq!(
+            spec.url_for(&captures, &[("name", Transform::Lowercase)])?,
+            "/users/jbr"
+        );
+        assert_eq!(
+            spec.url_for(&captures, &[("name", Transform::Uppercase)]),
+            Err(TemplateError::Invalid {
+                name: "name".into(),
+                value: "JBR".into()
+            })
+        );
+
+        let path = spec.url_for(&captures, &[("name", Transform::Lowercase)])?;
+        assert!(spec.matches(&path).is_some());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn separator_policy() -> Result {
+    let spec = RouteSpec::from_str("/:dir/:name.:ext/*")?;
+    let mut captures = Captures::from([("dir", "a/b"), ("name", "c.d"), ("ext", "e.f")]);
+    captures.set_wildcard("g/h.i");
+    let reverse_match = spec.template(&captures).unwrap();
+    assert_eq!(reverse_match.separators(), SeparatorPolicy::Allow);
+    assert_eq!(reverse_match.to_string(), "/a/b/c.d.e.f/g/h.i");
+
+    assert_eq!(
+        reverse_match
+            .with_separators(SeparatorPolicy::Reject)
+            .unwrap_err(),
+        TemplateError::Separator {
+            name: "dir".into(),
+            value: "a/b".into()
+        }
+    );
+
+    let escaped = reverse_match.with_separators(SeparatorPolicy::Escape)?;
+    let path = escaped.to_string();
+    assert_eq!(path, "/a%2Fb/c%2Ed.e.f/g/h.i");
+    assert_eq!(escaped.len_hint(), path.len());
+
+    // the escaped path matches with the same captures, once decoded
+    let mut router = Router::new();
+    router.add(spec.clone(), ())?;
+    let matched = router.best_match(&path).unwrap().captures();
+    for capture in captures.params() {
+        let value = matched.get(capture.name()).unwrap();
+        let decoded = value.replace("%2F", "/").replace("%2E", ".");
+        assert_eq!(decoded, capture.value());
+    }
+    assert_eq!(matched.wildcard(), captures.wildcard());
+
+    // values without separators are accepted and unchanged
+    let captures = Captures::from([("dir", "a"), ("name", "b"), ("ext", "c.d")]);
+    let reverse_match = spec.template(&captures).unwrap();
+    for policy in [
+        SeparatorPolicy::Allow,
+        SeparatorPolicy::Reject,
+        SeparatorPolicy::Escape,
+    ] {
+        assert_eq!(
+            reverse_match.with_separators(policy)?.to_string(),
+            "/a/b.c.d"
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn template_roundtrips() -> Result {
+    let mut state: u64 = 0x2950;
+    let mut next = |bound: usize| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state as usize % bound
+    };
+
+    let segments = [
+        Segment::Exact("a".into()),
+        Segment::Exact("b.c".into()),
+        Segment::Param("".into()),
+        Segment::OptionalParam("".into()),
+        Segment::OneOrMore("".into()),
+        Segment::ZeroOrMore("".into()),
+        Segment::Wildcard,
+    ];
+    let separators = [Segment::Slash, Segment::Dot];
+    let single = ["x", "yz", "1-2", "caf\u{e9}"];
+    let multiple = ["x", "x/y", "x/y.z", "1/2/3"];
+
+    let mut checked = 0;
+    while checked < 500 {
+        let mut route = vec![];
+        for index in 0..=next(4) {
+            route.push(separators[next(2)].clone());
+            let name = ["v0", "v1", "v2", "v3", "v4"][index];
+            route.push(match segments[next(segments.len())] {
+                Segment::Param(_) => Segment::Param(name.into()),
+                Segment::OptionalParam(_) => Segment::OptionalParam(name.into()),
+                Segment::OneOrMore(_) => Segment::OneOrMore(name.into()),
+                Segment::ZeroOrMore(_) => Segment::ZeroOrMore(name.into()),
+                ref segment => segment.clone(),
+            });
+        }
+        let spec = match RouteSpec::from_segments(route) {
+            Ok(spec) => spec,
+            Err(_) => continue,
+        };
+
+        // several variable captures can split a path more than one way
+        let variable = spec
+            .segments()
+            .iter()
+            .filter(|segment| {
+                matches!(
+                    segment,
+                    Segment::OptionalParam(_)
+                        | Segment::OneOrMore(_)
+                        | Segment::ZeroOrMore(_)
+                        | Segment::Wildcard
+                )
+            })
+            .count();
+        if variable > 1 {
+            continue;
+        }
+
+        let mut captures = Captures::new();
+        for segment in spec.segments() {
+            match segment {
+                Segment::Param(name) => captures.push((&**name, single[next(single.len())])),
+                Segment::OptionalParam(name) if next(2) == 0 => {
+                    captures.push((&**name, single[next(single.len())]))
+                }
+                Segment::OneOrMore(name) => {
+                    captures.push((&**name, multiple[next(multiple.len())]))
+                }
+                Segment::ZeroOrMore(name) if next(2) == 0 => {
+                    captures.push((&**name, multiple[next(multiple.len())]))
+                }
+                Segment::Wildcard if next(2) == 0 => {
+                    captures.set_wildcard(multiple[next(multiple.len())])
+                }
+                _ => {}
+            }
+        }
+
+        if let Err(error) = spec.roundtrip_check(&captures) {
+            panic!("{} with {:?}: {}", spec, captures, error);
+        }
+        checked += 1;
+    }
+
     Ok(())
 }
 
@@ -257,3 +509,3427 @@ fn append_captures() {
 
     assert_eq!(Some("other"), captures.wildcard());
 }
+
+#[test]
+fn mid_route_wildcard() -> Result {
+    let spec = RouteSpec::from_str("/files/*/raw")?;
+    assert_eq!(spec.matches("/files/a/b/raw"), Some(vec!["a/b"]));
+    assert_eq!(spec.matches("/files/raw"), Some(vec![""]));
+    assert!(spec.matches("/files/a/b").is_none());
+    Ok(())
+}
+
+#[test]
+fn match_modes() -> Result {
+    let mut router = Router::new();
+    router.add_with_mode("/docs", MatchMode::Exact, 1)?;
+    router.add_with_mode("/docs", MatchMode::Prefix, 2)?;
+    router.add_with_mode("/docs/api", MatchMode::Prefix, 3)?;
+    router.add_with_mode("/docs/api/v1", MatchMode::PriorityPrefix, 4)?;
+    router.add("/docs/:section/:page", 5)?;
+
+    assert_eq!(*router.best_match("/docs").unwrap(), 1);
+    assert_eq!(*router.best_match("/docs/").unwrap(), 2);
+    assert_eq!(*router.best_match("/docs/guide").unwrap(), 2);
+    assert_eq!(*router.best_match("/docs/api").unwrap(), 3);
+    assert_eq!(*router.best_match("/docs/api/a/b").unwrap(), 3);
+    assert_eq!(*router.best_match("/docs/api/types").unwrap(), 5);
+    assert_eq!(*router.best_match("/docs/api/v1/types").unwrap(), 4);
+    assert!(router.best_match("/documentation").is_none());
+
+    assert_eq!(
+        router
+            .matches("/docs/api/v1")
+            .into_iter()
+            .map(|m| *m)
+            .collect::<Vec<_>>(),
+        vec![4, 5, 3, 2]
+    );
+
+    assert_eq!(
+        router
+            .best_match("/docs/api/v1/x/y")
+            .unwrap()
+            .captures()
+            .wildcard(),
+        Some("x/y")
+    );
+    assert_eq!(
+        router
+            .best_match("/docs/api/v1")
+            .unwrap()
+            .captures()
+            .wildcard(),
+        Some("")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn weighted_routes() -> Result {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    let mut router = Router::new();
+    router.add("/users/:id", "user")?;
+    let feed = router.add_weighted("/users/:id/feed", [(3, "stable"), (1, "canary")])?;
+    router.add("/users/*", "fallback")?;
+
+    let counter = AtomicU32::new(0);
+    router.set_weight_strategy(move |total| counter.fetch_add(1, Ordering::Relaxed) % total);
+
+    let handlers = (0..8)
+        .map(|_| *router.best_match("/users/10/feed").unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        handlers,
+        ["stable", "stable", "stable", "canary", "stable", "stable", "stable", "canary"]
+    );
+
+    let m = router.best_match("/users/10/feed").unwrap();
+    assert_eq!(m.captures().get("id"), Some("10"));
+    assert!(m.route().weight().is_some());
+
+    assert_eq!(*router.best_match("/users/10").unwrap(), "user");
+    assert_eq!(router.matches("/users/10/feed").len(), 3);
+    assert_eq!(router.len(), 4);
+
+    // alternatives are kept up to date as routes are added and removed
+    router.set_storage(Storage::Sorted);
+    router.add_weighted("/users/:id/feed", [(4, "beta")])?;
+    router.add_weighted("/users/:name/feed", [(4, "other")])?;
+    router.remove(feed[0]);
+    router.set_weight_strategy(|total| total - 1);
+    assert_eq!(*router.best_match("/users/10/feed").unwrap(), "beta");
+    router.set_weight_strategy(|_| 0);
+    assert_eq!(*router.best_match("/users/10/feed").unwrap(), "canary");
+
+    Ok(())
+}
+
+#[test]
+fn conditional_routes() -> Result {
+    let flag = Flag::new(true);
+    let mut router = Router::new();
+    router.add("/search", "v1")?;
+    router.add_conditional("/search", "v2", flag.clone())?;
+    router.add_conditional("/search/:query", "v2 query", flag.clone())?;
+    router.add("/search/*", "v1 query")?;
+
+    assert_eq!(*router.best_match("/search").unwrap(), "v2");
+    assert_eq!(*router.best_match("/search/rust").unwrap(), "v2 query");
+    assert_eq!(router.matches("/search").len(), 3);
+
+    flag.disable();
+    assert_eq!(*router.best_match("/search").unwrap(), "v1");
+    assert_eq!(*router.best_match("/search/rust").unwrap(), "v1 query");
+    assert_eq!(router.matches("/search").len(), 2);
+    assert_eq!(router.len(), 4);
+
+    flag.enable();
+    assert_eq!(*router.best_match("/search").unwrap(), "v2");
+
+    Ok(())
+}
+
+#[test]
+fn guarded_routes() -> Result {
+    struct Headers {
+        accept: &'static str,
+        version: u8,
+    }
+
+    let mut router = Router::new();
+    router.add("/api/*", "fallback")?;
+    router.add_guarded("/api/:resource", "v2", |headers: &Headers| {
+        headers.version == 2
+    })?;
+    router.add_guarded("/api/:resource", "json", |headers: &Headers| {
+        headers.accept == "application/json"
+    })?;
+    router.add("/api/:resource", "html")?;
+
+    let json_v2 = Headers {
+        accept: "application/json",
+        version: 2,
+    };
+    let json = Headers {
+        accept: "application/json",
+        version: 1,
+    };
+    let html = Headers {
+        accept: "text/html",
+        version: 1,
+    };
+
+    assert_eq!(*router.best_match_in("/api/users", &json_v2).unwrap(), "v2");
+    assert_eq!(*router.best_match_in("/api/users", &json).unwrap(), "json");
+    assert_eq!(*router.best_match_in("/api/users", &html).unwrap(), "html");
+    assert_eq!(
+        *router.best_match_in("/api/users/1", &json).unwrap(),
+        "fallback"
+    );
+    assert_eq!(router.match_iter_in("/api/users", &json_v2).count(), 4);
+    assert_eq!(router.match_iter_in("/api/users", &html).count(), 2);
+
+    // without a context, or with a context of another type, guards are not met
+    assert_eq!(*router.best_match("/api/users").unwrap(), "html");
+    assert_eq!(*router.best_match_in("/api/users", &2u8).unwrap(), "html");
+
+    let guarded: Vec<_> = router
+        .iter()
+        .filter(|(route, _)| route.is_guarded())
+        .collect();
+    assert_eq!(guarded.len(), 2);
+    assert!(!guarded[0].0.is_enabled());
+    assert!(guarded[0].0.canonical().ends_with(" guarded"));
+
+    Ok(())
+}
+
+#[test]
+fn change_events() -> Result {
+    use std::sync::{Arc, Mutex};
+
+    let events = Arc::new(Mutex::new(vec![]));
+    let observed = events.clone();
+    let mut router = Router::new();
+    router.add("/before", "before")?;
+    router.on_change(move |event: &ChangeEvent<'_>| {
+        observed
+            .lock()
+            .unwrap()
+            .push((event.to_string(), event.id(), event.route().id()));
+    });
+    let take = || std::mem::take(&mut *events.lock().unwrap());
+
+    let users = router.add("/users/:id", "users")?;
+    let formats = router.add_with_formats("/reports/:id", ["json", "csv"], "reports")?;
+    let denied = router.deny("/admin/*")?;
+    assert_eq!(
+        take(),
+        [
+            ("added /users/:id".to_string(), users, Some(users)),
+            (
+                "added /reports/:id.csv".to_string(),
+                formats[0],
+                Some(formats[0])
+            ),
+            (
+                "added /reports/:id.json".to_string(),
+                formats[1],
+                Some(formats[1])
+            ),
+            (
+                "added /reports/:id".to_string(),
+                formats[2],
+                Some(formats[2])
+            ),
+            ("added /admin/*".to_string(), denied, Some(denied)),
+        ]
+    );
+
+    assert_eq!(router.replace(users, "new users"), Some("users"));
+    assert_eq!(router.remove(users), Some("new users"));
+    assert_eq!(router.replace(users, "users"), None);
+    assert_eq!(router.replace(denied, "denied"), None);
+    assert_eq!(router.remove(users), None);
+    assert_eq!(
+        take(),
+        [
+            ("replaced /users/:id".to_string(), users, Some(users)),
+            ("removed /users/:id".to_string(), users, Some(users)),
+        ]
+    );
+    assert!(router.best_match("/users/1").is_none());
+
+    let mut cloned = router.clone();
+    cloned.add("/cloned", "cloned")?;
+    assert_eq!(take().len(), 1);
+
+    let zipped = router.zip_meta(vec![1]);
+    assert_eq!(zipped.len(), 1);
+    let removed: Vec<_> = take().into_iter().map(|(event, _, _)| event).collect();
+    assert_eq!(
+        removed,
+        [
+            "removed /reports/:id.csv",
+            "removed /reports/:id.json",
+            "removed /reports/:id"
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn transactions() -> Result {
+    let mut router = Router::new();
+    let users = router.add("/users/:id", "users")?;
+    let posts = router.add("/posts/:id", "posts")?;
+    let fingerprint = router.fingerprint();
+
+    let error: String = router
+        .transaction(|txn| {
+            txn.remove(users)?;
+            txn.add("/users/:user_id", "new users")?;
+            txn.add("/posts/*named_wildcard", "named wildcard")?;
+            Ok(())
+        })
+        .unwrap_err();
+    assert!(error.contains("wildcard"));
+    assert_eq!(router.fingerprint(), fingerprint);
+    assert_eq!(*router.best_match("/users/1").unwrap(), "users");
+
+    let error = router
+        .transaction(|txn| {
+            txn.remove(posts)?;
+            txn.remove(posts)
+        })
+        .unwrap_err();
+    assert_eq!(error, format!("no route with id {} to remove", posts));
+    assert_eq!(router.len(), 2);
+
+    let staged = router.transaction(|txn| {
+        assert!(txn.is_empty());
+        let comments = txn.add("/comments/:id", "comments")?;
+        txn.remove(comments)?;
+        txn.remove(users)?;
+        let new_users = txn.add("/users/:user_id", "new users")?;
+        assert_eq!(txn.len(), 4);
+        assert_eq!(txn.router().len(), 2);
+        Ok::<_, String>((comments, new_users))
+    })?;
+
+    let (comments, new_users) = staged;
+    assert_eq!(router.handler(comments), None);
+    assert_eq!(router.handler(users), None);
+    assert_eq!(router.handler(new_users), Some(&"new users"));
+    assert_eq!(*router.best_match("/users/1").unwrap(), "new users");
+    assert_eq!(router.len(), 2);
+
+    let unknown = router.transaction(|txn| txn.remove(users));
+    assert!(unknown.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn shadow_comparisons() -> Result {
+    let mut current = Router::new();
+    current.add("/users/:id", 1)?;
+    current.add("/posts/:id", 2)?;
+    current.add_weighted("/checkout", [(50, 3), (50, 4)])?;
+
+    let mut pending = Router::new();
+    pending.add("/users/:user_id", "users")?;
+    pending.add("/posts/:id", "posts")?;
+    pending.add_weighted("/checkout", [(50, "a"), (50, "b")])?;
+    pending.add("/comments/:id", "comments")?;
+
+    let comparisons = current.shadow_compare(
+        &pending,
+        vec![
+            "/users/1",
+            "/posts/1",
+            "/checkout",
+            "/comments/1",
+            "/missing",
+        ],
+    );
+    let summary: Vec<_> = comparisons
+        .iter()
+        .map(|comparison| {
+            (
+                comparison.path(),
+                comparison.current(),
+                comparison.pending(),
+                comparison.is_different(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            (
+                "/users/1",
+                Some("/users/:id"),
+                Some("/users/:user_id"),
+                true
+            ),
+            ("/posts/1", Some("/posts/:id"), Some("/posts/:id"), false),
+            (
+                "/checkout",
+                Some("/checkout weight=50"),
+                Some("/checkout weight=50"),
+                false
+            ),
+            ("/comments/1", None, Some("/comments/:id"), true),
+            ("/missing", None, None, false),
+        ]
+    );
+    assert_eq!(comparisons[1].to_string(), "/posts/1: /posts/:id");
+    assert_eq!(
+        comparisons[3].to_string(),
+        "/comments/1: no match -> /comments/:id"
+    );
+
+    let sampled = current.sample_paths(|bound| bound / 2, 20);
+    assert!(current
+        .shadow_compare(&current, &sampled)
+        .iter()
+        .all(|comparison| !comparison.is_different()));
+
+    Ok(())
+}
+
+#[test]
+fn characterization_matrix() -> Result {
+    let mut router = Router::new();
+    router.add("/", "root")?;
+    router.add("/users/:id", "user")?;
+    router.add("/users/:id/posts/:post?", "posts")?;
+    router.add("/files/:name.:ext", "file")?;
+    router.add("/assets/*", "assets")?;
+    router.add_weighted("/checkout", [(1, "a"), (1, "b")])?;
+
+    let matrix = testing::matrix(&router);
+    let paths: std::collections::BTreeSet<_> = matrix.iter().map(|(path, _)| path).collect();
+    assert_eq!(paths.len(), matrix.len());
+
+    for (path, route) in &matrix {
+        let best = router.best_match(path).map(|m| m.route().to_string());
+        assert_eq!(best, route.as_ref().map(ToString::to_string), "{}", path);
+    }
+
+    // weighted routes are represented by their first alternative
+    for (route, _) in router.iter().filter(|(route, _)| route.weight().is_none()) {
+        assert!(matrix
+            .iter()
+            .any(|(_, selected)| selected.as_ref() == Some(route)));
+    }
+
+    let selected = |path: &str| {
+        matrix
+            .iter()
+            .find(|(probe, _)| probe == path)
+            .map(|(_, route)| route.as_ref().map(ToString::to_string))
+    };
+    assert_eq!(selected("/users/1/"), Some(Some("/users/:id".into())));
+    assert_eq!(
+        selected("/users/1/posts"),
+        Some(Some("/users/:id/posts/:post?".into()))
+    );
+    assert_eq!(selected("/users/"), Some(None));
+    assert_eq!(selected("/files/1"), Some(None));
+    assert_eq!(
+        selected("/files/1.a.probe"),
+        Some(Some("/files/:name.:ext".into()))
+    );
+    assert_eq!(selected("/assets"), Some(Some("/assets/*".into())));
+
+    // a new route shows up as changes in the matrix
+    let mut changed = router.clone();
+    changed.add("/users/:id/*", "user fallback")?;
+    let after = testing::matrix(&changed);
+    let changes: Vec<&str> = matrix
+        .iter()
+        .filter(|(path, route)| after.iter().any(|(p, r)| p == path && r != route))
+        .map(|(path, _)| path.as_str())
+        .collect();
+    assert!(changes.contains(&"/users/1/probe"));
+
+    Ok(())
+}
+
+#[test]
+fn scoped_routes() -> Result {
+    let mut router = Router::new();
+    router.add("/orgs/:org", "org")?;
+    let mut ids = vec![];
+    let orgs = router.scope_with_param("/orgs/:org/", |scope| {
+        assert_eq!(scope.params().collect::<Vec<_>>(), ["org"]);
+        ids.push(scope.add("repos", "repos")?);
+        ids.push(scope.add("/repos/:repo", "repo")?);
+        ids.push(scope.add("/teams/:team/members/:member?", "member")?);
+        Ok::<_, String>(())
+    })?;
+
+    assert_eq!(orgs.ids(), &ids[..]);
+    assert_eq!(orgs.prefix().to_string(), "/orgs/:org");
+    assert_eq!(router.len(), 4);
+    for (path, handler) in [
+        ("/orgs/acme/repos", "repos"),
+        ("/orgs/acme/repos/routefinder", "repo"),
+        ("/orgs/acme/teams/core/members", "member"),
+    ] {
+        let m = router.best_match(path).unwrap();
+        assert_eq!(*m, handler);
+        assert_eq!(m.captures().get("org"), Some("acme"));
+    }
+
+    let acme = orgs.bind([("org", "acme")].into());
+    assert_eq!(
+        acme.url_for(&router, ids[0], &Captures::new()).as_deref(),
+        Some("/orgs/acme/repos")
+    );
+    assert_eq!(
+        acme.url_for(&router, ids[2], &[("team", "core")].into())
+            .as_deref(),
+        Some("/orgs/acme/teams/core/members")
+    );
+    // the bound value is kept over a capture with the same name
+    assert_eq!(
+        acme.url_for(&router, ids[1], &[("org", "other"), ("repo", "r")].into())
+            .as_deref(),
+        Some("/orgs/acme/repos/r")
+    );
+    assert_eq!(acme.url_for(&router, ids[1], &Captures::new()), None);
+    let outside = router.find_by_source("/orgs/:org").unwrap();
+    assert_eq!(acme.url_for(&router, outside, &Captures::new()), None);
+
+    // a failed scope adds none of its routes
+    let error = router
+        .scope_with_param("/users/:user", |scope| {
+            scope.add("/posts", "posts")?;
+            scope.add("/orgs/:user", "duplicate")?;
+            Ok::<_, String>(())
+        })
+        .unwrap_err();
+    assert_eq!(
+        error,
+        "`user` is already a param of the scope `/users/:user`"
+    );
+    assert_eq!(router.len(), 4);
+
+    for prefix in ["/users", "/users/:user?", "/files/*", "/files/:path+"] {
+        assert!(router
+            .scope_with_param(prefix, |_| Ok::<_, String>(()))
+            .is_err());
+    }
+
+    Ok(())
+}
+
+routefinder::scope_params! {
+    /// the params of `/orgs/:org/teams/:team`
+    struct TeamParams { org, team, }
+}
+
+#[test]
+fn typed_scopes() -> Result {
+    let mut router = Router::new();
+    router.add("/orgs/:org/teams/:team/about", "outside")?;
+    let teams = router.typed_scope::<TeamParams, String>("/orgs/:org/teams/:team", |scope| {
+        scope.add("/members/:member", "member")?;
+        scope.add("/", "team")?;
+        Ok(())
+    })?;
+    assert_eq!(teams.ids().len(), 2);
+
+    let m = router
+        .best_match("/orgs/acme/teams/core/members/jbr")
+        .unwrap();
+    let captures = m.captures();
+    let params = teams.params(m.route(), &captures).unwrap();
+    assert_eq!(
+        params,
+        TeamParams {
+            org: "acme",
+            team: "core"
+        }
+    );
+    assert_eq!(captures.get("member"), Some("jbr"));
+
+    let m = router.best_match("/orgs/acme/teams/core").unwrap();
+    assert_eq!(teams.params(m.route(), &m.captures()).unwrap().team, "core");
+
+    let outside = router.best_match("/orgs/acme/teams/core/about").unwrap();
+    assert_eq!(teams.params(outside.route(), &outside.captures()), None);
+
+    let bound = teams.bind([("org", "acme"), ("team", "core")].into());
+    assert_eq!(
+        bound
+            .url_for(&router, teams.ids()[0], &[("member", "jbr")].into())
+            .as_deref(),
+        Some("/orgs/acme/teams/core/members/jbr")
+    );
+
+    let len = router.len();
+    let error = router
+        .typed_scope::<TeamParams, String>("/orgs/:org", |scope| {
+            scope.add("/repos", "repos")?;
+            Ok(())
+        })
+        .unwrap_err();
+    assert!(error.contains("fields"));
+    assert_eq!(router.len(), len);
+
+    Ok(())
+}
+
+#[test]
+fn sort_keys() -> Result {
+    let routes = [
+        "/",
+        "*",
+        "/users",
+        "/users/:id",
+        "/users/:id?",
+        "/users/new",
+        "/users/:id/*",
+        "/users/:id.:format",
+        "/users/:id.json",
+        "/files/:path+",
+        "/files/:path*",
+        "/files/*.txt",
+        "/:a/:b",
+        "/:a.:b",
+        "/:a",
+    ];
+
+    let mut router = Router::new();
+    for (index, route) in routes.iter().enumerate() {
+        router.add(*route, index)?;
+        router.add(*route, index)?;
+    }
+    router.add_with_mode("/docs", MatchMode::Prefix, 100)?;
+    router.add_with_mode("/docs/api", MatchMode::Prefix, 101)?;
+    router.add_with_mode("/docs/api", MatchMode::PriorityPrefix, 102)?;
+    router.add_with_mode("/docs/:page", MatchMode::Pattern, 103)?;
+    router.add_conditional("/users/:id", 104, || true)?;
+
+    let in_router: Vec<&RouteSpec> = router.iter().map(|(route, _)| route).collect();
+    let mut sorted = in_router.clone();
+    sorted.reverse();
+    sorted.sort_by_key(|route| route.sort_key());
+    assert_eq!(sorted, in_router);
+
+    // keys sort the same as text
+    let mut keys: Vec<String> = in_router
+        .iter()
+        .map(|route| route.sort_key().to_string())
+        .collect();
+    let expected = keys.clone();
+    keys.sort();
+    assert_eq!(keys, expected);
+    assert!(keys.iter().all(|key| key.is_ascii()));
+
+    let unassigned: RouteSpec = "/users/:id".parse()?;
+    let assigned = router
+        .iter()
+        .find(|(r, _)| r.to_string() == "/users/:id")
+        .unwrap();
+    assert!(unassigned.sort_key() > assigned.0.sort_key());
+
+    Ok(())
+}
+
+#[test]
+fn from_sorted_iter() -> Result {
+    let mut router = Router::new();
+    for route in [
+        "/*",
+        "/users/:id",
+        "/users/new",
+        "/users/:id",
+        "/:a.:b",
+        "/",
+    ] {
+        router.add(route, route.len())?;
+    }
+    router.add_with_mode("/docs", MatchMode::Prefix, 0)?;
+
+    let mut rows: Vec<(String, RouteSpec, usize)> = router
+        .iter()
+        .map(|(route, handler)| (route.sort_key().to_string(), route.clone(), *handler))
+        .collect();
+    rows.reverse();
+    rows.sort();
+
+    let loaded =
+        Router::from_sorted_iter(rows.into_iter().map(|(_, route, handler)| (route, handler)));
+    assert_eq!(loaded.len(), router.len());
+    assert_eq!(loaded.fingerprint(), router.fingerprint());
+    for (index, ((a, a_handler), (b, b_handler))) in loaded.iter().zip(router.iter()).enumerate() {
+        assert_eq!(a.canonical(), b.canonical());
+        // ids are assigned in order of precedence
+        assert_eq!(a.id().unwrap().get(), index);
+        assert_eq!(a_handler, b_handler);
+    }
+
+    for path in ["/", "/users/1", "/users/new", "/a.b", "/docs/api", "/other"] {
+        assert_eq!(
+            loaded.best_match(path).map(|m| *m),
+            router.best_match(path).map(|m| *m)
+        );
+    }
+
+    let mut loaded = loaded;
+    let id = loaded.add("/users/:id/edit", 1)?;
+    assert_eq!(loaded.spec(id).unwrap().to_string(), "/users/:id/edit");
+
+    assert!(Router::<()>::from_sorted_iter(vec![]).is_empty());
+    Ok(())
+}
+
+#[test]
+fn limits() -> Result {
+    let mut router = Router::new();
+    router.add("/*", "wildcard")?;
+    router.add("/:a/*/:b", "mid wildcard")?;
+    router.add("/:name.:ext", "dots")?;
+    router.add("/:a/:b/:c", "three")?;
+
+    let long = "/a".repeat(10_000);
+    let dots = format!("/{}", "a.".repeat(10_000));
+    assert_eq!(*router.best_match(&long).unwrap(), "mid wildcard");
+    assert_eq!(*router.best_match(&dots).unwrap(), "dots");
+
+    router.set_limits(
+        Limits::new()
+            .max_bytes(1024)
+            .max_segments(16)
+            .max_consecutive_slashes(1),
+    );
+    assert!(router.best_match(&long).is_none());
+    assert!(router.best_match(&dots).is_none());
+    assert!(router.matches(&"/".repeat(10_000)).is_empty());
+    assert!(router
+        .best_match(&format!("/{}", "a".repeat(1024)))
+        .is_none());
+    assert!(router.best_match("/a//b").is_none());
+
+    assert_eq!(*router.best_match("/a/b/c").unwrap(), "three");
+    assert_eq!(*router.best_match("/index.html").unwrap(), "dots");
+    assert_eq!(
+        router.limits(),
+        Limits::new()
+            .max_bytes(1024)
+            .max_segments(16)
+            .max_consecutive_slashes(1)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn dot_segments() -> Result {
+    let mut router = Router::new();
+    router.add("/assets/*", "assets")?;
+    router.add("/admin/:page", "admin")?;
+    router.add("/:name.:ext", "file")?;
+    router.set_resolve_dot_segments(true);
+
+    let m = router.best_match("/assets/css/../js/./app.js").unwrap();
+    assert_eq!(*m, "assets");
+    assert_eq!(m.path(), "/assets/js/app.js");
+    assert_eq!(m.captures().wildcard(), Some("js/app.js"));
+
+    let m = router.best_match("/assets/%2e%2e/admin/users").unwrap();
+    assert_eq!(*m, "admin");
+    assert_eq!(m.captures().get("page"), Some("users"));
+
+    let m = router.best_match("/index.html").unwrap();
+    assert_eq!(*m, "file");
+    assert_eq!(m.path(), "/index.html");
+
+    assert!(router.best_match("/assets/../../etc/passwd").is_none());
+    assert!(router.matches("/..").is_empty());
+
+    router.set_resolve_dot_segments(false);
+    assert_eq!(
+        router
+            .best_match("/assets/../../etc/passwd")
+            .unwrap()
+            .path(),
+        "/assets/../../etc/passwd"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn normalized_paths() -> Result {
+    use routefinder::normalize::NormalizedPath;
+
+    let mut router = Router::new();
+    router.add("/files/*", "files")?;
+    router.add("/:a/:b", "two")?;
+    router.set_resolve_dot_segments(true);
+
+    let path = NormalizedPath::new("/files/a/../b").unwrap();
+    assert_eq!(path.as_str(), "/files/b");
+    assert_eq!(NormalizedPath::new(path.as_str()).unwrap(), path);
+    assert!(NormalizedPath::new("/files/../..").is_none());
+
+    let m = router.best_match_normalized(&path).unwrap();
+    assert_eq!(*m, "files");
+    assert_eq!(m.path(), "/files/b");
+    assert_eq!(router.match_iter_normalized(&path).count(), 2);
+
+    let owned = NormalizedPath::new("/x/./y").unwrap().into_owned();
+    assert_eq!(&*owned, "/x/y");
+    assert_eq!(*router.best_match_normalized(&owned).unwrap(), "two");
+
+    router.set_limits(Limits::new().max_segments(1));
+    assert!(router.best_match_normalized(&owned).is_none());
+
+    // a decoding router decodes normalized paths as it does any other
+    let mut router = Router::new();
+    router.add("/café", "cafe")?;
+    router.add("/users/:name", "user")?;
+    router.set_match_options(MatchOptions::new().decode(true).plus_as_space(true));
+    for path in ["/caf%C3%A9", "/users/jane%20doe", "/users/jane+doe"] {
+        let normalized = NormalizedPath::new(path).unwrap();
+        let m = router.best_match_normalized(&normalized).unwrap();
+        let expected = router.best_match(path).unwrap();
+        assert_eq!(*m, *expected, "{}", path);
+        assert_eq!(
+            m.captures().get("name"),
+            expected.captures().get("name"),
+            "{}",
+            path
+        );
+    }
+    let path = NormalizedPath::new("/users/x/../jane%20doe").unwrap();
+    let m = router.best_match_normalized(&path).unwrap();
+    assert_eq!(m.captures().get("name"), Some("jane doe"));
+
+    Ok(())
+}
+
+#[test]
+fn head_and_options() -> Result {
+    let mut router = MethodRouter::new();
+    router.add("GET", "/users/:id", "show")?;
+    router.add("HEAD", "/users/me", "head me")?;
+    router.add("PATCH", "/users/:id", "update")?;
+    router.add("OPTIONS", "/users", "users options")?;
+    router.add("GET", "/users", "index")?;
+
+    assert_eq!(*router.best_match("head", "/users/1").unwrap(), "show");
+    assert_eq!(*router.best_match("HEAD", "/users/me").unwrap(), "head me");
+    assert!(router.best_match("HEAD", "/posts").is_none());
+
+    assert_eq!(
+        router.allowed_methods("/users/1"),
+        ["GET", "HEAD", "OPTIONS", "PATCH"]
+    );
+    assert_eq!(router.allowed_methods("/users"), ["GET", "HEAD", "OPTIONS"]);
+
+    assert_eq!(
+        router.resolve("OPTIONS", "/users/1").allow(),
+        Some(&["GET", "HEAD", "OPTIONS", "PATCH"][..])
+    );
+    assert_eq!(
+        *router.resolve("OPTIONS", "/users").into_match().unwrap(),
+        "users options"
+    );
+    assert_eq!(
+        router.resolve("DELETE", "/users/1").allow(),
+        Some(&["GET", "HEAD", "OPTIONS", "PATCH"][..])
+    );
+    assert!(matches!(
+        router.resolve("DELETE", "/users/1"),
+        MethodMatch::MethodNotAllowed(_)
+    ));
+    assert!(matches!(
+        router.resolve("OPTIONS", "/posts"),
+        MethodMatch::NotFound
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn router_map() -> Result {
+    let mut routers = RouterMap::new();
+    routers.add_fallback("/*", "not found")?;
+    routers.add_fallback("/health", "health")?;
+    routers.add(String::from("acme"), "/users/:id", "acme users")?;
+    routers.add(String::from("globex"), "/users/:id", "globex users")?;
+    routers.add(String::from("globex"), "/health", "globex health")?;
+
+    assert_eq!(
+        *routers.best_match("acme", "/users/1").unwrap(),
+        "acme users"
+    );
+    assert_eq!(*routers.best_match("acme", "/health").unwrap(), "health");
+    assert_eq!(
+        *routers.best_match("globex", "/health").unwrap(),
+        "globex health"
+    );
+    assert_eq!(
+        *routers.best_match("initech", "/users/1").unwrap(),
+        "not found"
+    );
+    assert_eq!(routers.keys().collect::<Vec<_>>(), ["acme", "globex"]);
+
+    let previous = routers.reload(
+        String::from("acme"),
+        vec![("/accounts/:id", "acme accounts")],
+    )?;
+    assert_eq!(previous.unwrap().len(), 1);
+    assert_eq!(
+        *routers.best_match("acme", "/users/1").unwrap(),
+        "not found"
+    );
+    assert_eq!(
+        *routers.best_match("acme", "/accounts/1").unwrap(),
+        "acme accounts"
+    );
+    assert_eq!(
+        *routers.best_match("globex", "/users/1").unwrap(),
+        "globex users"
+    );
+
+    assert!(routers.remove("globex").is_some());
+    assert_eq!(*routers.best_match("globex", "/health").unwrap(), "health");
+    assert_eq!(routers.len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn formats() -> Result {
+    let mut router = Router::new();
+    router.add_with_formats("/reports/:id", ["json", "csv"], 1)?;
+    router.add_with_formats("/reports/:id/summary", vec![String::from("html")], 2)?;
+    router.add("/reports/:id.pdf", 3)?;
+
+    let formats = |path| {
+        let m = router.best_match(path).unwrap();
+        (*m, m.format(), m.captures().get("id").map(String::from))
+    };
+
+    assert_eq!(formats("/reports/10"), (1, Some("json"), Some("10".into())));
+    assert_eq!(
+        formats("/reports/10.json"),
+        (1, Some("json"), Some("10".into()))
+    );
+    assert_eq!(
+        formats("/reports/10.csv"),
+        (1, Some("csv"), Some("10".into()))
+    );
+    assert_eq!(formats("/reports/10.pdf"), (3, None, Some("10".into())));
+    assert_eq!(
+        formats("/reports/10.xml"),
+        (1, Some("json"), Some("10.xml".into()))
+    );
+    assert_eq!(
+        formats("/reports/10/summary.html"),
+        (2, Some("html"), Some("10".into()))
+    );
+    assert_eq!(
+        formats("/reports/10/summary"),
+        (2, Some("html"), Some("10".into()))
+    );
+
+    assert_eq!(
+        router
+            .iter()
+            .map(|(r, _)| r.to_string())
+            .collect::<Vec<_>>(),
+        [
+            "/reports/:id.csv",
+            "/reports/:id.json",
+            "/reports/:id.pdf",
+            "/reports/:id",
+            "/reports/:id/summary.html",
+            "/reports/:id/summary",
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn negotiated_formats() -> Result {
+    let mut router = Router::new();
+    router.add_with_formats("/reports/:id", ["json", "csv", "html"], "report")?;
+    router.add("/reports/latest.json", "latest")?;
+    router.add("/reports/:id/raw", "raw")?;
+
+    let negotiate = |path, accept| {
+        let m = router.best_match_negotiated(path, accept).unwrap();
+        (*m, m.format())
+    };
+
+    assert_eq!(
+        negotiate("/reports/1", "text/html"),
+        ("report", Some("html"))
+    );
+    assert_eq!(
+        negotiate("/reports/1", "text/csv;q=0.9, text/html;q=0.5"),
+        ("report", Some("csv"))
+    );
+    assert_eq!(
+        negotiate("/reports/1", "text/*, application/json;q=0.1"),
+        ("report", Some("csv"))
+    );
+    assert_eq!(negotiate("/reports/1", "*/*"), ("report", Some("json")));
+    assert_eq!(negotiate("/reports/1", ""), ("report", Some("json")));
+    assert_eq!(
+        negotiate("/reports/1", "image/png"),
+        ("report", Some("json"))
+    );
+    assert_eq!(
+        negotiate("/reports/1", "text/html;q=0"),
+        ("report", Some("json"))
+    );
+
+    // a suffix, and routes that outrank the negotiated route, take precedence
+    assert_eq!(
+        negotiate("/reports/1.csv", "text/html"),
+        ("report", Some("csv"))
+    );
+    assert_eq!(
+        negotiate("/reports/latest.json", "text/html"),
+        ("latest", None)
+    );
+    assert_eq!(negotiate("/reports/1/raw", "text/html"), ("raw", None));
+    assert!(router
+        .best_match_negotiated("/posts", "text/html")
+        .is_none());
+
+    router.set_negotiator(Negotiator::new().with_media_type("csv", "application/vnd.ms-excel"));
+    assert_eq!(
+        negotiate_with(&router, "application/vnd.ms-excel"),
+        Some("csv")
+    );
+    assert_eq!(negotiate_with(&router, "text/csv"), Some("json"));
+
+    Ok(())
+}
+
+fn negotiate_with<'a>(router: &'a Router<&str>, accept: &str) -> Option<&'a str> {
+    router.best_match_negotiated("/reports/1", accept)?.format()
+}
+
+#[test]
+fn incremental_matcher() -> Result {
+    let mut router = Router::new();
+    router.add("/", "root")?;
+    router.add("/users", "users")?;
+    router.add("/users/:id", "user")?;
+    router.add("/users/:id.:format", "user format")?;
+    router.add("/users/:id/files/*", "files")?;
+    router.add("/assets/*.css", "css")?;
+
+    let mut matcher = router.matcher();
+    assert_eq!(matcher.candidates(), 6);
+    matcher.feed("users");
+    assert_eq!(matcher.candidates(), 4);
+    matcher.feed("10.json");
+    assert_eq!(matcher.candidates(), 3);
+    assert_eq!(matcher.path(), "/users/10.json");
+    let m = matcher.finish().unwrap();
+    assert_eq!(*m, "user format");
+    assert_eq!(m.captures().get("format"), Some("json"));
+
+    let mut matcher = router.matcher();
+    matcher
+        .feed("users/10")
+        .feed("files")
+        .feed("a")
+        .feed("b.txt");
+    let m = matcher.finish().unwrap();
+    assert_eq!(*m, "files");
+    assert_eq!(m.path(), "/users/10/files/a/b.txt");
+    assert_eq!(m.captures().wildcard(), Some("a/b.txt"));
+
+    let mut matcher = router.matcher();
+    matcher.feed("assets").feed("css").feed("site.css");
+    assert_eq!(*matcher.finish().unwrap(), "css");
+
+    let mut matcher = router.matcher();
+    matcher.feed("nope");
+    assert!(!matcher.is_viable());
+    assert!(matcher.finish().is_none());
+
+    assert_eq!(*router.matcher().finish().unwrap(), "root");
+
+    let mut prefixes = Router::new();
+    prefixes.add_with_mode("/api", MatchMode::Prefix, "api")?;
+    let mut matcher = prefixes.matcher();
+    matcher.feed("api").feed("v1").feed("users");
+    assert!(matcher.is_viable());
+    assert_eq!(
+        matcher.finish().unwrap().captures().wildcard(),
+        Some("v1/users")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn lookup_and_resolve() -> Result {
+    let mut router = Router::new();
+    router.add("/", 0)?;
+    router.add("/users/:id", 0)?;
+    router.add("/files/*", 0)?;
+
+    for path in ["/users/1", "/users/2", "/files/a/b", "/users/3"] {
+        let index = router.lookup(path).unwrap();
+        *router.resolve_mut(&index).unwrap().1 += 1;
+    }
+
+    assert_eq!(
+        router
+            .iter()
+            .map(|(r, h)| (r.to_string(), *h))
+            .collect::<Vec<_>>(),
+        [
+            (String::from("/"), 0),
+            (String::from("/users/:id"), 3),
+            (String::from("/files/*"), 1),
+        ]
+    );
+
+    let index = router.lookup("/files/a/b").unwrap();
+    assert_eq!(index.id(), router.find_by_source("/files/*").unwrap());
+    assert_eq!(index.path(), "/files/a/b");
+    assert_eq!(index.captures().wildcard(), Some("a/b"));
+    assert_eq!(router.resolve(&index).unwrap().0.to_string(), "/files/*");
+
+    // an index only resolves on the router it was looked up from
+    let mut clone = router.clone();
+    assert!(clone.resolve(&index).is_none());
+    assert!(clone.resolve_mut(&index).is_none());
+    let mut other = Router::new();
+    other.add("/delete-all", 0)?;
+    other.add("/b", 0)?;
+    other.add("/c", 0)?;
+    assert!(other.resolve(&index).is_none());
+    let index = clone.lookup("/users/1").unwrap();
+    assert_eq!(clone.resolve(&index).unwrap().0.to_string(), "/users/:id");
+    assert!(router.resolve(&index).is_none());
+
+    let index = router.lookup("/files/a/b").unwrap();
+    router.add("/about", 0)?;
+    assert!(router.resolve(&index).is_none());
+    assert!(router.lookup("/nope/nope").is_none());
+
+    Ok(())
+}
+
+#[test]
+fn route_sets() -> Result {
+    let mut allowed = RouteSet::new();
+    assert!(allowed.insert("/")?);
+    assert!(allowed.insert("/posts/:id")?);
+    assert!(allowed.insert("/assets/*")?);
+    assert!(!allowed.insert("posts/:id/")?);
+    assert!(allowed.insert("/posts/:slug")?);
+    assert_eq!(allowed.len(), 4);
+
+    assert!(allowed.contains("/posts/:id"));
+    assert!(!allowed.contains("/posts"));
+    assert!(!allowed.contains("*invalid"));
+    assert!(allowed.contains_matching("/posts/1"));
+    assert!(!allowed.contains_matching("/admin"));
+    assert_eq!(
+        allowed.best_match("/assets/a.css").unwrap().to_string(),
+        "/assets/*"
+    );
+
+    let mut denied = RouteSet::new();
+    denied.insert("/assets/*")?;
+    denied.insert("/admin/*")?;
+
+    let names = |set: RouteSet| set.iter().map(|r| r.to_string()).collect::<Vec<_>>();
+    assert_eq!(
+        names(allowed.union(&denied)),
+        ["/", "/posts/:id", "/posts/:slug", "/assets/*", "/admin/*"]
+    );
+    assert_eq!(names(allowed.intersection(&denied)), ["/assets/*"]);
+    assert_eq!(
+        names(allowed.difference(&denied)),
+        ["/", "/posts/:id", "/posts/:slug"]
+    );
+    assert_eq!(names(denied.difference(&allowed)), ["/admin/*"]);
+
+    Ok(())
+}
+
+#[test]
+fn policy_modes() -> Result {
+    let mut policy = PolicyRouter::new();
+    policy.allow("/*")?;
+    policy.deny("/internal/*")?;
+    policy.allow("/internal/health")?;
+    policy.deny("/users/:id/secrets")?;
+    policy.allow("/users/:id/secrets")?;
+
+    assert_eq!(policy.mode(), PolicyMode::MostSpecific);
+    assert!(policy.is_allowed("/"));
+    assert!(policy.is_allowed("/internal/health"));
+    assert!(!policy.is_allowed("/internal/metrics"));
+    assert!(!policy.is_allowed("/users/1/secrets"));
+    assert_eq!(policy.evaluate("/users/1"), Some(Effect::Allow));
+
+    policy.set_mode(PolicyMode::DenyOverrides);
+    assert!(!policy.is_allowed("/internal/health"));
+    assert!(policy.is_allowed("/users/1"));
+
+    policy.set_mode(PolicyMode::AllowOverrides);
+    assert!(policy.is_allowed("/internal/metrics"));
+    assert!(policy.is_allowed("/users/1/secrets"));
+
+    let mut policy = PolicyRouter::new();
+    policy.allow("/public/*")?;
+    assert_eq!(policy.evaluate("/private"), None);
+    assert!(!policy.is_allowed("/private"));
+    policy.set_default(Effect::Allow);
+    assert!(policy.is_allowed("/private"));
+    assert_eq!(policy.rules().count(), 1);
+    assert!(policy.route_set(Effect::Allow).contains("/public/*"));
+    assert!(policy.route_set(Effect::Deny).is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn robots_txt() {
+    use routefinder::robots::Robots;
+    let robots = Robots::parse(
+        "# comments are ignored
+user-agent: a-bot
+user-agent: b-bot
+disallow: /
+allow: /public
+allow: /*.css$
+
+User-Agent: *
+Disallow: /tmp
+Disallow: /search*?q=
+Allow: /tmp/
+
+user-agent: A-Bot
+disallow: /public/drafts
+",
+    );
+
+    assert!(!robots.is_allowed("a-bot", "/"));
+    assert!(robots.is_allowed("A-Bot/1.0", "/public/posts"));
+    assert!(!robots.is_allowed("a-bot", "/public/drafts/1"));
+    assert!(robots.is_allowed("b-bot", "/public/drafts/1"));
+    assert!(robots.is_allowed("a-bot", "/assets/site.css"));
+    assert!(!robots.is_allowed("a-bot", "/assets/site.css.map"));
+    assert!(robots.is_allowed("a-bot", "/robots.txt"));
+
+    assert!(robots.is_allowed("c-bot", "/posts"));
+    assert!(!robots.is_allowed("c-bot", "/tmpfiles"));
+    assert!(robots.is_allowed("c-bot", "/tmp/"));
+    assert!(!robots.is_allowed("c-bot", "/search/results?q=routing"));
+    assert!(robots.is_allowed("c-bot", "/search"));
+
+    let rule = robots.matching_rule("c-bot", "/tmp/1").unwrap();
+    assert_eq!(rule.pattern(), "/tmp/");
+    assert_eq!(rule.effect(), Effect::Allow);
+    assert_eq!(robots.rules_for("a-bot").len(), 4);
+
+    let robots = Robots::parse("User-agent: *\nDisallow: /a\nAllow: /a\nDisallow:\n");
+    assert!(robots.is_allowed("any", "/a"));
+    assert_eq!(robots.rules_for("any").len(), 2);
+    assert!(Robots::parse("").is_allowed("any", "/"));
+}
+
+#[test]
+fn route_enumeration() -> Result {
+    let mut router = Router::new();
+    router.add("/", 0)?;
+    router.add("/users", 1)?;
+    router.add("/users/:id", 2)?;
+    router.add("/users/:id/posts/:post_id", 3)?;
+    router.add("/assets/*", 4)?;
+    router.add_weighted("/landing", [(1, 5), (1, 6)])?;
+    router.add(RouteSpec::parse_with("/files/:name?", Syntax::Express)?, 7)?;
+    router.add(RouteSpec::parse_with("*.md", Syntax::Glob)?, 8)?;
+
+    assert_eq!(router.static_paths(), ["/", "/users", "/landing"]);
+
+    let mut values = std::collections::HashMap::new();
+    values.insert("id", vec!["1", "2"]);
+    values.insert("post_id", vec!["a"]);
+    values.insert("name", vec!["readme.txt"]);
+    let paths = router.expand_with(&values);
+    for path in [
+        "/users/1",
+        "/users/2",
+        "/users/1/posts/a",
+        "/users/2/posts/a",
+        "/files/readme.txt",
+        "/files",
+        "/assets",
+    ] {
+        assert!(paths.iter().any(|p| p == path), "{} missing", path);
+    }
+    assert_eq!(paths.len(), 10);
+    for path in &paths {
+        assert!(router.best_match(path).is_some());
+    }
+
+    assert_eq!(router.expand_with([("id", ["a/b"])]).len(), 5);
+    Ok(())
+}
+
+#[test]
+fn sampled_paths() -> Result {
+    let seeded = |seed: u64| {
+        let mut state = seed;
+        move |bound: u32| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % u64::from(bound)) as u32
+        }
+    };
+
+    let mut router = Router::new();
+    router.add("/", 0)?;
+    router.add("/users/:id", 1)?;
+    router.add("/users/:id/posts/:post_id", 2)?;
+    router.add("/assets/*", 3)?;
+    router.add("/docs/:path+", 4)?;
+    router.add(RouteSpec::parse_with("/files/:name?", Syntax::Express)?, 5)?;
+    router.add(RouteSpec::parse_with("*.md", Syntax::Glob)?, 6)?;
+    router.add_with_mode("/static", MatchMode::Prefix, 7)?;
+
+    let paths = router.sample_paths(seeded(0x2951), 1000);
+    assert_eq!(paths.len(), 1000);
+    assert_eq!(paths, router.sample_paths(seeded(0x2951), 1000));
+    assert_ne!(paths, router.sample_paths(seeded(0x2952), 1000));
+    assert!(paths.iter().all(|path| path.starts_with('/')));
+
+    // mostly valid matches, with some near misses and garbage
+    let matched = paths
+        .iter()
+        .filter(|path| router.best_match(*path).is_some())
+        .count();
+    assert!(matched > 500 && matched < 950, "{} matched", matched);
+    for route in [1, 2, 3, 4, 5, 7] {
+        assert!(paths
+            .iter()
+            .any(|path| router.best_match(path).is_some_and(|m| *m == route)));
+    }
+
+    let empty: Router<()> = Router::new();
+    let garbage = empty.sample_paths(seeded(1), 10);
+    assert_eq!(garbage.len(), 10);
+    assert!(router.sample_paths(seeded(1), 0).is_empty());
+    Ok(())
+}
+
+#[test]
+fn structured_explanations() -> Result {
+    let mut router = Router::new();
+    let user = router.add("/users/:id", ())?;
+    let new_user = router.add("/users/new", ())?;
+    let posts = router.add("/users/:id/posts/:post.:format", ())?;
+    let files = router.add("/files/*", ())?;
+    let flagged = router.add_conditional("/users/:id/edit", (), || false)?;
+    router.pin_engine(Engine::Partitioned);
+
+    let explanation = router.explain("/users/new");
+    assert_eq!(explanation.path(), "/users/new");
+    assert_eq!(explanation.routes().len(), 5);
+    assert_eq!(explanation.selected().unwrap().id(), new_user);
+    let outcome = |id| explanation.route(id).unwrap().outcome().clone();
+    assert_eq!(outcome(user), Outcome::Shadowed);
+    assert_eq!(
+        outcome(posts),
+        Outcome::Rejected(Rejection::PathEnded {
+            segment: 2,
+            expected: "posts".into()
+        })
+    );
+    assert_eq!(
+        outcome(files),
+        Outcome::Rejected(Rejection::Mismatch {
+            segment: 0,
+            expected: "files".into(),
+            found: "users".into()
+        })
+    );
+    assert!(explanation.route(files).unwrap().skipped());
+    assert!(!explanation.route(user).unwrap().skipped());
+
+    let explanation = router.explain("/users/1/posts/2");
+    let rejection = match explanation.route(posts).unwrap().outcome() {
+        Outcome::Rejected(rejection) => rejection.clone(),
+        outcome => panic!("{:?}", outcome),
+    };
+    assert_eq!(rejection.segment(), 3);
+    assert_eq!(rejection.expected(), Some(":post.:format"));
+    assert_eq!(rejection.found(), Some("2"));
+    assert_eq!(rejection.to_string(), "`2` does not match `:post.:format`");
+
+    let explanation = router.explain("/users/1/edit");
+    assert_eq!(
+        explanation.route(flagged).unwrap().outcome(),
+        &Outcome::Rejected(Rejection::Excluded { segment: 3 })
+    );
+    assert_eq!(
+        explanation.route(user).unwrap().outcome(),
+        &Outcome::Rejected(Rejection::PathContinues {
+            segment: 2,
+            found: "edit".into()
+        })
+    );
+    assert!(explanation.selected().is_none());
+
+    let empty: Router<()> = Router::new();
+    assert_eq!(
+        empty.explain("/").to_string(),
+        "routes for /:\n    the router has no routes"
+    );
+    Ok(())
+}
+
+#[test]
+fn path_completion() -> Result {
+    let mut router = Router::new();
+    router.add("/", ())?;
+    router.add("/users", ())?;
+    router.add("/users/new", ())?;
+    router.add("/users/:id/posts", ())?;
+    router.add("/users/:id/settings", ())?;
+    router.add("/uploads/*", ())?;
+    router.add("/feed.xml", ())?;
+    router.add(
+        RouteSpec::parse_with("/blog/:year?/archive", Syntax::Express)?,
+        (),
+    )?;
+    router.add_with_mode("/static", MatchMode::Prefix, ())?;
+    router.deny("/admin")?;
+
+    let literals = |prefix: &str| -> Vec<String> {
+        router
+            .complete(prefix)
+            .literals()
+            .map(String::from)
+            .collect()
+    };
+
+    assert_eq!(
+        literals("/"),
+        ["blog", "feed.xml", "static", "uploads", "users"]
+    );
+    assert_eq!(literals(""), literals("/"));
+    assert_eq!(literals("/u"), ["uploads", "users"]);
+    assert_eq!(literals("/users"), ["users"]);
+    assert_eq!(literals("/users/"), ["new"]);
+    assert_eq!(literals("/users/42/"), ["posts", "settings"]);
+    assert_eq!(literals("/users/42/s"), ["settings"]);
+    assert_eq!(literals("/blog/"), ["archive"]);
+    assert_eq!(literals("/blog/2024/"), ["archive"]);
+
+    let completion = router.complete("/users/");
+    assert!(completion.param());
+    assert!(!completion.wildcard());
+    assert!(!router.complete("/").param());
+    assert!(router.complete("/blog/").param());
+    assert!(router.complete("/uploads/").wildcard());
+    assert!(router.complete("/uploads/a/b/").wildcard());
+    assert!(router.complete("/static/").wildcard());
+    assert!(router.complete("/static/css/").wildcard());
+
+    assert!(router.complete("/admin/").is_empty());
+    assert!(router.complete("/users/42/posts/").is_empty());
+    assert!(router.complete("/x").is_empty());
+
+    #[cfg(feature = "regex")]
+    {
+        let mut router = Router::new();
+        router.add(
+            RouteSpec::parse_with("/items/:id(\\d+)/edit", Syntax::Express)?,
+            (),
+        )?;
+        assert_eq!(
+            router.complete("/items/1/").literals().collect::<Vec<_>>(),
+            ["edit"]
+        );
+        assert!(router.complete("/items/one/").is_empty());
+    }
+    Ok(())
+}
+
+#[test]
+fn route_ids() -> Result {
+    let mut router = Router::new();
+    let a = router.add("/:a", "a")?;
+    let b = router.add("/:b", "b")?;
+    let hello = router.add("/hello", "hello")?;
+    let landing = router.add_weighted("/landing", [(1, "x"), (1, "y")])?;
+    assert_eq!([a.get(), b.get(), hello.get()], [0, 1, 2]);
+    assert_eq!(
+        landing.iter().map(|id| id.get()).collect::<Vec<_>>(),
+        [3, 4]
+    );
+
+    // routes of equal precedence are in the order they were added
+    let order: Vec<_> = router.iter().map(|(_, handler)| *handler).collect();
+    assert_eq!(order, ["hello", "x", "y", "a", "b"]);
+    let ids: Vec<_> = router.iter().filter_map(|(route, _)| route.id()).collect();
+    assert_eq!(ids, [hello, landing[0], landing[1], a, b]);
+
+    let m = router.best_match("/world").unwrap();
+    assert_eq!(m.route().id(), Some(a));
+    assert_eq!(m.captures().get("a"), Some("world"));
+
+    assert_eq!(router.remove(a), Some("a"));
+    assert_eq!(router.remove(a), None);
+    assert_eq!(router.len(), 4);
+    assert_eq!(router.best_match("/world").unwrap().route().id(), Some(b));
+
+    // ids are not reused after a removal
+    let c = router.add("/:c", "c")?;
+    assert_eq!(c.get(), 5);
+    assert_eq!(**router.matches("/world").last().unwrap(), "c");
+
+    let collected: Router<_> = router.into_iter().collect();
+    let order: Vec<_> = collected.iter().map(|(_, handler)| *handler).collect();
+    assert_eq!(order, ["hello", "x", "y", "b", "c"]);
+    Ok(())
+}
+
+#[test]
+fn handler_access() -> Result {
+    let mut router = Router::new();
+    let users = router.add("/users/:id", 1)?;
+    let fallback = router.add("*", 2)?;
+    let formats = router.add_with_formats("/reports/:id", ["json", "csv"], 3)?;
+
+    assert_eq!(router.handler(users), Some(&1));
+    *router.handler_mut(fallback).unwrap() = 20;
+    assert_eq!(*router.best_match("/nothing").unwrap(), 20);
+    assert_eq!(router.spec(users).unwrap().source(), Some("/users/:id"));
+    assert_eq!(
+        router.spec(formats[0]).unwrap().to_string(),
+        "/reports/:id.csv"
+    );
+
+    assert_eq!(router.find_by_source("/users/:id"), Some(users));
+    assert_eq!(router.find_by_source("//users/:id//"), Some(users));
+    assert_eq!(router.find_by_source("/reports/:id.json"), Some(formats[1]));
+    assert_eq!(router.find_by_source("/users/:id/"), Some(users));
+    assert_eq!(router.find_by_source("/users/:user"), None);
+
+    assert_eq!(router.get_handler("/users/:id"), Some(&1));
+    assert_eq!(router.get_handler("users/:id/"), Some(&1));
+    *router.get_handler_mut("*").unwrap() = 30;
+    assert_eq!(router.handler(fallback), Some(&30));
+    assert_eq!(router.get_handler("/reports/:id"), Some(&3));
+
+    router.remove(users);
+    assert_eq!(router.handler(users), None);
+    assert_eq!(router.spec(users), None);
+    assert_eq!(router.find_by_source("/users/:id"), None);
+    assert_eq!(router.get_handler("/users/:id"), None);
+    Ok(())
+}
+
+#[test]
+fn match_options() -> Result {
+    let mut router = Router::new();
+    router.add("/health", "health")?;
+    router.add("/files/:name.tar.gz", "archive")?;
+    router.add("/files/:name", "file")?;
+    router.add(RouteSpec::parse_with("/images/*.PNG", Syntax::Glob)?, "png")?;
+    router.add_with_mode("/api", MatchMode::Prefix, "api")?;
+
+    // the defaults are lenient about trailing slashes only
+    assert_eq!(router.match_options(), MatchOptions::new());
+    assert_eq!(*router.best_match("/health/").unwrap(), "health");
+    assert!(router.best_match("/HEALTH").is_none());
+    assert_eq!(
+        router
+            .best_match("/files/a%20b")
+            .unwrap()
+            .captures()
+            .get("name"),
+        Some("a%20b")
+    );
+
+    let strict = MatchOptions::new().strict_trailing_slash(true);
+    assert!(router.best_match_with("/health/", &strict).is_none());
+    assert_eq!(
+        *router.best_match_with("/health", &strict).unwrap(),
+        "health"
+    );
+    assert_eq!(*router.best_match_with("/api/", &strict).unwrap(), "api");
+
+    let lenient = MatchOptions::new().case_insensitive(true).decode(true);
+    let m = router
+        .best_match_with("/FILES/Report%201.TAR.GZ", &lenient)
+        .unwrap();
+    assert_eq!(*m, "archive");
+    assert_eq!(m.captures().get("name"), Some("Report 1"));
+    assert_eq!(m.path(), "/FILES/Report 1.TAR.GZ");
+    assert_eq!(
+        *router
+            .best_match_with("/images/LOGO.png", &lenient)
+            .unwrap(),
+        "png"
+    );
+    assert!(router.best_match_with("/files/%FF", &lenient).is_none());
+    assert_eq!(
+        router
+            .best_match_with("/files/a%2Fb", &lenient)
+            .unwrap()
+            .captures()
+            .get("name"),
+        Some("a%2Fb")
+    );
+
+    // router options apply to best_match, and per call options replace them
+    router.set_resolve_dot_segments(true);
+    router.set_match_options(router.match_options().decode(true));
+    assert_eq!(
+        router.match_options(),
+        MatchOptions::new().decode(true).resolve_dot_segments(true)
+    );
+    let m = router.best_match("/files/../files/caf%C3%A9").unwrap();
+    assert_eq!(m.captures().get("name"), Some("café"));
+    assert!(router
+        .best_match_with("/files/../files/caf%C3%A9", &MatchOptions::new())
+        .is_none());
+
+    let mut matcher = router.matcher();
+    matcher.feed("files").feed("..").feed("health");
+    assert_eq!(*matcher.finish().unwrap(), "health");
+    Ok(())
+}
+
+#[test]
+fn raw_captures() -> Result {
+    let mut router = Router::new();
+    router.add("/files/:name.:ext", "file")?;
+    router.add("/proxy/*", "proxy")?;
+    router.add_with_mode("/static", MatchMode::Prefix, "static")?;
+
+    // without normalization, raw captures only differ by trailing slashes
+    let m = router.best_match("/proxy/a/b//").unwrap();
+    assert_eq!(m.captures().wildcard(), Some("a/b"));
+    assert_eq!(m.raw_captures().wildcard(), Some("a/b//"));
+    assert_eq!(m.raw_path(), "/proxy/a/b//");
+
+    let m = router.best_match("/files/report.pdf/").unwrap();
+    assert_eq!(m.raw_captures().get("name"), Some("report"));
+    assert_eq!(m.raw_captures().get("ext"), Some("pdf"));
+
+    router.set_match_options(MatchOptions::new().decode(true).resolve_dot_segments(true));
+    let m = router.best_match("/files/./my%20report.p%64f").unwrap();
+    assert_eq!(m.captures().get("name"), Some("my report"));
+    assert_eq!(m.captures().get("ext"), Some("pdf"));
+    assert_eq!(m.raw_captures().get("name"), Some("my%20report"));
+    assert_eq!(m.raw_captures().get("ext"), Some("p%64f"));
+
+    let m = router.best_match("/static/css/../js/%C3%A9/").unwrap();
+    assert_eq!(m.path(), "/static/js/é/");
+    assert_eq!(m.captures().wildcard(), Some("js/é"));
+    assert_eq!(m.raw_captures().wildcard(), Some("js/%C3%A9/"));
+
+    let m = router.best_match("/proxy/%2F%2f/x").unwrap().into_owned();
+    assert_eq!(m.captures().wildcard(), Some("%2F%2f/x"));
+    assert_eq!(m.raw_captures().wildcard(), Some("%2F%2f/x"));
+
+    let path = normalize::NormalizedPath::new("/proxy/a/../b%20c").unwrap();
+    let m = router.best_match_normalized(&path).unwrap();
+    assert_eq!(m.raw_path(), "/proxy/b%20c");
+    assert_eq!(m.raw_captures().wildcard(), Some("b%20c"));
+    Ok(())
+}
+
+#[test]
+fn wildcard_slashes() -> Result {
+    let mut router = Router::new();
+    router.add("/proxy/*", "proxy")?;
+    router.add("/files/*/raw", "raw")?;
+    router.add_with_mode("/static", MatchMode::Prefix, "static")?;
+
+    for (path, captured, normalized, raw) in [
+        ("/proxy/a/b", "a/b", "a/b", "a/b"),
+        ("/proxy//a//b", "/a//b", "a/b", "/a//b"),
+        ("/proxy//a//b//", "/a//b", "a/b", "/a//b//"),
+        ("/proxy/", "", "", "/"),
+        ("/files//a//b/raw", "/a//b", "a/b", "/a//b"),
+        (
+            "/static//css//site.css",
+            "css//site.css",
+            "css/site.css",
+            "css//site.css",
+        ),
+    ] {
+        let m = router.best_match(path).unwrap();
+        assert_eq!(m.captures().wildcard(), Some(captured), "{}", path);
+        assert_eq!(m.wildcard().unwrap(), normalized, "{}", path);
+        assert_eq!(m.wildcard_raw().unwrap(), raw, "{}", path);
+    }
+    assert!(router
+        .best_match("/static")
+        .unwrap()
+        .wildcard()
+        .unwrap()
+        .is_empty());
+
+    router.set_match_options(MatchOptions::new().collapse_wildcard_slashes(true));
+    let m = router.best_match("/proxy//a//b//").unwrap();
+    assert_eq!(m.captures().wildcard(), Some("a/b"));
+    assert_eq!(m.wildcard().unwrap(), "a/b");
+    assert_eq!(m.wildcard_raw().unwrap(), "/a//b//");
+    assert_eq!(m.canonical_path().unwrap(), "/proxy/a/b");
+    assert_eq!(
+        router
+            .best_match("/static//css//site.css")
+            .unwrap()
+            .captures()
+            .wildcard(),
+        Some("css/site.css")
+    );
+
+    // decoded wildcards are collapsed after decoding
+    router.set_match_options(router.match_options().decode(true));
+    let m = router.best_match("/proxy/%20a//b").unwrap().into_owned();
+    assert_eq!(m.captures().wildcard(), Some(" a/b"));
+    assert_eq!(m.wildcard_raw().unwrap(), "%20a//b");
+    Ok(())
+}
+
+#[test]
+fn plus_as_space() -> Result {
+    let mut router = Router::new();
+    router.add("/search/red shoes", "red shoes")?;
+    router.add("/search/:query", "search")?;
+    router.add("/files/*", "files")?;
+
+    // by default, a plus is only a plus
+    let m = router.best_match("/search/red+shoes").unwrap();
+    assert_eq!(*m, "search");
+    assert_eq!(m.captures().get("query"), Some("red+shoes"));
+
+    router.set_plus_as_space(true);
+    assert_eq!(
+        router.match_options(),
+        MatchOptions::new().plus_as_space(true)
+    );
+    assert_eq!(
+        *router.best_match("/search/red+shoes").unwrap(),
+        "red shoes"
+    );
+    let m = router.best_match("/search/blue+shoes").unwrap();
+    assert_eq!(m.captures().get("query"), Some("blue shoes"));
+    assert_eq!(m.raw_captures().get("query"), Some("blue+shoes"));
+    assert_eq!(m.path(), "/search/blue shoes");
+    assert_eq!(m.raw_path(), "/search/blue+shoes");
+
+    // without decoding, an encoded plus is left as it is
+    let m = router.best_match("/search/c%2B%2B").unwrap();
+    assert_eq!(m.captures().get("query"), Some("c%2B%2B"));
+
+    // with decoding, an encoded plus is a plus and an encoded space is a space
+    router.set_match_options(router.match_options().decode(true));
+    let m = router.best_match("/search/c%2B%2B+for%20kids").unwrap();
+    assert_eq!(m.captures().get("query"), Some("c++ for kids"));
+    assert_eq!(m.raw_captures().get("query"), Some("c%2B%2B+for%20kids"));
+    assert_eq!(
+        *router.best_match("/search/red%20shoes").unwrap(),
+        "red shoes"
+    );
+
+    let m = router.best_match("/files/a+b/c%2Bd/").unwrap();
+    assert_eq!(m.captures().wildcard(), Some("a b/c+d"));
+    assert_eq!(m.raw_captures().wildcard(), Some("a+b/c%2Bd/"));
+
+    let strict = MatchOptions::new();
+    let m = router
+        .best_match_with("/search/red+shoes", &strict)
+        .unwrap();
+    assert_eq!(*m, "search");
+    Ok(())
+}
+
+#[test]
+fn canonical_path() -> Result {
+    let mut router = Router::new();
+    router.add("/", "root")?;
+    router.add("/users/:id", "user")?;
+    router.add("/docs/*", "docs")?;
+    router.add(
+        RouteSpec::parse_with("/files/:name?", Syntax::Express)?,
+        "files",
+    )?;
+    router.add(RouteSpec::parse_with("/logs/*.txt", Syntax::Glob)?, "logs")?;
+    router.add_with_mode("/static", MatchMode::Prefix, "static")?;
+    router.add_with_formats("/reports/:id", ["json", "csv"], "report")?;
+
+    // paths that round trip
+    for path in [
+        "/",
+        "/users/1",
+        "/docs/a/b",
+        "/docs/a//b",
+        "/files",
+        "/files/a",
+        "/static",
+        "/static/css/site.css",
+        "/reports/1.csv",
+        "/reports/1",
+    ] {
+        let m = router.best_match(path).unwrap();
+        assert_eq!(m.canonical_path().unwrap(), path);
+    }
+
+    // lossy paths
+    for (path, canonical) in [
+        ("//", "/"),
+        ("/users/1/", "/users/1"),
+        ("///users/1", "/users/1"),
+        ("/docs", "/docs"),
+        ("/docs/", "/docs"),
+        ("/static/", "/static"),
+        ("/files/", "/files"),
+    ] {
+        let m = router.best_match(path).unwrap();
+        assert_eq!(m.canonical_path().unwrap(), canonical, "{}", path);
+        let again = router.best_match(canonical).unwrap();
+        assert_eq!(again.route(), m.route());
+        assert_eq!(
+            again.captures().iter().collect::<Vec<_>>(),
+            m.captures().iter().collect::<Vec<_>>()
+        );
+        assert_eq!(again.captures().wildcard(), m.captures().wildcard());
+    }
+
+    assert!(router
+        .best_match("/logs/a.txt")
+        .unwrap()
+        .canonical_path()
+        .is_none());
+
+    let options = MatchOptions::new().case_insensitive(true);
+    let m = router.best_match_with("/USERS/Jane", &options).unwrap();
+    assert_eq!(m.canonical_path().unwrap(), "/users/Jane");
+
+    // the registered spelling of exact text is kept, whatever the case
+    // of the path
+    let mut router = Router::new();
+    router.add("/Users/:name/Profile", "profile")?;
+    router.add_with_mode("/Static", MatchMode::Prefix, "static")?;
+    router.add_with_formats("/Reports/:id", ["JSON", "csv"], "report")?;
+    router.set_match_options(options);
+    for (path, canonical) in [
+        ("/users/Jane/PROFILE", "/Users/Jane/Profile"),
+        ("/Users/Jane/Profile/", "/Users/Jane/Profile"),
+        ("/STATIC/CSS/Site.css", "/Static/CSS/Site.css"),
+        ("/reports/Q1.json", "/Reports/Q1.JSON"),
+        ("/REPORTS/q1.CSV", "/Reports/q1.csv"),
+    ] {
+        assert_eq!(router.canonical_path(path).unwrap(), canonical, "{}", path);
+        assert_eq!(router.canonical_path(canonical).unwrap(), canonical);
+    }
+    assert!(router.canonical_path("/users/Jane").is_none());
+    router.set_match_options(MatchOptions::new());
+    assert!(router.canonical_path("/users/Jane/PROFILE").is_none());
+    Ok(())
+}
+
+#[test]
+fn cache_keys() -> Result {
+    let mut router = Router::new();
+    router.add("/users/:id", ())?;
+    router.add("/files/:name.:ext", ())?;
+    router.add(RouteSpec::parse_with("/posts/:slug?", Syntax::Express)?, ())?;
+    router.add_with_mode("/static", MatchMode::Prefix, ())?;
+    #[cfg(feature = "regex")]
+    router.add(
+        RouteSpec::parse_with("/users/:id(\\d+)/edit", Syntax::Express)?,
+        (),
+    )?;
+
+    let key = |path: &str, mask: &[&str]| router.best_match(path).unwrap().cache_key(mask);
+    assert_eq!(key("/users/42", &[]), "/users/:id");
+    assert_eq!(key("/users/42", &["id"]), "/users/42");
+    assert_eq!(key("/users/42", &["other", "*"]), "/users/:id");
+    assert_eq!(key("/files/report.pdf", &["ext"]), "/files/:name.pdf");
+    assert_eq!(key("/posts", &[]), "/posts/:slug?");
+    assert_eq!(key("/posts", &["slug"]), "/posts");
+    assert_eq!(key("/posts/hello", &["slug"]), "/posts/hello");
+    assert_eq!(key("/static/css/site.css", &[]), "/static");
+    assert_eq!(key("/static/css/site.css", &["*"]), "/static/css/site.css");
+    #[cfg(feature = "regex")]
+    assert_eq!(key("/users/42/edit", &[]), "/users/:id(\\d+)/edit");
+
+    // different paths for the same route share a key
+    assert_eq!(key("/users/1", &[]), key("/users/2", &[]));
+    Ok(())
+}
+
+#[test]
+fn partitioned_matching() -> Result {
+    let mut router = Router::new();
+    for route in [
+        "/",
+        "/api",
+        "/api/users/:id",
+        "/api/users/new",
+        "/api/users/:id/posts",
+        "/api/users.json",
+        "/api/v1/users/me/settings/:tab",
+        "/api/*",
+        "/api.json",
+        "/admin/:section",
+        "/assets/*",
+        "/:page",
+        "/:page/edit",
+        "/*",
+    ] {
+        router.add(route, route)?;
+    }
+    router.add_with_mode("/api/v2", MatchMode::Prefix, "/api/v2 prefix")?;
+    assert_eq!(router.engine(), Engine::Linear);
+
+    // with every engine, each path matches the same routes, in the same
+    // order, as trying each route in turn
+    for engine in [
+        Engine::Linear,
+        Engine::Partitioned,
+        Engine::Trie,
+        Engine::Hashed,
+    ] {
+        router.pin_engine(engine);
+        assert_eq!(router.engine(), engine);
+        for path in [
+            "/",
+            "/api",
+            "/api/",
+            "//api/users/1",
+            "/api/users/new",
+            "/api/users/1/posts",
+            "/api/users.json",
+            "/api/users//",
+            "/api/v1/users/me/settings/privacy",
+            "/api/v2/users",
+            "/api.json",
+            "/apix",
+            "/admin/settings",
+            "/assets/app.js",
+            "/about/edit",
+            "/nothing/here",
+            "",
+        ] {
+            let matched: Vec<_> = router.matches(path).iter().map(|m| **m).collect();
+            let expected: Vec<_> = router
+                .iter()
+                .filter(|(route, _)| route.matches(path).is_some())
+                .map(|(_, handler)| *handler)
+                .collect();
+            assert_eq!(matched, expected, "{:?} {}", engine, path);
+        }
+    }
+    router.pin_engine(Engine::Partitioned);
+
+    assert_eq!(
+        *router.best_match("/api/users/1").unwrap(),
+        "/api/users/:id"
+    );
+    assert_eq!(*router.best_match("/apix").unwrap(), "/:page");
+
+    // the partitions follow additions and removals
+    let id = router.add("/apix", "/apix")?;
+    assert_eq!(*router.best_match("/apix").unwrap(), "/apix");
+    router.remove(id);
+    assert_eq!(*router.best_match("/apix").unwrap(), "/:page");
+
+    router.set_match_options(MatchOptions::new().case_insensitive(true));
+    assert_eq!(
+        *router.best_match("/API/users/1").unwrap(),
+        "/api/users/:id"
+    );
+    Ok(())
+}
+
+#[test]
+fn incremental_index() -> Result {
+    fn assert_consistent(router: &Router<&str>) {
+        for path in [
+            "/",
+            "/api",
+            "/api/users/1",
+            "/api.json",
+            "/apix",
+            "/about",
+            "/about/edit",
+            "/a/b/c",
+        ] {
+            let matched: Vec<_> = router.matches(path).iter().map(|m| **m).collect();
+            let expected: Vec<_> = router
+                .iter()
+                .filter(|(route, _)| route.matches(path).is_some())
+                .map(|(_, handler)| *handler)
+                .collect();
+            assert_eq!(matched, expected, "{:?} {}", router.engine(), path);
+        }
+    }
+
+    let literals = ["/", "/api", "/api.json", "/about"];
+    let dynamic = ["/api/users/:id", "/api/*", "/:page", "/:page/edit", "/*"];
+    for storage in [Storage::Tree, Storage::Sorted] {
+        for engine in [
+            None,
+            Some(Engine::Partitioned),
+            Some(Engine::Trie),
+            Some(Engine::Hashed),
+        ] {
+            let mut router = Router::new();
+            router.set_storage(storage);
+            if let Some(engine) = engine {
+                router.pin_engine(engine);
+            }
+
+            // the index is patched after it is built by each match
+            for route in literals {
+                router.add(route, route)?;
+                assert_consistent(&router);
+            }
+            let mut ids = vec![];
+            for route in dynamic {
+                ids.push(router.add(route, route)?);
+                assert_consistent(&router);
+            }
+            for index in 0..20 {
+                let route = format!("/resource{}/:id", index);
+                router.add(route.as_str(), "resource")?;
+                assert_consistent(&router);
+            }
+            if engine.is_none() {
+                assert_eq!(router.engine(), Engine::Partitioned);
+            }
+
+            for id in ids {
+                router.remove(id);
+                assert_consistent(&router);
+            }
+            let resources: Vec<_> = router
+                .iter()
+                .filter(|(_, handler)| **handler == "resource")
+                .filter_map(|(route, _)| route.id())
+                .collect();
+            for id in resources {
+                router.remove(id);
+                assert_consistent(&router);
+            }
+            assert_eq!(router.len(), literals.len());
+            if engine.is_none() {
+                assert_eq!(router.engine(), Engine::Hashed);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn trie_matching() -> Result {
+    let mut router = Router::new();
+    router.add("/api/*", "/api/*")?;
+    router.add("/:page", "/:page")?;
+    for resource in 0..70 {
+        router.add(format!("/api/resource{}", resource), "literal")?;
+        router.add(format!("/api/resource{}/:id", resource), "param")?;
+    }
+    // a large table under a single first segment is indexed as a trie
+    assert_eq!(router.engine(), Engine::Trie);
+
+    fn matched(router: &Router<&'static str>, path: &str) -> Vec<&'static str> {
+        router.matches(path).iter().map(|m| **m).collect()
+    }
+    assert_eq!(matched(&router, "/api/resource3/1"), ["param", "/api/*"]);
+    assert_eq!(matched(&router, "/api/resource3"), ["literal", "/api/*"]);
+    assert_eq!(matched(&router, "/api"), ["/api/*", "/:page"]);
+    assert_eq!(matched(&router, "/api/other/1"), ["/api/*"]);
+
+    let id = router.add("/api/resource3/new", "new")?;
+    assert_eq!(
+        matched(&router, "/api/resource3/new"),
+        ["new", "param", "/api/*"]
+    );
+    router.remove(id);
+    assert_eq!(matched(&router, "/api/resource3/new"), ["param", "/api/*"]);
+    assert_eq!(router.engine(), Engine::Trie);
+    Ok(())
+}
+
+#[test]
+fn storage() -> Result {
+    let routes = [
+        "/",
+        "/users/:id",
+        "/users/new",
+        "/users/:id/posts/:post",
+        "/posts/*",
+        "/:page",
+        "/*",
+    ];
+
+    let mut tree = Router::new();
+    let mut sorted = Router::new();
+    sorted.set_storage(Storage::Sorted);
+    for route in routes {
+        tree.add(route, route)?;
+        sorted.add(route, route)?;
+    }
+    assert_eq!(tree.storage(), Storage::Tree);
+    assert_eq!(sorted.storage(), Storage::Sorted);
+
+    fn order(router: &Router<&'static str>) -> Vec<&'static str> {
+        router.iter().map(|(_, handler)| *handler).collect()
+    }
+    assert_eq!(order(&tree), order(&sorted));
+
+    for path in [
+        "/",
+        "/users/new",
+        "/users/1",
+        "/users/1/posts/2",
+        "/posts/a/b",
+        "/x/y",
+    ] {
+        assert_eq!(
+            tree.best_match(path).map(|m| *m),
+            sorted.best_match(path).map(|m| *m)
+        );
+    }
+
+    let index = sorted.lookup("/users/1").unwrap();
+    assert_eq!(sorted.resolve(&index).unwrap().1, &"/users/:id");
+
+    let id = sorted.find_by_source("/users/new").unwrap();
+    assert_eq!(sorted.remove(id), Some("/users/new"));
+    assert_eq!(*sorted.best_match("/users/new").unwrap(), "/users/:id");
+
+    // switching storage keeps the order
+    tree.set_storage(Storage::Sorted);
+    tree.set_storage(Storage::Tree);
+    sorted.set_storage(Storage::Tree);
+    assert_eq!(order(&tree).len(), order(&sorted).len() + 1);
+    assert_eq!(
+        order(&sorted),
+        order(&tree)
+            .into_iter()
+            .filter(|route| *route != "/users/new")
+            .collect::<Vec<_>>()
+    );
+    Ok(())
+}
+
+#[test]
+fn static_router() -> Result {
+    let beta = Flag::new(false);
+    let build = |beta: &Flag| -> std::result::Result<Router<&'static str>, String> {
+        let mut router = Router::new();
+        router.add("/", "home")?;
+        router.add("/about", "about")?;
+        router.add_with_mode("/about", MatchMode::Exact, "exact about")?;
+        router.add("/users/new", "new user")?;
+        router.add("/users/:id", "user")?;
+        router.add_with_mode("/static", MatchMode::PriorityPrefix, "static")?;
+        router.add("/static/logo.png", "shadowed logo")?;
+        router.add("/beta", "beta fallback")?;
+        router.add_conditional("/beta", "beta", beta.clone())?;
+        router.add_weighted("/checkout", [(1, "stable"), (1, "canary")])?;
+        router.add_with_formats("/reports", ["json", "csv"], "reports")?;
+        router.add("/docs/*", "docs")?;
+        router.add("/:page", "page")?;
+        router.set_weight_strategy(|_total| 0);
+        Ok(router)
+    };
+
+    let router = build(&beta)?;
+    let static_router = build(&beta)?.into_static();
+    assert_eq!(static_router.len(), router.len());
+    assert_eq!(static_router.static_len(), 9);
+
+    let paths = [
+        "/",
+        "//",
+        "/about",
+        "/about/",
+        "//about",
+        "/users/new",
+        "/users/1",
+        "/static/logo.png",
+        "/beta",
+        "/checkout",
+        "/reports",
+        "/reports.csv",
+        "/docs/intro",
+        "/anything",
+        "/no/such/route",
+    ];
+
+    let compare = || {
+        for path in paths {
+            assert_eq!(
+                static_router.best_match(path).map(|m| (*m, m.route().id())),
+                router.best_match(path).map(|m| (*m, m.route().id())),
+                "{}",
+                path
+            );
+        }
+    };
+    compare();
+    beta.enable();
+    compare();
+
+    // a larger table of static routes
+    let mut router = Router::new();
+    for index in 0..1000 {
+        router.add(format!("/pages/{}", index), index)?;
+    }
+    let router = router.into_static();
+    assert_eq!(router.static_len(), 1000);
+    for index in 0..1000 {
+        let path = format!("/pages/{}", index);
+        assert_eq!(*router.best_match(&path).unwrap(), index);
+    }
+    assert!(router.best_match("/pages/1000").is_none());
+    assert!(router.best_match("/pages").is_none());
+    Ok(())
+}
+
+#[test]
+fn standalone_route() -> Result {
+    let route: Route = "/users/:id/*".parse()?;
+    assert!(route.is_match("/users/1/posts"));
+    assert!(!route.is_match("/posts/1"));
+    let captures = route.captures("/users/1/posts/2").unwrap();
+    assert_eq!(captures.get("id"), Some("1"));
+    assert_eq!(captures.wildcard(), Some("posts/2"));
+
+    // matching with a route is the same as with a router of one route
+    let options = MatchOptions::new().decode(true).case_insensitive(true);
+    let route = Route::new("/Files/:name", "file")?.with_match_options(options);
+    let mut router = Router::new();
+    router.add("/Files/:name", "file")?;
+    router.set_match_options(options);
+    for path in [
+        "/files/a%20b",
+        "/FILES/a/",
+        "/files",
+        "/files/%ff",
+        "/other/a",
+    ] {
+        let from_route = route.matches(path);
+        let from_router = router.best_match(path);
+        assert_eq!(from_route.is_some(), from_router.is_some(), "{}", path);
+        if let (Some(from_route), Some(from_router)) = (from_route, from_router) {
+            assert_eq!(
+                from_route.captures().get("name"),
+                from_router.captures().get("name")
+            );
+            assert_eq!(*from_route, *from_router);
+        }
+    }
+
+    let (spec, handler) = route.into_parts();
+    assert_eq!(spec.to_string(), "/Files/:name");
+    assert_eq!(handler, "file");
+    Ok(())
+}
+
+#[test]
+fn match_iter_utilities() -> Result {
+    let mut router = Router::new();
+    router.add("/users/:id", "user")?;
+    router.add("/users/new", "new user")?;
+    router.add("/users/*", "users")?;
+    router.add("*", "fallback")?;
+
+    for path in ["/users/new", "/users/1", "/users/1/posts", "/"] {
+        assert_eq!(
+            router.match_iter(path).best().map(|m| *m),
+            router.best_match(path).map(|m| *m)
+        );
+
+        let filtered: Vec<_> = router
+            .match_iter(path)
+            .filter_routes(|route| route.segments().len() > 1)
+            .map(|m| *m)
+            .collect();
+        let expected: Vec<_> = router
+            .matches(path)
+            .into_iter()
+            .filter(|m| m.route().segments().len() > 1)
+            .map(|m| *m)
+            .collect();
+        assert_eq!(filtered, expected);
+
+        let without_wildcards: Vec<_> = router
+            .match_iter_where(path, |route| !route.has_wildcard())
+            .map(|m| *m)
+            .collect();
+        let expected: Vec<_> = router
+            .matches(path)
+            .into_iter()
+            .filter(|m| !m.route().has_wildcard())
+            .map(|m| *m)
+            .collect();
+        assert_eq!(without_wildcards, expected);
+    }
+
+    // routes that are filtered out are never applied to the path
+    let checked = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let counter = checked.clone();
+    router.add_conditional("/users/:id/*", "conditional", move || {
+        counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        true
+    })?;
+    assert_eq!(router.match_iter("/users/1/posts").count(), 3);
+    assert_eq!(checked.load(std::sync::atomic::Ordering::Relaxed), 1);
+    let matched: Vec<_> = router
+        .match_iter_where("/users/1/posts", |route| !route.has_wildcard())
+        .collect();
+    assert!(matched.is_empty());
+    assert_eq!(checked.load(std::sync::atomic::Ordering::Relaxed), 1);
+
+    Ok(())
+}
+
+#[test]
+fn segment_precedence() {
+    let segments = [
+        Segment::Slash,
+        Segment::Dot,
+        Segment::Exact("a".into()),
+        Segment::Glob("*.a".into()),
+        Segment::Param("a".into()),
+        Segment::OptionalParam("a".into()),
+        Segment::OneOrMore("a".into()),
+        Segment::ZeroOrMore("a".into()),
+        Segment::Wildcard,
+    ];
+
+    for a in &segments {
+        for b in &segments {
+            assert_eq!(a.cmp(b), a.precedence().cmp(&b.precedence()));
+            assert_eq!(a.cmp(b), b.cmp(a).reverse(), "{:?} {:?}", a, b);
+            for c in &segments {
+                if a <= b && b <= c {
+                    assert!(a <= c, "{:?} {:?} {:?}", a, b, c);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn route_spec_total_order() -> Result {
+    use std::cmp::Ordering;
+
+    // a small deterministic generator, so failures are reproducible
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut next = |bound: usize| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state as usize % bound
+    };
+
+    let pieces = [
+        "/", "/", ".", "-", "a", "b", ":p", ":q?", ":r+", ":s*", "*", "*.css",
+    ];
+    let modes = [
+        MatchMode::Exact,
+        MatchMode::Pattern,
+        MatchMode::Prefix,
+        MatchMode::PriorityPrefix,
+    ];
+
+    let mut router = Router::new();
+    let mut specs = vec![];
+    while specs.len() < 120 {
+        let len = next(6);
+        let source: String = (0..len).map(|_| pieces[next(pieces.len())]).collect();
+        let syntax = if source.contains("*.") {
+            Syntax::Glob
+        } else {
+            Syntax::Express
+        };
+        let spec = match RouteSpec::parse_with(&source, syntax) {
+            Ok(spec) => spec.with_mode(modes[next(modes.len())]),
+            Err(_) => continue,
+        };
+        router.add(spec.clone(), ())?;
+        specs.push(spec);
+    }
+
+    // routes without ids, as parsed, and with ids, as in a router
+    let with_ids: Vec<_> = router.iter().map(|(route, _)| route.clone()).collect();
+    for specs in [specs, with_ids] {
+        for a in &specs {
+            for b in &specs {
+                assert_eq!(a.cmp(b), b.cmp(a).reverse(), "{} {}", a, b);
+                assert_eq!(a.cmp(b) == Ordering::Equal, a == b, "{} {}", a, b);
+                for c in &specs {
+                    if a <= b && b <= c {
+                        assert!(a <= c, "{} <= {} <= {}", a, b, c);
+                    }
+                }
+            }
+        }
+
+        let mut sorted = specs.clone();
+        sorted.sort();
+        assert!(sorted.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    // specs without ids are only equal when they are the same spec
+    fn distinct(mut specs: Vec<RouteSpec>) -> usize {
+        specs.sort();
+        specs.dedup_by(|a, b| a.cmp(&b) == Ordering::Equal);
+        specs.len()
+    }
+
+    let specs = ["/a", "/b", "/c", "/b", "/:a", "/:b", "/a.b", "/a./b"]
+        .iter()
+        .map(|source| source.parse())
+        .collect::<std::result::Result<_, _>>()?;
+    assert_eq!(distinct(specs), 7);
+    let specs = vec![
+        "/a".parse::<RouteSpec>()?,
+        "/a".parse::<RouteSpec>()?.with_mode(MatchMode::Exact),
+        RouteSpec::from_segments(vec![
+            Segment::Exact("a.".into()),
+            Segment::Exact("b".into()),
+        ])?,
+        RouteSpec::from(vec![
+            Segment::Exact("a.".into()),
+            Segment::Exact("b".into()),
+        ]),
+        RouteSpec::from(vec![Segment::Exact("a.b".into())]),
+    ];
+    assert_eq!(distinct(specs), 4);
+    Ok(())
+}
+
+#[test]
+fn borrowed_string_paths() -> Result {
+    use std::borrow::Cow;
+    let mut router = Router::new();
+    router.add("/users/:id", "user")?;
+
+    let owned = String::from("/users/1");
+    let cow: Cow<str> = Cow::Owned(String::from("/users/2"));
+    let boxed: Box<str> = "/users/3".into();
+    assert_eq!(
+        router.best_match(&owned).unwrap().captures().get("id"),
+        Some("1")
+    );
+    assert_eq!(router.matches(&cow).len(), 1);
+    assert_eq!(router.match_iter(&boxed).count(), 1);
+    assert!(router.best_match("/users").is_none());
+    Ok(())
+}
+
+#[test]
+fn custom_paths() -> Result {
+    struct Target<'a>(&'a str);
+
+    impl AsPath for Target<'_> {
+        fn raw(&self) -> &str {
+            self.0
+        }
+
+        fn trimmed(&self) -> &str {
+            self.0.split(['?', '#']).next().unwrap_or_default()
+        }
+    }
+
+    let mut router = Router::new();
+    router.add("/users/:id", "user")?;
+    router.add("/users/new", "new user")?;
+
+    let target = Target("/users/1?tab=posts#top");
+    assert_eq!(target.raw(), "/users/1?tab=posts#top");
+    let m = router.best_match(&target).unwrap();
+    assert_eq!(m.captures().get("id"), Some("1"));
+    assert_eq!(router.matches(&Target("/users/new?x")).len(), 2);
+    assert_eq!(router.match_iter(&&target).count(), 1);
+
+    let normalized = normalize::NormalizedPath::new("/users/./new").unwrap();
+    assert_eq!(*router.best_match(&normalized).unwrap(), "new user");
+
+    let mut methods = MethodRouter::new();
+    methods.add("GET", "/users/:id", "show")?;
+    assert_eq!(*methods.best_match("GET", &target).unwrap(), "show");
+    let router = StaticRouter::from(router);
+    assert_eq!(*router.best_match(&target).unwrap(), "user");
+    Ok(())
+}
+
+#[test]
+fn mapping_handlers() -> Result {
+    let mut router = Router::new();
+    let about = router.add("/about", "about")?;
+    router.add("/users/:id", "user")?;
+    router.add("*", "fallback")?;
+    router.set_match_options(MatchOptions::new().case_insensitive(true));
+
+    let mut seen = vec![];
+    let lengths = router.try_map(|route, handler| {
+        seen.push(route.to_string());
+        Ok::<_, String>(handler.len())
+    })?;
+    assert_eq!(seen, ["/about", "/users/:id", "/*"]);
+    assert_eq!(*lengths.best_match("/ABOUT").unwrap(), 5);
+    assert_eq!(
+        lengths.best_match("/about").unwrap().route().id(),
+        Some(about)
+    );
+
+    let numbered = lengths.zip_meta(["first", "second"]);
+    assert_eq!(numbered.len(), 2);
+    assert_eq!(*numbered.best_match("/users/1").unwrap(), (4, "second"));
+    assert!(numbered.best_match("/anything/else").is_none());
+
+    let result = numbered.try_map(|route, (_, name)| match name {
+        "second" => Err(route.to_string()),
+        _ => Ok(()),
+    });
+    assert_eq!(result.unwrap_err(), "/users/:id");
+    Ok(())
+}
+
+#[test]
+fn stacks() -> Result {
+    let mut redirects = Router::new();
+    redirects.add("/old/*", "redirect")?;
+    let mut app = Router::new();
+    app.add("/old/exact", "app old")?;
+    app.add("/users/:id", "user")?;
+    let mut fallback = Router::new();
+    fallback.add("*", "fallback")?;
+
+    let mut stack: Stack<_> = vec![redirects, app].into_iter().collect();
+    assert_eq!(stack.push(fallback), 2);
+    assert_eq!(stack.len(), 3);
+
+    // earlier layers win regardless of precedence
+    let (layer, m) = stack.best_match("/old/exact").unwrap();
+    assert_eq!((layer, *m), (0, "redirect"));
+    assert_eq!(m.captures().wildcard(), Some("exact"));
+
+    let (layer, m) = stack.best_match("/users/1").unwrap();
+    assert_eq!((layer, *m), (1, "user"));
+    assert_eq!(m.captures().get("id"), Some("1"));
+
+    let layers: Vec<_> = stack
+        .matches("/old/exact")
+        .into_iter()
+        .map(|(layer, m)| (layer, *m))
+        .collect();
+    assert_eq!(layers, [(0, "redirect"), (1, "app old"), (2, "fallback")]);
+
+    stack
+        .layer_mut(2)
+        .unwrap()
+        .add("/users/:id/posts", "posts")?;
+    assert_eq!(stack.best_match("/users/1/posts").unwrap().0, 2);
+    assert!(Stack::<()>::new().best_match("/").is_none());
+    Ok(())
+}
+
+#[test]
+fn router_views() -> Result {
+    let mut redirects = Router::new();
+    redirects.add("/old/*", "redirect")?;
+    let mut app = Router::new();
+    app.add("/old/exact", "app old")?;
+    app.add("/users/:id", "user")?;
+    app.add("/users/:id", "duplicate")?;
+    app.reserve_prefix("/_plugin")?;
+    let mut plugin = Router::new();
+    plugin.add("/users/:user_id", "plugin user")?;
+    plugin.add("*", "fallback")?;
+    plugin.deny("/secret")?;
+
+    let view = RouterView::union([&redirects, &app, &plugin]);
+    assert_eq!(view.len(), 3);
+    assert!(view.router(1).is_some_and(|router| router.len() == 3));
+    assert!(view.router(3).is_none());
+
+    // the route of highest precedence wins, regardless of its router
+    let (index, m) = view.best_match("/old/exact").unwrap();
+    assert_eq!((index, *m), (1, "app old"));
+    let (index, m) = view.best_match("/old/other").unwrap();
+    assert_eq!((index, *m), (0, "redirect"));
+    assert_eq!(m.captures().wildcard(), Some("other"));
+
+    // routes of equal precedence are tried in the order of their routers
+    let (index, m) = view.best_match("/users/1").unwrap();
+    assert_eq!((index, *m), (1, "user"));
+    let matches: Vec<_> = view
+        .matches("/users/1")
+        .into_iter()
+        .map(|(index, m)| (index, *m))
+        .collect();
+    assert_eq!(
+        matches,
+        [
+            (1, "user"),
+            (1, "duplicate"),
+            (2, "plugin user"),
+            (2, "fallback")
+        ]
+    );
+
+    // each router applies its own deny routes and reserved prefixes
+    assert!(view.best_match("/secret").is_none());
+    assert_eq!(*view.best_match("/_plugin/a").unwrap().1, "fallback");
+    assert!(RouterView::<()>::union([]).best_match("/").is_none());
+    Ok(())
+}
+
+#[test]
+fn chained_captures() -> Result {
+    let mut outer = Router::new();
+    outer.add("/tenants/:tenant/:id/*", "tenant")?;
+    let mut inner = Router::new();
+    inner.add("/users/:id", "user")?;
+    inner.add("/files/*", "files")?;
+
+    let outer_match = outer.best_match("/tenants/acme/7/users/1").unwrap();
+    let outer_captures = outer_match.captures();
+    let inner_match = inner
+        .best_match(outer_captures.wildcard().unwrap())
+        .unwrap();
+
+    let collected = |captures: Captures| {
+        let params: Vec<_> = captures
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect();
+        (params, captures.wildcard().map(String::from))
+    };
+
+    assert_eq!(
+        collected(outer_match.chain(&inner_match)),
+        (vec!["tenant=acme".into(), "id=7".into()], None)
+    );
+    assert_eq!(
+        collected(outer_match.chain_with(&inner_match, Collision::KeepLast)),
+        (vec!["tenant=acme".into(), "id=1".into()], None)
+    );
+    assert_eq!(
+        collected(outer_match.chain_with(&inner_match, Collision::KeepBoth)),
+        (
+            vec!["tenant=acme".into(), "id=7".into(), "id=1".into()],
+            None
+        )
+    );
+
+    // the inner wildcard replaces the outer one
+    let outer_match = outer.best_match("/tenants/acme/7/files/a/b").unwrap();
+    let outer_captures = outer_match.captures();
+    let inner_match = inner
+        .best_match(outer_captures.wildcard().unwrap())
+        .unwrap();
+    let captures = outer_match.chain(&inner_match);
+    assert_eq!(captures.wildcard(), Some("a/b"));
+    assert_eq!(captures.get("tenant"), Some("acme"));
+    Ok(())
+}
+
+#[test]
+fn relative_routes() -> Result {
+    let mut router = Router::new();
+    router.add("/users/:user/files/*", "files")?;
+    router.add("/", "home")?;
+    let resolve = |path: &str, relative: &str| {
+        let base = router.best_match(path).unwrap();
+        RouteSpec::resolve_relative(&base, relative)
+    };
+
+    assert_eq!(resolve("/users/1/files/a/b", "c")?, "/users/1/files/a/c");
+    assert_eq!(resolve("/users/1/files/a/b", "./")?, "/users/1/files/a");
+    assert_eq!(
+        resolve("/users/1/files/a/b/", "../:user.json")?,
+        "/users/1/files/1.json"
+    );
+    assert_eq!(resolve("/users/1/files/a/b", "/archive/*")?, "/archive/a/b");
+    assert_eq!(resolve("/users/1/files", "../..")?, "/");
+    assert_eq!(resolve("/", "about")?, "/about");
+    assert!(resolve("/", "../about").is_err());
+    assert!(resolve("/", "./:user").is_err());
+    Ok(())
+}
+
+#[test]
+fn exclusions() -> Result {
+    let mut router = Router::new();
+    router.add("*", "fallback")?;
+    router.add("/assets/public/readme", "readme")?;
+    router.add_excluding("/assets/*", ["/assets/private/*"], "assets")?;
+    router.add_excluding("/about", ["/about"], "never")?;
+
+    for engine in [
+        Engine::Linear,
+        Engine::Partitioned,
+        Engine::Trie,
+        Engine::Hashed,
+    ] {
+        router.pin_engine(engine);
+        assert_eq!(*router.best_match("/assets/app.js").unwrap(), "assets");
+        assert_eq!(*router.best_match("/assets/private").unwrap(), "fallback");
+        assert_eq!(
+            *router.best_match("/assets/private/a/b").unwrap(),
+            "fallback"
+        );
+        assert_eq!(*router.best_match("/assets/privately").unwrap(), "assets");
+        assert_eq!(*router.best_match("/about").unwrap(), "fallback");
+        assert_eq!(router.matches("/assets/private/key").len(), 1);
+    }
+
+    let route = router
+        .iter()
+        .find(|(_, handler)| **handler == "assets")
+        .unwrap()
+        .0;
+    assert_eq!(route.exclusions().len(), 1);
+    assert!(route.matches("/assets/private/key").is_none());
+
+    let router = router.into_static();
+    assert_eq!(*router.best_match("/about").unwrap(), "fallback");
+    assert_eq!(
+        *router.best_match("/assets/public/readme").unwrap(),
+        "readme"
+    );
+    assert_eq!(
+        *router.best_match("/assets/private/key").unwrap(),
+        "fallback"
+    );
+    Ok(())
+}
+
+#[test]
+fn deny_routes() -> Result {
+    let mut router = Router::new();
+    router.add("*", "fallback")?;
+    router.add("/admin/login", "login")?;
+    router.add("/admin/*", "admin")?;
+    router.add("/posts/:id", "post")?;
+    let denied = router.deny("/admin/*")?;
+    assert_eq!(router.len(), 4);
+    assert_eq!(router.denied().count(), 1);
+    assert_eq!(router.deny_mode(), DenyMode::Precedence);
+
+    for engine in [
+        Engine::Linear,
+        Engine::Partitioned,
+        Engine::Trie,
+        Engine::Hashed,
+    ] {
+        router.pin_engine(engine);
+        assert_eq!(*router.best_match("/posts/1").unwrap(), "post");
+        assert_eq!(*router.best_match("/admin/login").unwrap(), "login");
+        assert!(router.best_match("/admin/users").is_none());
+        assert!(router.best_match("/admin").is_none());
+        assert_eq!(router.matches("/admin/login").len(), 1);
+        assert!(router.matches("/admin/users").is_empty());
+    }
+
+    router.set_deny_mode(DenyMode::Always);
+    assert!(router.best_match("/admin/login").is_none());
+    assert_eq!(*router.best_match("/posts/1").unwrap(), "post");
+    assert!(router.matches("/admin/login").is_empty());
+
+    let id = router.add("/other", "other")?;
+    assert_ne!(id, denied);
+
+    router.set_deny_mode(DenyMode::Precedence);
+    let router = router.into_static();
+    assert_eq!(*router.best_match("/admin/login").unwrap(), "login");
+    assert!(router.best_match("/admin/users").is_none());
+    assert_eq!(*router.best_match("/other").unwrap(), "other");
+    Ok(())
+}
+
+#[test]
+fn removing_denies() -> Result {
+    use std::sync::{Arc, Mutex};
+
+    let mut router = Router::new();
+    router.add("/admin/*", "admin")?;
+    router.add("/posts", "posts")?;
+    let events = Arc::new(Mutex::new(vec![]));
+    let log = Arc::clone(&events);
+    router.on_change(move |event| log.lock().unwrap().push(event.to_string()));
+
+    let denied = router.deny("/admin/*")?;
+    assert!(router.best_match("/admin/users").is_none());
+    let index = router.lookup("/posts").unwrap();
+    assert_eq!(router.remove(denied), None);
+    assert_eq!(router.denied().count(), 0);
+    // removing the deny changes the routes, so earlier lookups are stale
+    assert!(router.resolve(&index).is_none());
+    assert_eq!(*router.best_match("/admin/users").unwrap(), "admin");
+    assert_eq!(router.remove(denied), None);
+    assert_eq!(
+        *events.lock().unwrap(),
+        ["added /admin/*", "removed /admin/*"]
+    );
+
+    // a deny that does not parse does not use up an id
+    assert!(router.deny("/:").is_err());
+    let id = router.add("/other", "other")?;
+    assert_eq!(id.get(), denied.get() + 1);
+    assert_eq!(router.len(), 3);
+    Ok(())
+}
+
+#[test]
+fn reserved_prefixes() -> Result {
+    let mut router = Router::new();
+    router.add("*", "fallback")?;
+    router.add("/:page", "page")?;
+    router.add("/.well-known/acme-challenge/:token", "acme")?;
+    router.add("/_internal/debug/*", "debug")?;
+    router.reserve_prefix("/.well-known")?;
+    router.reserve_prefix("/_internal")?;
+    router.reserve_prefix("/_internal/health")?;
+    router.reserve_prefix("/.well-known")?;
+    assert_eq!(
+        router
+            .reserved_prefixes()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+        ["/_internal/health", "/.well-known", "/_internal"]
+    );
+
+    for engine in [
+        Engine::Linear,
+        Engine::Partitioned,
+        Engine::Trie,
+        Engine::Hashed,
+    ] {
+        router.pin_engine(engine);
+        assert_eq!(*router.best_match("/about").unwrap(), "page");
+        assert_eq!(*router.best_match("/a/b").unwrap(), "fallback");
+        assert_eq!(
+            *router.best_match("/.well-known/acme-challenge/x").unwrap(),
+            "acme"
+        );
+        assert!(router.best_match("/.well-known/security.txt").is_none());
+        assert!(router.best_match("/.well-known").is_none());
+        assert!(router.matches("/.well-known/security.txt").is_empty());
+        assert_eq!(*router.best_match("/_internal/debug/a").unwrap(), "debug");
+        assert!(router.best_match("/_internal/health").is_none());
+        assert_eq!(*router.best_match("/.well-knowns").unwrap(), "page");
+    }
+
+    assert_eq!(
+        router.try_best_match("/nope/x").ok().map(|m| *m),
+        Some("fallback")
+    );
+    match router.try_best_match("/_internal/health/ready") {
+        Err(Unrouted::Reserved(prefix)) => assert_eq!(prefix.to_string(), "/_internal/health"),
+        _ => panic!("expected a reserved path"),
+    }
+    match router.try_best_match("/.well-known/openid-configuration") {
+        Err(Unrouted::Reserved(prefix)) => assert_eq!(prefix.to_string(), "/.well-known"),
+        _ => panic!("expected a reserved path"),
+    }
+
+    let mut empty: Router<()> = Router::new();
+    assert_eq!(empty.try_best_match("/").unwrap_err(), Unrouted::NotFound);
+    let before = empty.fingerprint();
+    empty.reserve_prefix("/.well-known")?;
+    assert_ne!(empty.fingerprint(), before);
+
+    let router = router.into_static();
+    assert!(router.best_match("/.well-known/security.txt").is_none());
+    assert_eq!(
+        *router.best_match("/.well-known/acme-challenge/x").unwrap(),
+        "acme"
+    );
+    Ok(())
+}
+
+#[test]
+fn shared_handlers() -> Result {
+    use std::sync::Arc;
+
+    #[derive(Debug)]
+    struct Pool(&'static str);
+
+    let pool = Arc::new(Pool("primary"));
+    let mut router = Router::new();
+    let users = router.add_shared("/users/:id", Arc::clone(&pool))?;
+    router.add_shared("/posts/:id", Arc::clone(&pool))?;
+    router.add_shared("/health", Arc::new(Pool("none")))?;
+    assert_eq!(Arc::strong_count(&pool), 3);
+
+    let snapshot = router.snapshot();
+    assert_eq!(Arc::strong_count(&pool), 5);
+
+    let shared = snapshot.best_match("/posts/1").unwrap().shared();
+    assert!(Arc::ptr_eq(&shared, &pool));
+    assert_eq!(shared.0, "primary");
+    assert_eq!(snapshot.best_match("/health").unwrap().shared().0, "none");
+
+    // the shared handler outlives the router
+    drop(snapshot);
+    router.remove(users);
+    drop(router);
+    assert_eq!(Arc::strong_count(&pool), 2);
+    assert_eq!(shared.0, "primary");
+    Ok(())
+}
+
+#[test]
+fn erased_routers() -> Result {
+    use std::{any::TypeId, collections::BTreeMap};
+
+    let mut pages = Router::new();
+    pages.add("/", "home")?;
+    pages.add("/posts/:slug", "post")?;
+    let mut api = Router::new();
+    api.add("/users/:id", 1_u32)?;
+    api.deny("/users/0")?;
+    let mut assets = Router::new();
+    assets.add("/*", String::from("assets"))?;
+
+    let mut registry: BTreeMap<&str, Box<dyn ErasedRouter>> = BTreeMap::new();
+    registry.insert("pages", Box::new(pages));
+    registry.insert("api", Box::new(api.freeze()));
+    registry.insert("assets", Box::new(assets));
+    assert_eq!(registry["api"].handler_type(), TypeId::of::<u32>());
+    assert_eq!(registry["pages"].len(), 2);
+    assert!(!registry["assets"].is_empty());
+
+    let m = registry["pages"]
+        .best_match_erased(&"/posts/hello")
+        .unwrap();
+    assert_eq!(m.downcast_handler::<&str>(), Some(&"post"));
+    assert!(m.downcast_handler::<u32>().is_none());
+    assert_eq!(m.route().to_string(), "/posts/:slug");
+    assert_eq!(m.path(), "/posts/hello");
+    assert_eq!(m.captures().get("slug"), Some("hello"));
+
+    let m = {
+        let path = String::from("/users/7");
+        registry["api"]
+            .best_match_erased(&path)
+            .unwrap()
+            .into_owned()
+    };
+    assert_eq!(m.handler().downcast_ref::<u32>(), Some(&1));
+    assert_eq!(m.captures().get("id"), Some("7"));
+    assert!(registry["api"].best_match_erased(&"/users/0").is_none());
+
+    let m = registry["assets"]
+        .best_match_erased(&"/css/site.css")
+        .unwrap();
+    assert_eq!(
+        m.downcast_handler::<String>().map(String::as_str),
+        Some("assets")
+    );
+    assert_eq!(
+        format!("{:?}", m),
+        r#"ErasedMatch { route: /*, path: "/css/site.css", captures: {"*": "css/site.css"} }"#
+    );
+    Ok(())
+}
+
+#[test]
+fn route_configs() -> Result {
+    use std::time::Duration;
+
+    let uploads = RouteConfig::new()
+        .with_timeout(Duration::from_secs(60))
+        .with_max_body_bytes(10 << 20)
+        .with_rate_class("uploads");
+    let mut router = Router::new();
+    router.add_with_config("/uploads/:name", uploads.clone(), "upload")?;
+    router.add_with_config("/uploads/*", RouteConfig::new(), "uploads")?;
+    router.add("/users/:id", "user")?;
+
+    let m = router.best_match("/uploads/a.png").unwrap();
+    assert_eq!(*m, "upload");
+    assert_eq!(m.config(), Some(&uploads));
+    assert_eq!(m.route().config(), Some(&uploads));
+    let snapshot = router.snapshot();
+    let config = snapshot.best_match("/uploads/a").unwrap().config();
+    assert_eq!(config.and_then(RouteConfig::timeout), uploads.timeout());
+
+    let config = router.best_match("/uploads/a/b").unwrap().config().unwrap();
+    assert_eq!(config, &RouteConfig::default());
+    assert_eq!(config.timeout(), None);
+    assert_eq!(config.max_body_bytes(), None);
+    assert_eq!(config.rate_class(), None);
+    assert!(router.best_match("/users/1").unwrap().config().is_none());
+
+    // configs do not affect matching or the fingerprint
+    let mut plain = Router::new();
+    plain.add("/uploads/:name", "upload")?;
+    plain.add("/uploads/*", "uploads")?;
+    plain.add("/users/:id", "user")?;
+    assert_eq!(plain.fingerprint(), router.fingerprint());
+    Ok(())
+}
+
+#[test]
+fn route_decisions() -> Result {
+    struct Target<'a>(&'a str);
+
+    impl AsPath for Target<'_> {
+        fn raw(&self) -> &str {
+            self.0
+        }
+
+        fn trimmed(&self) -> &str {
+            self.0.split('?').next().unwrap_or_default()
+        }
+    }
+
+    let mut router = Router::new();
+    router.add("/users/:id", "user")?;
+    router.add("/users/:id/Posts", "posts")?;
+    router.add("/users/:id/posts/:post", "post")?;
+    router.add_with_mode("/docs", MatchMode::Exact, "docs")?;
+    router.set_match_options(MatchOptions::new().strict_trailing_slash(true));
+
+    let decision = router.route_decision("/users/1");
+    assert_eq!(**decision.matched().unwrap(), "user");
+    assert!(decision.redirect_to().is_none());
+
+    assert!(matches!(
+        router.route_decision(&Target("/docs/?page=2")),
+        RouteDecision::WouldMatchWithSlash(location) if location == "/docs?page=2"
+    ));
+    assert!(matches!(
+        router.route_decision("/users/1//"),
+        RouteDecision::WouldMatchWithSlash(location) if location == "/users/1"
+    ));
+    assert!(matches!(
+        router.route_decision(&Target("/USERS/1/posts?page=2")),
+        RouteDecision::WouldMatchOtherCase(location) if location == "/users/1/Posts?page=2"
+    ));
+
+    let nearest = |path| match router.route_decision(path) {
+        RouteDecision::NoMatch { nearest } => {
+            nearest.iter().map(ToString::to_string).collect::<Vec<_>>()
+        }
+        decision => panic!("expected no match for {}, got {:?}", path, decision),
+    };
+    assert_eq!(
+        nearest("/users/1/comments"),
+        ["/users/:id", "/users/:id/Posts", "/users/:id/posts/:post"]
+    );
+    assert_eq!(nearest("/users/1/posts/2/edit"), ["/users/:id/posts/:post"]);
+    assert!(nearest("/about").is_empty());
+    assert_eq!(
+        format!("{:?}", router.route_decision("/docs/x")),
+        r#"NoMatch { nearest: ["/docs"] }"#
+    );
+
+    router.set_match_options(MatchOptions::new().case_insensitive(true));
+    assert_eq!(
+        **router.route_decision("/USERS/1/posts/").matched().unwrap(),
+        "posts"
+    );
+
+    // redirects never leave the host
+    let mut router = Router::new();
+    router.add("/:page", "page")?;
+    router.add("/Files/*", "files")?;
+    router.set_match_options(MatchOptions::new().strict_trailing_slash(true));
+    for (path, location) in [
+        ("//evil.com/", Some("/evil.com")),
+        ("///evil.com/", Some("/evil.com")),
+        ("/\\evil.com/", None),
+        ("//files//evil.com", Some("/Files//evil.com")),
+    ] {
+        assert_eq!(
+            router.route_decision(path).redirect_to(),
+            location,
+            "{}",
+            path
+        );
+    }
+
+    // redirects keep captures encoded, and never contain control characters
+    let mut router = Router::new();
+    router.add("/Users/:id", "user")?;
+    router.set_match_options(MatchOptions::new().decode(true));
+    for (path, location) in [
+        (
+            "/USERS/x%0d%0aSet-Cookie:%20a=b",
+            Some("/Users/x%0d%0aSet-Cookie:%20a=b"),
+        ),
+        ("/USERS/a%3Fadmin=1", Some("/Users/a%3Fadmin=1")),
+        ("/USERS/x\r\nSet-Cookie: a=b", None),
+    ] {
+        assert_eq!(
+            router.route_decision(path).redirect_to(),
+            location,
+            "{}",
+            path
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn resources() -> Result {
+    let mut router = MethodRouter::new();
+    router.add("GET", "/posts/new", "new post")?;
+    router.add_resource(
+        "/api/posts/",
+        Resource::new()
+            .with_index("list posts")
+            .with_create("create post")
+            .with_show("show post")
+            .with_update("update post")
+            .with_delete("delete post")
+            .with_nested(
+                "replies",
+                Resource::new()
+                    .with_index("list replies")
+                    .with_show("show reply")
+                    .with_nested("likes", Resource::new().with_create("like")),
+            ),
+    )?;
+    router.add_resource("/categories", Resource::new().with_show("show category"))?;
+
+    let table: Vec<String> = router
+        .iter()
+        .filter_map(|(method, route, _)| Some(format!("{} {} {}", method, route, route.name()?)))
+        .collect();
+    assert_eq!(
+        table,
+        [
+            "DELETE /api/posts/:id posts.delete",
+            "GET /api/posts posts.index",
+            "GET /api/posts/:id posts.show",
+            "GET /api/posts/:post_id/replies posts.replies.index",
+            "GET /api/posts/:post_id/replies/:id posts.replies.show",
+            "GET /categories/:id categories.show",
+            "PATCH /api/posts/:id posts.update",
+            "POST /api/posts posts.create",
+            "POST /api/posts/:post_id/replies/:reply_id/likes posts.replies.likes.create",
+            "PUT /api/posts/:id posts.update",
+        ]
+    );
+
+    assert_eq!(
+        *router.best_match("PUT", "/api/posts/1").unwrap(),
+        "update post"
+    );
+    let m = router
+        .best_match("POST", "/api/posts/1/replies/2/likes")
+        .unwrap();
+    assert_eq!(m.captures().get("post_id"), Some("1"));
+    assert_eq!(m.captures().get("reply_id"), Some("2"));
+    assert_eq!(*router.best_match("GET", "/posts/new").unwrap(), "new post");
+    assert!(router
+        .best_match("DELETE", "/api/posts/1/replies/2")
+        .is_none());
+    assert!(router.best_match("GET", "/categories").is_none());
+
+    assert!(router
+        .add_resource("/", Resource::new().with_index(""))
+        .is_err());
+    assert!(router
+        .add_resource("/posts/:id", Resource::new().with_index(""))
+        .is_err());
+    assert!(router
+        .add_resource(
+            "/posts",
+            Resource::new().with_nested("a/b", Resource::new())
+        )
+        .is_err());
+
+    // an invalid nested resource leaves the router unchanged
+    let routes = router.len();
+    assert!(router
+        .add_resource(
+            "/comments",
+            Resource::new()
+                .with_index("list comments")
+                .with_nested("likes", Resource::new().with_nested("", Resource::new())),
+        )
+        .is_err());
+    assert_eq!(router.len(), routes);
+    assert!(router.best_match("GET", "/comments").is_none());
+    Ok(())
+}
+
+#[test]
+fn frozen_snapshots() -> Result {
+    let mut router = Router::new();
+    router.add("/", "home")?;
+    router.add("/users/:id", "user")?;
+    router.set_match_options(MatchOptions::new().case_insensitive(true));
+    router.deny("/users/banned")?;
+
+    let snapshot = router.snapshot();
+    router.add("/about", "about")?;
+    assert!(snapshot.best_match("/about").is_none());
+    assert_eq!(snapshot.len(), 2);
+    assert_eq!(snapshot.match_options(), router.match_options());
+    assert!(snapshot.best_match("/users/banned").is_none());
+
+    let shared = snapshot.clone();
+    assert!(shared.ptr_eq(&snapshot));
+
+    let updated = snapshot.try_update(|router| {
+        router.add("/users/new", "new user")?;
+        router.add("/posts/:id", "post").map(|_| ())
+    })?;
+    assert!(!updated.ptr_eq(&snapshot));
+    assert_eq!(updated.len(), 4);
+    assert_eq!(*updated.best_match("/USERS/new").unwrap(), "new user");
+    assert_eq!(*snapshot.best_match("/users/new").unwrap(), "user");
+
+    let failed = snapshot.try_update(|router| {
+        router.add("/about", "about")?;
+        Err(String::from("rolled back"))
+    });
+    assert_eq!(failed.unwrap_err(), "rolled back");
+    assert!(snapshot.best_match("/about").is_none());
+
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            let updated = updated.clone();
+            std::thread::spawn(move || *updated.best_match("/posts/1").unwrap())
+        })
+        .collect();
+    for thread in threads {
+        assert_eq!(thread.join().unwrap(), "post");
+    }
+
+    drop(shared);
+    let mut router = snapshot.into_router();
+    router.add("/about", "about")?;
+    assert_eq!(router.len(), 3);
+    Ok(())
+}
+
+#[test]
+fn root_policy() -> Result {
+    fn best(router: &Router<&'static str>, path: &str) -> Option<&'static str> {
+        router.best_match(path).map(|m| *m)
+    }
+
+    let mut router = Router::new();
+    router.add("/:param", "param")?;
+    router.add("*", "wildcard")?;
+
+    for engine in [
+        Engine::Linear,
+        Engine::Partitioned,
+        Engine::Trie,
+        Engine::Hashed,
+    ] {
+        router.pin_engine(engine);
+        router.set_match_options(MatchOptions::new());
+        for path in ["", "/", "//"] {
+            assert_eq!(best(&router, path), Some("wildcard"));
+        }
+        assert_eq!(best(&router, "/a"), Some("param"));
+
+        let policy = RootPolicy::new().wildcard_matches_root(false);
+        router.set_match_options(MatchOptions::new().root_policy(policy));
+        for path in ["", "/", "//"] {
+            assert_eq!(best(&router, path), None);
+        }
+        assert_eq!(best(&router, "/a/b"), Some("wildcard"));
+
+        let policy = policy.param_matches_root(true).empty_path_is_root(false);
+        router.set_match_options(MatchOptions::new().root_policy(policy));
+        assert_eq!(best(&router, ""), None);
+        assert_eq!(best(&router, "/"), Some("param"));
+        assert_eq!(best(&router, "//"), Some("param"));
+        assert_eq!(
+            router.best_match("/").unwrap().captures().get("param"),
+            Some("")
+        );
+    }
+
+    let mut router = Router::new();
+    let root = router.add("/", "root")?;
+    router.add("/:a/:b", "pair")?;
+    router.set_match_options(
+        MatchOptions::new().root_policy(RootPolicy::new().param_matches_root(true)),
+    );
+    assert_eq!(best(&router, ""), Some("root"));
+    assert_eq!(best(&router, "//"), Some("root"));
+    router.remove(root);
+    assert_eq!(best(&router, "/"), None);
+
+    let mut router = Router::new();
+    router.add("/", "root")?;
+    router.add("/about", "about")?;
+    router.set_match_options(
+        MatchOptions::new().root_policy(RootPolicy::new().empty_path_is_root(false)),
+    );
+    let router = router.into_static();
+    assert_eq!(*router.best_match("/").unwrap(), "root");
+    assert!(router.best_match("").is_none());
+    Ok(())
+}
+
+#[test]
+fn readable_debug() -> Result {
+    let mut router = Router::new();
+    router.add("/:name.:ext", 1)?;
+    router.add("/", 2)?;
+
+    let m = router.best_match("/report.pdf").unwrap();
+    assert_eq!(
+        format!("{:?}", m.captures()),
+        r#"{"name": "report", "ext": "pdf"}"#
+    );
+    assert_eq!(
+        format!("{:?}", m),
+        r#"Match { route: /:name.:ext, path: "/report.pdf", captures: {"name": "report", "ext": "pdf"}, handler: 1 }"#
+    );
+
+    let m = router.best_match("/").unwrap();
+    assert_eq!(format!("{:?}", m.captures()), "{}");
+    assert_eq!(
+        format!("{:?}", Capture::new("id", String::from("1"))),
+        r#"Capture("id", "1")"#
+    );
+    Ok(())
+}
+
+#[test]
+fn handler_maps() -> Result {
+    #[derive(Debug, PartialEq)]
+    struct Users(&'static str);
+    #[derive(Debug, PartialEq)]
+    struct Assets {
+        root: String,
+    }
+
+    let mut router: Router<()> = Router::new();
+    let mut handlers = HandlerMap::new();
+    let users = handlers.add(&mut router, "/users/:id", Users("users"))?;
+    let assets = handlers.add(
+        &mut router,
+        "/assets/*",
+        Assets {
+            root: String::from("public"),
+        },
+    )?;
+    assert!(handlers
+        .add(&mut router, "/*named", Users("invalid"))
+        .is_err());
+    assert_eq!(router.len(), 2);
+    assert_eq!(handlers.len(), 2);
+    assert_eq!(handlers.ids().collect::<Vec<_>>(), vec![users, assets]);
+
+    let m = router.best_match("/assets/app.js").unwrap();
+    assert_eq!(handlers.for_match::<Assets, _>(&m).unwrap().root, "public");
+    assert_eq!(
+        handlers.type_id(assets),
+        Some(std::any::TypeId::of::<Assets>())
+    );
+
+    handlers.get_mut::<Users>(users).unwrap().0 = "people";
+    assert_eq!(handlers.get::<Users>(users), Some(&Users("people")));
+    assert!(handlers.get::<Assets>(users).is_none());
+
+    assert!(handlers.remove::<Assets>(users).is_none());
+    assert!(handlers.contains(users));
+    assert_eq!(handlers.remove::<Users>(users), Some(Users("people")));
+    assert!(!handlers.contains(users));
+
+    assert!(handlers.insert(assets, Users("replaced")).is_some());
+    assert!(handlers.get::<Assets>(assets).is_none());
+    Ok(())
+}
+
+#[test]
+fn fingerprints() -> Result {
+    let mut router = Router::new();
+    router.add("/", "home")?;
+    router.add("/users/:id", "user")?;
+    router.add_excluding("/assets/*", ["/assets/private/*"], "assets")?;
+    router.add_with_mode("/static", MatchMode::Prefix, "static")?;
+
+    let canonical: Vec<String> = router.iter().map(|(route, _)| route.canonical()).collect();
+    assert_eq!(
+        canonical,
+        [
+            "/",
+            "/users/:id",
+            "/assets/* except=/assets/private/*",
+            "/static mode=Prefix",
+        ]
+    );
+    assert_eq!(router.fingerprint(), 0xeb31_f4e1_9415_d374);
+
+    let before = router.fingerprint();
+    let mut router = router.zip_meta(vec![1, 2, 3, 4]);
+    assert_eq!(router.fingerprint(), before);
+    router.deny("/users/admin")?;
+    assert_ne!(router.fingerprint(), before);
+
+    let mut flagged = Router::new();
+    let flag = Flag::new(true);
+    flagged.add_conditional("/beta", (), flag)?;
+    let mut plain = Router::new();
+    plain.add("/beta", ())?;
+    assert_ne!(flagged.fingerprint(), plain.fingerprint());
+    assert_eq!(
+        flagged.iter().next().unwrap().0.canonical(),
+        "/beta conditional"
+    );
+
+    use std::hash::{BuildHasher, RandomState};
+    let state = RandomState::new();
+    let hash = |spec: &RouteSpec| state.hash_one(spec);
+    let built = RouteSpec::from_segments(vec![
+        Segment::Exact("a".into()),
+        Segment::Slash,
+        Segment::Param("b".into()),
+    ])?;
+    let parsed: RouteSpec = "a/:b/".parse()?;
+    let mut hashes = std::collections::HashSet::new();
+    assert!(hashes.insert(hash(&built)));
+    assert!(!hashes.insert(hash(&parsed)));
+    Ok(())
+}
+
+#[test]
+fn adversarial_wildcards() -> Result {
+    use std::time::{Duration, Instant};
+
+    let mut router = Router::new();
+    router.add("/*/x/*/y/*/z/*/q", "default")?;
+    router.add(
+        RouteSpec::parse_with("**/**/**/**/zzz", Syntax::Glob)?,
+        "glob",
+    )?;
+    let path = "/x/y/z".repeat(300);
+    let segments = format!("/{}", vec!["a"; 200].join("/"));
+
+    let start = Instant::now();
+    assert!(router.best_match(&path).is_none());
+    assert!(router.best_match(&segments).is_none());
+    assert!(
+        start.elapsed() < Duration::from_secs(2),
+        "{:?}",
+        start.elapsed()
+    );
+
+    let m = router.best_match("/a/x/b/c/y/z/d/q").unwrap();
+    assert_eq!(*m, "default");
+    assert_eq!(*router.best_match("/a/b/zzz").unwrap(), "glob");
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use routefinder::{UrlRouter, UrlSpec};
+
+#[test]
+fn scheme_and_host() {
+    let mut router = UrlRouter::new();
+    router.add("https://example.com/", 1).unwrap();
+    router.add("http://example.com/", 2).unwrap();
+    router.add("/", 3).unwrap();
+
+    assert_eq!(*router.best_match("https://example.com/").unwrap(), 1);
+    assert_eq!(*router.best_match("http://EXAMPLE.com/").unwrap(), 2);
+    assert_eq!(*router.best_match("https://other.com/").unwrap(), 3);
+    assert_eq!(*router.best_match("/").unwrap(), 3);
+    assert!(router.best_match("https://example.com/nope").is_none());
+}
+
+#[test]
+fn host_captures() {
+    let spec: UrlSpec = "//:tenant.example.com/users/:id".parse().unwrap();
+    let captures = spec.matches("https://acme.example.com/users/10").unwrap();
+    assert_eq!(captures.get("tenant"), Some("acme"));
+    assert_eq!(captures.get("id"), Some("10"));
+    assert!(spec.matches("https://acme.example.org/users/10").is_none());
+}
+
+#[test]
+fn query_params() {
+    let spec: UrlSpec = "/search?q=:q&format=json&debug".parse().unwrap();
+    let captures = spec
+        .matches("/search?page=2&debug&format=json&q=rust")
+        .unwrap();
+    assert_eq!(captures.get("q"), Some("rust"));
+    assert!(spec.matches("/search?q=rust&format=xml&debug").is_none());
+    assert!(spec.matches("/search?q=rust&format=json").is_none());
+    assert!(spec.matches("/search").is_none());
+}
+
+#[test]
+fn wildcards_merge() {
+    let spec: UrlSpec = "//*.example.com/static/*".parse().unwrap();
+    let captures = spec
+        .matches("//cdn.example.com/static/css/app.css")
+        .unwrap();
+    assert_eq!(captures.wildcard(), Some("css/app.css"));
+
+    let spec: UrlSpec = "//*.example.com/".parse().unwrap();
+    let captures = spec.matches("//cdn.eu.example.com/").unwrap();
+    assert_eq!(captures.wildcard(), Some("cdn.eu"));
+}
+
+#[test]
+fn precedence() {
+    let mut router = UrlRouter::new();
+    router.add("/users/:id", "any host").unwrap();
+    router
+        .add("/users/:id?tab=:tab", "any host with tab")
+        .unwrap();
+    router.add("//api.example.com/users/:id", "api").unwrap();
+    router
+        .add("//:tenant.example.com/users/:id", "tenant")
+        .unwrap();
+
+    let url = "https://api.example.com/users/1?tab=posts";
+    assert_eq!(*router.best_match(url).unwrap(), "api");
+    assert_eq!(
+        router
+            .matches(url)
+            .iter()
+            .map(|m| *m.handler())
+            .collect::<Vec<_>>(),
+        ["api", "tenant", "any host with tab", "any host"]
+    );
+
+    assert_eq!(
+        *router.best_match("https://other.com/users/1").unwrap(),
+        "any host"
+    );
+}
+
+#[test]
+fn path_precedence_before_query() {
+    let mut router = UrlRouter::new();
+    router.add("/users/:id?tab", "user with tab").unwrap();
+    router.add("/users/new", "new user").unwrap();
+
+    assert_eq!(*router.best_match("/users/new?tab=1").unwrap(), "new user");
+    assert_eq!(
+        *router.best_match("/users/1?tab=1").unwrap(),
+        "user with tab"
+    );
+    assert!(router.best_match("/users/1").is_none());
+}
+
+#[test]
+fn display_round_trip() {
+    for source in [
+        "https://api.example.com/users/:id?tab=:tab",
+        "/users/:id",
+        "/search?q=:q&format=json&debug",
+    ] {
+        let spec: UrlSpec = source.parse().unwrap();
+        assert_eq!(spec.to_string(), source);
+        assert_eq!(spec.to_string().parse::<UrlSpec>().unwrap(), spec);
+    }
+}
@@ -0,0 +1,119 @@
+use routefinder::*;
+
+type Result = std::result::Result<(), Box<dyn std::error::Error>>;
+
+#[test]
+fn multi_byte_exact_segments() -> Result {
+    for route in ["/café/:id", "/日本/語", "/é.json", "/ü/:a.:b", "/😀/*"] {
+        let spec: RouteSpec = route.parse()?;
+        assert_eq!(spec.to_string(), route);
+    }
+
+    let mut router = Router::new();
+    router.add("/café/:id", "cafe")?;
+    router.add("/日本/語", "japanese")?;
+    router.add("/é.json", "json")?;
+    assert_eq!(*router.best_match("/café/1").unwrap(), "cafe");
+    assert_eq!(*router.best_match("/日本/語").unwrap(), "japanese");
+    assert_eq!(*router.best_match("/é.json").unwrap(), "json");
+    assert!(router.best_match("/cafe/1").is_none());
+    assert!(router.best_match("/é").is_none());
+    Ok(())
+}
+
+#[test]
+fn multi_byte_params() -> Result {
+    let mut router = Router::new();
+    router.add("/users/:name", "user")?;
+    router.add("/files/:name.:ext", "file")?;
+    router.add("/tags/:tag/posts", "tag")?;
+
+    let m = router.best_match("/users/José").unwrap();
+    assert_eq!(m.captures().get("name"), Some("José"));
+    let m = router.best_match("/files/日本.😀").unwrap();
+    assert_eq!(m.captures().get("name"), Some("日本"));
+    assert_eq!(m.captures().get("ext"), Some("😀"));
+    let m = router.best_match("/tags/日本語/posts").unwrap();
+    assert_eq!(m.captures().get("tag"), Some("日本語"));
+    assert!(router.best_match("/files/é").is_none());
+    Ok(())
+}
+
+#[test]
+fn dots_next_to_multi_byte_chars() -> Result {
+    let spec: RouteSpec = "/:a.é".parse()?;
+    assert_eq!(
+        spec.segments(),
+        [
+            Segment::Param("a".into()),
+            Segment::Dot,
+            Segment::Exact("é".into())
+        ]
+    );
+    assert_eq!(spec.matches("/日.é"), Some(vec!["日"]));
+    assert_eq!(spec.matches("/é.é"), Some(vec!["é"]));
+    assert!(spec.matches("/é.e").is_none());
+
+    let spec: RouteSpec = "/é.:ext".parse()?;
+    assert_eq!(spec.matches("/é.ü"), Some(vec!["ü"]));
+    assert!(spec.matches("/éü").is_none());
+
+    let spec: RouteSpec = "/é/.:ext".parse()?;
+    assert_eq!(spec.to_string(), "/é/.:ext");
+    Ok(())
+}
+
+/// routes and paths built from delimiters, route syntax, and
+/// multi-byte chars, from a deterministic pseudo-random sequence
+#[test]
+fn arbitrary_utf8_never_panics() {
+    let pieces = [
+        "/", ".", ":", "*", "?", "+", "a", "A", "é", "É", "日", "😀", "ß", "İ", ":x", ":y?", ":z+",
+        ":w*", "é.", ".é", "%", "%C3%A9", "..", "./", "{", "}", "(", ")", "[", "]", "-",
+    ];
+    let syntaxes = [
+        Syntax::Routefinder,
+        Syntax::Glob,
+        Syntax::Express,
+        Syntax::HttpRule,
+    ];
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = move |bound: usize| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state % bound as u64) as usize
+    };
+
+    let options = MatchOptions::new()
+        .case_insensitive(true)
+        .decode(true)
+        .resolve_dot_segments(true);
+    let captures: Captures = [("x", "é.日"), ("y", "😀"), ("z", "a/é"), ("w", "")].into();
+    for _ in 0..20_000 {
+        let route: String = (0..next(10)).map(|_| pieces[next(pieces.len())]).collect();
+        let path: String = (0..next(10)).map(|_| pieces[next(pieces.len())]).collect();
+        let spec = match RouteSpec::parse_with(&route, syntaxes[next(syntaxes.len())]) {
+            Ok(spec) => spec,
+            Err(_) => continue,
+        };
+
+        spec.matches(&path);
+        spec.matches_with(&path, &options);
+        spec.template(&captures);
+
+        let mut router = Router::new();
+        router.add(spec, ()).unwrap();
+        router.best_match(&path);
+        router.set_match_options(options);
+        if let Some(m) = router.best_match(&path) {
+            m.captures();
+        }
+        router.explain(&path);
+        let mut matcher = router.matcher();
+        for segment in path.split_inclusive('/') {
+            matcher.feed(segment);
+        }
+        matcher.finish();
+    }
+}
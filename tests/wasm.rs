@@ -0,0 +1,60 @@
+#![cfg(all(feature = "wasm", target_arch = "wasm32"))]
+// run with `wasm-pack test --node -- --features wasm`
+
+use js_sys::{Object, Reflect};
+use routefinder::wasm::JsRouter;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+fn get(object: &JsValue, key: &str) -> JsValue {
+    Reflect::get(object, &key.into()).unwrap()
+}
+
+fn object(entries: &[(&str, &str)]) -> JsValue {
+    let object = Object::new();
+    for (key, value) in entries {
+        Reflect::set(&object, &(*key).into(), &(*value).into()).unwrap();
+    }
+    object.into()
+}
+
+#[wasm_bindgen_test]
+fn best_match_returns_an_object() {
+    let mut router = JsRouter::new();
+    assert_eq!(router.add("/users/:id", "user".into()).ok(), Some(0));
+    assert!(router.add("/files/*", JsValue::from(2)).is_ok());
+    assert_eq!(router.size(), 2);
+
+    let m = router.best_match("/users/1").unwrap();
+    assert_eq!(get(&m, "handler"), JsValue::from("user"));
+    assert_eq!(get(&m, "route"), JsValue::from("/users/:id"));
+    assert_eq!(get(&get(&m, "params"), "id"), JsValue::from("1"));
+    assert!(get(&m, "wildcard").is_undefined());
+
+    let m = router.best_match("/files/a/b.txt").unwrap();
+    assert_eq!(get(&m, "handler"), JsValue::from(2));
+    assert_eq!(get(&m, "wildcard"), JsValue::from("a/b.txt"));
+    assert_eq!(Object::keys(&Object::from(get(&m, "params"))).length(), 0);
+
+    assert!(router.best_match("/posts").unwrap().is_null());
+    assert!(router.add("/:", JsValue::NULL).is_err());
+}
+
+#[wasm_bindgen_test]
+fn url_for_fills_in_params() {
+    let router = JsRouter::new();
+    assert_eq!(
+        router
+            .url_for("/users/:id", object(&[("id", "2"), ("extra", "x")]))
+            .unwrap(),
+        Some(String::from("/users/2"))
+    );
+    assert_eq!(
+        router
+            .url_for("/files/*", object(&[("*", "a/b.txt")]))
+            .unwrap(),
+        Some(String::from("/files/a/b.txt"))
+    );
+    assert_eq!(router.url_for("/users/:id", object(&[])).unwrap(), None);
+    assert!(router.url_for("/:", object(&[])).is_err());
+}